@@ -1,8 +1,16 @@
 use clap::{Parser, Subcommand};
 use colored::Colorize;
-use netscout_core::config::{self, Config};
-use netscout_core::output::format_output;
+use netscout_core::batch;
+use netscout_core::config::{self, TimeoutProfile};
+use netscout_core::health::{HealthCheck, HealthStatus};
+use netscout_core::locale::{set_number_locale, NumberLocale};
+use netscout_core::output::{
+    format_health, format_oneline, format_output, format_ping_classic, parse_csv_delimiter,
+    ping_probe_line, ping_probe_line_classic, ping_summary, ping_summary_classic,
+    set_csv_delimiter, HumanReadable,
+};
 use netscout_core::OutputFormat;
+use std::io::{BufWriter, IsTerminal, Stdout, Write};
 use std::time::Duration;
 
 #[derive(Parser)]
@@ -16,7 +24,17 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    /// Output as JSON
+    /// Output format: human, json, table, csv, ndjson or prometheus (or h, j, t, c, jsonl, prom); overrides --json/--table/--csv
+    #[arg(
+        long,
+        short = 'o',
+        global = true,
+        value_name = "FORMAT",
+        value_parser = |s: &str| s.parse::<OutputFormat>()
+    )]
+    output: Option<OutputFormat>,
+
+    /// Output as JSON (same as -o json)
     #[arg(long, global = true)]
     json: bool,
 
@@ -28,6 +46,14 @@ struct Cli {
     #[arg(long, global = true)]
     csv: bool,
 
+    /// Output as tab-separated values (CSV with a tab delimiter)
+    #[arg(long, global = true)]
+    tsv: bool,
+
+    /// Field delimiter for CSV output, e.g. ';' or '\t' (implies --csv)
+    #[arg(long, global = true, value_name = "CHAR")]
+    delimiter: Option<String>,
+
     /// Disable colored output
     #[arg(long, global = true)]
     no_color: bool,
@@ -36,6 +62,46 @@ struct Cli {
     #[arg(long, short, global = true)]
     verbose: bool,
 
+    /// Stop at the first failing target and exit non-zero
+    #[arg(long, global = true)]
+    fail_fast: bool,
+
+    /// Report failing targets as error entries and still exit 0
+    #[arg(long, global = true)]
+    ignore_errors: bool,
+
+    /// With several targets, check at most N of them at the same time
+    #[arg(long, global = true, value_parser = clap::value_parser!(u64).range(1..))]
+    max_concurrent: Option<u64>,
+
+    /// Flush streamed output after every record even when stdout is a pipe
+    #[arg(long, global = true)]
+    line_buffered: bool,
+
+    /// Output a Consul-style health check (passing/warning/critical) instead
+    #[arg(long, global = true)]
+    health: bool,
+
+    /// Print a single plain-text summary line per result, e.g. for status bars
+    #[arg(long, global = true)]
+    oneline: bool,
+
+    /// Show only failures: lost ping probes, timed-out trace hops and failing targets
+    #[arg(long, global = true)]
+    only_errors: bool,
+
+    /// Number format for human output, e.g. en_US or de_DE ("auto" reads LC_NUMERIC/LANG)
+    #[arg(long, global = true)]
+    locale: Option<String>,
+
+    /// Timeout profile for the link: fast (0.5x), default or slow (3x default timeouts)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Default timeout in milliseconds for any command (a command's own --timeout wins)
+    #[arg(long, value_name = "MS", global = true)]
+    timeout: Option<u64>,
+
     /// Path to config file (default: ~/.netscout.toml)
     #[arg(long, global = true)]
     config: Option<String>,
@@ -43,13 +109,17 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
-    /// TCP ping a target host
+    /// TCP ping one or more target hosts
     Ping {
-        /// Target hostname or IP
-        target: String,
-        /// Number of pings to send
+        /// Target hostnames or IPs
+        #[arg(required = true)]
+        targets: Vec<String>,
+        /// Number of pings to send (0 keeps pinging until interrupted)
         #[arg(short, long)]
         count: Option<u32>,
+        /// Ping until interrupted with Ctrl-C (same as --count 0)
+        #[arg(long, conflicts_with = "count")]
+        continuous: bool,
         /// Interval between pings in milliseconds
         #[arg(short, long)]
         interval: Option<u64>,
@@ -59,42 +129,135 @@ enum Commands {
         /// TCP port to ping
         #[arg(short, long)]
         port: Option<u16>,
+        /// Send ICMP echo requests instead of timing TCP connects (needs root or CAP_NET_RAW)
+        #[arg(long, conflicts_with = "port")]
+        icmp: bool,
+        /// Look up which organisation owns the resolved address (IP WHOIS)
+        #[arg(long)]
+        owner: bool,
+        /// Stop after this many seconds even if --count isn't reached
+        #[arg(short, long)]
+        duration: Option<f64>,
+        /// Human output in the classic ping(8) format (rtt min/avg/max/mdev)
+        #[arg(long)]
+        classic: bool,
     },
     /// Query DNS records
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Dns {
+        #[command(subcommand)]
+        action: Option<DnsAction>,
         /// Domain to query
-        domain: String,
-        /// Record type (A, AAAA, MX, TXT, CNAME, NS, SOA, PTR)
+        #[arg(required = true)]
+        domain: Option<String>,
+        /// Record type (A, AAAA, MX, TXT, CNAME, NS, SOA, PTR, DS, DNSKEY, RRSIG, CAA, TLSA, SSHFP)
         #[arg(short = 't', long = "type")]
         record_type: Option<String>,
-        /// DNS resolver IP
+        /// DNS resolver IP, optionally with a port (e.g., 127.0.0.1:5353 or [::1]:5353)
         #[arg(short, long)]
         resolver: Option<String>,
+        /// EDNS client subnet to send (e.g., "203.0.113.0/24")
+        #[arg(long)]
+        client_subnet: Option<String>,
+        /// Query several resolvers at once and compare (comma-separated)
+        #[arg(long, conflicts_with = "resolver")]
+        compare: Option<String>,
+        /// With --compare, return the first resolver's answer without waiting for the rest
+        #[arg(long, requires = "compare")]
+        first_response: bool,
+        /// With --compare, require N resolvers to agree on the answer
+        #[arg(long, requires = "compare", conflicts_with = "first_response")]
+        quorum: Option<usize>,
+        /// With --compare, query at most N resolvers at the same time
+        #[arg(long, requires = "compare", value_parser = clap::value_parser!(u64).range(1..))]
+        parallel: Option<u64>,
+        /// Resolve through the OS stub resolver (/etc/hosts, nsswitch) instead of querying DNS
+        #[arg(long, conflicts_with_all = ["resolver", "compare", "client_subnet"])]
+        system: bool,
+        /// Compare this internal resolver's answer with a public one (--resolver, default 8.8.8.8)
+        #[arg(long, value_name = "INTERNAL_RESOLVER", conflicts_with_all = ["compare", "system"])]
+        split_check: Option<String>,
+        /// Report whether the name is DNSSEC-signed and validated by the resolver
+        #[arg(long, conflicts_with_all = ["compare", "system", "split_check"])]
+        dnssec: bool,
+        /// Check each A/AAAA address's PTR points back to the name (forward-confirmed reverse DNS)
+        #[arg(long, conflicts_with_all = ["compare", "system", "split_check", "dnssec", "record_type"])]
+        verify_reverse: bool,
+        /// Query several types (e.g. A,AAAA,MX,NS) into one sorted set for `netscout diff`
+        #[arg(
+            long,
+            value_name = "TYPES",
+            conflicts_with_all = ["compare", "system", "split_check", "dnssec", "verify_reverse", "record_type"]
+        )]
+        monitor_set: Option<String>,
+        /// Clear the RD bit to query an authoritative server without recursion
+        #[arg(long, conflicts_with = "system")]
+        no_recurse: bool,
+        /// Set the CD bit so the resolver skips DNSSEC validation
+        #[arg(long, conflicts_with = "system")]
+        cd: bool,
+        /// Query over TCP (UDP answers marked truncated are retried over TCP anyway)
+        #[arg(long, conflicts_with = "system")]
+        tcp: bool,
+        /// Timeout per query in milliseconds
+        #[arg(long, global = true)]
+        timeout: Option<u64>,
     },
     /// Scan TCP ports on a target
     Port {
         /// Target hostname or IP
         target: String,
-        /// Ports to scan (e.g., "80,443,8000-9000")
+        /// Ports to scan (e.g., "80,443,8000-9000" or "ssh,https,mysql")
         #[arg(short, long)]
         ports: Option<String>,
+        /// Scan a small set of high-signal ports (ssh, web, databases, ...)
+        #[arg(short, long, conflicts_with = "ports")]
+        quick: bool,
+        /// Scan only the first N ports of the built-in top-ports list
+        #[arg(long, value_name = "N", conflicts_with_all = ["ports", "quick"], value_parser = clap::value_parser!(u16).range(1..))]
+        top: Option<u16>,
+        /// Ports to leave out of the scan (same syntax as --ports)
+        #[arg(long)]
+        exclude_ports: Option<String>,
+        /// Print the ports that would be scanned and exit without connecting
+        #[arg(long)]
+        dry_run: bool,
         /// Timeout per connection in milliseconds
         #[arg(short, long)]
         timeout: Option<u64>,
         /// Number of parallel connections
         #[arg(long)]
         parallel: Option<usize>,
+        /// Flag detected service versions in known-vulnerable ranges (heuristic; implies --banner)
+        #[arg(long)]
+        security_notes: bool,
+        /// Save progress to FILE and resume from it, skipping ports already scanned
+        #[arg(long, value_name = "FILE")]
+        checkpoint: Option<std::path::PathBuf>,
+        /// Read each open port's banner (sends HEAD / to HTTP ports) to show service versions
+        #[arg(long)]
+        banner: bool,
     },
     /// Traceroute to a target
     Trace {
-        /// Target hostname or IP
-        target: String,
+        /// Target hostnames or IPs
+        #[arg(required = true)]
+        targets: Vec<String>,
         /// Maximum number of hops
         #[arg(long)]
         max_hops: Option<u8>,
         /// Timeout per hop in milliseconds
         #[arg(short, long)]
         timeout: Option<u64>,
+        /// Probes to send per hop (default 3)
+        #[arg(short = 'q', long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..))]
+        queries: Option<u8>,
+        /// Look up which organisation owns each hop address (IP WHOIS)
+        #[arg(long)]
+        owner: bool,
+        /// Emit a Graphviz DOT path map, merging hops shared between targets
+        #[arg(long)]
+        dot: bool,
     },
     /// Probe an HTTP(S) URL
     Http {
@@ -106,17 +269,78 @@ enum Commands {
         /// Extra headers (K:V format, repeatable)
         #[arg(short = 'H', long = "header")]
         headers: Vec<String>,
+        /// File with one `Key: Value` header per line (`-H` flags win on conflict)
+        #[arg(long)]
+        headers_file: Option<String>,
         /// Follow redirects
         #[arg(short, long)]
         follow: bool,
+        /// Send `Connection: keep-alive` instead of `Connection: close`
+        #[arg(long)]
+        keep_alive: bool,
+        /// Fail if the time to first byte exceeds this many milliseconds
+        #[arg(long, value_name = "MS")]
+        max_ttfb: Option<u64>,
+        /// Negotiate HTTP/2 via ALPN (https:// only); reports any HTTP/1.1 fallback
+        #[arg(long)]
+        http2: bool,
+        /// PEM file of extra CA certificates to trust for HTTPS
+        #[arg(long, value_name = "PATH")]
+        ca_file: Option<std::path::PathBuf>,
+        /// Show TLS version, cipher, ALPN and session resumption for https:// URLs
+        #[arg(long)]
+        tls_info: bool,
+        /// Offer gzip/br and report the Content-Encoding and compression ratio
+        #[arg(long)]
+        compression: bool,
+        /// Make a discarded request first, then report cold and warm timings side by side
+        #[arg(long)]
+        warmup: bool,
+        /// Report the body download rate (needs a transfer of at least 50ms)
+        #[arg(long)]
+        throughput: bool,
+        /// Write the response body to this file; `-` prints the body instead of the report
+        #[arg(long, value_name = "PATH")]
+        save: Option<std::path::PathBuf>,
+        /// Largest body to hold in memory for --save or --compression (default 64 MiB)
+        #[arg(long, value_name = "BYTES")]
+        max_body: Option<usize>,
+        /// List the response headers, sorted by name, in human and table output
+        #[arg(short = 'i', long)]
+        show_headers: bool,
+        /// Timeout per request in milliseconds
+        #[arg(short, long)]
+        timeout: Option<u64>,
     },
-    /// Inspect TLS certificate
+    /// Inspect TLS certificates
     Cert {
-        /// Hostname to inspect
-        host: String,
+        /// Hostnames to inspect
+        #[arg(required = true)]
+        hosts: Vec<String>,
         /// Port number
         #[arg(short, long)]
         port: Option<u16>,
+        /// Number of handshakes to time (reports min/avg/p95/max)
+        #[arg(short, long, default_value_t = 1)]
+        count: u32,
+        /// PEM file with extra CA certificates to trust (e.g. an internal CA)
+        #[arg(long)]
+        ca_file: Option<std::path::PathBuf>,
+        /// Report the chain even if it doesn't validate
+        #[arg(short = 'k', long)]
+        insecure: bool,
+        /// Exit non-zero if the certificate doesn't cover the host or the chain is invalid
+        #[arg(long, conflicts_with = "insecure")]
+        verify_hostname: bool,
+        /// Only list certificates expiring within this many days, soonest first
+        #[arg(long, value_name = "DAYS")]
+        expiring_within: Option<i64>,
+        /// With --expiring-within, exit non-zero if any expire within this many days
+        #[arg(long, value_name = "DAYS", requires = "expiring_within")]
+        critical_within: Option<i64>,
+        /// Timeout per handshake in milliseconds
+        #[arg(short, long)]
+        timeout: Option<u64>,
     },
     /// Run a bandwidth speed test
     Speed {
@@ -129,84 +353,768 @@ enum Commands {
         /// Upload only
         #[arg(long)]
         upload_only: bool,
+        /// Timeout per transfer in milliseconds
+        #[arg(short, long)]
+        timeout: Option<u64>,
     },
     /// WHOIS lookup
     Whois {
         /// Domain or IP to query
         target: String,
+        /// WHOIS server to ask (host or host:port) instead of the one picked for the target
+        #[arg(long)]
+        server: Option<String>,
+        /// Send this exact query string instead of the target (e.g. "n + 8.8.8.8" for ARIN)
+        #[arg(long)]
+        query: Option<String>,
+        /// Timeout in milliseconds
+        #[arg(short, long)]
+        timeout: Option<u64>,
     },
     /// List network interfaces
     Netif {
         /// Show only interfaces that are UP
         #[arg(long)]
         up_only: bool,
+        /// Show the IPv4 and IPv6 default gateways instead of the interfaces
+        #[arg(long, conflicts_with = "up_only")]
+        gateway: bool,
     },
     /// Scan a LAN subnet for hosts
     Scan {
-        /// Subnet in CIDR notation (e.g., 192.168.1.0/24)
-        subnet: String,
+        /// Subnet in CIDR notation (e.g., 192.168.1.0/24, fd00::/120 or fe80::/120%eth0)
+        #[arg(required_unless_present_any = ["hosts", "hosts_file"])]
+        subnet: Option<String>,
+        /// Scan these hosts (IPs or hostnames, comma-separated) instead of a subnet
+        #[arg(long, conflicts_with = "subnet")]
+        hosts: Option<String>,
+        /// File with hosts to scan, one per line (`#` comments allowed)
+        #[arg(long, conflicts_with_all = ["subnet", "hosts"])]
+        hosts_file: Option<String>,
         /// Ports to check per host
         #[arg(short, long)]
         ports: Option<String>,
+        /// Ports to leave out of the scan (same syntax as --ports)
+        #[arg(long)]
+        exclude_ports: Option<String>,
+        /// Print the host count and ports that would be scanned and exit without probing
+        #[arg(long)]
+        dry_run: bool,
         /// Timeout per connection in milliseconds
         #[arg(short, long)]
         timeout: Option<u64>,
+        /// With --json, print one JSON line per host as it is found, then a summary line
+        #[arg(long)]
+        stream: bool,
+        /// Wait this many milliseconds between starting host probes
+        #[arg(long, value_name = "MS")]
+        delay: Option<u64>,
+        /// Add a random extra wait of up to this many milliseconds per host
+        #[arg(long, value_name = "MS")]
+        jitter: Option<u64>,
+        /// Derive the timeout from the RTTs of the first responsive hosts
+        #[arg(long)]
+        adaptive_timeout: bool,
+        /// Lowest timeout adaptive mode may pick, in milliseconds
+        #[arg(long, value_name = "MS", requires = "adaptive_timeout")]
+        timeout_floor: Option<u64>,
+        /// Highest timeout adaptive mode may pick, in milliseconds
+        #[arg(long, value_name = "MS", requires = "adaptive_timeout")]
+        timeout_ceiling: Option<u64>,
+        /// Skip reverse DNS (PTR) lookups of found hosts
+        #[arg(long)]
+        no_resolve: bool,
+        /// ICMP-ping every address first and report hosts that answer even with no open ports
+        #[arg(long)]
+        ping_sweep: bool,
     },
+    /// Check whether a host is reachable (ping + port + optional HTTP)
+    Reach {
+        /// Hostname or IP address
+        target: String,
+        /// Ports to try; reachable if any connects (default: 443,80)
+        #[arg(short, long)]
+        ports: Option<String>,
+        /// Also require an HTTP status below 400 from http://<target>/
+        #[arg(long)]
+        http: bool,
+        /// Timeout per check in milliseconds
+        #[arg(short, long)]
+        timeout: Option<u64>,
+    },
+    /// Show this host's public IPv4 and IPv6 addresses
+    Pubip {
+        /// Echo service that answers with the IP as plain text (skips the default IPv6 lookup)
+        #[arg(long, value_name = "URL")]
+        endpoint: Option<String>,
+        /// IPv6-only echo service
+        #[arg(long, value_name = "URL")]
+        endpoint_v6: Option<String>,
+        /// Reverse-resolve each address
+        #[arg(long)]
+        ptr: bool,
+        /// Look up the owning organisation via WHOIS
+        #[arg(long)]
+        owner: bool,
+        /// Timeout per request in milliseconds
+        #[arg(short, long)]
+        timeout: Option<u64>,
+    },
+    /// Summarize the local network: interfaces, gateway, DNS servers, public IP and connectivity
+    Report {
+        /// Host used for the DNS and ping checks
+        #[arg(long, default_value = "one.one.one.one")]
+        host: String,
+        /// Service that answers with the public IP as plain text
+        #[arg(long, value_name = "URL", default_value = "https://api.ipify.org/")]
+        ip_url: String,
+        /// Timeout per check in milliseconds
+        #[arg(short, long)]
+        timeout: Option<u64>,
+    },
+    /// Compare two saved JSON results
+    Diff {
+        /// Older result file
+        old: String,
+        /// Newer result file
+        new: String,
+    },
+    /// Check the config file (default ~/.netscout.toml, or --config PATH)
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Dump built-in data tables as JSON for other tools
+    #[command(hide = true)]
+    Data {
+        #[command(subcommand)]
+        table: DataTable,
+    },
+}
+
+#[derive(Subcommand)]
+enum DnsAction {
+    /// Benchmark resolvers on the same names and recommend the fastest
+    Fastest {
+        /// Resolvers to compare (comma-separated)
+        #[arg(long, default_value = "1.1.1.1,8.8.8.8,9.9.9.9")]
+        resolvers: String,
+        /// Names to query through each resolver (comma-separated)
+        #[arg(long)]
+        domains: Option<String>,
+        /// Record type to query
+        #[arg(short = 't', long = "type", default_value = "A")]
+        record_type: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Parse the file strictly and summarize the defaults it sets, without running a command
+    Validate,
+}
+
+#[derive(Subcommand)]
+enum DataTable {
+    /// Service names by port plus the top and quick port lists
+    Ports,
 }
 
-fn get_format(cli: &Cli, cfg: &Config) -> OutputFormat {
-    if cli.json {
-        OutputFormat::Json
-    } else if cli.csv {
-        OutputFormat::Csv
+/// `--dry-run` output: what a port or LAN scan would probe, without probing it.
+fn format_dry_run(
+    target: &str,
+    hosts: Option<usize>,
+    ports: &[u16],
+    format: OutputFormat,
+) -> String {
+    if matches!(format, OutputFormat::Json | OutputFormat::Ndjson) {
+        let mut plan = serde_json::json!({ "target": target, "ports": ports });
+        if let Some(n) = hosts {
+            plan["hosts"] = n.into();
+        }
+        return match format {
+            OutputFormat::Json => format!("{plan:#}\n"),
+            _ => format!("{plan}\n"),
+        };
+    }
+    let list = ports
+        .iter()
+        .map(u16::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut out = format!("Dry run for {target}, nothing scanned\n");
+    if let Some(n) = hosts {
+        out.push_str(&format!("Hosts: {n}\n"));
+    }
+    out.push_str(&format!("Ports ({}): {list}\n", ports.len()));
+    out
+}
+
+/// Summary printed by `config validate` for a file that passed.
+fn format_config_check(check: &config::ConfigCheck) -> String {
+    let effective = &check.effective;
+    let mut out = format!(
+        "{} {} is valid\n",
+        "✓".green(),
+        check.path.display().to_string().bold()
+    );
+    out.push_str(&format!("  output:  {}\n", effective.output));
+    out.push_str(&format!(
+        "  color:   {}\n",
+        format!("{:?}", effective.color).to_lowercase()
+    ));
+    out.push_str(&format!("  verbose: {}\n", effective.verbose));
+    out.push_str(&format!(
+        "  profile: {}\n",
+        format!("{:?}", effective.profile).to_lowercase()
+    ));
+    for (key, value) in &check.settings {
+        out.push_str(&format!("  {key} = {value}\n"));
+    }
+    out
+}
+
+/// Global flags as given on the command line, to be layered over `[defaults]`.
+fn global_flags(cli: &Cli) -> Result<config::GlobalFlags, String> {
+    let output = if cli.output.is_some() {
+        cli.output
+    } else if cli.json {
+        Some(OutputFormat::Json)
+    } else if cli.csv || cli.tsv || cli.delimiter.is_some() {
+        Some(OutputFormat::Csv)
     } else if cli.table {
-        OutputFormat::Table
-    } else if let Some(ref fmt) = cfg.defaults.output {
-        OutputFormat::parse(fmt).unwrap_or(OutputFormat::Human)
+        Some(OutputFormat::Table)
+    } else {
+        None
+    };
+    Ok(config::GlobalFlags {
+        output,
+        no_color: cli.no_color,
+        verbose: cli.verbose,
+        profile: cli
+            .profile
+            .as_deref()
+            .map(TimeoutProfile::parse)
+            .transpose()?,
+    })
+}
+
+/// How a batch run treats targets that fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FailurePolicy {
+    /// Report every failure and exit non-zero.
+    Report,
+    /// Stop at the first failure and exit non-zero (`--fail-fast`).
+    FailFast,
+    /// Report failures but exit 0 (`--ignore-errors`).
+    Ignore,
+}
+
+/// Turn a rendered batch into the command result.
+///
+/// With several targets, successful output is printed first and each failure is
+/// reported on stderr; a single target keeps the plain error message.
+fn finish_batch(
+    total: usize,
+    (output, failed): (String, Vec<(String, String)>),
+    policy: FailurePolicy,
+) -> Result<String, String> {
+    if failed.is_empty() {
+        return Ok(output);
+    }
+    if policy == FailurePolicy::Ignore {
+        for (target, e) in &failed {
+            eprintln!("{} {target}: {e}", "Error:".red().bold());
+        }
+        return Ok(output);
+    }
+    if total == 1 {
+        return Err(failed[0].1.clone());
+    }
+    print!("{output}");
+    for (target, e) in &failed {
+        eprintln!("{} {target}: {e}", "Error:".red().bold());
+    }
+    if policy == FailurePolicy::FailFast {
+        Err(format!(
+            "stopped after {} failed (--fail-fast)",
+            failed[0].0
+        ))
     } else {
-        OutputFormat::Human
+        Err(format!("{} of {total} targets failed", failed.len()))
+    }
+}
+
+/// [`finish_batch`], except that with `--fail-fast` a target whose result is
+/// critical (`critical`, e.g. a ping with no replies) also fails the run.
+fn finish_checked_batch(
+    total: usize,
+    rendered: (String, Vec<(String, String)>),
+    policy: FailurePolicy,
+    critical: Option<String>,
+) -> Result<String, String> {
+    match (finish_batch(total, rendered, policy), critical) {
+        (Ok(output), Some(target)) if policy == FailurePolicy::FailFast => {
+            print!("{output}");
+            Err(format!("stopped after {target} failed (--fail-fast)"))
+        }
+        (result, _) => result,
+    }
+}
+
+/// Apply `--exclude-ports` to a resolved port list.
+fn apply_port_exclusions(ports: Vec<u16>, exclude: Option<&str>) -> Result<Vec<u16>, String> {
+    let Some(exclude) = exclude else {
+        return Ok(ports);
+    };
+    let ports =
+        netscout_core::port::exclude_ports(&ports, &netscout_core::port::parse_ports(exclude)?);
+    if ports.is_empty() {
+        return Err("No ports left to scan after --exclude-ports".to_string());
+    }
+    Ok(ports)
+}
+
+/// Stdout writer for records streamed while a command is still running.
+///
+/// Each record is flushed as soon as it is written when stdout is a terminal or
+/// with `--line-buffered`; otherwise output is block-buffered for throughput.
+struct RecordWriter {
+    out: BufWriter<Stdout>,
+    flush_each: bool,
+}
+
+impl RecordWriter {
+    fn new(line_buffered: bool) -> Self {
+        let stdout = std::io::stdout();
+        Self {
+            flush_each: line_buffered || stdout.is_terminal(),
+            out: BufWriter::new(stdout),
+        }
+    }
+
+    /// Write one record; a closed pipe ends the program quietly.
+    fn record(&mut self, text: &str) {
+        let written = self.out.write_all(text.as_bytes()).and_then(|()| {
+            if self.flush_each {
+                self.out.flush()
+            } else {
+                Ok(())
+            }
+        });
+        if written.is_err() {
+            std::process::exit(0);
+        }
+    }
+
+    fn finish(mut self) {
+        if self.out.flush().is_err() {
+            std::process::exit(0);
+        }
+    }
+}
+
+/// A progress bar on stderr for long scans, redrawn at most every 250ms.
+///
+/// Only installed when stderr is a terminal, so redirected logs stay clean.
+fn stderr_progress(label: &'static str) -> Option<netscout_core::progress::Progress> {
+    if !std::io::stderr().is_terminal() {
+        return None;
     }
+    let last_draw = std::sync::Mutex::new(None::<std::time::Instant>);
+    let progress = netscout_core::progress::Progress::new(move |done, total| {
+        let mut last_draw = last_draw.lock().unwrap_or_else(|e| e.into_inner());
+        if done >= total {
+            // Clear the bar so the result starts on a clean line
+            if last_draw.is_some() {
+                eprint!("\r\x1b[2K");
+            }
+            return;
+        }
+        if last_draw.is_some_and(|t| t.elapsed() < Duration::from_millis(250)) {
+            return;
+        }
+        *last_draw = Some(std::time::Instant::now());
+        let bar = netscout_core::progress::format_bar(done, total, 30);
+        eprint!("\r{} {}", label.dimmed(), bar.cyan());
+    });
+    Some(progress)
+}
+
+/// Write an `http --save` body to `path`, or to stdout when it is `-`.
+fn save_body(path: &std::path::Path, body: &[u8]) -> Result<(), String> {
+    if path == std::path::Path::new("-") {
+        let mut stdout = std::io::stdout().lock();
+        return stdout
+            .write_all(body)
+            .and_then(|_| stdout.flush())
+            .map_err(|e| format!("Failed to write body to stdout: {e}"));
+    }
+    std::fs::write(path, body).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// IP-to-organisation lookup used by `--owner`; lookup failures leave the owner empty.
+///
+/// Blocks on a whois query, so call it from `runtime::blocking`.
+fn owner_lookup(ip: &str) -> Option<String> {
+    netscout_core::whois::ip_owner(ip, Duration::from_secs(5))
+        .ok()
+        .flatten()
 }
 
 #[tokio::main]
 async fn main() -> Result<(), String> {
     let cli = Cli::parse();
 
+    // Validation has to run before a broken config stops every other command
+    if let Commands::Config {
+        action: ConfigAction::Validate,
+    } = cli.command
+    {
+        if cli.no_color {
+            colored::control::set_override(false);
+        }
+        let path = match &cli.config {
+            Some(path) => Some(std::path::PathBuf::from(path)),
+            None => config::config_path(),
+        };
+        let checked = path
+            .ok_or_else(|| "No home directory for ~/.netscout.toml; pass --config".to_string())
+            .and_then(|path| config::validate_config(&path));
+        match checked {
+            Ok(check) => print!("{}", format_config_check(&check)),
+            Err(e) => {
+                eprintln!("{} {e}", "Error:".red().bold());
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
     let cfg = match &cli.config {
         Some(path) => config::load_config_from(Some(path.into()))?,
         None => config::load_config()?,
     };
 
-    let no_color = cli.no_color || cfg.defaults.no_color.unwrap_or(false);
-    if no_color {
-        colored::control::set_override(false);
+    let effective = cfg.defaults.resolve(&global_flags(&cli)?)?;
+    match effective.color {
+        config::ColorMode::Never => colored::control::set_override(false),
+        config::ColorMode::Always => colored::control::set_override(true),
+        config::ColorMode::Auto => {}
+    }
+
+    let format = effective.output;
+    let verbose = effective.verbose;
+    match cli.locale.as_deref() {
+        Some("auto") => set_number_locale(NumberLocale::from_env()),
+        Some(name) => set_number_locale(NumberLocale::parse(name)?),
+        None => {}
     }
+    let profile = effective.profile;
+    // Timeout precedence: command --timeout, then the global one, then the config file
+    let global_timeout = cli.timeout;
+    let batch_opts = batch::BatchOptions {
+        fail_fast: cli.fail_fast,
+        max_concurrent: cli.max_concurrent.map(|n| n as usize),
+    };
+
+    if cli.fail_fast && cli.ignore_errors {
+        return Err("--fail-fast and --ignore-errors cannot be used together".to_string());
+    }
+    let policy = if cli.fail_fast {
+        FailurePolicy::FailFast
+    } else if cli.ignore_errors {
+        FailurePolicy::Ignore
+    } else {
+        FailurePolicy::Report
+    };
 
-    let format = get_format(&cli, &cfg);
+    let health = cli.health;
+    let only_errors = cli.only_errors;
+    let oneline = cli.oneline;
+    if oneline && (cli.output.is_some() || cli.json || cli.table || cli.csv || cli.tsv || health) {
+        return Err(
+            "--oneline cannot be used with --output, --json, --table, --csv, --tsv or --health"
+                .to_string(),
+        );
+    }
+    if format == OutputFormat::Prometheus
+        && !health
+        && !matches!(
+            cli.command,
+            Commands::Ping { .. } | Commands::Http { .. } | Commands::Cert { .. }
+        )
+    {
+        return Err("Prometheus output is only available for ping, http and cert".to_string());
+    }
+    if cli.tsv && cli.delimiter.is_some() {
+        return Err("--tsv and --delimiter cannot be used together".to_string());
+    }
+    if cli.tsv {
+        set_csv_delimiter('\t');
+    } else if let Some(ref delimiter) = cli.delimiter {
+        set_csv_delimiter(parse_csv_delimiter(delimiter)?);
+    }
+    if health || oneline {
+        colored::control::set_override(false);
+    }
+    // Render a result in the selected format, or as a health check with --health
+    macro_rules! render {
+        ($value:expr) => {
+            render!($value, format_output($value, format))
+        };
+        // `$formatted` replaces the plain `--output` rendering, e.g. for `http --show-headers`
+        ($value:expr, $formatted:expr) => {
+            if health {
+                format!("{}\n", format_health($value))
+            } else if oneline {
+                format_oneline($value)
+            } else {
+                let output = $formatted;
+                // Human output only shows the start time with --verbose
+                let started = (verbose && format == OutputFormat::Human)
+                    .then(|| netscout_core::timestamp::of($value))
+                    .flatten();
+                match started {
+                    Some(ts) => format!("{}\n{output}", format!("Started {ts}").dimmed()),
+                    None => output,
+                }
+            }
+        };
+    }
+    // Render a batch (one line per result with --oneline); --ignore-errors keeps failures as entries
+    macro_rules! render_batch {
+        ($items:expr) => {
+            if policy == FailurePolicy::Ignore {
+                // Failures stay in the output as error entries
+                let output = if oneline {
+                    batch::render_oneline_with_errors($items)
+                } else {
+                    batch::render_with_errors($items, format)
+                };
+                (output, Vec::new())
+            } else if oneline {
+                batch::render_oneline($items)
+            } else {
+                batch::render($items, format)
+            }
+        };
+    }
 
     let result: Result<String, String> = match cli.command {
         Commands::Ping {
-            target,
+            targets,
             count,
+            continuous,
             interval,
             timeout,
             port,
+            icmp,
+            owner,
+            duration,
+            classic,
         } => {
-            let config = netscout_core::ping::PingConfig {
-                target,
-                count: count.or(cfg.ping.count).unwrap_or(4),
+            let duration = duration
+                .map(|d| {
+                    Duration::try_from_secs_f64(d)
+                        .ok()
+                        .filter(|d| !d.is_zero())
+                        .ok_or_else(|| format!("Invalid duration: {d}"))
+                })
+                .transpose()?;
+            let base = netscout_core::ping::PingConfig {
+                target: String::new(),
+                count: if continuous {
+                    0
+                } else {
+                    count.or(cfg.ping.count).unwrap_or(4)
+                },
                 interval: Duration::from_millis(interval.or(cfg.ping.interval).unwrap_or(1000)),
-                timeout: Duration::from_millis(timeout.or(cfg.ping.timeout).unwrap_or(2000)),
+                timeout: profile.timeout(timeout.or(global_timeout).or(cfg.ping.timeout), 2000),
                 port: port.or(cfg.ping.port).unwrap_or(80),
+                duration,
+                mode: if icmp {
+                    netscout_core::ping::PingMode::Icmp
+                } else {
+                    netscout_core::ping::PingMode::Tcp
+                },
+            };
+            // A single target in human format is printed probe by probe
+            let streaming = targets.len() == 1
+                && format == OutputFormat::Human
+                && !health
+                && !oneline
+                && !owner;
+            if base.count == 0 && base.duration.is_none() && !streaming {
+                Err(
+                    "--count 0 needs --duration unless pinging one target with human output"
+                        .to_string(),
+                )
+            } else if streaming {
+                let config = netscout_core::ping::PingConfig {
+                    target: targets[0].clone(),
+                    ..base
+                };
+                let mut out = RecordWriter::new(cli.line_buffered);
+                // Ctrl-C ends the session and still prints the summary so far
+                let interrupted = async {
+                    if tokio::signal::ctrl_c().await.is_err() {
+                        std::future::pending::<()>().await;
+                    }
+                };
+                let stats = netscout_core::ping::ping_until(
+                    &config,
+                    |p| {
+                        if classic {
+                            if p.seq == 0 {
+                                out.record(&format!(
+                                    "PING {} ({}) via {}\n",
+                                    config.target,
+                                    p.addr,
+                                    config.mode.label()
+                                ));
+                            }
+                            if !(only_errors && p.success) {
+                                out.record(&ping_probe_line_classic(p));
+                            }
+                            return;
+                        }
+                        if p.seq == 0 {
+                            out.record(&format!(
+                                "{} {} ({})\n",
+                                "PING".green().bold(),
+                                config.target,
+                                p.addr
+                            ));
+                        }
+                        if !(only_errors && p.success) {
+                            out.record(&ping_probe_line(p));
+                        }
+                    },
+                    interrupted,
+                )
+                .await;
+                if let Ok(ref stats) = stats {
+                    out.record(&if classic {
+                        ping_summary_classic(stats)
+                    } else {
+                        ping_summary(stats)
+                    });
+                }
+                out.finish();
+                stats.map(|_| String::new())
+            } else {
+                let mut items = batch::run_batch_checked(&targets, &batch_opts, |target| {
+                    let config = netscout_core::ping::PingConfig {
+                        target,
+                        ..base.clone()
+                    };
+                    async move { netscout_core::ping::ping(&config).await }
+                })
+                .await;
+                if owner {
+                    // whois lookups block, so run them on the blocking pool
+                    items = netscout_core::runtime::blocking(move || {
+                        for stats in items.iter_mut().filter_map(|i| i.result.as_mut().ok()) {
+                            stats.enrich_owner(owner_lookup);
+                        }
+                        Ok(items)
+                    })
+                    .await?;
+                }
+                let critical = batch::first_critical(&items).map(str::to_string);
+                if only_errors && !health {
+                    batch::retain_failing(&mut items, |s| s.lost > 0);
+                    for stats in items.iter_mut().filter_map(|i| i.result.as_mut().ok()) {
+                        stats.retain_failures();
+                    }
+                }
+                if health {
+                    Ok(format!("{}\n", batch::health(&items).to_json()))
+                } else if classic && format == OutputFormat::Human && !oneline {
+                    let mut output = String::new();
+                    let mut failed = Vec::new();
+                    for item in items {
+                        match item.result {
+                            Ok(stats) => output.push_str(&format_ping_classic(&stats, base.mode)),
+                            Err(e) => failed.push((item.target, e)),
+                        }
+                    }
+                    finish_checked_batch(targets.len(), (output, failed), policy, critical)
+                } else {
+                    finish_checked_batch(targets.len(), render_batch!(items), policy, critical)
+                }
+            }
+        }
+        Commands::Dns {
+            action:
+                Some(DnsAction::Fastest {
+                    resolvers,
+                    domains,
+                    record_type,
+                }),
+            timeout,
+            ..
+        } => {
+            let list = |s: &str| -> Vec<String> {
+                s.split(',')
+                    .map(|r| r.trim().to_string())
+                    .filter(|r| !r.is_empty())
+                    .collect()
+            };
+            let domains = match domains {
+                Some(d) => list(&d),
+                None => netscout_core::dns::BENCH_DOMAINS
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect(),
+            };
+            let mut config = netscout_core::dns::DnsConfig {
+                record_type: netscout_core::dns::RecordType::from_str_loose(&record_type)
+                    .ok_or_else(|| format!("Unknown record type: {record_type}"))?,
+                ..Default::default()
             };
-            netscout_core::ping::ping(&config)
+            config.timeout = profile.timeout(timeout.or(global_timeout).or(cfg.dns.timeout), 5000);
+            netscout_core::dns::fastest_resolver(&config, &list(&resolvers), &domains)
+                .await
+                .map(|r| render!(&r))
+        }
+        Commands::Dns { domain: None, .. } => Err("A domain to query is required".to_string()),
+        Commands::Dns {
+            domain: Some(domain),
+            record_type,
+            system: true,
+            ..
+        } => {
+            // Only an explicit --type narrows the address family
+            let rt = record_type
+                .map(|t| {
+                    netscout_core::dns::RecordType::from_str_loose(&t)
+                        .ok_or_else(|| format!("Unknown record type: {t}"))
+                })
+                .transpose()?;
+            netscout_core::dns::system_resolve_async(&domain, rt)
                 .await
-                .map(|r| format_output(&r, format))
+                .map(|r| render!(&r))
         }
         Commands::Dns {
-            domain,
+            domain: Some(domain),
             record_type,
             resolver,
+            client_subnet,
+            compare,
+            first_response,
+            quorum,
+            parallel,
+            split_check,
+            dnssec,
+            verify_reverse,
+            monitor_set,
+            no_recurse,
+            cd,
+            tcp,
+            timeout,
+            ..
         } => {
             let rt_str = record_type
                 .or(cfg.dns.record_type.clone())
@@ -219,58 +1127,188 @@ async fn main() -> Result<(), String> {
                 resolver: resolver
                     .or(cfg.dns.resolver.clone())
                     .unwrap_or_else(|| "8.8.8.8".to_string()),
+                client_subnet: client_subnet
+                    .as_deref()
+                    .map(netscout_core::dns::ClientSubnet::parse)
+                    .transpose()?,
+                recursion_desired: !no_recurse,
+                checking_disabled: cd,
+                tcp,
+                timeout: profile.timeout(timeout.or(global_timeout).or(cfg.dns.timeout), 5000),
                 ..Default::default()
             };
-            netscout_core::dns::query(&config).map(|r| format_output(&r, format))
+            match (compare, split_check, monitor_set) {
+                (Some(list), _, _) => {
+                    let resolvers: Vec<String> = list
+                        .split(',')
+                        .map(|r| r.trim().to_string())
+                        .filter(|r| !r.is_empty())
+                        .collect();
+                    let mode = match (first_response, quorum) {
+                        (true, _) => netscout_core::dns::MultiMode::FirstResponse,
+                        (_, Some(n)) => netscout_core::dns::MultiMode::Quorum(n),
+                        _ => netscout_core::dns::MultiMode::All,
+                    };
+                    let parallel = parallel.map(|n| n as usize);
+                    netscout_core::dns::query_multi(&config, &resolvers, mode, parallel)
+                        .await
+                        .map(|r| render!(&r))
+                }
+                (None, Some(internal), _) => netscout_core::dns::split_check(&config, &internal)
+                    .await
+                    .map(|r| render!(&r)),
+                (None, None, Some(types)) => {
+                    let types = netscout_core::dns::parse_record_types(&types)?;
+                    netscout_core::dns::monitor_set(&config, &types)
+                        .await
+                        .map(|r| render!(&r))
+                }
+                (None, None, None) if dnssec => netscout_core::dns::dnssec_check_async(&config)
+                    .await
+                    .map(|r| render!(&r)),
+                (None, None, None) if verify_reverse => {
+                    netscout_core::dns::verify_reverse_async(&config)
+                        .await
+                        .map(|r| render!(&r))
+                }
+                (None, None, None) => netscout_core::dns::query_async(&config)
+                    .await
+                    .map(|r| render!(&r)),
+            }
         }
         Commands::Port {
             target,
             ports,
+            quick,
+            top,
+            exclude_ports,
+            dry_run,
             timeout,
             parallel,
+            security_notes,
+            checkpoint,
+            banner,
         } => {
-            let port_list = match ports {
-                Some(p) => netscout_core::port::parse_ports(&p)?,
-                None => netscout_core::port::top_ports(),
+            let port_list = match (ports, top) {
+                (Some(p), _) => netscout_core::port::parse_ports(&p)?,
+                (None, Some(n)) => netscout_core::port::top_ports()
+                    .into_iter()
+                    .take(usize::from(n))
+                    .collect(),
+                (None, None) if quick => netscout_core::port::quick_ports(),
+                (None, None) => netscout_core::port::top_ports(),
             };
+            let port_list = apply_port_exclusions(port_list, exclude_ports.as_deref())?;
+            if dry_run {
+                print!("{}", format_dry_run(&target, None, &port_list, format));
+                return Ok(());
+            }
             let config = netscout_core::port::PortConfig {
                 target,
                 ports: port_list,
-                timeout: Duration::from_millis(timeout.or(cfg.port.timeout).unwrap_or(2000)),
+                timeout: profile.timeout(timeout.or(global_timeout).or(cfg.port.timeout), 2000),
                 parallel: parallel.or(cfg.port.parallel).unwrap_or(100),
+                security_notes,
+                checkpoint,
+                banner,
+                progress: stderr_progress("Scanning ports"),
             };
             netscout_core::port::scan(&config)
                 .await
-                .map(|r| format_output(&r, format))
+                .map(|r| render!(&r))
         }
         Commands::Trace {
-            target,
+            targets,
             max_hops,
             timeout,
+            queries,
+            owner,
+            dot,
         } => {
-            let config = netscout_core::trace::TraceConfig {
-                target,
+            let defaults = netscout_core::trace::TraceConfig::default();
+            let base = netscout_core::trace::TraceConfig {
                 max_hops: max_hops.or(cfg.trace.max_hops).unwrap_or(30),
-                timeout: Duration::from_millis(timeout.or(cfg.trace.timeout).unwrap_or(2000)),
-                ..Default::default()
+                timeout: profile.timeout(timeout.or(global_timeout).or(cfg.trace.timeout), 2000),
+                probes_per_hop: queries.unwrap_or(defaults.probes_per_hop),
+                ..defaults
             };
-            netscout_core::trace::trace(&config)
-                .await
-                .map(|r| format_output(&r, format))
+            let mut items = batch::run_batch(&targets, &batch_opts, |target| {
+                let config = netscout_core::trace::TraceConfig {
+                    target,
+                    ..base.clone()
+                };
+                async move { netscout_core::trace::trace(&config).await }
+            })
+            .await;
+            if owner {
+                // whois lookups block, so run them on the blocking pool
+                items = netscout_core::runtime::blocking(move || {
+                    for r in items.iter_mut().filter_map(|i| i.result.as_mut().ok()) {
+                        r.enrich_owner(owner_lookup);
+                    }
+                    Ok(items)
+                })
+                .await?;
+            }
+            if only_errors && !health {
+                batch::retain_failing(&mut items, |t| t.has_failures());
+                for r in items.iter_mut().filter_map(|i| i.result.as_mut().ok()) {
+                    r.retain_failures();
+                }
+            }
+            if health {
+                Ok(format!("{}\n", batch::health(&items).to_json()))
+            } else if dot && !oneline {
+                let (ok, failed): (Vec<_>, Vec<_>) =
+                    items.into_iter().partition(|i| i.result.is_ok());
+                let traces: Vec<_> = ok.into_iter().filter_map(|i| i.result.ok()).collect();
+                let failed = failed
+                    .into_iter()
+                    .filter_map(|i| i.result.err().map(|e| (i.target, e)))
+                    .collect();
+                let dot = if traces.is_empty() {
+                    String::new()
+                } else {
+                    netscout_core::trace::to_dot(&traces)
+                };
+                finish_batch(targets.len(), (dot, failed), policy)
+            } else {
+                finish_batch(targets.len(), render_batch!(items), policy)
+            }
         }
         Commands::Http {
             url,
             method,
             headers,
+            headers_file,
             follow,
+            keep_alive,
+            max_ttfb,
+            http2,
+            ca_file,
+            tls_info,
+            compression,
+            warmup,
+            throughput,
+            save,
+            max_body,
+            show_headers,
+            timeout,
         } => {
-            let parsed_headers: Vec<(String, String)> = headers
+            let body_to_stdout = save.as_deref() == Some(std::path::Path::new("-"));
+            let flag_headers: Vec<(String, String)> = headers
                 .iter()
-                .filter_map(|h| {
-                    h.split_once(':')
-                        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
-                })
+                .filter_map(|h| netscout_core::http::parse_header(h))
                 .collect();
+            let file_headers = match headers_file {
+                Some(path) => {
+                    let content = std::fs::read_to_string(&path)
+                        .map_err(|e| format!("Failed to read {path}: {e}"))?;
+                    netscout_core::http::parse_headers_file(&content)?
+                }
+                None => Vec::new(),
+            };
+            let parsed_headers = netscout_core::http::merge_headers(file_headers, flag_headers);
             let config = netscout_core::http::HttpConfig {
                 url,
                 method: method
@@ -278,22 +1316,120 @@ async fn main() -> Result<(), String> {
                     .unwrap_or_else(|| "GET".to_string()),
                 headers: parsed_headers,
                 follow_redirects: follow || cfg.http.follow.unwrap_or(false),
+                connection: if keep_alive {
+                    netscout_core::http::ConnectionMode::KeepAlive
+                } else {
+                    netscout_core::http::ConnectionMode::Close
+                },
+                max_ttfb: max_ttfb.map(Duration::from_millis),
+                http2,
+                ca_file,
+                tls_info,
+                compression,
+                warmup,
+                throughput,
+                max_body: max_body.unwrap_or(netscout_core::http::DEFAULT_MAX_BODY),
+                keep_body: save.is_some(),
+                timeout: profile.timeout(timeout.or(global_timeout).or(cfg.http.timeout), 10_000),
                 ..Default::default()
             };
-            netscout_core::http::probe(&config).map(|r| format_output(&r, format))
+            netscout_core::http::probe_async(&config)
+                .await
+                .and_then(|r| {
+                    if let (Some(path), Some(body)) = (&save, &r.body) {
+                        save_body(path, body)?;
+                    }
+                    // With `--save -` the body went to stdout in place of the report
+                    let output = if body_to_stdout {
+                        String::new()
+                    } else {
+                        render!(
+                            &r,
+                            match format {
+                                OutputFormat::Human if show_headers => r.to_human_with_headers(),
+                                OutputFormat::Table if show_headers => r.to_table_with_headers(),
+                                _ => format_output(&r, format),
+                            }
+                        )
+                    };
+                    if r.ttfb_exceeded() && !health {
+                        // Show the result, then fail on the SLA breach
+                        print!("{output}");
+                        Err(format!(
+                            "TTFB {:.1}ms exceeds --max-ttfb {}ms",
+                            r.timing.ttfb_ms,
+                            max_ttfb.unwrap_or_default()
+                        ))
+                    } else {
+                        Ok(output)
+                    }
+                })
         }
-        Commands::Cert { host, port } => {
-            let config = netscout_core::cert::CertConfig {
-                host,
-                port: port.or(cfg.cert.port).unwrap_or(443),
-                ..Default::default()
-            };
-            netscout_core::cert::inspect(&config).map(|r| format_output(&r, format))
+        Commands::Cert {
+            hosts,
+            port,
+            count,
+            ca_file,
+            insecure,
+            verify_hostname,
+            expiring_within,
+            critical_within,
+            timeout,
+        } => {
+            let port = port.or(cfg.cert.port).unwrap_or(443);
+            let timeout = profile.timeout(timeout.or(global_timeout).or(cfg.cert.timeout), 10_000);
+            let mut items = batch::run_batch_checked(&hosts, &batch_opts, |host| {
+                let config = netscout_core::cert::CertConfig {
+                    host,
+                    port,
+                    count,
+                    ca_file: ca_file.clone(),
+                    insecure,
+                    verify_hostname,
+                    timeout,
+                };
+                async move { netscout_core::cert::inspect_async(&config).await }
+            })
+            .await;
+            let critical = batch::first_critical(&items).map(str::to_string);
+            if only_errors && !health {
+                batch::retain_failing(&mut items, |c| c.health().status != HealthStatus::Passing);
+            }
+            if let Some(window) = expiring_within {
+                let critical = critical_within.unwrap_or(netscout_core::health::CERT_CRITICAL_DAYS);
+                let mut certs = Vec::new();
+                let mut failed = Vec::new();
+                for item in items {
+                    match item.result {
+                        Ok(r) => certs.push(r),
+                        Err(e) => failed.push((item.target, e)),
+                    }
+                }
+                let report = netscout_core::cert::ExpiryReport::new(&certs, window, critical);
+                let output = render!(&report);
+                if report.critical_count() > 0 && !health {
+                    print!("{output}");
+                    for (target, e) in &failed {
+                        eprintln!("{} {target}: {e}", "Error:".red().bold());
+                    }
+                    Err(format!(
+                        "{} certificate(s) expire within {critical} days",
+                        report.critical_count()
+                    ))
+                } else {
+                    finish_batch(hosts.len(), (output, failed), policy)
+                }
+            } else if health {
+                Ok(format!("{}\n", batch::health(&items).to_json()))
+            } else {
+                finish_checked_batch(hosts.len(), render_batch!(items), policy, critical)
+            }
         }
         Commands::Speed {
             url,
             download_only,
             upload_only,
+            timeout,
         } => {
             let config = netscout_core::speed::SpeedConfig {
                 download_url: url.unwrap_or_else(|| {
@@ -301,45 +1437,233 @@ async fn main() -> Result<(), String> {
                 }),
                 download_only,
                 upload_only,
+                timeout: profile.timeout(timeout.or(global_timeout), 30_000),
                 ..Default::default()
             };
-            netscout_core::speed::test_speed(&config).map(|r| format_output(&r, format))
+            netscout_core::speed::test_speed_async(&config)
+                .await
+                .map(|r| render!(&r))
         }
-        Commands::Whois { target } => {
+        Commands::Whois {
+            target,
+            server,
+            query,
+            timeout,
+        } => {
             let config = netscout_core::whois::WhoisConfig {
                 target,
-                ..Default::default()
+                server,
+                query_override: query,
+                timeout: profile.timeout(timeout.or(global_timeout), 10_000),
             };
-            netscout_core::whois::query(&config).map(|r| format_output(&r, format))
+            netscout_core::whois::query_async(&config)
+                .await
+                .map(|r| render!(&r))
+        }
+        Commands::Netif { gateway: true, .. } => {
+            netscout_core::netif::list_gateways().map(|r| render!(&r))
         }
-        Commands::Netif { up_only } => netscout_core::netif::list_interfaces().map(|mut r| {
+        Commands::Netif { up_only, .. } => netscout_core::netif::list_interfaces().map(|mut r| {
             if up_only {
                 r.interfaces.retain(|i| i.is_up);
                 r.total = r.interfaces.len();
             }
-            format_output(&r, format)
+            render!(&r)
         }),
         Commands::Scan {
             subnet,
+            hosts,
+            hosts_file,
             ports,
+            exclude_ports,
+            dry_run,
             timeout,
+            stream,
+            delay,
+            jitter,
+            adaptive_timeout,
+            timeout_floor,
+            timeout_ceiling,
+            no_resolve,
+            ping_sweep,
         } => {
             let port_list = match ports {
                 Some(p) => netscout_core::port::parse_ports(&p)?,
                 None => vec![22, 80, 443, 8080],
             };
+            let port_list = apply_port_exclusions(port_list, exclude_ports.as_deref())?;
+            let hosts = match (hosts, hosts_file) {
+                (Some(list), _) => netscout_core::scan::parse_host_list(&list),
+                (None, Some(path)) => {
+                    let content = std::fs::read_to_string(&path)
+                        .map_err(|e| format!("Failed to read {path}: {e}"))?;
+                    netscout_core::scan::parse_host_list(&content)
+                }
+                (None, None) => Vec::new(),
+            };
+            if subnet.is_none() && hosts.is_empty() {
+                return Err("No hosts to scan".to_string());
+            }
+            if dry_run {
+                let (target, count) = match subnet {
+                    Some(ref cidr) => {
+                        (cidr.clone(), netscout_core::scan::parse_subnet(cidr)?.len())
+                    }
+                    None => (format!("{} listed hosts", hosts.len()), hosts.len()),
+                };
+                print!(
+                    "{}",
+                    format_dry_run(&target, Some(count), &port_list, format)
+                );
+                return Ok(());
+            }
+            if timeout_floor
+                .zip(timeout_ceiling)
+                .is_some_and(|(f, c)| f > c)
+            {
+                return Err("--timeout-floor must not exceed --timeout-ceiling".to_string());
+            }
             let config = netscout_core::scan::LanScanConfig {
-                subnet,
+                subnet: subnet.unwrap_or_default(),
+                hosts,
                 ports: port_list,
-                timeout: Duration::from_millis(timeout.or(cfg.scan.timeout).unwrap_or(500)),
+                timeout: profile.timeout(timeout.or(global_timeout).or(cfg.scan.timeout), 500),
+                inter_host_delay: delay.map(Duration::from_millis),
+                jitter: jitter.map(Duration::from_millis),
+                adaptive_timeout: adaptive_timeout.then(|| {
+                    let defaults = netscout_core::scan::AdaptiveTimeout::default();
+                    netscout_core::scan::AdaptiveTimeout {
+                        floor: timeout_floor.map_or(defaults.floor, Duration::from_millis),
+                        ceiling: timeout_ceiling.map_or(defaults.ceiling, Duration::from_millis),
+                        ..defaults
+                    }
+                }),
+                progress: stderr_progress("Scanning hosts"),
+                rdns_timeout: netscout_core::scan::LanScanConfig::default()
+                    .rdns_timeout
+                    .filter(|_| !no_resolve),
+                ping_sweep,
+                ..Default::default()
+            };
+            if stream && format != OutputFormat::Json {
+                Err("--stream requires --json".to_string())
+            } else if stream && !health {
+                let mut out = RecordWriter::new(cli.line_buffered);
+                let json_line = |event: &netscout_core::scan::ScanEvent| {
+                    serde_json::to_string(event)
+                        .map_or_else(|e| format!("JSON error: {e}\n"), |j| j + "\n")
+                };
+                let result = netscout_core::scan::scan_streaming(&config, |host| {
+                    out.record(&json_line(&netscout_core::scan::ScanEvent::Host(
+                        host.clone(),
+                    )));
+                })
+                .await;
+                if let Ok(ref r) = result {
+                    out.record(&json_line(&netscout_core::scan::ScanEvent::summary(r)));
+                }
+                out.finish();
+                result.map(|_| String::new())
+            } else {
+                netscout_core::scan::scan(&config)
+                    .await
+                    .map(|r| render!(&r))
+            }
+        }
+        Commands::Reach {
+            target,
+            ports,
+            http,
+            timeout,
+        } => {
+            let mut config = netscout_core::reach::ReachConfig {
+                http_url: http.then(|| format!("http://{target}/")),
+                target,
                 ..Default::default()
             };
-            netscout_core::scan::scan(&config)
+            if let Some(p) = ports {
+                config.ports = netscout_core::port::parse_ports(&p)?;
+            }
+            config.timeout = match timeout.or(global_timeout) {
+                Some(t) => Duration::from_millis(t),
+                None => profile.scale(config.timeout),
+            };
+            let r = netscout_core::reach::reach(&config).await?;
+            let output = render!(&r);
+            if r.reachable || health {
+                Ok(output)
+            } else {
+                print!("{output}");
+                Err(format!("{} is unreachable", r.target))
+            }
+        }
+        Commands::Pubip {
+            endpoint,
+            endpoint_v6,
+            ptr,
+            owner,
+            timeout,
+        } => {
+            let defaults = netscout_core::pubip::PubipConfig::default();
+            let endpoint = endpoint.or(cfg.pubip.endpoint);
+            // A custom endpoint means no request goes to the default IPv6 service either
+            let endpoint_v6 = match endpoint_v6.or(cfg.pubip.endpoint_v6) {
+                Some(e) => Some(e),
+                None if endpoint.is_some() => None,
+                None => defaults.endpoint_v6,
+            };
+            let config = netscout_core::pubip::PubipConfig {
+                endpoint: endpoint.unwrap_or(defaults.endpoint),
+                endpoint_v6,
+                ptr,
+                owner,
+                timeout: profile.timeout(timeout.or(global_timeout), 5000),
+            };
+            netscout_core::pubip::lookup(&config)
                 .await
-                .map(|r| format_output(&r, format))
+                .map(|r| render!(&r))
         }
+        Commands::Report {
+            host,
+            ip_url,
+            timeout,
+        } => {
+            let mut config = netscout_core::report::ReportConfig {
+                probe_host: host,
+                public_ip_url: ip_url,
+                ..Default::default()
+            };
+            config.timeout = match timeout.or(global_timeout) {
+                Some(t) => Duration::from_millis(t),
+                None => profile.scale(config.timeout),
+            };
+            let r = netscout_core::report::report(&config).await;
+            Ok(render!(&r))
+        }
+        Commands::Diff { old, new } => {
+            let read = |path: &str| {
+                std::fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))
+            };
+            netscout_core::diff::diff_json(&read(&old)?, &read(&new)?).map(|r| render!(&r))
+        }
+        Commands::Config { .. } => unreachable!("handled before the config is loaded"),
+        Commands::Data { table } => match table {
+            DataTable::Ports => serde_json::to_string_pretty(&netscout_core::port::port_data())
+                .map(|json| format!("{json}\n"))
+                .map_err(|e| format!("JSON error: {e}")),
+        },
     };
 
+    if health {
+        let check = match result {
+            Ok(output) => serde_json::from_str::<HealthCheck>(&output)
+                .unwrap_or_else(|_| HealthCheck::from_error("result has no health summary")),
+            Err(e) => HealthCheck::from_error(&e),
+        };
+        println!("{}", check.to_json());
+        std::process::exit(check.status.exit_code());
+    }
+
     match result {
         Ok(output) => print!("{output}"),
         Err(e) => {
@@ -59,6 +59,18 @@ fn test_port_help() {
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("ports"));
     assert!(stdout.contains("parallel"));
+    assert!(stdout.contains("--exclude-ports"));
+}
+
+#[test]
+fn test_port_exclude_all_ports_fails() {
+    let output = netscout_bin()
+        .args(["port", "127.0.0.1", "-p", "80", "--exclude-ports", "80"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No ports left to scan"));
 }
 
 #[test]
@@ -67,6 +79,7 @@ fn test_trace_help() {
     assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("max-hops"));
+    assert!(stdout.contains("--dot"));
 }
 
 #[test]
@@ -102,6 +115,14 @@ fn test_whois_help() {
     assert!(output.status.success());
 }
 
+#[test]
+fn test_report_help() {
+    let output = netscout_bin().args(["report", "--help"]).output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("--ip-url"));
+}
+
 #[test]
 fn test_scan_help() {
     let output = netscout_bin().args(["scan", "--help"]).output().unwrap();
@@ -170,6 +191,17 @@ fn test_dns_missing_domain() {
     assert!(!output.status.success());
 }
 
+#[test]
+fn test_dns_fastest_rejects_unknown_type() {
+    let output = netscout_bin()
+        .args(["dns", "fastest", "--type", "BOGUS"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Unknown record type: BOGUS"));
+}
+
 #[test]
 fn test_global_table_flag() {
     let output = netscout_bin().args(["--table", "netif"]).output().unwrap();
@@ -187,11 +219,250 @@ fn test_global_csv_flag() {
     assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
     // CSV output should have header row with commas
-    assert!(stdout.contains("name,state,type,mtu,addresses"));
+    assert!(stdout.contains("name,state,type,mtu,mac,vendor,addresses"));
     // Should have at least one data row
     assert!(stdout.lines().count() >= 2);
 }
 
+#[test]
+fn test_global_table_flag_for_ping() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let output = netscout_bin()
+        .args(["--table", "ping", "127.0.0.1", "-c", "1", "-p", &port])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("SEQ"));
+    assert!(stdout.contains("STATUS"));
+    assert!(stdout.contains("RTT"));
+}
+
+#[test]
+fn test_json_ping_is_object_for_one_target_and_array_for_several() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let run = |targets: &[&str]| {
+        let output = netscout_bin()
+            .arg("--json")
+            .arg("ping")
+            .args(targets)
+            .args(["-c", "1", "-p", &port])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+    let single = run(&["127.0.0.1"]);
+    assert_eq!(single["target"], "127.0.0.1");
+    let several = run(&["127.0.0.1", "localhost"]);
+    assert_eq!(several.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_output_flag_selects_format() {
+    let output = netscout_bin().args(["-o", "c", "netif"]).output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("name,state,type,mtu,mac,vendor,addresses"));
+}
+
+#[test]
+fn test_output_flag_overrides_json() {
+    let output = netscout_bin()
+        .args(["--json", "--output", "table", "netif"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("NAME"));
+    assert!(serde_json::from_str::<serde_json::Value>(&stdout).is_err());
+}
+
+#[test]
+fn test_output_flag_rejects_unknown_format() {
+    let output = netscout_bin()
+        .args(["-o", "xml", "netif"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown output format: 'xml'"));
+}
+
+#[test]
+fn test_tsv_flag_uses_tabs() {
+    let output = netscout_bin().args(["--tsv", "netif"]).output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.lines().next(),
+        Some("name\tstate\ttype\tmtu\tmac\tvendor\taddresses")
+    );
+}
+
+#[test]
+fn test_delimiter_flag_implies_csv() {
+    let output = netscout_bin()
+        .args(["--delimiter", ";", "netif"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.lines().next(),
+        Some("name;state;type;mtu;mac;vendor;addresses")
+    );
+}
+
+#[test]
+fn test_tsv_conflicts_with_delimiter() {
+    let output = netscout_bin()
+        .args(["--tsv", "--delimiter", ";", "netif"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--tsv and --delimiter cannot be used together"));
+}
+
+#[test]
+fn test_config_defaults_select_output() {
+    let path = std::env::temp_dir().join(format!("netscout-config-{}.toml", std::process::id()));
+    std::fs::write(
+        &path,
+        "[defaults]\noutput = \"table\"\nprofile = \"slow\"\n",
+    )
+    .unwrap();
+    let config = path.to_str().unwrap();
+    let output = netscout_bin()
+        .args(["--config", config, "netif"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("MTU"));
+    // A format flag still wins over the config.
+    let output = netscout_bin()
+        .args(["--config", config, "--csv", "netif"])
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).ok();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.lines().next(),
+        Some("name,state,type,mtu,mac,vendor,addresses")
+    );
+}
+
+#[test]
+fn test_port_dry_run_top_prints_ports_without_connecting() {
+    // An unresolvable target proves there is no DNS lookup or connect
+    let output = netscout_bin()
+        .args(["port", "--top", "5", "--dry-run", "host.invalid"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let ports = stdout
+        .lines()
+        .find_map(|l| l.strip_prefix("Ports (5): "))
+        .unwrap();
+    assert_eq!(ports.split(',').count(), 5);
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let output = netscout_bin()
+        .args(["--json", "port", "127.0.0.1", "--ports", &port, "--dry-run"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let plan: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(plan["ports"][0].as_u64(), Some(port.parse().unwrap()));
+    assert!(listener.accept().is_err(), "dry run connected");
+}
+
+#[test]
+fn test_scan_dry_run_counts_hosts() {
+    let output = netscout_bin()
+        .args(["scan", "10.1.2.0/24", "--ports", "22,80", "--dry-run"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Hosts: 254"));
+    assert!(stdout.contains("Ports (2): 22,80"));
+}
+
+#[test]
+fn test_config_validate() {
+    let path = std::env::temp_dir().join(format!(
+        "netscout-config-validate-{}.toml",
+        std::process::id()
+    ));
+    std::fs::write(
+        &path,
+        "[defaults]\noutput = \"json\"\n\n[ping]\ncount = 3\n",
+    )
+    .unwrap();
+    let config = path.to_str().unwrap();
+    let output = netscout_bin()
+        .args(["--no-color", "config", "validate", "--config", config])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("is valid"));
+    assert!(stdout.contains("output:  json"));
+    assert!(stdout.contains("ping.count = 3"));
+
+    std::fs::write(&path, "[ping]\ncount = 3\nintervall = 500\n").unwrap();
+    let output = netscout_bin()
+        .args(["config", "validate", "--config", config])
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).ok();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown key `intervall` in [ping]"));
+}
+
+#[test]
+fn test_prometheus_rejected_for_unsupported_command() {
+    let output = netscout_bin()
+        .args(["-o", "prometheus", "port", "127.0.0.1", "-p", "1"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("only available for ping, http and cert"));
+}
+
+#[test]
+fn test_only_errors_hides_successful_probes() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let output = netscout_bin()
+        .args([
+            "--json",
+            "--only-errors",
+            "ping",
+            "127.0.0.1",
+            "--port",
+            &port,
+        ])
+        .args(["--count", "2", "--interval", "10"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["probes"], serde_json::json!([]));
+    assert_eq!(json["sent"], 2);
+}
+
 #[test]
 fn test_csv_netif_parseable() {
     let output = netscout_bin().args(["--csv", "netif"]).output().unwrap();
@@ -199,12 +470,12 @@ fn test_csv_netif_parseable() {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let lines: Vec<&str> = stdout.lines().collect();
     // Header
-    assert_eq!(lines[0], "name,state,type,mtu,addresses");
-    // Each data row should have at least 4 commas (5 fields)
+    assert_eq!(lines[0], "name,state,type,mtu,mac,vendor,addresses");
+    // Each data row should have at least 6 commas (7 fields)
     for line in &lines[1..] {
         assert!(
-            line.matches(',').count() >= 4,
-            "CSV row should have at least 5 fields: {line}"
+            line.matches(',').count() >= 6,
+            "CSV row should have at least 7 fields: {line}"
         );
     }
 }
@@ -216,3 +487,857 @@ fn test_ping_port_flag_help() {
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("--port"));
 }
+
+#[test]
+fn test_dns_invalid_client_subnet() {
+    let output = netscout_bin()
+        .args(["dns", "example.com", "--client-subnet", "203.0.113.0/40"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("prefix"));
+}
+
+#[test]
+fn test_fail_fast_stops_after_first_failure() {
+    let output = netscout_bin()
+        .args([
+            "--fail-fast",
+            "ping",
+            "nonexistent.invalid",
+            "127.0.0.1",
+            "--count",
+            "3",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // The second target's ping never completed
+    assert!(!stdout.contains("127.0.0.1"));
+    assert!(stderr.contains("nonexistent.invalid"));
+    assert!(stderr.contains("--fail-fast"));
+}
+
+#[test]
+fn test_fail_fast_stops_on_dead_but_resolvable_target() {
+    // Nothing listens on port 1, so every probe is lost but ping itself succeeds
+    let output = netscout_bin()
+        .args([
+            "--fail-fast",
+            "--oneline",
+            "ping",
+            "127.0.0.1",
+            "localhost",
+            "-p",
+            "1",
+            "-c",
+            "1",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stdout.lines().count(), 1);
+    assert!(stderr.contains("--fail-fast"));
+}
+
+#[test]
+fn test_diff_port_scans() {
+    let dir = std::env::temp_dir().join(format!("netscout-diff-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let scan = |ports: &[(u16, &str)]| {
+        let ports: Vec<String> = ports
+            .iter()
+            .map(|(p, s)| format!(r#"{{"port":{p},"open":true,"service":"{s}","rtt_ms":1.0}}"#))
+            .collect();
+        format!(
+            r#"{{"target":"example.com","resolved_addr":"93.184.216.34","ports":[{}],"open_count":{},"closed_count":0,"scan_time_ms":10.0}}"#,
+            ports.join(","),
+            ports.len()
+        )
+    };
+    let old = dir.join("old.json");
+    let new = dir.join("new.json");
+    std::fs::write(&old, scan(&[(22, "ssh"), (80, "http")])).unwrap();
+    std::fs::write(&new, scan(&[(80, "http"), (443, "https")])).unwrap();
+
+    let output = netscout_bin()
+        .args(["--no-color", "diff"])
+        .arg(&old)
+        .arg(&new)
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("+ port 443"));
+    assert!(stdout.contains("- port 22"));
+}
+
+#[test]
+fn test_diff_saved_ping_output() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let dir = std::env::temp_dir().join(format!("netscout-diff-ping-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut files = Vec::new();
+    for name in ["old.json", "new.json"] {
+        let output = netscout_bin()
+            .args(["--json", "ping", "127.0.0.1", "-c", "1", "-p", &port])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let path = dir.join(name);
+        std::fs::write(&path, &output.stdout).unwrap();
+        files.push(path);
+    }
+
+    let output = netscout_bin()
+        .args(["--no-color", "diff"])
+        .args(&files)
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("ping 127.0.0.1"));
+}
+
+#[test]
+fn test_diff_missing_file() {
+    let output = netscout_bin()
+        .args(["diff", "/nonexistent/a.json", "/nonexistent/b.json"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_reach_open_local_port() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let output = netscout_bin()
+        .args(["--json", "reach", "127.0.0.1", "--ports", &port.to_string()])
+        .args(["--timeout", "500"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["reachable"], true);
+    assert_eq!(json["port"]["open_port"], port);
+}
+
+#[test]
+fn test_reach_invalid_ports() {
+    let output = netscout_bin()
+        .args(["reach", "127.0.0.1", "--ports", "notaport"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_http_headers_file_missing() {
+    let output = netscout_bin()
+        .args([
+            "http",
+            "http://127.0.0.1:1/",
+            "--headers-file",
+            "/nonexistent/headers.txt",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("/nonexistent/headers.txt"));
+}
+
+#[test]
+fn test_port_quick_conflicts_with_ports() {
+    let output = netscout_bin()
+        .args(["port", "127.0.0.1", "--quick", "--ports", "80"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used with"));
+}
+
+#[test]
+fn test_data_ports_json() {
+    let output = netscout_bin().args(["data", "ports"]).output().unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["services"]["80"], "http");
+    assert!(!json["top_ports"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_health_passing_for_open_port() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let output = netscout_bin()
+        .args([
+            "--health",
+            "reach",
+            "127.0.0.1",
+            "--ports",
+            &port.to_string(),
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["status"], "passing");
+}
+
+#[test]
+fn test_health_critical_on_failure() {
+    let output = netscout_bin()
+        .args(["--health", "http", "http://127.0.0.1:1/"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["status"], "critical");
+    assert!(json["output"].as_str().unwrap().contains("Connect failed"));
+}
+
+#[test]
+fn test_ping_invalid_duration() {
+    let output = netscout_bin()
+        .args(["ping", "127.0.0.1", "--duration", "0"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Invalid duration"));
+}
+
+#[test]
+fn test_unsupported_locale() {
+    let output = netscout_bin()
+        .args(["--locale", "xx_YY", "dns", "example.com"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Unsupported locale"));
+}
+
+#[test]
+fn test_unknown_profile() {
+    let output = netscout_bin()
+        .args(["--profile", "turbo", "dns", "example.com"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Unknown profile"));
+}
+
+#[test]
+fn test_dns_quorum_requires_compare() {
+    let output = netscout_bin()
+        .args(["dns", "example.com", "--quorum", "2"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--compare"));
+}
+
+#[test]
+fn test_dns_quorum_larger_than_resolvers() {
+    let output = netscout_bin()
+        .args([
+            "dns",
+            "example.com",
+            "--compare",
+            "127.0.0.1:1",
+            "--quorum",
+            "2",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Quorum must be between 1 and 1"));
+}
+
+#[test]
+fn test_dns_quorum_fails_when_every_resolver_refuses() {
+    let refusing = || {
+        let sock = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = sock.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 512];
+            let (len, src) = sock.recv_from(&mut buf).unwrap();
+            let mut resp = buf[..len].to_vec();
+            resp[2] = 0x81; // QR + RD
+            resp[3] = 0x85; // RA, REFUSED
+            resp[10] = 0;
+            resp[11] = 0;
+            sock.send_to(&resp, src).unwrap();
+        });
+        addr.to_string()
+    };
+    let list = format!("{},{}", refusing(), refusing());
+    let output = netscout_bin()
+        .args(["dns", "example.com", "--compare", &list, "--quorum", "2"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No resolver answered"));
+    assert!(stderr.contains("REFUSED"));
+}
+
+#[test]
+fn test_ping_streams_probes_incrementally() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::time::{Duration, Instant};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let mut child = netscout_bin()
+        .args(["--line-buffered", "ping", "127.0.0.1", "-p", &port])
+        .args(["-c", "3", "-i", "500"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut seen = Vec::new();
+    for line in stdout.lines() {
+        let line = line.unwrap();
+        if line.contains("seq=") {
+            seen.push(Instant::now());
+        }
+    }
+    assert!(child.wait().unwrap().success());
+    assert_eq!(seen.len(), 3);
+    // Buffered output would arrive all at once when the process exits
+    assert!(seen[2] - seen[0] >= Duration::from_millis(700));
+}
+
+#[test]
+fn test_ping_unlimited_count_needs_duration_for_json() {
+    let output = netscout_bin()
+        .args(["--json", "ping", "127.0.0.1", "-c", "0"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--count 0 needs --duration"));
+}
+
+#[test]
+fn test_dns_system_resolves_localhost() {
+    let output = netscout_bin()
+        .args(["--json", "dns", "localhost", "--system"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["resolver"], "system");
+    let addresses = json["addresses"].as_array().unwrap();
+    assert!(addresses.iter().any(|a| a == "127.0.0.1" || a == "::1"));
+}
+
+#[test]
+fn test_dns_system_conflicts_with_resolver() {
+    let output = netscout_bin()
+        .args(["dns", "localhost", "--system", "--resolver", "1.1.1.1"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_ping_continuous_conflicts_with_count() {
+    let output = netscout_bin()
+        .args(["ping", "127.0.0.1", "--continuous", "--count", "3"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used with"), "stderr: {stderr}");
+}
+
+#[test]
+fn test_ping_icmp_conflicts_with_port() {
+    let output = netscout_bin()
+        .args(["ping", "127.0.0.1", "--icmp", "--port", "443"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used with"), "stderr: {stderr}");
+}
+
+#[test]
+fn test_dns_monitor_set_rejects_unknown_type() {
+    let output = netscout_bin()
+        .args(["dns", "example.com", "--monitor-set", "A,BOGUS"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Unknown record type: BOGUS"),
+        "stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_dns_verify_reverse_conflicts_with_type() {
+    let output = netscout_bin()
+        .args(["dns", "example.com", "--verify-reverse", "-t", "MX"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used with"), "stderr: {stderr}");
+}
+
+#[test]
+fn test_max_concurrent_rejects_zero() {
+    let output = netscout_bin()
+        .args(["--max-concurrent", "0", "ping", "127.0.0.1"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_ping_classic_summary_line() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let output = netscout_bin()
+        .args([
+            "ping",
+            "127.0.0.1",
+            "-p",
+            &port,
+            "-c",
+            "2",
+            "-i",
+            "50",
+            "--classic",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("2 packets transmitted, 2 received, 0% packet loss"));
+    assert!(stdout.contains("rtt min/avg/max/mdev = "));
+}
+
+#[test]
+fn test_scan_stream_emits_host_lines_then_summary() {
+    let listener = std::net::TcpListener::bind("0.0.0.0:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let output = netscout_bin()
+        .args(["--json", "scan", "127.0.0.0/30", "--stream", "-p", &port])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let lines: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["type"], "host");
+    assert_eq!(lines[1]["type"], "host");
+    assert_eq!(lines[2]["type"], "summary");
+    assert_eq!(lines[2]["hosts_found"], 2);
+}
+
+#[test]
+fn test_scan_stream_requires_json() {
+    let output = netscout_bin()
+        .args(["scan", "127.0.0.0/30", "--stream"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--stream requires --json"));
+}
+
+#[test]
+fn test_oneline_port_summary() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let output = netscout_bin()
+        .args(["port", "127.0.0.1", "--ports", &port, "--oneline"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout, format!("PORT 127.0.0.1: 1 open ({port})\n"));
+}
+
+#[test]
+fn test_oneline_conflicts_with_json() {
+    let output = netscout_bin()
+        .args(["--json", "ping", "127.0.0.1", "--oneline"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used with"));
+
+    let output = netscout_bin()
+        .args(["-o", "csv", "ping", "127.0.0.1", "--oneline"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+/// Serve one HTTP request on localhost, waiting `delay` before the first byte.
+fn delayed_http_server(delay: std::time::Duration) -> u16 {
+    use std::io::{Read, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 1024];
+        while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut chunk).unwrap();
+            if n == 0 {
+                return;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        std::thread::sleep(delay);
+        let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+    });
+    port
+}
+
+#[test]
+fn test_http_save_writes_body() {
+    let port = delayed_http_server(std::time::Duration::ZERO);
+    let output = netscout_bin()
+        .args(["http", &format!("http://127.0.0.1:{port}/"), "--save", "-"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"ok");
+
+    let path = std::env::temp_dir().join(format!("netscout-cli-save-{}.txt", std::process::id()));
+    let port = delayed_http_server(std::time::Duration::ZERO);
+    let output = netscout_bin()
+        .args(["http", &format!("http://127.0.0.1:{port}/"), "--save"])
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("200"));
+    assert_eq!(std::fs::read(&path).unwrap(), b"ok");
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_http_max_ttfb_trips_on_slow_server() {
+    let port = delayed_http_server(std::time::Duration::from_millis(300));
+    let output = netscout_bin()
+        .args([
+            "http",
+            &format!("http://127.0.0.1:{port}/"),
+            "--max-ttfb",
+            "100",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("200 OK"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("exceeds --max-ttfb 100ms"));
+}
+
+#[test]
+fn test_http_max_ttfb_passes_on_fast_server() {
+    let port = delayed_http_server(std::time::Duration::ZERO);
+    let output = netscout_bin()
+        .args([
+            "http",
+            &format!("http://127.0.0.1:{port}/"),
+            "--max-ttfb",
+            "2000",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_http_show_headers_lists_response_headers() {
+    let run = |extra: &[&str]| {
+        let port = delayed_http_server(std::time::Duration::ZERO);
+        let url = format!("http://127.0.0.1:{port}/");
+        let output = netscout_bin()
+            .args(["--no-color", "http", &url])
+            .args(extra)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    assert!(run(&["--show-headers"]).contains("content-length: 2"));
+    assert!(run(&["-i", "--table"]).contains("content-length: 2"));
+    assert!(!run(&[]).contains("content-length"));
+}
+
+#[test]
+fn test_http2_rejects_plain_http_url() {
+    let output = netscout_bin()
+        .args(["http", "http://127.0.0.1:1/", "--http2"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("requires an https:// URL"));
+}
+
+#[test]
+fn test_http_ca_file_and_tls_info_over_https_without_http2() {
+    use netscout_core::testutil::{response, spawn_https_server, testdata};
+    let port = spawn_https_server(1, response(200, "OK", &[], b"ok"));
+    let output = netscout_bin()
+        .args(["--json", "http", &format!("https://127.0.0.1:{port}/")])
+        .args(["--tls-info", "--ca-file"])
+        .arg(testdata("test-ca.pem"))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["status"], 200);
+    assert_eq!(json["tls_info"]["version"], "TLSv1.3");
+}
+
+#[test]
+fn test_ignore_errors_reports_failures_and_exits_zero() {
+    let output = netscout_bin()
+        .args([
+            "--ignore-errors",
+            "--json",
+            "ping",
+            "nonexistent.invalid",
+            "127.0.0.1",
+            "--count",
+            "1",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_str(&String::from_utf8_lossy(&output.stdout)).unwrap();
+    let entries = json.as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["target"], "nonexistent.invalid");
+    assert!(entries[0]["error"].is_string());
+    assert_eq!(entries[1]["target"], "127.0.0.1");
+    assert!(entries[1].get("error").is_none());
+}
+
+#[test]
+fn test_ignore_errors_conflicts_with_fail_fast() {
+    let output = netscout_bin()
+        .args(["--ignore-errors", "--fail-fast", "ping", "127.0.0.1"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used together"));
+}
+
+#[test]
+fn test_scan_hosts_file() {
+    let listener = std::net::TcpListener::bind("0.0.0.0:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let path = std::env::temp_dir().join(format!("netscout-hosts-{}.txt", std::process::id()));
+    std::fs::write(&path, "# lab\n127.0.0.1\n127.0.0.2\n").unwrap();
+    let output = netscout_bin()
+        .args([
+            "--json",
+            "scan",
+            "--hosts-file",
+            path.to_str().unwrap(),
+            "-p",
+            &port,
+        ])
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_str(&String::from_utf8_lossy(&output.stdout)).unwrap();
+    assert_eq!(json["total_scanned"], 2);
+    assert_eq!(json["hosts_found"], 2);
+}
+
+#[test]
+fn test_scan_requires_subnet_or_hosts() {
+    let output = netscout_bin().args(["scan"]).output().unwrap();
+    assert!(!output.status.success());
+    let output = netscout_bin()
+        .args(["scan", "10.0.0.0/24", "--hosts", "10.0.0.1"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used with"));
+}
+
+#[test]
+fn test_cert_critical_within_requires_expiring_within() {
+    let output = netscout_bin()
+        .args(["cert", "example.com", "--critical-within", "7"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--expiring-within"));
+}
+
+#[test]
+fn test_pubip_custom_endpoint() {
+    use std::io::{Read, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0u8; 1024];
+        let _ = stream.read(&mut buf);
+        let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 14\r\n\r\n198.51.100.23\n");
+    });
+    let output = netscout_bin()
+        .args([
+            "--json",
+            "pubip",
+            "--endpoint",
+            &format!("http://127.0.0.1:{port}/"),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_str(&String::from_utf8_lossy(&output.stdout)).unwrap();
+    assert_eq!(json["ipv4"]["ip"], "198.51.100.23");
+    // A custom endpoint disables the default IPv6 lookup
+    assert!(json["ipv6"].is_null());
+}
+
+#[test]
+fn test_cert_verify_hostname_fails_on_name_mismatch() {
+    use netscout_core::testutil::{spawn_tls_server, testdata};
+    // The test certificate covers only 127.0.0.1, not "localhost"
+    let ca_file = testdata("test-ca.pem");
+    let port = spawn_tls_server("localhost", 2);
+    let cert = |extra: &[&str]| {
+        netscout_bin()
+            .args(["cert", "localhost", "-p", &port.to_string(), "--ca-file"])
+            .arg(&ca_file)
+            .args(extra)
+            .output()
+            .unwrap()
+    };
+
+    let output = cert(&[]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("does not cover localhost"));
+
+    let output = cert(&["--verify-hostname"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("does not cover localhost (valid for 127.0.0.1)"));
+}
+
+#[test]
+fn test_scan_timeout_floor_requires_adaptive_timeout() {
+    let output = netscout_bin()
+        .args(["scan", "--hosts", "127.0.0.1", "--timeout-floor", "10"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--adaptive-timeout"));
+}
+
+#[test]
+fn test_global_timeout_applies_to_dns() {
+    // Without the global flag the query would wait the built-in 5s
+    let start = std::time::Instant::now();
+    let output = netscout_bin()
+        .args([
+            "--timeout",
+            "100",
+            "dns",
+            "example.com",
+            "--resolver",
+            "192.0.2.1",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(start.elapsed() < std::time::Duration::from_secs(2));
+}
+
+#[test]
+fn test_timeout_after_subcommand() {
+    // `dns fastest` has no --timeout of its own; the flag reaches it after the subcommand
+    let start = std::time::Instant::now();
+    let output = netscout_bin()
+        .args([
+            "dns",
+            "fastest",
+            "--resolvers",
+            "192.0.2.1",
+            "--domains",
+            "example.com",
+            "--timeout",
+            "100",
+        ])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("0/1 answered"));
+    assert!(start.elapsed() < std::time::Duration::from_secs(2));
+
+    let output = netscout_bin()
+        .args(["netif", "--timeout", "100"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_command_timeout_overrides_global() {
+    // Accepts connections (via the backlog) but never answers
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let start = std::time::Instant::now();
+    let output = netscout_bin()
+        .args(["--timeout", "20000", "http", &url, "--timeout", "200"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    drop(listener);
+}
@@ -0,0 +1,150 @@
+//! Host and port handling shared by the probes, including IPv6 zone ids
+//! such as `fe80::1%eth0`.
+
+use std::net::{Ipv6Addr, SocketAddr, SocketAddrV6, ToSocketAddrs};
+
+/// Split a `%zone` suffix off an IPv6 literal, dropping any brackets.
+///
+/// Accepts `fe80::1%eth0`, `[fe80::1%eth0]` and the URL form `[fe80::1%25eth0]`.
+/// Hosts without a zone are returned unchanged apart from the brackets.
+pub fn split_zone(host: &str) -> (&str, Option<&str>) {
+    let host = host
+        .strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host);
+    match host.split_once('%') {
+        Some((ip, zone)) if ip.contains(':') => {
+            let zone = zone
+                .strip_prefix("25")
+                .filter(|z| !z.is_empty())
+                .unwrap_or(zone);
+            (ip, Some(zone))
+        }
+        _ => (host, None),
+    }
+}
+
+/// Scope id for a zone: either a numeric interface index or an interface name.
+pub fn scope_id(zone: &str) -> Result<u32, String> {
+    if let Ok(index) = zone.parse() {
+        return Ok(index);
+    }
+    crate::netif::interface_index(zone).ok_or_else(|| format!("Unknown interface: {zone}"))
+}
+
+/// Join a host and port, bracketing IPv6 literals: `[::1]:443`.
+pub fn join_host_port(host: &str, port: u16) -> String {
+    if host.contains(':') && !host.starts_with('[') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    }
+}
+
+/// Split `host:port` (or `[v6]:port`) as found in a URL authority.
+///
+/// Brackets are kept on the host; the port is `None` when absent.
+pub fn split_host_port(authority: &str) -> Result<(&str, Option<u16>), String> {
+    let (host, port) = if authority.starts_with('[') {
+        match authority.find(']') {
+            Some(end) => match &authority[end + 1..] {
+                "" => (authority, None),
+                rest => match rest.strip_prefix(':') {
+                    Some(p) => (&authority[..=end], Some(p)),
+                    None => return Err(format!("Invalid host: {authority}")),
+                },
+            },
+            None => return Err(format!("Invalid host: {authority}")),
+        }
+    } else {
+        match authority.rsplit_once(':') {
+            Some((h, p)) => (h, Some(p)),
+            None => (authority, None),
+        }
+    };
+    let port = port
+        .map(|p| p.parse().map_err(|_| format!("Invalid port: {p}")))
+        .transpose()?;
+    Ok((host, port))
+}
+
+/// Resolve a host and port to socket addresses.
+///
+/// IPv6 literals may carry a zone (`fe80::1%eth0`), which becomes the scope id
+/// that link-local addresses need in order to connect.
+pub fn resolve(host: &str, port: u16) -> std::io::Result<Vec<SocketAddr>> {
+    let (name, zone) = split_zone(host);
+    if let Ok(ip) = name.parse::<Ipv6Addr>() {
+        let scope = zone
+            .map(scope_id)
+            .transpose()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?
+            .unwrap_or(0);
+        return Ok(vec![SocketAddr::V6(SocketAddrV6::new(ip, port, 0, scope))]);
+    }
+    Ok((name, port).to_socket_addrs()?.collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_zone() {
+        assert_eq!(split_zone("fe80::1%lo"), ("fe80::1", Some("lo")));
+        assert_eq!(split_zone("[fe80::1%25eth0]"), ("fe80::1", Some("eth0")));
+        assert_eq!(split_zone("[::1]"), ("::1", None));
+        assert_eq!(split_zone("example.com"), ("example.com", None));
+    }
+
+    #[test]
+    fn test_resolve_sets_scope_id_from_zone() {
+        let addrs = resolve("fe80::1%lo", 80).unwrap();
+        let SocketAddr::V6(v6) = addrs[0] else {
+            panic!("expected an IPv6 address");
+        };
+        assert_eq!(*v6.ip(), "fe80::1".parse::<Ipv6Addr>().unwrap());
+        assert_eq!(v6.port(), 80);
+        assert_eq!(Some(v6.scope_id()), crate::netif::interface_index("lo"));
+        assert_ne!(v6.scope_id(), 0);
+
+        let SocketAddr::V6(numeric) = resolve("[fe80::1%7]", 443).unwrap()[0] else {
+            panic!("expected an IPv6 address");
+        };
+        assert_eq!(numeric.scope_id(), 7);
+    }
+
+    #[test]
+    fn test_resolve_unknown_zone_fails() {
+        let err = resolve("fe80::1%no-such-if0", 80).unwrap_err();
+        assert!(err.to_string().contains("Unknown interface: no-such-if0"));
+    }
+
+    #[test]
+    fn test_resolve_plain_addresses() {
+        assert_eq!(
+            resolve("127.0.0.1", 22).unwrap(),
+            vec!["127.0.0.1:22".parse::<SocketAddr>().unwrap()]
+        );
+        assert_eq!(
+            resolve("[::1]", 22).unwrap(),
+            vec!["[::1]:22".parse::<SocketAddr>().unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_join_and_split_host_port() {
+        assert_eq!(join_host_port("::1", 80), "[::1]:80");
+        assert_eq!(join_host_port("example.com", 80), "example.com:80");
+        assert_eq!(
+            split_host_port("[fe80::1%25eth0]:8080").unwrap(),
+            ("[fe80::1%25eth0]", Some(8080))
+        );
+        assert_eq!(split_host_port("[::1]").unwrap(), ("[::1]", None));
+        assert_eq!(
+            split_host_port("example.com:81").unwrap(),
+            ("example.com", Some(81))
+        );
+        assert!(split_host_port("[::1]x").is_err());
+    }
+}
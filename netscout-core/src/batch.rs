@@ -0,0 +1,604 @@
+use crate::health::{self, HealthCheck, HealthStatus};
+use crate::output::{format_many, format_oneline, format_output, HumanReadable};
+use crate::OutputFormat;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Options for running one check against several targets.
+#[derive(Debug, Clone, Default)]
+pub struct BatchOptions {
+    /// Stop at the first failure and cancel checks still in flight.
+    pub fail_fast: bool,
+    /// Most targets checked at the same time; `None` runs them all at once.
+    pub max_concurrent: Option<usize>,
+}
+
+/// Outcome of a check for a single target.
+#[derive(Debug, Clone)]
+pub struct BatchItem<T> {
+    pub target: String,
+    pub result: Result<T, String>,
+}
+
+/// Run `check` against every target concurrently.
+///
+/// Results are returned in target order. With `fail_fast`, the first failure
+/// aborts the remaining checks and only completed ones are returned. With
+/// `max_concurrent`, later targets wait for a free slot before starting.
+pub async fn run_batch<T, F, Fut>(
+    targets: &[String],
+    options: &BatchOptions,
+    check: F,
+) -> Vec<BatchItem<T>>
+where
+    T: Send + 'static,
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T, String>> + Send + 'static,
+{
+    run_batch_until(targets, options, check, Result::is_err).await
+}
+
+/// Like [`run_batch`], but with `fail_fast` a result that is critical as a
+/// health check (a ping with no replies, a certificate failing its check)
+/// also stops the batch.
+pub async fn run_batch_checked<T, F, Fut>(
+    targets: &[String],
+    options: &BatchOptions,
+    check: F,
+) -> Vec<BatchItem<T>>
+where
+    T: HumanReadable + Send + 'static,
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T, String>> + Send + 'static,
+{
+    run_batch_until(targets, options, check, is_critical).await
+}
+
+/// Whether a check failed or its result is critical as a health check.
+pub fn is_critical<T: HumanReadable>(result: &Result<T, String>) -> bool {
+    result
+        .as_ref()
+        .map_or(true, |v| v.health().status == HealthStatus::Critical)
+}
+
+/// First target whose check succeeded but whose result is critical.
+pub fn first_critical<T: HumanReadable>(items: &[BatchItem<T>]) -> Option<&str> {
+    items
+        .iter()
+        .find(|i| i.result.is_ok() && is_critical(&i.result))
+        .map(|i| i.target.as_str())
+}
+
+async fn run_batch_until<T, F, Fut>(
+    targets: &[String],
+    options: &BatchOptions,
+    check: F,
+    failed: fn(&Result<T, String>) -> bool,
+) -> Vec<BatchItem<T>>
+where
+    T: Send + 'static,
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T, String>> + Send + 'static,
+{
+    let limit = Arc::new(Semaphore::new(
+        options
+            .max_concurrent
+            .unwrap_or(Semaphore::MAX_PERMITS)
+            .clamp(1, Semaphore::MAX_PERMITS),
+    ));
+    let mut set = JoinSet::new();
+    for (i, target) in targets.iter().enumerate() {
+        let fut = check(target.clone());
+        let limit = limit.clone();
+        set.spawn(async move {
+            let _permit = limit.acquire_owned().await;
+            (i, fut.await)
+        });
+    }
+
+    let mut done: Vec<(usize, Result<T, String>)> = Vec::with_capacity(targets.len());
+    while let Some(joined) = set.join_next().await {
+        let (i, result) = match joined {
+            Ok(r) => r,
+            Err(e) if e.is_cancelled() => continue,
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        };
+        let stop = options.fail_fast && failed(&result);
+        done.push((i, result));
+        if stop {
+            set.abort_all();
+            break;
+        }
+    }
+
+    done.sort_by_key(|(i, _)| *i);
+    done.into_iter()
+        .map(|(i, result)| BatchItem {
+            target: targets[i].clone(),
+            result,
+        })
+        .collect()
+}
+
+/// Number of failed items in a batch.
+pub fn failure_count<T>(items: &[BatchItem<T>]) -> usize {
+    items.iter().filter(|i| i.result.is_err()).count()
+}
+
+/// Drop targets whose check passed, for `--only-errors`.
+///
+/// Failed targets and results for which `failed` returns true stay. A lone
+/// target is always kept, so single-target runs still show their result.
+pub fn retain_failing<T>(items: &mut Vec<BatchItem<T>>, failed: impl Fn(&T) -> bool) {
+    if items.len() > 1 {
+        items.retain(|i| i.result.as_ref().map_or(true, &failed));
+    }
+}
+
+/// Format the successful results and collect `(target, error)` pairs for failures.
+///
+/// A single target renders as its plain result; JSON only becomes an array
+/// when several targets were checked.
+pub fn render<T: Serialize + HumanReadable>(
+    items: Vec<BatchItem<T>>,
+    format: OutputFormat,
+) -> (String, Vec<(String, String)>) {
+    let single = items.len() == 1;
+    let mut ok = Vec::new();
+    let mut failed = Vec::new();
+    for item in items {
+        match item.result {
+            Ok(v) => ok.push(v),
+            Err(e) => failed.push((item.target, e)),
+        }
+    }
+    let output = match ok.as_slice() {
+        [] => String::new(),
+        [value] if single => format_output(value, format),
+        _ => format_many(&ok, format),
+    };
+    (output, failed)
+}
+
+/// Like [`render`], but with one summary line per successful target.
+pub fn render_oneline<T: HumanReadable>(
+    items: Vec<BatchItem<T>>,
+) -> (String, Vec<(String, String)>) {
+    let mut output = String::new();
+    let mut failed = Vec::new();
+    for item in items {
+        match item.result {
+            Ok(v) => output.push_str(&format_oneline(&v)),
+            Err(e) => failed.push((item.target, e)),
+        }
+    }
+    (output, failed)
+}
+
+/// A failed target as it appears in `--ignore-errors` JSON output.
+#[derive(Serialize)]
+struct ErrorEntry {
+    target: String,
+    error: String,
+}
+
+/// Like [`render`], but failed targets stay in the output as error entries:
+/// `{"target", "error"}` objects in JSON and NDJSON, `# error:` comments in
+/// Prometheus output, an `Error:` line otherwise.
+pub fn render_with_errors<T: Serialize + HumanReadable>(
+    items: Vec<BatchItem<T>>,
+    format: OutputFormat,
+) -> String {
+    if format == OutputFormat::Json {
+        // Pretty-print entries one by one so results keep their field order
+        let entries: Vec<String> = items
+            .into_iter()
+            .map(|item| {
+                let entry = match item.result {
+                    Ok(v) => serde_json::to_string_pretty(&v),
+                    Err(error) => serde_json::to_string_pretty(&ErrorEntry {
+                        target: item.target,
+                        error,
+                    }),
+                };
+                entry.unwrap_or_else(|e| format!("\"JSON error: {e}\""))
+            })
+            .collect();
+        return match entries.as_slice() {
+            [] => "[]".to_string(),
+            [single] => single.clone(),
+            _ => {
+                // Indent each entry one level inside the array
+                let indented: Vec<String> =
+                    entries.iter().map(|e| e.replace('\n', "\n  ")).collect();
+                format!("[\n  {}\n]", indented.join(",\n  "))
+            }
+        };
+    }
+    if format == OutputFormat::Prometheus {
+        // Failures become comments, which scrapers and textfile collectors skip
+        let (output, failed) = render(items, format);
+        let comments: String = failed
+            .iter()
+            .map(|(target, e)| format!("# error: {target}: {}\n", e.replace('\n', " ")))
+            .collect();
+        return output + &comments;
+    }
+    if format == OutputFormat::Ndjson {
+        return items
+            .into_iter()
+            .map(|item| match item.result {
+                Ok(v) => format_output(&v, format),
+                Err(error) => serde_json::to_string(&ErrorEntry {
+                    target: item.target,
+                    error,
+                })
+                .map_or_else(|e| format!("JSON error: {e}\n"), |j| j + "\n"),
+            })
+            .collect();
+    }
+    items
+        .into_iter()
+        .map(|item| match item.result {
+            Ok(v) => format_output(&v, format),
+            Err(e) => format!("Error: {}: {e}\n", item.target),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Like [`render_oneline`], but failed targets get an `ERROR` line in place.
+pub fn render_oneline_with_errors<T: HumanReadable>(items: Vec<BatchItem<T>>) -> String {
+    items
+        .into_iter()
+        .map(|item| match item.result {
+            Ok(v) => format_oneline(&v),
+            Err(e) => format!("ERROR {}: {e}\n", item.target),
+        })
+        .collect()
+}
+
+/// Summarise a batch as one health check; failed targets count as critical.
+pub fn health<T: HumanReadable>(items: &[BatchItem<T>]) -> HealthCheck {
+    let checks: Vec<HealthCheck> = items
+        .iter()
+        .map(|item| match item.result {
+            Ok(ref v) => v.health(),
+            Err(ref e) => HealthCheck::from_error(&format!("{}: {e}", item.target)),
+        })
+        .collect();
+    health::combine(&checks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn targets(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_run_batch_all_succeed_in_order() {
+        let items = run_batch(
+            &targets(&["a", "b", "c"]),
+            &BatchOptions::default(),
+            |t| async move {
+                if t == "a" {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+                Ok::<_, String>(t.len())
+            },
+        )
+        .await;
+        let names: Vec<&str> = items.iter().map(|i| i.target.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        assert_eq!(failure_count(&items), 0);
+    }
+
+    #[tokio::test]
+    async fn test_run_batch_reports_all_failures_by_default() {
+        let items = run_batch(
+            &targets(&["bad", "good"]),
+            &BatchOptions::default(),
+            |t| async move {
+                if t == "bad" {
+                    Err(format!("{t} failed"))
+                } else {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    Ok(())
+                }
+            },
+        )
+        .await;
+        assert_eq!(items.len(), 2);
+        assert_eq!(failure_count(&items), 1);
+        assert!(items[1].result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_batch_fail_fast_cancels_remaining() {
+        let second_done = Arc::new(AtomicBool::new(false));
+        let options = BatchOptions {
+            fail_fast: true,
+            ..Default::default()
+        };
+        let flag = second_done.clone();
+        let items = run_batch(&targets(&["bad", "slow"]), &options, move |t| {
+            let flag = flag.clone();
+            async move {
+                if t == "bad" {
+                    return Err("connection refused".to_string());
+                }
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                flag.store(true, Ordering::SeqCst);
+                Ok(())
+            }
+        })
+        .await;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].target, "bad");
+        assert!(items[0].result.is_err());
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert!(!second_done.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_run_batch_checked_fail_fast_stops_on_critical_result() {
+        let options = BatchOptions {
+            fail_fast: true,
+            ..Default::default()
+        };
+        // "dead" answers but is critical, like a ping that lost every probe
+        let check = |t: String| async move {
+            if t == "slow" {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+            }
+            Ok(Probe { name: t })
+        };
+        let items = run_batch_checked(&targets(&["dead", "slow"]), &options, check).await;
+        assert_eq!(items.len(), 1);
+        assert_eq!(first_critical(&items), Some("dead"));
+
+        // Plain run_batch only stops on errors
+        let items = run_batch(&targets(&["dead", "slow"]), &options, check).await;
+        assert_eq!(items.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_batch_max_concurrent_limits_overlap() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let options = BatchOptions {
+            max_concurrent: Some(1),
+            ..Default::default()
+        };
+        let (r, p) = (running.clone(), peak.clone());
+        let items = run_batch(&targets(&["a", "b", "c"]), &options, move |t| {
+            let (running, peak) = (r.clone(), p.clone());
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok::<_, String>(t)
+            }
+        })
+        .await;
+        assert_eq!(items.len(), 3);
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_batch_unbounded_by_default() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (r, p) = (running.clone(), peak.clone());
+        run_batch(
+            &targets(&["a", "b", "c"]),
+            &BatchOptions::default(),
+            move |t| {
+                let (running, peak) = (r.clone(), p.clone());
+                async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    Ok::<_, String>(t)
+                }
+            },
+        )
+        .await;
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[derive(Serialize)]
+    struct Probe {
+        name: String,
+    }
+
+    impl HumanReadable for Probe {
+        fn to_human(&self) -> String {
+            format!("probe {}\n", self.name)
+        }
+
+        fn health(&self) -> HealthCheck {
+            let status = if self.name == "dead" {
+                HealthStatus::Critical
+            } else {
+                HealthStatus::Passing
+            };
+            HealthCheck::new(status, format!("probe {}", self.name), "")
+        }
+    }
+
+    #[test]
+    fn test_health_counts_failures_as_critical() {
+        let items = vec![
+            BatchItem {
+                target: "a".to_string(),
+                result: Ok(Probe { name: "a".into() }),
+            },
+            BatchItem {
+                target: "b".to_string(),
+                result: Err("timed out".to_string()),
+            },
+        ];
+        let check = health(&items);
+        assert_eq!(check.status, crate::health::HealthStatus::Critical);
+        assert_eq!(check.output, "probe a; b: timed out");
+    }
+
+    #[test]
+    fn test_retain_failing_keeps_errors_and_unhealthy_results() {
+        let ping = |lost: u32| crate::ping::PingStats {
+            target: format!("lost{lost}"),
+            resolved_addr: "127.0.0.1".to_string(),
+            probes: Vec::new(),
+            sent: 4,
+            received: 4 - lost,
+            lost,
+            loss_percent: lost as f64 * 25.0,
+            min_ms: None,
+            avg_ms: None,
+            max_ms: None,
+            stddev_ms: None,
+            mdev_ms: None,
+            jitter_ms: None,
+            p50_ms: None,
+            p95_ms: None,
+            p99_ms: None,
+            owner: None,
+            timestamp: String::new(),
+        };
+        let mut items = vec![
+            BatchItem {
+                target: "clean".to_string(),
+                result: Ok(ping(0)),
+            },
+            BatchItem {
+                target: "lossy".to_string(),
+                result: Ok(ping(1)),
+            },
+            BatchItem {
+                target: "down".to_string(),
+                result: Err("timed out".to_string()),
+            },
+        ];
+        retain_failing(&mut items, |s| s.lost > 0);
+        let names: Vec<&str> = items.iter().map(|i| i.target.as_str()).collect();
+        assert_eq!(names, vec!["lossy", "down"]);
+
+        let mut single = vec![BatchItem {
+            target: "clean".to_string(),
+            result: Ok(ping(0)),
+        }];
+        retain_failing(&mut single, |s| s.lost > 0);
+        assert_eq!(single.len(), 1);
+    }
+
+    #[test]
+    fn test_render_splits_failures() {
+        let items = vec![
+            BatchItem {
+                target: "a".to_string(),
+                result: Ok(Probe { name: "a".into() }),
+            },
+            BatchItem {
+                target: "b".to_string(),
+                result: Err("timed out".to_string()),
+            },
+        ];
+        let (output, failed) = render(items, OutputFormat::Json);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 1);
+        assert_eq!(failed, vec![("b".to_string(), "timed out".to_string())]);
+    }
+
+    #[test]
+    fn test_render_single_target_is_plain_object() {
+        let item = |result| {
+            vec![BatchItem {
+                target: "a".to_string(),
+                result,
+            }]
+        };
+        let (output, _) = render(item(Ok(Probe { name: "a".into() })), OutputFormat::Json);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["name"], "a");
+
+        let output = render_with_errors(item(Err("timed out".into())), OutputFormat::Json);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["error"], "timed out");
+
+        // Exactly the pretty-printed result, field indentation included
+        let probe = Probe { name: "a".into() };
+        let expected = serde_json::to_string_pretty(&probe).unwrap();
+        let output = render_with_errors(item(Ok(probe)), OutputFormat::Json);
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_render_oneline_one_line_per_target() {
+        let items = vec![
+            BatchItem {
+                target: "a".to_string(),
+                result: Ok(Probe { name: "a".into() }),
+            },
+            BatchItem {
+                target: "b".to_string(),
+                result: Err("timed out".to_string()),
+            },
+            BatchItem {
+                target: "c".to_string(),
+                result: Ok(Probe { name: "c".into() }),
+            },
+        ];
+        let (output, failed) = render_oneline(items);
+        assert_eq!(output, "probe a\nprobe c\n");
+        assert_eq!(failed.len(), 1);
+    }
+
+    #[test]
+    fn test_render_with_errors_keeps_failures_in_place() {
+        let items = || {
+            vec![
+                BatchItem {
+                    target: "a".to_string(),
+                    result: Ok(Probe { name: "a".into() }),
+                },
+                BatchItem {
+                    target: "b".to_string(),
+                    result: Err("timed out".to_string()),
+                },
+            ]
+        };
+        let json: serde_json::Value =
+            serde_json::from_str(&render_with_errors(items(), OutputFormat::Json)).unwrap();
+        let entries = json.as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1]["target"], "b");
+        assert_eq!(entries[1]["error"], "timed out");
+
+        let human = render_with_errors(items(), OutputFormat::Human);
+        assert!(human.contains("Error: b: timed out"));
+        let ndjson = render_with_errors(items(), OutputFormat::Ndjson);
+        let lines: Vec<serde_json::Value> = ndjson
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["error"], "timed out");
+        let prometheus = render_with_errors(items(), OutputFormat::Prometheus);
+        assert!(prometheus.ends_with("# error: b: timed out\n"));
+        assert_eq!(
+            render_oneline_with_errors(items()),
+            "probe a\nERROR b: timed out\n"
+        );
+    }
+}
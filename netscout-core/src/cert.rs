@@ -1,7 +1,13 @@
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, RootCertStore, SignatureScheme};
 use serde::Serialize;
 use std::io::{Read, Write};
-use std::net::{TcpStream, ToSocketAddrs};
-use std::sync::Arc;
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 /// Configuration for TLS certificate inspection.
@@ -10,6 +16,14 @@ pub struct CertConfig {
     pub host: String,
     pub port: u16,
     pub timeout: Duration,
+    /// Number of handshakes to perform; more than one reports timing statistics.
+    pub count: u32,
+    /// Extra PEM CA certificates to trust alongside the bundled Mozilla roots.
+    pub ca_file: Option<PathBuf>,
+    /// Report the chain even when it doesn't validate.
+    pub insecure: bool,
+    /// Fail when the certificate doesn't cover `host` or the chain doesn't validate.
+    pub verify_hostname: bool,
 }
 
 impl Default for CertConfig {
@@ -18,6 +32,10 @@ impl Default for CertConfig {
             host: String::new(),
             port: 443,
             timeout: Duration::from_secs(10),
+            count: 1,
+            ca_file: None,
+            insecure: false,
+            verify_hostname: false,
         }
     }
 }
@@ -32,6 +50,10 @@ pub struct CertInfo {
     pub days_until_expiry: i64,
     pub serial: String,
     pub is_ca: bool,
+    /// SHA-256 fingerprint of the DER, colon-separated hex.
+    pub sha256: String,
+    /// SHA-1 fingerprint of the DER, colon-separated hex.
+    pub sha1: String,
 }
 
 /// Result of a TLS certificate inspection.
@@ -44,19 +66,144 @@ pub struct CertResult {
     pub certificate_chain: Vec<CertInfo>,
     pub connection_time_ms: f64,
     pub warning: Option<String>,
+    /// Handshake timing across repeated connections (`count` > 1).
+    pub handshakes: Option<HandshakeStats>,
+    /// Whether the presented chain validates against the configured roots.
+    pub chain_valid: bool,
+    /// Whether the leaf certificate's common name or subject alternative names
+    /// cover `host`.
+    pub hostname_matches: bool,
+    /// Names the leaf certificate is valid for, reported when they don't cover `host`.
+    pub presented_names: Vec<String>,
+    /// When the inspection started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
+}
+
+/// Timing statistics across repeated TLS handshakes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HandshakeStats {
+    pub count: u32,
+    pub min_ms: f64,
+    pub avg_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+impl HandshakeStats {
+    /// Summarize handshake durations, or `None` if there are none.
+    pub fn from_samples(samples: &[f64]) -> Option<Self> {
+        Some(Self {
+            count: samples.len() as u32,
+            min_ms: samples.iter().copied().reduce(f64::min)?,
+            avg_ms: crate::stats::mean(samples)?,
+            p95_ms: crate::stats::percentile(samples, 95.0)?,
+            max_ms: samples.iter().copied().reduce(f64::max)?,
+        })
+    }
+}
+
+/// Connection details captured from a handshake.
+#[derive(Debug, Clone)]
+struct HandshakeDetails {
+    tls_version: String,
+    cipher_suite: String,
+    chain: Vec<CertInfo>,
+    /// Names the leaf certificate covers, from [`leaf_names`].
+    leaf_names: Vec<String>,
+}
+
+/// Timing of one handshake, with details when they were asked for.
+#[derive(Debug, Clone)]
+struct Handshake {
+    time_ms: f64,
+    details: Option<HandshakeDetails>,
 }
 
 /// Parse a DER-encoded X.509 certificate to extract basic fields.
-/// This is a simplified parser — a full ASN.1 parser would be more robust.
+///
+/// Falls back to scanning for common names, with unknown dates, when the
+/// certificate can't be decoded.
 fn parse_basic_cert_info(der: &[u8]) -> CertInfo {
-    // We'll extract what we can from the DER; for a robust solution,
-    // we'd use the x509-parser crate, but keeping deps minimal.
+    let info = match crate::x509::parse(der) {
+        Ok(cert) => cert_info(&cert, std::time::SystemTime::now()),
+        Err(_) => scan_cert_info(der),
+    };
+    CertInfo {
+        sha256: fingerprint::<sha2::Sha256>(der),
+        sha1: fingerprint::<sha1::Sha1>(der),
+        ..info
+    }
+}
+
+/// Digest of a certificate's DER in the familiar `AB:CD:...` form.
+pub fn fingerprint<D: sha2::Digest>(der: &[u8]) -> String {
+    colon_hex(&D::digest(der))
+}
+
+fn colon_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// The names a certificate covers: its subject alternative names plus its
+/// common name. Empty when the certificate can't be decoded.
+fn leaf_names(der: &[u8]) -> Vec<String> {
+    let Ok(cert) = crate::x509::parse(der) else {
+        return Vec::new();
+    };
+    let mut names = cert.subject_alt_names;
+    if let Some(cn) = cert.common_name {
+        if !names.iter().any(|n| n.eq_ignore_ascii_case(&cn)) {
+            names.push(cn);
+        }
+    }
+    names
+}
+
+/// Whether a certificate name covers `host`. A leading `*` label matches
+/// exactly one label of a DNS name, never part of an IP address.
+pub fn name_matches(name: &str, host: &str) -> bool {
+    let name = name.trim_end_matches('.');
+    let host = host.trim_end_matches('.');
+    if let Ok(ip) = host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        return name.parse::<IpAddr>() == Ok(ip);
+    }
+    match name.strip_prefix("*.") {
+        Some(parent) => host
+            .split_once('.')
+            .is_some_and(|(label, rest)| !label.is_empty() && rest.eq_ignore_ascii_case(parent)),
+        None => name.eq_ignore_ascii_case(host),
+    }
+}
+
+/// Report a decoded certificate, counting days until expiry from `now`.
+fn cert_info(cert: &crate::x509::Certificate, now: std::time::SystemTime) -> CertInfo {
+    const DAY: u64 = 86_400;
+    // Whole days, rounded down: a certificate that expired an hour ago is at -1
+    let days_until_expiry = match cert.not_after.duration_since(now) {
+        Ok(left) => (left.as_secs() / DAY) as i64,
+        Err(past) => -(past.duration().as_secs().div_ceil(DAY) as i64),
+    };
+    CertInfo {
+        subject: cert.subject.clone(),
+        issuer: cert.issuer.clone(),
+        not_before: crate::timestamp::format(cert.not_before),
+        not_after: crate::timestamp::format(cert.not_after),
+        days_until_expiry,
+        serial: colon_hex(&cert.serial),
+        is_ca: cert.is_ca,
+        sha256: String::new(),
+        sha1: String::new(),
+    }
+}
+
+/// Best-effort fields from DER that doesn't decode as a certificate.
+fn scan_cert_info(der: &[u8]) -> CertInfo {
     let hex_serial = if der.len() > 20 {
-        der[15..20]
-            .iter()
-            .map(|b| format!("{b:02X}"))
-            .collect::<Vec<_>>()
-            .join(":")
+        colon_hex(&der[15..20])
     } else {
         "unknown".to_string()
     };
@@ -69,6 +216,8 @@ fn parse_basic_cert_info(der: &[u8]) -> CertInfo {
         days_until_expiry: -1,
         serial: hex_serial,
         is_ca: false,
+        sha256: String::new(),
+        sha1: String::new(),
     }
 }
 
@@ -95,34 +244,25 @@ fn extract_cn_from_der(der: &[u8], _field: &str) -> String {
     "unknown".to_string()
 }
 
-/// Inspect TLS certificate for a host.
-pub fn inspect(config: &CertConfig) -> Result<CertResult, String> {
-    let addr = format!("{}:{}", config.host, config.port)
-        .to_socket_addrs()
-        .map_err(|e| format!("DNS failed: {e}"))?
-        .next()
-        .ok_or("No address")?;
-
+/// Perform one TLS handshake, capturing connection details if `capture` is set.
+fn handshake(
+    config: &CertConfig,
+    addr: &SocketAddr,
+    tls_config: &Arc<rustls::ClientConfig>,
+    capture: bool,
+) -> Result<Handshake, String> {
     let start = Instant::now();
 
-    // Set up rustls
-    let root_store =
-        rustls::RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
-
-    let tls_config = rustls::ClientConfig::builder()
-        .with_root_certificates(root_store)
-        .with_no_client_auth();
-
-    let server_name = config
-        .host
-        .clone()
+    let server_name = crate::addr::split_zone(&config.host)
+        .0
+        .to_string()
         .try_into()
         .map_err(|e| format!("Invalid server name: {e}"))?;
 
-    let mut conn = rustls::ClientConnection::new(Arc::new(tls_config), server_name)
+    let mut conn = rustls::ClientConnection::new(tls_config.clone(), server_name)
         .map_err(|e| format!("TLS setup failed: {e}"))?;
 
-    let mut sock = TcpStream::connect_timeout(&addr, config.timeout)
+    let mut sock = TcpStream::connect_timeout(addr, config.timeout)
         .map_err(|e| format!("Connect failed: {e}"))?;
     sock.set_read_timeout(Some(config.timeout)).ok();
     sock.set_write_timeout(Some(config.timeout)).ok();
@@ -135,7 +275,13 @@ pub fn inspect(config: &CertConfig) -> Result<CertResult, String> {
     // Ignore read errors — we just need the handshake to complete
     let _ = tls_stream.read(&mut buf);
 
-    let connection_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+    let time_ms = start.elapsed().as_secs_f64() * 1000.0;
+    if !capture {
+        return Ok(Handshake {
+            time_ms,
+            details: None,
+        });
+    }
 
     let tls_version = match conn.protocol_version() {
         Some(rustls::ProtocolVersion::TLSv1_2) => "TLSv1.2",
@@ -150,12 +296,178 @@ pub fn inspect(config: &CertConfig) -> Result<CertResult, String> {
         .unwrap_or_else(|| "unknown".to_string());
 
     let mut chain = Vec::new();
+    let mut names = Vec::new();
     if let Some(certs) = conn.peer_certificates() {
+        if let Some(leaf) = certs.first() {
+            names = leaf_names(leaf.as_ref());
+        }
         for cert_der in certs {
             chain.push(parse_basic_cert_info(cert_der.as_ref()));
         }
     }
 
+    Ok(Handshake {
+        time_ms,
+        details: Some(HandshakeDetails {
+            tls_version,
+            cipher_suite,
+            chain,
+            leaf_names: names,
+        }),
+    })
+}
+
+/// Run `count` handshakes, capturing connection details from the first only.
+fn repeat_handshakes(
+    count: u32,
+    mut run: impl FnMut(bool) -> Result<Handshake, String>,
+) -> Result<(HandshakeDetails, Vec<f64>), String> {
+    let first = run(true)?;
+    let details = first
+        .details
+        .ok_or("Handshake did not return connection details")?;
+    let mut times = vec![first.time_ms];
+    for _ in 1..count.max(1) {
+        times.push(run(false)?.time_ms);
+    }
+    Ok((details, times))
+}
+
+/// The bundled Mozilla roots plus any CA certificates in the PEM file `ca_file`.
+pub fn root_store(ca_file: Option<&Path>) -> Result<RootCertStore, String> {
+    let mut store = RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    if let Some(path) = ca_file {
+        let certs = CertificateDer::pem_file_iter(path)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read CA file {}: {e}", path.display()))?;
+        if certs.is_empty() {
+            return Err(format!("No certificates found in {}", path.display()));
+        }
+        let (_, rejected) = store.add_parsable_certificates(certs);
+        if rejected > 0 {
+            return Err(format!(
+                "{rejected} invalid CA certificate(s) in {}",
+                path.display()
+            ));
+        }
+    }
+    Ok(store)
+}
+
+/// Checks the server chain with webpki but only records the outcome, so the
+/// chain can still be inspected when it doesn't validate.
+#[derive(Debug)]
+struct RecordingVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    error: Mutex<Option<rustls::Error>>,
+}
+
+impl ServerCertVerifier for RecordingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if let Err(e) = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        ) {
+            *self.error.lock().unwrap() = Some(e);
+        }
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+/// Inspect TLS certificate for a host.
+pub fn inspect(config: &CertConfig) -> Result<CertResult, String> {
+    let timestamp = crate::timestamp::now();
+    let addr = crate::addr::resolve(&config.host, config.port)
+        .map_err(|e| format!("DNS failed: {e}"))?
+        .into_iter()
+        .next()
+        .ok_or("No address")?;
+
+    // Set up rustls; the config is shared so repeated handshakes can resume sessions
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let roots = Arc::new(root_store(config.ca_file.as_deref())?);
+    let verifier = Arc::new(RecordingVerifier {
+        inner: WebPkiServerVerifier::builder_with_provider(roots, provider.clone())
+            .build()
+            .map_err(|e| format!("TLS setup failed: {e}"))?,
+        error: Mutex::new(None),
+    });
+
+    let tls_config = Arc::new(
+        rustls::ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|e| format!("TLS setup failed: {e}"))?
+            .dangerous()
+            .with_custom_certificate_verifier(verifier.clone())
+            .with_no_client_auth(),
+    );
+
+    let (details, times) = repeat_handshakes(config.count, |capture| {
+        handshake(config, &addr, &tls_config, capture)
+    })?;
+    let chain = details.chain;
+
+    // webpki checks names only after the chain validates, so a name error means a
+    // trusted chain; names are matched below, against the common name too
+    let validation_error = match verifier.error.lock().unwrap().take() {
+        Some(rustls::Error::InvalidCertificate(
+            rustls::CertificateError::NotValidForName
+            | rustls::CertificateError::NotValidForNameContext { .. },
+        )) => None,
+        other => other,
+    };
+    if let Some(ref e) = validation_error {
+        if !config.insecure || config.verify_hostname {
+            return Err(format!(
+                "Certificate chain does not validate: {e} (use --ca-file or --insecure)"
+            ));
+        }
+    }
+    let hostname_matches = details
+        .leaf_names
+        .iter()
+        .any(|name| name_matches(name, &config.host));
+    let presented_names = if hostname_matches {
+        Vec::new()
+    } else {
+        details.leaf_names
+    };
+    if config.verify_hostname && !hostname_matches {
+        return Err(hostname_mismatch(&config.host, &presented_names));
+    }
+
     let warning = chain.first().and_then(|c| {
         if c.days_until_expiry >= 0 && c.days_until_expiry <= 30 {
             Some(format!(
@@ -170,14 +482,41 @@ pub fn inspect(config: &CertConfig) -> Result<CertResult, String> {
     Ok(CertResult {
         host: config.host.clone(),
         port: config.port,
-        tls_version,
-        cipher_suite,
+        tls_version: details.tls_version,
+        cipher_suite: details.cipher_suite,
         certificate_chain: chain,
-        connection_time_ms,
+        connection_time_ms: times[0],
         warning,
+        handshakes: if times.len() > 1 {
+            HandshakeStats::from_samples(&times)
+        } else {
+            None
+        },
+        chain_valid: validation_error.is_none(),
+        hostname_matches,
+        presented_names,
+        timestamp,
     })
 }
 
+/// Why a certificate was rejected for not covering `host`.
+fn hostname_mismatch(host: &str, presented: &[String]) -> String {
+    if presented.is_empty() {
+        format!("Certificate does not cover {host}")
+    } else {
+        format!(
+            "Certificate does not cover {host} (valid for {})",
+            presented.join(", ")
+        )
+    }
+}
+
+/// Async [`inspect`]; the handshakes run on the blocking pool.
+pub async fn inspect_async(config: &CertConfig) -> Result<CertResult, String> {
+    let config = config.clone();
+    crate::runtime::blocking(move || inspect(&config)).await
+}
+
 impl CertInfo {
     /// Check if this certificate is self-signed (subject equals issuer).
     pub fn is_self_signed(&self) -> bool {
@@ -189,14 +528,20 @@ impl CertInfo {
         self.days_until_expiry >= 0 && self.days_until_expiry <= 30
     }
 
+    /// Whether the expiry is known. -1 days means unknown unless `not_after` is a
+    /// decoded date, as for a certificate that expired within the last day.
+    pub fn expiry_known(&self) -> bool {
+        self.days_until_expiry != -1 || crate::timestamp::parse(&self.not_after).is_some()
+    }
+
     /// Check if the certificate has already expired.
     pub fn is_expired(&self) -> bool {
-        self.days_until_expiry < 0 && self.days_until_expiry != -1
+        self.expiry_known() && self.days_until_expiry < 0
     }
 
     /// Return a human-readable validity status.
     pub fn validity_status(&self) -> &'static str {
-        if self.days_until_expiry == -1 {
+        if !self.expiry_known() {
             "unknown"
         } else if self.days_until_expiry < 0 {
             "expired"
@@ -241,6 +586,67 @@ impl CertResult {
     pub fn has_expiring_cert(&self) -> bool {
         self.certificate_chain.iter().any(|c| c.is_expiring_soon())
     }
+
+    /// Why the certificate doesn't cover `host`, if it doesn't.
+    pub fn hostname_problem(&self) -> Option<String> {
+        (!self.hostname_matches).then(|| hostname_mismatch(&self.host, &self.presented_names))
+    }
+}
+
+/// A leaf certificate inside the renewal window.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpiringCert {
+    pub host: String,
+    pub port: u16,
+    pub subject: String,
+    pub not_after: String,
+    pub days_until_expiry: i64,
+    /// Inside the critical sub-threshold (or already expired).
+    pub critical: bool,
+}
+
+/// Certificates from a batch that expire within a window, soonest first.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpiryReport {
+    pub window_days: i64,
+    pub critical_days: i64,
+    pub checked: usize,
+    pub expiring: Vec<ExpiringCert>,
+}
+
+impl ExpiryReport {
+    /// Keep the leaf certificates expiring within `window_days`, sorted by soonest.
+    ///
+    /// Expired certificates are included; ones with an unknown expiry are not.
+    pub fn new(results: &[CertResult], window_days: i64, critical_days: i64) -> Self {
+        let mut expiring: Vec<ExpiringCert> = results
+            .iter()
+            .filter_map(|r| {
+                let leaf = r.leaf_cert()?;
+                let days = leaf.days_until_expiry;
+                (leaf.expiry_known() && days <= window_days).then(|| ExpiringCert {
+                    host: r.host.clone(),
+                    port: r.port,
+                    subject: leaf.subject.clone(),
+                    not_after: leaf.not_after.clone(),
+                    days_until_expiry: days,
+                    critical: days < critical_days,
+                })
+            })
+            .collect();
+        expiring.sort_by_key(|c| c.days_until_expiry);
+        Self {
+            window_days,
+            critical_days,
+            checked: results.len(),
+            expiring,
+        }
+    }
+
+    /// Number of certificates inside the critical sub-threshold.
+    pub fn critical_count(&self) -> usize {
+        self.expiring.iter().filter(|c| c.critical).count()
+    }
 }
 
 impl std::fmt::Display for CertResult {
@@ -255,6 +661,9 @@ impl std::fmt::Display for CertResult {
             depth = self.certificate_chain.len(),
             time = self.connection_time_ms,
         )?;
+        if let Some(problem) = self.hostname_problem() {
+            write!(f, " [{problem}]")?;
+        }
         if let Some(ref w) = self.warning {
             write!(f, " [{w}]")?;
         }
@@ -266,12 +675,181 @@ impl std::fmt::Display for CertResult {
 mod tests {
     use super::*;
 
+    use crate::testutil::{spawn_tls_server, testdata};
+
+    fn local_config(port: u16) -> CertConfig {
+        CertConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_root_store_loads_ca_file() {
+        let bundled = root_store(None).unwrap().len();
+        let with_ca = root_store(Some(&testdata("test-ca.pem"))).unwrap();
+        assert_eq!(with_ca.len(), bundled + 1);
+
+        let err = root_store(Some(&testdata("missing.pem"))).unwrap_err();
+        assert!(err.contains("Failed to read CA file"));
+        // A key file parses as PEM but holds no certificates
+        let err = root_store(Some(&testdata("test-server.key"))).unwrap_err();
+        assert!(err.contains("No certificates found"));
+    }
+
+    #[test]
+    fn test_inspect_validates_with_ca_file() {
+        let config = CertConfig {
+            ca_file: Some(testdata("test-ca.pem")),
+            ..local_config(spawn_tls_server("127.0.0.1", 1))
+        };
+        let result = inspect(&config).unwrap();
+        assert!(result.chain_valid);
+        assert!(result.hostname_matches);
+        assert_eq!(result.certificate_chain.len(), 1);
+        let leaf = result.leaf_cert().unwrap();
+        assert_eq!(leaf.subject, "127.0.0.1");
+        assert_eq!(leaf.issuer, "netscout test CA");
+        assert_eq!(leaf.not_after, "2126-09-22T01:40:42.000Z");
+        // Matches `openssl x509 -fingerprint -sha256`
+        assert_eq!(
+            leaf.sha256,
+            "F4:DB:A1:2E:23:20:0F:79:6E:1B:26:5C:1C:59:D3:33:\
+             E6:B9:3F:08:2F:A9:CF:0A:5A:33:EA:11:B7:C5:59:F2"
+        );
+        assert!(leaf.days_until_expiry > 36_000);
+        assert_eq!(leaf.validity_status(), "valid");
+        assert!(result.warning.is_none());
+    }
+
+    #[test]
+    fn test_cert_info_counts_days_from_decoded_dates() {
+        use rustls::pki_types::pem::PemObject;
+        let der = CertificateDer::from_pem_file(testdata("test-server.pem")).unwrap();
+        let cert = crate::x509::parse(der.as_ref()).unwrap();
+        let day = Duration::from_secs(86_400);
+
+        let info = cert_info(&cert, cert.not_after - day * 10);
+        assert_eq!(info.days_until_expiry, 10);
+        assert_eq!(info.not_before, "2026-10-16T01:40:42.000Z");
+        assert_eq!(
+            info.serial,
+            "41:8E:1B:38:49:5F:39:F9:45:DF:B3:38:86:19:C2:5E:8C:A8:49:66"
+        );
+        assert!(!info.is_ca);
+        assert!(info.is_expiring_soon());
+
+        // Expired an hour ago: -1 day, yet known since the date decoded
+        let info = cert_info(&cert, cert.not_after + Duration::from_secs(3600));
+        assert_eq!(info.days_until_expiry, -1);
+        assert!(info.is_expired());
+        assert_eq!(info.validity_status(), "expired");
+
+        let info = parse_basic_cert_info(
+            CertificateDer::from_pem_file(testdata("test-ca.pem"))
+                .unwrap()
+                .as_ref(),
+        );
+        assert!(info.is_ca);
+        assert!(info.is_self_signed());
+    }
+
+    #[test]
+    fn test_inspect_hostname_mismatch() {
+        // The test certificate covers 127.0.0.1 only, not the name localhost
+        let port = spawn_tls_server("localhost", 2);
+        let config = CertConfig {
+            host: "localhost".to_string(),
+            ca_file: Some(testdata("test-ca.pem")),
+            ..local_config(port)
+        };
+        let result = inspect(&config).unwrap();
+        assert!(result.chain_valid);
+        assert!(!result.hostname_matches);
+        assert_eq!(result.presented_names, vec!["127.0.0.1"]);
+        assert_eq!(
+            result.hostname_problem().as_deref(),
+            Some("Certificate does not cover localhost (valid for 127.0.0.1)")
+        );
+
+        let strict = CertConfig {
+            verify_hostname: true,
+            ..config
+        };
+        let err = inspect(&strict).unwrap_err();
+        assert!(err.contains("does not cover localhost"), "{err}");
+    }
+
+    #[test]
+    fn test_fingerprint_known_digest() {
+        assert_eq!(
+            fingerprint::<sha2::Sha256>(b"abc"),
+            "BA:78:16:BF:8F:01:CF:EA:41:41:40:DE:5D:AE:22:23:\
+             B0:03:61:A3:96:17:7A:9C:B4:10:FF:61:F2:00:15:AD"
+        );
+        assert_eq!(
+            fingerprint::<sha1::Sha1>(b"abc"),
+            "A9:99:3E:36:47:06:81:6A:BA:3E:25:71:78:50:C2:6C:9C:D0:D8:9D"
+        );
+
+        use rustls::pki_types::pem::PemObject;
+        let der = CertificateDer::from_pem_file(testdata("test-server.pem")).unwrap();
+        let info = parse_basic_cert_info(der.as_ref());
+        assert_eq!(info.sha256, fingerprint::<sha2::Sha256>(der.as_ref()));
+        assert_eq!(info.sha256.len(), 32 * 3 - 1);
+        assert_eq!(info.sha1.len(), 20 * 3 - 1);
+    }
+
+    #[test]
+    fn test_name_matches_wildcards() {
+        assert!(name_matches("example.com", "EXAMPLE.com."));
+        assert!(name_matches("*.example.com", "www.example.com"));
+        assert!(!name_matches("*.example.com", "example.com"));
+        assert!(!name_matches("*.example.com", "a.b.example.com"));
+        assert!(!name_matches("*.example.com", "www.example.org"));
+        assert!(name_matches("127.0.0.1", "127.0.0.1"));
+        assert!(name_matches("::1", "[::1]"));
+        assert!(!name_matches("*.0.0.1", "127.0.0.1"));
+        assert!(!name_matches("other.example", "127.0.0.1"));
+    }
+
+    #[test]
+    fn test_leaf_names_include_common_name() {
+        use rustls::pki_types::pem::PemObject;
+        let der = CertificateDer::from_pem_file(testdata("test-server.pem")).unwrap();
+        // The common name repeats the IP SAN, so it's listed once
+        assert_eq!(leaf_names(der.as_ref()), vec!["127.0.0.1"]);
+        let der = CertificateDer::from_pem_file(testdata("test-ca.pem")).unwrap();
+        assert_eq!(leaf_names(der.as_ref()), vec!["netscout test CA"]);
+        assert!(leaf_names(&[0u8; 32]).is_empty());
+    }
+
+    #[test]
+    fn test_inspect_untrusted_chain() {
+        let port = spawn_tls_server("127.0.0.1", 2);
+        let err = inspect(&local_config(port)).unwrap_err();
+        assert!(err.contains("does not validate"), "{err}");
+
+        let config = CertConfig {
+            insecure: true,
+            ..local_config(port)
+        };
+        let result = inspect(&config).unwrap();
+        assert!(!result.chain_valid);
+        // Names are checked locally, so even an untrusted chain is matched
+        assert!(result.hostname_matches);
+        assert_eq!(result.certificate_chain.len(), 1);
+    }
+
     #[test]
     fn test_cert_config_default() {
         let cfg = CertConfig::default();
         assert!(cfg.host.is_empty());
         assert_eq!(cfg.port, 443);
         assert_eq!(cfg.timeout, Duration::from_secs(10));
+        assert_eq!(cfg.count, 1);
     }
 
     #[test]
@@ -280,12 +858,70 @@ mod tests {
             host: "example.com".to_string(),
             port: 8443,
             timeout: Duration::from_secs(30),
+            count: 5,
+            ca_file: None,
+            insecure: false,
+            verify_hostname: false,
         };
         assert_eq!(cfg.host, "example.com");
         assert_eq!(cfg.port, 8443);
         assert_eq!(cfg.timeout, Duration::from_secs(30));
     }
 
+    #[test]
+    fn test_handshake_stats_from_samples() {
+        let stats = HandshakeStats::from_samples(&[30.0, 10.0, 20.0, 40.0, 50.0]).unwrap();
+        assert_eq!(stats.count, 5);
+        assert_eq!(stats.min_ms, 10.0);
+        assert_eq!(stats.avg_ms, 30.0);
+        assert_eq!(stats.max_ms, 50.0);
+        assert!((stats.p95_ms - 48.0).abs() < 1e-9);
+        assert!(HandshakeStats::from_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn test_repeat_handshakes_captures_chain_once() {
+        let durations = [12.0, 8.0, 9.0, 11.0];
+        let mut calls = 0;
+        let mut captures = 0;
+        let (details, times) = repeat_handshakes(4, |capture| {
+            if capture {
+                captures += 1;
+            }
+            let time_ms = durations[calls];
+            calls += 1;
+            Ok(Handshake {
+                time_ms,
+                details: capture.then(|| HandshakeDetails {
+                    tls_version: "TLSv1.3".into(),
+                    cipher_suite: "TLS13_AES_128_GCM_SHA256".into(),
+                    chain: vec![parse_basic_cert_info(&[0u8; 32])],
+                    leaf_names: Vec::new(),
+                }),
+            })
+        })
+        .unwrap();
+        assert_eq!(calls, 4);
+        assert_eq!(captures, 1);
+        assert_eq!(details.chain.len(), 1);
+        assert_eq!(times, durations.to_vec());
+        let stats = HandshakeStats::from_samples(&times).unwrap();
+        assert_eq!(stats.min_ms, 8.0);
+        assert_eq!(stats.avg_ms, 10.0);
+        assert_eq!(stats.max_ms, 12.0);
+    }
+
+    #[test]
+    fn test_repeat_handshakes_stops_on_error() {
+        let mut calls = 0;
+        let result = repeat_handshakes(3, |_| {
+            calls += 1;
+            Err("Connect failed: refused".to_string())
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
     #[test]
     fn test_extract_cn_no_match() {
         let data = vec![0u8; 100];
@@ -360,6 +996,8 @@ mod tests {
             days_until_expiry: 180,
             serial: "AA:BB:CC".into(),
             is_ca: false,
+            sha256: String::new(),
+            sha1: String::new(),
         };
         let json = serde_json::to_string(&info).unwrap();
         assert!(json.contains("example.com"));
@@ -368,6 +1006,62 @@ mod tests {
         assert!(json.contains("false"));
     }
 
+    fn result_expiring_in(host: &str, days: i64) -> CertResult {
+        CertResult {
+            host: host.into(),
+            port: 443,
+            tls_version: "TLSv1.3".into(),
+            cipher_suite: "AES_256_GCM".into(),
+            certificate_chain: vec![CertInfo {
+                subject: format!("CN={host}"),
+                issuer: "CA".into(),
+                not_before: "2024-01-01".into(),
+                not_after: "2025-01-01".into(),
+                days_until_expiry: days,
+                serial: "AA".into(),
+                is_ca: false,
+                sha256: String::new(),
+                sha1: String::new(),
+            }],
+            connection_time_ms: 10.0,
+            warning: None,
+            handshakes: None,
+            chain_valid: true,
+            hostname_matches: true,
+            presented_names: Vec::new(),
+            timestamp: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_expiry_report_keeps_certs_inside_window() {
+        let results = vec![
+            result_expiring_in("later.example", 45),
+            result_expiring_in("soon.example", 5),
+            result_expiring_in("fine.example", 400),
+        ];
+        let report = ExpiryReport::new(&results, 30, crate::health::CERT_CRITICAL_DAYS);
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.expiring.len(), 1);
+        assert_eq!(report.expiring[0].host, "soon.example");
+        assert_eq!(report.expiring[0].days_until_expiry, 5);
+        assert_eq!(report.critical_count(), 1);
+    }
+
+    #[test]
+    fn test_expiry_report_sorts_soonest_first() {
+        let results = vec![
+            result_expiring_in("b.example", 20),
+            result_expiring_in("a.example", -3),
+            result_expiring_in("unknown.example", -1),
+            result_expiring_in("c.example", 10),
+        ];
+        let report = ExpiryReport::new(&results, 30, 7);
+        let hosts: Vec<&str> = report.expiring.iter().map(|c| c.host.as_str()).collect();
+        assert_eq!(hosts, vec!["a.example", "c.example", "b.example"]);
+        assert_eq!(report.critical_count(), 1);
+    }
+
     #[test]
     fn test_cert_info_ca_certificate() {
         let info = CertInfo {
@@ -378,6 +1072,8 @@ mod tests {
             days_until_expiry: 2000,
             serial: "01".into(),
             is_ca: true,
+            sha256: String::new(),
+            sha1: String::new(),
         };
         assert!(info.is_ca);
         assert_eq!(info.subject, info.issuer); // Self-signed root
@@ -398,9 +1094,16 @@ mod tests {
                 days_until_expiry: 15,
                 serial: "AA".into(),
                 is_ca: false,
+                sha256: String::new(),
+                sha1: String::new(),
             }],
             connection_time_ms: 50.0,
             warning: Some("⚠️  Certificate expires in 15 days!".into()),
+            handshakes: None,
+            chain_valid: true,
+            hostname_matches: true,
+            presented_names: Vec::new(),
+            timestamp: String::new(),
         };
         assert!(result.warning.is_some());
         assert!(result.warning.unwrap().contains("15 days"));
@@ -421,9 +1124,16 @@ mod tests {
                 days_until_expiry: 180,
                 serial: "12:34:56".into(),
                 is_ca: false,
+                sha256: String::new(),
+                sha1: String::new(),
             }],
             connection_time_ms: 25.0,
             warning: None,
+            handshakes: None,
+            chain_valid: true,
+            hostname_matches: true,
+            presented_names: Vec::new(),
+            timestamp: String::new(),
         };
         assert!(result.warning.is_none());
         assert_eq!(result.cipher_suite, "ChaCha20Poly1305");
@@ -439,6 +1149,11 @@ mod tests {
             certificate_chain: vec![],
             connection_time_ms: 100.0,
             warning: None,
+            handshakes: None,
+            chain_valid: true,
+            hostname_matches: true,
+            presented_names: Vec::new(),
+            timestamp: String::new(),
         };
         assert!(result.certificate_chain.is_empty());
         assert_eq!(result.port, 8443);
@@ -456,6 +1171,8 @@ mod tests {
                 days_until_expiry: 200,
                 serial: "LEAF".into(),
                 is_ca: false,
+                sha256: String::new(),
+                sha1: String::new(),
             },
             CertInfo {
                 subject: "Intermediate CA".into(),
@@ -465,6 +1182,8 @@ mod tests {
                 days_until_expiry: 1500,
                 serial: "INTERMEDIATE".into(),
                 is_ca: true,
+                sha256: String::new(),
+                sha1: String::new(),
             },
             CertInfo {
                 subject: "Root CA".into(),
@@ -474,6 +1193,8 @@ mod tests {
                 days_until_expiry: 3000,
                 serial: "ROOT".into(),
                 is_ca: true,
+                sha256: String::new(),
+                sha1: String::new(),
             },
         ];
 
@@ -485,6 +1206,11 @@ mod tests {
             certificate_chain: chain,
             connection_time_ms: 35.0,
             warning: None,
+            handshakes: None,
+            chain_valid: true,
+            hostname_matches: true,
+            presented_names: Vec::new(),
+            timestamp: String::new(),
         };
 
         assert_eq!(result.certificate_chain.len(), 3);
@@ -503,6 +1229,11 @@ mod tests {
             certificate_chain: vec![],
             connection_time_ms: 75.5,
             warning: None,
+            handshakes: None,
+            chain_valid: true,
+            hostname_matches: true,
+            presented_names: Vec::new(),
+            timestamp: String::new(),
         };
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("api.example.com"));
@@ -520,6 +1251,8 @@ mod tests {
             days_until_expiry: 2000,
             serial: "01".into(),
             is_ca: true,
+            sha256: String::new(),
+            sha1: String::new(),
         };
         assert!(self_signed.is_self_signed());
 
@@ -531,6 +1264,8 @@ mod tests {
             days_until_expiry: 180,
             serial: "02".into(),
             is_ca: false,
+            sha256: String::new(),
+            sha1: String::new(),
         };
         assert!(!not_self_signed.is_self_signed());
     }
@@ -545,6 +1280,8 @@ mod tests {
             days_until_expiry: 15,
             serial: "AA".into(),
             is_ca: false,
+            sha256: String::new(),
+            sha1: String::new(),
         };
         assert!(expiring.is_expiring_soon());
 
@@ -556,6 +1293,8 @@ mod tests {
             days_until_expiry: 180,
             serial: "BB".into(),
             is_ca: false,
+            sha256: String::new(),
+            sha1: String::new(),
         };
         assert!(!healthy.is_expiring_soon());
     }
@@ -566,6 +1305,7 @@ mod tests {
             subject: "t".into(), issuer: "t".into(),
             not_before: "".into(), not_after: "".into(),
             days_until_expiry: -1, serial: "".into(), is_ca: false,
+            sha256: String::new(), sha1: String::new(),
         };
         assert_eq!(unknown.validity_status(), "unknown");
 
@@ -573,6 +1313,7 @@ mod tests {
             subject: "t".into(), issuer: "t".into(),
             not_before: "".into(), not_after: "".into(),
             days_until_expiry: -30, serial: "".into(), is_ca: false,
+            sha256: String::new(), sha1: String::new(),
         };
         assert_eq!(expired.validity_status(), "expired");
 
@@ -580,6 +1321,7 @@ mod tests {
             subject: "t".into(), issuer: "t".into(),
             not_before: "".into(), not_after: "".into(),
             days_until_expiry: 10, serial: "".into(), is_ca: false,
+            sha256: String::new(), sha1: String::new(),
         };
         assert_eq!(expiring.validity_status(), "expiring soon");
 
@@ -587,6 +1329,7 @@ mod tests {
             subject: "t".into(), issuer: "t".into(),
             not_before: "".into(), not_after: "".into(),
             days_until_expiry: 200, serial: "".into(), is_ca: false,
+            sha256: String::new(), sha1: String::new(),
         };
         assert_eq!(valid.validity_status(), "valid");
     }
@@ -601,6 +1344,8 @@ mod tests {
             days_until_expiry: 180,
             serial: "AA:BB".into(),
             is_ca: false,
+            sha256: String::new(),
+            sha1: String::new(),
         };
         let display = format!("{}", info);
         assert!(display.contains("example.com"));
@@ -617,6 +1362,11 @@ mod tests {
             cipher_suite: "AES_256_GCM".into(),
             certificate_chain: vec![], connection_time_ms: 50.0,
             warning: None,
+            handshakes: None,
+            chain_valid: true,
+            hostname_matches: true,
+            presented_names: Vec::new(),
+            timestamp: String::new(),
         };
         assert!(result.is_tls13());
 
@@ -626,6 +1376,11 @@ mod tests {
             cipher_suite: "AES_256_GCM".into(),
             certificate_chain: vec![], connection_time_ms: 50.0,
             warning: None,
+            handshakes: None,
+            chain_valid: true,
+            hostname_matches: true,
+            presented_names: Vec::new(),
+            timestamp: String::new(),
         };
         assert!(!result12.is_tls13());
     }
@@ -641,9 +1396,13 @@ mod tests {
                     subject: "leaf.com".into(), issuer: "CA".into(),
                     not_before: "".into(), not_after: "".into(),
                     days_until_expiry: 100, serial: "01".into(), is_ca: false,
+                    sha256: String::new(), sha1: String::new(),
                 },
             ],
-            connection_time_ms: 50.0, warning: None,
+            connection_time_ms: 50.0, warning: None, handshakes: None, chain_valid: true,
+            hostname_matches: true,
+            presented_names: Vec::new(),
+            timestamp: String::new(),
         };
         assert_eq!(result.leaf_cert().unwrap().subject, "leaf.com");
         assert_eq!(result.chain_depth(), 1);
@@ -651,7 +1410,10 @@ mod tests {
         let empty = CertResult {
             host: "t".into(), port: 443, tls_version: "".into(),
             cipher_suite: "".into(), certificate_chain: vec![],
-            connection_time_ms: 0.0, warning: None,
+            connection_time_ms: 0.0, warning: None, handshakes: None, chain_valid: true,
+            hostname_matches: true,
+            presented_names: Vec::new(),
+            timestamp: String::new(),
         };
         assert!(empty.leaf_cert().is_none());
         assert_eq!(empty.chain_depth(), 0);
@@ -668,9 +1430,13 @@ mod tests {
                     subject: "test.com".into(), issuer: "CA".into(),
                     not_before: "".into(), not_after: "".into(),
                     days_until_expiry: 5, serial: "01".into(), is_ca: false,
+                    sha256: String::new(), sha1: String::new(),
                 },
             ],
-            connection_time_ms: 50.0, warning: None,
+            connection_time_ms: 50.0, warning: None, handshakes: None, chain_valid: true,
+            hostname_matches: true,
+            presented_names: Vec::new(),
+            timestamp: String::new(),
         };
         assert!(result.has_expiring_cert());
     }
@@ -686,9 +1452,13 @@ mod tests {
                     subject: "example.com".into(), issuer: "CA".into(),
                     not_before: "".into(), not_after: "".into(),
                     days_until_expiry: 100, serial: "01".into(), is_ca: false,
+                    sha256: String::new(), sha1: String::new(),
                 },
             ],
-            connection_time_ms: 42.5, warning: None,
+            connection_time_ms: 42.5, warning: None, handshakes: None, chain_valid: true,
+            hostname_matches: true,
+            presented_names: Vec::new(),
+            timestamp: String::new(),
         };
         let display = format!("{}", result);
         assert!(display.contains("example.com:443"));
@@ -706,6 +1476,11 @@ mod tests {
             certificate_chain: vec![],
             connection_time_ms: 10.0,
             warning: Some("expiring!".into()),
+            handshakes: None,
+            chain_valid: true,
+            hostname_matches: true,
+            presented_names: Vec::new(),
+            timestamp: String::new(),
         };
         let display = format!("{}", result);
         assert!(display.contains("[expiring!]"));
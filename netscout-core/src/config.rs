@@ -4,9 +4,10 @@
 //!
 //! ```toml
 //! [defaults]
-//! output = "json"          # human | json | table | csv
-//! no_color = false
+//! output = "json"          # human | json | table | csv (`format` also accepted)
+//! color = "auto"           # auto | always | never (`no_color = true` means never)
 //! verbose = false
+//! profile = "slow"         # fast | default | slow (scales default timeouts)
 //!
 //! [ping]
 //! count = 4
@@ -17,6 +18,7 @@
 //! [dns]
 //! resolver = "8.8.8.8"
 //! record_type = "A"
+//! timeout = 5000
 //!
 //! [port]
 //! timeout = 2000
@@ -29,16 +31,24 @@
 //! [http]
 //! method = "GET"
 //! follow = false
+//! timeout = 10000
 //!
 //! [cert]
 //! port = 443
+//! timeout = 10000
 //!
 //! [scan]
 //! timeout = 500
+//!
+//! [pubip]
+//! endpoint = "https://ip.example.net/"     # IPv4 (or dual-stack) echo service
+//! endpoint_v6 = "https://ip6.example.net/" # IPv6-only echo service
 //! ```
 
+use crate::OutputFormat;
 use serde::Deserialize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Top-level config file structure.
 #[derive(Debug, Default, Deserialize, Clone)]
@@ -52,14 +62,122 @@ pub struct Config {
     pub http: HttpDefaults,
     pub cert: CertDefaults,
     pub scan: ScanDefaults,
+    pub pubip: PubipDefaults,
 }
 
 #[derive(Debug, Default, Deserialize, Clone)]
 #[serde(default)]
 pub struct DefaultsConfig {
+    #[serde(alias = "format")]
     pub output: Option<String>,
+    pub color: Option<ColorMode>,
     pub no_color: Option<bool>,
     pub verbose: Option<bool>,
+    pub profile: Option<TimeoutProfile>,
+}
+
+/// When to color human output.
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// Color when stdout is a terminal.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Global options given on the command line; `None`/`false` means not given.
+#[derive(Debug, Default, Clone)]
+pub struct GlobalFlags {
+    pub output: Option<OutputFormat>,
+    pub no_color: bool,
+    pub verbose: bool,
+    pub profile: Option<TimeoutProfile>,
+}
+
+/// Global settings once command-line flags are layered over `[defaults]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectiveDefaults {
+    pub output: OutputFormat,
+    pub color: ColorMode,
+    pub verbose: bool,
+    pub profile: TimeoutProfile,
+}
+
+impl DefaultsConfig {
+    /// Resolve the settings in effect: flags win, then the config, then built-in defaults.
+    pub fn resolve(&self, flags: &GlobalFlags) -> Result<EffectiveDefaults, String> {
+        let output = match (flags.output, self.output.as_deref()) {
+            (Some(format), _) => format,
+            (None, Some(name)) => OutputFormat::parse(name).ok_or_else(|| {
+                format!(
+                    "Unknown output format in config: {name} (expected human, json, table, csv, ndjson or prometheus)"
+                )
+            })?,
+            (None, None) => OutputFormat::Human,
+        };
+        let color = if flags.no_color || self.no_color == Some(true) {
+            ColorMode::Never
+        } else {
+            self.color.unwrap_or_default()
+        };
+        Ok(EffectiveDefaults {
+            output,
+            color,
+            verbose: flags.verbose || self.verbose.unwrap_or(false),
+            profile: flags.profile.or(self.profile).unwrap_or_default(),
+        })
+    }
+}
+
+/// Scaling applied to built-in timeouts for fast or slow links.
+///
+/// `fast` halves them and `slow` triples them (ping's 2s becomes 6s).
+/// Timeouts given on the command line or in the config file are never scaled.
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeoutProfile {
+    Fast,
+    #[default]
+    Default,
+    Slow,
+}
+
+impl TimeoutProfile {
+    /// Parse a profile name (`fast`, `default` or `slow`).
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "fast" => Ok(Self::Fast),
+            "default" => Ok(Self::Default),
+            "slow" => Ok(Self::Slow),
+            _ => Err(format!(
+                "Unknown profile: {name} (expected fast, default or slow)"
+            )),
+        }
+    }
+
+    /// Multiplier applied to default timeouts.
+    pub fn factor(self) -> f64 {
+        match self {
+            Self::Fast => 0.5,
+            Self::Default => 1.0,
+            Self::Slow => 3.0,
+        }
+    }
+
+    /// Scale a built-in default timeout.
+    pub fn scale(self, default: Duration) -> Duration {
+        default.mul_f64(self.factor())
+    }
+
+    /// Use `explicit_ms` if given, otherwise `default_ms` scaled by this profile.
+    pub fn timeout(self, explicit_ms: Option<u64>, default_ms: u64) -> Duration {
+        match explicit_ms {
+            Some(ms) => Duration::from_millis(ms),
+            None => self.scale(Duration::from_millis(default_ms)),
+        }
+    }
 }
 
 #[derive(Debug, Default, Deserialize, Clone)]
@@ -76,6 +194,7 @@ pub struct PingDefaults {
 pub struct DnsDefaults {
     pub resolver: Option<String>,
     pub record_type: Option<String>,
+    pub timeout: Option<u64>,
 }
 
 #[derive(Debug, Default, Deserialize, Clone)]
@@ -97,12 +216,14 @@ pub struct TraceDefaults {
 pub struct HttpDefaults {
     pub method: Option<String>,
     pub follow: Option<bool>,
+    pub timeout: Option<u64>,
 }
 
 #[derive(Debug, Default, Deserialize, Clone)]
 #[serde(default)]
 pub struct CertDefaults {
     pub port: Option<u16>,
+    pub timeout: Option<u64>,
 }
 
 #[derive(Debug, Default, Deserialize, Clone)]
@@ -111,6 +232,13 @@ pub struct ScanDefaults {
     pub timeout: Option<u64>,
 }
 
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default)]
+pub struct PubipDefaults {
+    pub endpoint: Option<String>,
+    pub endpoint_v6: Option<String>,
+}
+
 /// Return the default config file path (`~/.netscout.toml`).
 pub fn config_path() -> Option<PathBuf> {
     dirs::home_dir().map(|h| h.join(".netscout.toml"))
@@ -138,6 +266,77 @@ pub fn load_config_from(path: Option<PathBuf>) -> Result<Config, String> {
     toml::from_str(&contents).map_err(|e| format!("Invalid config at {}: {e}", path.display()))
 }
 
+/// Every section and key the config file understands, including aliases.
+const KNOWN_KEYS: &[(&str, &[&str])] = &[
+    (
+        "defaults",
+        &[
+            "output", "format", "color", "no_color", "verbose", "profile",
+        ],
+    ),
+    ("ping", &["count", "interval", "timeout", "port"]),
+    ("dns", &["resolver", "record_type", "timeout"]),
+    ("port", &["timeout", "parallel"]),
+    ("trace", &["max_hops", "timeout"]),
+    ("http", &["method", "follow", "timeout"]),
+    ("cert", &["port", "timeout"]),
+    ("scan", &["timeout"]),
+    ("pubip", &["endpoint", "endpoint_v6"]),
+];
+
+/// A config file that passed [`validate_config`].
+#[derive(Debug, Clone)]
+pub struct ConfigCheck {
+    pub path: PathBuf,
+    pub config: Config,
+    /// `[defaults]` resolved as if no flags were given.
+    pub effective: EffectiveDefaults,
+    /// Per-command settings the file sets, as `(section.key, value)` sorted by name.
+    pub settings: Vec<(String, String)>,
+}
+
+/// Strictly check a config file: it must exist, parse, use only known sections
+/// and keys, and name a valid output format.
+pub fn validate_config(path: &Path) -> Result<ConfigCheck, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read config {}: {e}", path.display()))?;
+    let invalid = |e: String| format!("Invalid config at {}: {e}", path.display());
+    let table: toml::Table = toml::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+    let mut settings = Vec::new();
+    for (section, value) in &table {
+        let Some((_, keys)) = KNOWN_KEYS.iter().find(|(name, _)| name == section) else {
+            return Err(invalid(format!("unknown section [{section}]")));
+        };
+        let Some(entries) = value.as_table() else {
+            return Err(invalid(format!(
+                "`{section}` must be a [{section}] section"
+            )));
+        };
+        for (key, value) in entries {
+            if !keys.contains(&key.as_str()) {
+                return Err(invalid(format!(
+                    "unknown key `{key}` in [{section}] (expected one of: {})",
+                    keys.join(", ")
+                )));
+            }
+            if section != "defaults" {
+                settings.push((format!("{section}.{key}"), value.to_string()));
+            }
+        }
+    }
+    let config: Config = toml::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+    let effective = config
+        .defaults
+        .resolve(&GlobalFlags::default())
+        .map_err(invalid)?;
+    Ok(ConfigCheck {
+        path: path.to_path_buf(),
+        config,
+        effective,
+        settings,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -150,6 +349,74 @@ mod tests {
         assert!(cfg.dns.resolver.is_none());
     }
 
+    #[test]
+    fn test_slow_profile_scales_ping_default() {
+        assert_eq!(
+            TimeoutProfile::Slow.timeout(None, 2000),
+            Duration::from_secs(6)
+        );
+        assert_eq!(
+            TimeoutProfile::Fast.timeout(None, 2000),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            TimeoutProfile::Default.timeout(None, 2000),
+            Duration::from_secs(2)
+        );
+    }
+
+    #[test]
+    fn test_explicit_timeout_overrides_profile() {
+        assert_eq!(
+            TimeoutProfile::Slow.timeout(Some(500), 2000),
+            Duration::from_millis(500)
+        );
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        assert_eq!(TimeoutProfile::parse("SLOW").unwrap(), TimeoutProfile::Slow);
+        assert!(TimeoutProfile::parse("turbo").is_err());
+        let cfg: Config = toml::from_str("[defaults]\nprofile = \"fast\"").unwrap();
+        assert_eq!(cfg.defaults.profile, Some(TimeoutProfile::Fast));
+    }
+
+    #[test]
+    fn test_defaults_apply_without_flags() {
+        let cfg: Config =
+            toml::from_str("[defaults]\noutput = \"table\"\nprofile = \"slow\"").unwrap();
+        let effective = cfg.defaults.resolve(&GlobalFlags::default()).unwrap();
+        assert_eq!(effective.output, OutputFormat::Table);
+        assert_eq!(effective.profile, TimeoutProfile::Slow);
+        assert_eq!(effective.color, ColorMode::Auto);
+        assert!(!effective.verbose);
+    }
+
+    #[test]
+    fn test_flags_override_defaults() {
+        let cfg: Config = toml::from_str(
+            "[defaults]\nformat = \"table\"\nprofile = \"slow\"\ncolor = \"always\"",
+        )
+        .unwrap();
+        let flags = GlobalFlags {
+            output: Some(OutputFormat::Json),
+            no_color: true,
+            verbose: true,
+            profile: Some(TimeoutProfile::Fast),
+        };
+        let effective = cfg.defaults.resolve(&flags).unwrap();
+        assert_eq!(effective.output, OutputFormat::Json);
+        assert_eq!(effective.profile, TimeoutProfile::Fast);
+        assert_eq!(effective.color, ColorMode::Never);
+        assert!(effective.verbose);
+    }
+
+    #[test]
+    fn test_unknown_config_output_is_an_error() {
+        let cfg: Config = toml::from_str("[defaults]\noutput = \"yaml\"").unwrap();
+        assert!(cfg.defaults.resolve(&GlobalFlags::default()).is_err());
+    }
+
     #[test]
     fn test_parse_empty_toml() {
         let cfg: Config = toml::from_str("").unwrap();
@@ -173,6 +440,7 @@ port = 443
 [dns]
 resolver = "1.1.1.1"
 record_type = "AAAA"
+timeout = 4000
 
 [port]
 timeout = 5000
@@ -185,12 +453,17 @@ timeout = 3000
 [http]
 method = "HEAD"
 follow = true
+timeout = 7000
 
 [cert]
 port = 8443
+timeout = 6000
 
 [scan]
 timeout = 1000
+
+[pubip]
+endpoint = "https://ip.example.net/"
 "#;
         let cfg: Config = toml::from_str(toml_str).unwrap();
         assert_eq!(cfg.defaults.output.as_deref(), Some("json"));
@@ -202,14 +475,22 @@ timeout = 1000
         assert_eq!(cfg.ping.port, Some(443));
         assert_eq!(cfg.dns.resolver.as_deref(), Some("1.1.1.1"));
         assert_eq!(cfg.dns.record_type.as_deref(), Some("AAAA"));
+        assert_eq!(cfg.dns.timeout, Some(4000));
         assert_eq!(cfg.port.timeout, Some(5000));
         assert_eq!(cfg.port.parallel, Some(200));
         assert_eq!(cfg.trace.max_hops, Some(64));
         assert_eq!(cfg.trace.timeout, Some(3000));
         assert_eq!(cfg.http.method.as_deref(), Some("HEAD"));
         assert_eq!(cfg.http.follow, Some(true));
+        assert_eq!(cfg.http.timeout, Some(7000));
         assert_eq!(cfg.cert.port, Some(8443));
+        assert_eq!(cfg.cert.timeout, Some(6000));
         assert_eq!(cfg.scan.timeout, Some(1000));
+        assert_eq!(
+            cfg.pubip.endpoint.as_deref(),
+            Some("https://ip.example.net/")
+        );
+        assert!(cfg.pubip.endpoint_v6.is_none());
     }
 
     #[test]
@@ -283,4 +564,64 @@ foo = "bar"
         // That's fine — we document valid fields
         let _ = result;
     }
+
+    fn write_temp(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "netscout-validate-{name}-{}.toml",
+            std::process::id()
+        ));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_validate_config_summarizes_settings() {
+        let path = write_temp(
+            "ok",
+            "[defaults]\nformat = \"json\"\nprofile = \"slow\"\n\n[ping]\ncount = 4\n\n[dns]\nresolver = \"9.9.9.9\"\n",
+        );
+        let check = validate_config(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(check.effective.output, OutputFormat::Json);
+        assert_eq!(check.effective.profile, TimeoutProfile::Slow);
+        assert_eq!(check.config.ping.count, Some(4));
+        assert_eq!(
+            check.settings,
+            vec![
+                ("dns.resolver".to_string(), "\"9.9.9.9\"".to_string()),
+                ("ping.count".to_string(), "4".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_config_rejects_unknown_keys() {
+        let path = write_temp("key", "[ping]\ncount = 4\ncuont = 5\n");
+        let err = validate_config(&path).unwrap_err();
+        assert!(err.contains("unknown key `cuont` in [ping]"), "{err}");
+
+        std::fs::write(&path, "[pnig]\ncount = 4\n").unwrap();
+        let err = validate_config(&path).unwrap_err();
+        assert!(err.contains("unknown section [pnig]"), "{err}");
+
+        std::fs::write(&path, "[defaults]\noutput = \"xml\"\n").unwrap();
+        let err = validate_config(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(
+            err.contains("Unknown output format in config: xml"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_validate_config_reports_type_errors() {
+        let path = write_temp("type", "[ping]\ncount = \"four\"\n");
+        let err = validate_config(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(err.contains("count"), "{err}");
+
+        assert!(validate_config(Path::new("/nonexistent/netscout.toml"))
+            .unwrap_err()
+            .contains("Failed to read config"));
+    }
 }
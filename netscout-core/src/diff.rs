@@ -0,0 +1,574 @@
+use crate::dns::{DnsMonitorSet, DnsResult};
+use crate::output::{csv_row, HumanReadable};
+use crate::ping::PingStats;
+use crate::port::ScanResult;
+use crate::scan::LanScanResult;
+use crate::trace::TraceResult;
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Kind of change between two runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A single difference between two runs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub item: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl Change {
+    fn added(item: impl Into<String>, new: impl Into<String>) -> Self {
+        Self {
+            kind: ChangeKind::Added,
+            item: item.into(),
+            old: None,
+            new: Some(new.into()),
+        }
+    }
+
+    fn removed(item: impl Into<String>, old: impl Into<String>) -> Self {
+        Self {
+            kind: ChangeKind::Removed,
+            item: item.into(),
+            old: Some(old.into()),
+            new: None,
+        }
+    }
+
+    fn changed(item: impl Into<String>, old: impl Into<String>, new: impl Into<String>) -> Self {
+        Self {
+            kind: ChangeKind::Changed,
+            item: item.into(),
+            old: Some(old.into()),
+            new: Some(new.into()),
+        }
+    }
+}
+
+/// Compare two results of the same type.
+pub trait Diff {
+    /// Changes going from `self` (older run) to `newer`.
+    fn diff(&self, newer: &Self) -> Vec<Change>;
+}
+
+/// Differences between two saved runs.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffReport {
+    pub command: String,
+    pub target: String,
+    pub changes: Vec<Change>,
+}
+
+impl DiffReport {
+    /// Returns true if the two runs are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Record a change if an optional measurement differs by more than `tolerance`.
+fn diff_ms(
+    changes: &mut Vec<Change>,
+    item: &str,
+    old: Option<f64>,
+    new: Option<f64>,
+    tolerance: f64,
+) {
+    let fmt = |v: Option<f64>| {
+        v.map(|v| format!("{v:.2} ms"))
+            .unwrap_or_else(|| "-".into())
+    };
+    let differs = match (old, new) {
+        (Some(a), Some(b)) => (a - b).abs() > tolerance,
+        (a, b) => a.is_some() != b.is_some(),
+    };
+    if differs {
+        changes.push(Change::changed(item, fmt(old), fmt(new)));
+    }
+}
+
+impl Diff for ScanResult {
+    fn diff(&self, newer: &Self) -> Vec<Change> {
+        let mut changes = Vec::new();
+        let old: BTreeSet<u16> = self
+            .ports
+            .iter()
+            .filter(|p| p.open)
+            .map(|p| p.port)
+            .collect();
+        let new: BTreeSet<u16> = newer
+            .ports
+            .iter()
+            .filter(|p| p.open)
+            .map(|p| p.port)
+            .collect();
+        let service = |r: &ScanResult, port: u16| {
+            r.ports
+                .iter()
+                .find(|p| p.port == port)
+                .and_then(|p| p.service.clone())
+                .unwrap_or_else(|| "unknown".to_string())
+        };
+        for port in new.difference(&old) {
+            changes.push(Change::added(
+                format!("port {port}"),
+                format!("open ({})", service(newer, *port)),
+            ));
+        }
+        for port in old.difference(&new) {
+            changes.push(Change::removed(
+                format!("port {port}"),
+                format!("open ({})", service(self, *port)),
+            ));
+        }
+        if self.resolved_addr != newer.resolved_addr {
+            changes.push(Change::changed(
+                "resolved_addr",
+                &self.resolved_addr,
+                &newer.resolved_addr,
+            ));
+        }
+        changes
+    }
+}
+
+impl Diff for PingStats {
+    fn diff(&self, newer: &Self) -> Vec<Change> {
+        let mut changes = Vec::new();
+        if self.resolved_addr != newer.resolved_addr {
+            changes.push(Change::changed(
+                "resolved_addr",
+                &self.resolved_addr,
+                &newer.resolved_addr,
+            ));
+        }
+        if (self.loss_percent - newer.loss_percent).abs() > f64::EPSILON {
+            changes.push(Change::changed(
+                "loss",
+                format!("{:.1}%", self.loss_percent),
+                format!("{:.1}%", newer.loss_percent),
+            ));
+        }
+        diff_ms(&mut changes, "avg", self.avg_ms, newer.avg_ms, 0.01);
+        diff_ms(&mut changes, "min", self.min_ms, newer.min_ms, 0.01);
+        diff_ms(&mut changes, "max", self.max_ms, newer.max_ms, 0.01);
+        diff_ms(
+            &mut changes,
+            "jitter",
+            self.jitter_ms,
+            newer.jitter_ms,
+            0.01,
+        );
+        changes
+    }
+}
+
+impl Diff for DnsResult {
+    fn diff(&self, newer: &Self) -> Vec<Change> {
+        let mut changes = Vec::new();
+        if self.response_code != newer.response_code {
+            changes.push(Change::changed(
+                "status",
+                &self.response_code,
+                &newer.response_code,
+            ));
+        }
+        let key = |r: &crate::dns::DnsRecord| format!("{} {}", r.record_type, r.value);
+        let old: BTreeSet<String> = self.records.iter().map(key).collect();
+        let new: BTreeSet<String> = newer.records.iter().map(key).collect();
+        for r in new.difference(&old) {
+            changes.push(Change::added("record", r));
+        }
+        for r in old.difference(&new) {
+            changes.push(Change::removed("record", r));
+        }
+        changes
+    }
+}
+
+impl Diff for DnsMonitorSet {
+    fn diff(&self, newer: &Self) -> Vec<Change> {
+        let mut changes = Vec::new();
+        for (rtype, new) in &newer.status {
+            match self.status.get(rtype) {
+                Some(old) if old != new => {
+                    changes.push(Change::changed(format!("{rtype} status"), old, new))
+                }
+                Some(_) => {}
+                None => changes.push(Change::added(format!("{rtype} status"), new)),
+            }
+        }
+        // Keyed by type and value, naming the owner only when it isn't the domain
+        let records = |set: &Self| -> BTreeMap<String, u32> {
+            set.records
+                .iter()
+                .map(|r| {
+                    let key = if r.name == set.domain {
+                        format!("{} {}", r.record_type, r.value)
+                    } else {
+                        format!("{} {} ({})", r.record_type, r.value, r.name)
+                    };
+                    (key, r.ttl)
+                })
+                .collect()
+        };
+        let (old, new) = (records(self), records(newer));
+        for (key, ttl) in &new {
+            match old.get(key) {
+                None => changes.push(Change::added("record", key)),
+                Some(old_ttl) if old_ttl != ttl => changes.push(Change::changed(
+                    format!("ttl {key}"),
+                    old_ttl.to_string(),
+                    ttl.to_string(),
+                )),
+                Some(_) => {}
+            }
+        }
+        for key in old.keys().filter(|k| !new.contains_key(*k)) {
+            changes.push(Change::removed("record", key));
+        }
+        changes
+    }
+}
+
+impl Diff for LanScanResult {
+    fn diff(&self, newer: &Self) -> Vec<Change> {
+        let mut changes = Vec::new();
+        for host in &newer.hosts {
+            match self.hosts.iter().find(|h| h.ip == host.ip) {
+                None => changes.push(Change::added(format!("host {}", host.ip), "up")),
+                Some(old) if old.open_ports != host.open_ports => {
+                    let ports = |p: &[u16]| {
+                        p.iter()
+                            .map(|p| p.to_string())
+                            .collect::<Vec<_>>()
+                            .join(",")
+                    };
+                    changes.push(Change::changed(
+                        format!("host {} ports", host.ip),
+                        ports(&old.open_ports),
+                        ports(&host.open_ports),
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        for host in &self.hosts {
+            if !newer.hosts.iter().any(|h| h.ip == host.ip) {
+                changes.push(Change::removed(format!("host {}", host.ip), "up"));
+            }
+        }
+        changes
+    }
+}
+
+impl Diff for TraceResult {
+    fn diff(&self, newer: &Self) -> Vec<Change> {
+        let mut changes = Vec::new();
+        let addr = |h: Option<&crate::trace::TraceHop>| {
+            h.and_then(|h| h.addr.clone())
+                .unwrap_or_else(|| "*".to_string())
+        };
+        let len = self.hops.len().max(newer.hops.len());
+        for i in 0..len {
+            let (old, new) = (addr(self.hops.get(i)), addr(newer.hops.get(i)));
+            if old != new {
+                changes.push(Change::changed(format!("hop {}", i + 1), old, new));
+            }
+        }
+        if self.reached != newer.reached {
+            changes.push(Change::changed(
+                "reached",
+                self.reached.to_string(),
+                newer.reached.to_string(),
+            ));
+        }
+        changes
+    }
+}
+
+/// Unwrap a one-element array, as multi-target JSON output holds; longer
+/// arrays can't be paired up and are rejected.
+fn single_result(value: Value, which: &str) -> Result<Value, String> {
+    match value {
+        Value::Array(mut items) if items.len() == 1 => Ok(items.remove(0)),
+        Value::Array(items) => Err(format!(
+            "The {which} file holds {} results; diff one target at a time",
+            items.len()
+        )),
+        other => Ok(other),
+    }
+}
+
+/// Unwrap a `{"command": ..., "result": ...}` envelope, if present.
+fn unwrap_envelope(value: Value) -> (Option<String>, Value) {
+    match value {
+        Value::Object(mut map) if map.contains_key("result") => {
+            let command = map
+                .get("command")
+                .or_else(|| map.get("tool"))
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            (command, map.remove("result").unwrap_or(Value::Null))
+        }
+        other => (None, other),
+    }
+}
+
+/// Guess which command produced a result from its fields.
+fn sniff_command(value: &Value) -> Option<&'static str> {
+    let has = |k: &str| value.get(k).is_some();
+    if has("open_count") && has("ports") {
+        Some("port")
+    } else if has("probes") && has("loss_percent") {
+        Some("ping")
+    } else if has("records") && has("response_code") {
+        Some("dns")
+    } else if has("records") && has("types") {
+        Some("dns-set")
+    } else if has("hosts") && has("subnet") {
+        Some("scan")
+    } else if has("hops") && has("reached") {
+        Some("trace")
+    } else {
+        None
+    }
+}
+
+fn parse<T: serde::de::DeserializeOwned>(value: Value, which: &str) -> Result<T, String> {
+    serde_json::from_value(value).map_err(|e| format!("Failed to read {which} result: {e}"))
+}
+
+fn report<T: Diff + serde::de::DeserializeOwned>(
+    command: &str,
+    old: Value,
+    new: Value,
+    target: impl Fn(&T) -> String,
+) -> Result<DiffReport, String> {
+    let old: T = parse(old, "old")?;
+    let new: T = parse(new, "new")?;
+    Ok(DiffReport {
+        command: command.to_string(),
+        target: target(&new),
+        changes: old.diff(&new),
+    })
+}
+
+/// Diff two saved JSON results, detecting their type.
+pub fn diff_json(old: &str, new: &str) -> Result<DiffReport, String> {
+    let old: Value =
+        serde_json::from_str(old).map_err(|e| format!("Invalid JSON in old file: {e}"))?;
+    let new: Value =
+        serde_json::from_str(new).map_err(|e| format!("Invalid JSON in new file: {e}"))?;
+    let (old_cmd, old) = unwrap_envelope(single_result(old, "old")?);
+    let (new_cmd, new) = unwrap_envelope(single_result(new, "new")?);
+    let old_cmd = old_cmd
+        .or_else(|| sniff_command(&old).map(String::from))
+        .ok_or("Cannot tell which command produced the old file")?;
+    let new_cmd = new_cmd
+        .or_else(|| sniff_command(&new).map(String::from))
+        .ok_or("Cannot tell which command produced the new file")?;
+    if old_cmd != new_cmd {
+        return Err(format!(
+            "Cannot diff results of different commands: {old_cmd} vs {new_cmd}"
+        ));
+    }
+
+    match old_cmd.as_str() {
+        "port" => report::<ScanResult>("port", old, new, |r| r.target.clone()),
+        "ping" => report::<PingStats>("ping", old, new, |r| r.target.clone()),
+        "dns" => report::<DnsResult>("dns", old, new, |r| {
+            format!("{} {}", r.domain, r.record_type)
+        }),
+        "dns-set" => report::<DnsMonitorSet>("dns-set", old, new, |r| r.domain.clone()),
+        "scan" => report::<LanScanResult>("scan", old, new, |r| r.subnet.clone()),
+        "trace" => report::<TraceResult>("trace", old, new, |r| r.target.clone()),
+        other => Err(format!("Diff is not supported for {other} results")),
+    }
+}
+
+impl HumanReadable for DiffReport {
+    fn to_human(&self) -> String {
+        let mut out = format!(
+            "{} {} {}\n",
+            "DIFF".cyan().bold(),
+            self.command,
+            self.target
+        );
+        if self.changes.is_empty() {
+            out.push_str("  No changes.\n");
+        }
+        for c in &self.changes {
+            let line = match c.kind {
+                ChangeKind::Added => {
+                    format!("+ {}: {}", c.item, c.new.as_deref().unwrap_or("")).green()
+                }
+                ChangeKind::Removed => {
+                    format!("- {}: {}", c.item, c.old.as_deref().unwrap_or("")).red()
+                }
+                ChangeKind::Changed => format!(
+                    "~ {}: {} → {}",
+                    c.item,
+                    c.old.as_deref().unwrap_or(""),
+                    c.new.as_deref().unwrap_or("")
+                )
+                .yellow(),
+            };
+            out.push_str(&format!("  {line}\n"));
+        }
+        out
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = csv_row(&[&"change", &"item", &"old", &"new"]);
+        for c in &self.changes {
+            let kind = match c.kind {
+                ChangeKind::Added => "added",
+                ChangeKind::Removed => "removed",
+                ChangeKind::Changed => "changed",
+            };
+            out.push_str(&csv_row(&[
+                &kind,
+                &c.item,
+                &c.old.as_deref().unwrap_or(""),
+                &c.new.as_deref().unwrap_or(""),
+            ]));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::port::PortResult;
+
+    fn port_scan(open: &[(u16, &str)]) -> ScanResult {
+        ScanResult {
+            target: "example.com".to_string(),
+            resolved_addr: "93.184.216.34".to_string(),
+            ports: open
+                .iter()
+                .map(|(port, svc)| PortResult {
+                    port: *port,
+                    open: true,
+                    service: Some(svc.to_string()),
+                    rtt_ms: Some(1.0),
+                    security_note: None,
+                    banner: None,
+                })
+                .collect(),
+            open_count: open.len(),
+            closed_count: 10 - open.len(),
+            scan_time_ms: 100.0,
+            timestamp: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_port_scan_json() {
+        let old = serde_json::to_string_pretty(&port_scan(&[(22, "ssh"), (80, "http")])).unwrap();
+        let new =
+            serde_json::to_string_pretty(&port_scan(&[(80, "http"), (443, "https")])).unwrap();
+        let report = diff_json(&old, &new).unwrap();
+        assert_eq!(report.command, "port");
+        assert_eq!(report.target, "example.com");
+        assert_eq!(report.changes.len(), 2);
+        assert!(report
+            .changes
+            .contains(&Change::added("port 443", "open (https)")));
+        assert!(report
+            .changes
+            .contains(&Change::removed("port 22", "open (ssh)")));
+    }
+
+    #[test]
+    fn test_diff_identical_runs() {
+        let json = serde_json::to_string(&port_scan(&[(80, "http")])).unwrap();
+        let report = diff_json(&json, &json).unwrap();
+        assert!(report.is_empty());
+        assert!(report.to_human().contains("No changes"));
+    }
+
+    #[test]
+    fn test_diff_envelope() {
+        let inner = serde_json::to_value(port_scan(&[(80, "http")])).unwrap();
+        let old = serde_json::json!({"command": "port", "result": inner}).to_string();
+        let new = serde_json::to_string(&port_scan(&[])).unwrap();
+        let report = diff_json(&old, &new).unwrap();
+        assert_eq!(
+            report.changes,
+            vec![Change::removed("port 80", "open (http)")]
+        );
+    }
+
+    #[test]
+    fn test_diff_single_result_array() {
+        let inner = serde_json::to_value(port_scan(&[(80, "http")])).unwrap();
+        let old = serde_json::json!([inner]).to_string();
+        let new = serde_json::to_string(&port_scan(&[])).unwrap();
+        assert_eq!(diff_json(&old, &new).unwrap().changes.len(), 1);
+
+        let both = serde_json::json!([inner, inner]).to_string();
+        let err = diff_json(&both, &new).unwrap_err();
+        assert!(err.contains("old file holds 2 results"));
+    }
+
+    #[test]
+    fn test_diff_mismatched_types() {
+        let port = serde_json::to_string(&port_scan(&[(80, "http")])).unwrap();
+        let trace = r#"{"target":"x","resolved_addr":"1.2.3.4","hops":[],"reached":true}"#;
+        let err = diff_json(&port, trace).unwrap_err();
+        assert!(err.contains("port vs trace"));
+    }
+
+    #[test]
+    fn test_diff_unknown_type() {
+        let err = diff_json(r#"{"foo": 1}"#, r#"{"foo": 2}"#).unwrap_err();
+        assert!(err.contains("Cannot tell"));
+        assert!(diff_json("not json", "{}").is_err());
+    }
+
+    #[test]
+    fn test_diff_trace_hops() {
+        let old = r#"{"target":"x","resolved_addr":"1.2.3.4","hops":[{"hop":1,"addr":"10.0.0.1","hostname":null,"rtt_ms":1.0,"timed_out":false}],"reached":true}"#;
+        let new = r#"{"target":"x","resolved_addr":"1.2.3.4","hops":[{"hop":1,"addr":"10.0.0.2","hostname":null,"rtt_ms":1.0,"timed_out":false}],"reached":true}"#;
+        let report = diff_json(old, new).unwrap();
+        assert_eq!(
+            report.changes,
+            vec![Change::changed("hop 1", "10.0.0.1", "10.0.0.2")]
+        );
+    }
+    #[test]
+    fn test_diff_dns_monitor_set() {
+        let old = r#"{"domain":"example.com","resolver":"8.8.8.8","types":["A","MX"],
+            "status":{"A":"NOERROR","MX":"NOERROR"},
+            "records":[{"name":"example.com","record_type":"A","ttl":300,"value":"192.0.2.1"},
+                       {"name":"example.com","record_type":"MX","ttl":3600,"value":"10 mx1.example.com"}]}"#;
+        let new = r#"{"domain":"example.com","resolver":"8.8.8.8","types":["A","MX"],
+            "status":{"A":"NOERROR","MX":"NOERROR"},
+            "records":[{"name":"example.com","record_type":"A","ttl":60,"value":"192.0.2.1"},
+                       {"name":"example.com","record_type":"MX","ttl":3600,"value":"20 mx2.example.com"}]}"#;
+        let report = diff_json(old, new).unwrap();
+        assert_eq!(report.command, "dns-set");
+        assert_eq!(
+            report.changes,
+            vec![
+                Change::changed("ttl A 192.0.2.1", "300", "60"),
+                Change::added("record", "MX 20 mx2.example.com"),
+                Change::removed("record", "MX 10 mx1.example.com"),
+            ]
+        );
+    }
+}
@@ -1,6 +1,10 @@
-use serde::Serialize;
-use std::net::{SocketAddr, UdpSocket};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
 
 /// Supported DNS record types.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
@@ -13,6 +17,12 @@ pub enum RecordType {
     NS,
     SOA,
     PTR,
+    DS,
+    DNSKEY,
+    RRSIG,
+    SSHFP,
+    TLSA,
+    CAA,
 }
 
 impl RecordType {
@@ -26,6 +36,12 @@ impl RecordType {
             Self::NS => 2,
             Self::SOA => 6,
             Self::PTR => 12,
+            Self::DS => 43,
+            Self::RRSIG => 46,
+            Self::DNSKEY => 48,
+            Self::SSHFP => 44,
+            Self::TLSA => 52,
+            Self::CAA => 257,
         }
     }
 
@@ -39,6 +55,12 @@ impl RecordType {
             "NS" => Some(Self::NS),
             "SOA" => Some(Self::SOA),
             "PTR" => Some(Self::PTR),
+            "DS" => Some(Self::DS),
+            "DNSKEY" => Some(Self::DNSKEY),
+            "RRSIG" => Some(Self::RRSIG),
+            "SSHFP" => Some(Self::SSHFP),
+            "TLSA" => Some(Self::TLSA),
+            "CAA" => Some(Self::CAA),
             _ => None,
         }
     }
@@ -57,6 +79,16 @@ pub struct DnsConfig {
     pub record_type: RecordType,
     pub resolver: String,
     pub timeout: Duration,
+    /// EDNS client-subnet to send with the query (for GeoDNS testing).
+    pub client_subnet: Option<ClientSubnet>,
+    /// Set the EDNS DO bit so the resolver returns DNSSEC records.
+    pub dnssec: bool,
+    /// Set the RD bit; clear it to query an authoritative server directly.
+    pub recursion_desired: bool,
+    /// Set the CD bit so the resolver skips DNSSEC validation.
+    pub checking_disabled: bool,
+    /// Query over TCP from the start instead of only after a truncated UDP reply.
+    pub tcp: bool,
 }
 
 impl Default for DnsConfig {
@@ -66,12 +98,84 @@ impl Default for DnsConfig {
             record_type: RecordType::A,
             resolver: "8.8.8.8".to_string(),
             timeout: Duration::from_secs(5),
+            client_subnet: None,
+            dnssec: false,
+            recursion_desired: true,
+            checking_disabled: false,
+            tcp: false,
         }
     }
 }
 
+/// An EDNS client-subnet prefix, e.g. `203.0.113.0/24`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientSubnet {
+    pub addr: IpAddr,
+    pub prefix_len: u8,
+}
+
+impl ClientSubnet {
+    /// Parse a CIDR string. A bare address is treated as a full-length prefix.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (addr_str, prefix_str) = match s.split_once('/') {
+            Some((a, p)) => (a, Some(p)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr_str
+            .trim()
+            .parse()
+            .map_err(|e| format!("Invalid client subnet address '{addr_str}': {e}"))?;
+        let max_len = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_str {
+            Some(p) => p
+                .trim()
+                .parse::<u8>()
+                .map_err(|e| format!("Invalid client subnet prefix '{p}': {e}"))?,
+            None => max_len,
+        };
+        if prefix_len > max_len {
+            return Err(format!(
+                "Client subnet prefix /{prefix_len} exceeds /{max_len}"
+            ));
+        }
+        Ok(Self { addr, prefix_len })
+    }
+
+    /// ECS address family (1 = IPv4, 2 = IPv6).
+    fn family(&self) -> u16 {
+        if self.addr.is_ipv4() {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Address bytes truncated to the prefix length, with host bits zeroed.
+    fn truncated_addr(&self) -> Vec<u8> {
+        let octets = match self.addr {
+            IpAddr::V4(v4) => v4.octets().to_vec(),
+            IpAddr::V6(v6) => v6.octets().to_vec(),
+        };
+        let n = (self.prefix_len as usize).div_ceil(8);
+        let mut bytes = octets[..n].to_vec();
+        let rem = self.prefix_len % 8;
+        if rem != 0 {
+            if let Some(last) = bytes.last_mut() {
+                *last &= 0xFFu8 << (8 - rem);
+            }
+        }
+        bytes
+    }
+}
+
+impl std::fmt::Display for ClientSubnet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
 /// A single DNS record in the response.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DnsRecord {
     pub name: String,
     pub record_type: String,
@@ -80,7 +184,7 @@ pub struct DnsRecord {
 }
 
 /// Result of a DNS query.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DnsResult {
     pub domain: String,
     pub resolver: String,
@@ -91,15 +195,48 @@ pub struct DnsResult {
     pub truncated: bool,
     pub recursion_available: bool,
     pub authenticated_data: bool,
+    /// Client subnet sent in the query, if any.
+    pub client_subnet: Option<String>,
+    /// ECS scope prefix length returned by the server, if any.
+    pub client_subnet_scope: Option<u8>,
+    /// Unicode/punycode forms of an internationalized domain.
+    pub idn: Option<crate::idn::IdnInfo>,
+    /// CNAME targets followed from the queried name, in order; empty if not an alias.
+    #[serde(default)]
+    pub cname_chain: Vec<String>,
+    /// When the query started (RFC 3339, UTC, milliseconds).
+    #[serde(default)]
+    pub timestamp: String,
+}
+
+/// Follow CNAME records in an answer section starting from `name`.
+///
+/// Returns each alias target in order, stopping at a name with no CNAME or on a loop.
+pub fn cname_chain(name: &str, records: &[DnsRecord]) -> Vec<String> {
+    let same = |a: &str, b: &str| a.trim_end_matches('.').eq_ignore_ascii_case(b);
+    let mut chain: Vec<String> = Vec::new();
+    let mut current = name.trim_end_matches('.');
+    while let Some(target) = records
+        .iter()
+        .find(|r| r.is_type("CNAME") && same(&r.name, current))
+        .map(|r| r.value.trim_end_matches('.'))
+    {
+        if same(name, target) || chain.iter().any(|c| same(c, target)) {
+            break;
+        }
+        chain.push(target.to_string());
+        current = target;
+    }
+    chain
 }
 
-/// Build a DNS query packet.
-fn build_query(domain: &str, qtype: u16) -> Vec<u8> {
+/// Build a DNS query packet with a random transaction ID, returning the packet and the ID.
+fn build_query(domain: &str, qtype: u16) -> (Vec<u8>, u16) {
+    let id: u16 = rand::random();
     let mut buf = Vec::with_capacity(512);
-    // Header: ID=0xABCD, flags=0x0100 (RD=1), QDCOUNT=1
-    buf.extend_from_slice(&[
-        0xAB, 0xCD, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    ]);
+    buf.extend_from_slice(&id.to_be_bytes());
+    // Header: flags=0x0100 (RD=1), QDCOUNT=1
+    buf.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
     // Question section
     for label in domain.split('.') {
         buf.push(label.len() as u8);
@@ -108,13 +245,61 @@ fn build_query(domain: &str, qtype: u16) -> Vec<u8> {
     buf.push(0); // root label
     buf.extend_from_slice(&qtype.to_be_bytes());
     buf.extend_from_slice(&[0x00, 0x01]); // QCLASS=IN
-    buf
+    (buf, id)
+}
+
+/// Append an EDNS0 OPT record to a query, optionally carrying a client-subnet option
+/// and the DNSSEC OK bit.
+fn append_edns(buf: &mut Vec<u8>, client_subnet: Option<&ClientSubnet>, dnssec_ok: bool) {
+    let mut rdata = Vec::new();
+    if let Some(subnet) = client_subnet {
+        let addr = subnet.truncated_addr();
+        rdata.extend_from_slice(&8u16.to_be_bytes()); // OPTION-CODE=ECS
+        rdata.extend_from_slice(&(4 + addr.len() as u16).to_be_bytes());
+        rdata.extend_from_slice(&subnet.family().to_be_bytes());
+        rdata.push(subnet.prefix_len); // SOURCE PREFIX-LENGTH
+        rdata.push(0); // SCOPE PREFIX-LENGTH
+        rdata.extend_from_slice(&addr);
+    }
+    buf.push(0); // root owner name
+    buf.extend_from_slice(&41u16.to_be_bytes()); // TYPE=OPT
+    buf.extend_from_slice(&4096u16.to_be_bytes()); // UDP payload size
+    buf.extend_from_slice(&[0, 0]); // extended RCODE, version
+    buf.extend_from_slice(&if dnssec_ok { [0x80, 0] } else { [0, 0] }); // flags (DO)
+    buf.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+    buf.extend_from_slice(&rdata);
+    // ARCOUNT += 1
+    let arcount = read_u16(buf, 10) + 1;
+    buf[10..12].copy_from_slice(&arcount.to_be_bytes());
 }
 
+/// Extract the ECS scope prefix length from OPT record RDATA.
+fn parse_ecs_scope(rdata: &[u8]) -> Option<u8> {
+    let mut pos = 0;
+    while pos + 4 <= rdata.len() {
+        let code = read_u16(rdata, pos);
+        let len = read_u16(rdata, pos + 2) as usize;
+        pos += 4;
+        if code == 8 && len >= 4 && pos + len <= rdata.len() {
+            return Some(rdata[pos + 3]);
+        }
+        pos += len;
+    }
+    None
+}
+
+/// Most compression pointers followed in one name; a valid name of at most
+/// 255 bytes can't need more, so hitting it means a pointer loop.
+const MAX_POINTER_JUMPS: usize = 128;
+
 /// Parse a DNS name from the response buffer at the given offset.
+///
+/// Follows compression pointers; `*offset` always ends just past the name's
+/// bytes at the original position (after the first pointer, if any).
 fn parse_name(buf: &[u8], offset: &mut usize) -> String {
     let mut labels = Vec::new();
     let mut jumped = false;
+    let mut jumps = 0;
     let mut jump_back = 0usize;
     let mut pos = *offset;
 
@@ -131,6 +316,10 @@ fn parse_name(buf: &[u8], offset: &mut usize) -> String {
             if !jumped {
                 jump_back = pos + 2;
             }
+            jumps += 1;
+            if jumps > MAX_POINTER_JUMPS {
+                break;
+            }
             let ptr = ((len & 0x3F) << 8) | buf.get(pos + 1).copied().unwrap_or(0) as usize;
             pos = ptr;
             jumped = true;
@@ -187,27 +376,49 @@ fn rcode_str(rcode: u8) -> &'static str {
     }
 }
 
+/// Name of a record type code.
+fn rtype_name(rtype: u16) -> &'static str {
+    match rtype {
+        1 => "A",
+        28 => "AAAA",
+        5 => "CNAME",
+        2 => "NS",
+        15 => "MX",
+        16 => "TXT",
+        6 => "SOA",
+        12 => "PTR",
+        43 => "DS",
+        44 => "SSHFP",
+        46 => "RRSIG",
+        47 => "NSEC",
+        48 => "DNSKEY",
+        50 => "NSEC3",
+        52 => "TLSA",
+        257 => "CAA",
+        _ => "UNKNOWN",
+    }
+}
+
 /// Parse a resource record's RDATA into a human-readable string.
 fn parse_rdata(buf: &[u8], offset: &mut usize, rdlength: u16, rtype: u16) -> String {
     let start = *offset;
     let end = start + rdlength as usize;
+    // A truncated packet or a lying RDLENGTH leaves nothing to decode
+    let Some(rdata) = buf.get(start..end) else {
+        *offset = buf.len();
+        return String::new();
+    };
 
     let result = match rtype {
         1 if rdlength == 4 => {
             // A record
-            format!(
-                "{}.{}.{}.{}",
-                buf[start],
-                buf[start + 1],
-                buf[start + 2],
-                buf[start + 3]
-            )
+            format!("{}.{}.{}.{}", rdata[0], rdata[1], rdata[2], rdata[3])
         }
         28 if rdlength == 16 => {
             // AAAA record
             let mut parts = Vec::with_capacity(8);
             for i in 0..8 {
-                parts.push(format!("{:x}", read_u16(buf, start + i * 2)));
+                parts.push(format!("{:x}", read_u16(rdata, i * 2)));
             }
             parts.join(":")
         }
@@ -218,7 +429,7 @@ fn parse_rdata(buf: &[u8], offset: &mut usize, rdlength: u16, rtype: u16) -> Str
         }
         15 => {
             // MX
-            let preference = read_u16(buf, start);
+            let preference = read_u16(rdata, 0);
             let mut pos = start + 2;
             let exchange = parse_name(buf, &mut pos);
             format!("{preference} {exchange}")
@@ -226,12 +437,12 @@ fn parse_rdata(buf: &[u8], offset: &mut usize, rdlength: u16, rtype: u16) -> Str
         16 => {
             // TXT
             let mut texts = Vec::new();
-            let mut pos = start;
-            while pos < end {
-                let tlen = buf[pos] as usize;
+            let mut pos = 0;
+            while pos < rdata.len() {
+                let tlen = rdata[pos] as usize;
                 pos += 1;
-                if pos + tlen <= end {
-                    texts.push(String::from_utf8_lossy(&buf[pos..pos + tlen]).to_string());
+                if let Some(text) = rdata.get(pos..pos + tlen) {
+                    texts.push(String::from_utf8_lossy(text).to_string());
                 }
                 pos += tlen;
             }
@@ -245,7 +456,53 @@ fn parse_rdata(buf: &[u8], offset: &mut usize, rdlength: u16, rtype: u16) -> Str
             let serial = read_u32(buf, pos);
             format!("{mname} {rname} {serial}")
         }
-        _ => hex::encode(buf.get(start..end).unwrap_or_default()),
+        43 if rdlength >= 4 => {
+            // DS: key tag, algorithm, digest type, digest
+            format!(
+                "{} {} {} {}",
+                read_u16(rdata, 0),
+                rdata[2],
+                rdata[3],
+                hex::encode(&rdata[4..])
+            )
+        }
+        46 if rdlength >= 18 => {
+            // RRSIG: type covered, algorithm, key tag and signer; the signature is omitted
+            let mut pos = start + 18;
+            let signer = parse_name(buf, &mut pos);
+            format!(
+                "{} {} keytag={} signer={signer}",
+                rtype_name(read_u16(rdata, 0)),
+                rdata[2],
+                read_u16(rdata, 16)
+            )
+        }
+        48 if rdlength >= 4 => {
+            // DNSKEY: flags (257 = key-signing key), protocol, algorithm; the key is omitted
+            format!("{} {} {}", read_u16(rdata, 0), rdata[2], rdata[3])
+        }
+        44 if rdlength >= 2 => {
+            // SSHFP: algorithm, fingerprint type, fingerprint
+            format!("{} {} {}", rdata[0], rdata[1], hex::encode(&rdata[2..]))
+        }
+        52 if rdlength >= 3 => {
+            // TLSA: certificate usage, selector, matching type, association data
+            format!(
+                "{} {} {} {}",
+                rdata[0],
+                rdata[1],
+                rdata[2],
+                hex::encode(&rdata[3..])
+            )
+        }
+        257 if rdlength >= 2 && 2 + rdata[1] as usize <= rdata.len() => {
+            // CAA: flags (128 = critical), tag such as issue/issuewild/iodef, quoted value
+            let tag_end = 2 + rdata[1] as usize;
+            let tag = String::from_utf8_lossy(&rdata[2..tag_end]);
+            let value = String::from_utf8_lossy(&rdata[tag_end..]);
+            format!("{} {tag} \"{value}\"", rdata[0])
+        }
+        _ => hex::encode(rdata),
     };
 
     *offset = end;
@@ -259,33 +516,1155 @@ mod hex {
     }
 }
 
-/// Perform a DNS query.
-pub fn query(config: &DnsConfig) -> Result<DnsResult, String> {
-    let qtype = config.record_type.to_qtype();
-    let packet = build_query(&config.domain, qtype);
-
-    let resolver_addr: SocketAddr = format!("{}:53", config.resolver)
-        .parse()
-        .map_err(|e| format!("Invalid resolver address: {e}"))?;
-
-    let socket =
-        UdpSocket::bind("0.0.0.0:0").map_err(|e| format!("Failed to bind UDP socket: {e}"))?;
-    socket
-        .set_read_timeout(Some(config.timeout))
-        .map_err(|e| format!("Failed to set timeout: {e}"))?;
-
+/// Resolve a resolver string to a socket address; port 53 unless one is given.
+///
+/// Accepts `8.8.8.8`, `127.0.0.1:5353`, `2001:4860:4860::8888` and `[::1]:5353`.
+fn resolver_addr(resolver: &str) -> Result<SocketAddr, String> {
+    if let Ok(ip) = resolver.parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip, 53));
+    }
+    resolver
+        .parse()
+        .map_err(|e| format!("Invalid resolver address: {e}"))
+}
+
+/// The wildcard local address of the same family as `addr`, with an ephemeral port.
+fn unspecified_for(addr: &SocketAddr) -> SocketAddr {
+    match addr {
+        SocketAddr::V4(_) => SocketAddr::from(([0, 0, 0, 0], 0)),
+        SocketAddr::V6(_) => SocketAddr::from(([0u16; 8], 0)),
+    }
+}
+
+/// Build the query packet for `config`, returning it with its transaction ID.
+fn query_packet(config: &DnsConfig) -> (Vec<u8>, u16, crate::idn::IdnInfo) {
+    let idn = crate::idn::analyze_idn(&config.domain);
+    let (mut packet, id) = build_query(&idn.ascii, config.record_type.to_qtype());
+    if config.client_subnet.is_some() || config.dnssec {
+        append_edns(&mut packet, config.client_subnet.as_ref(), config.dnssec);
+    }
+    if !config.recursion_desired {
+        packet[2] &= !0x01; // RD
+    }
+    if config.checking_disabled {
+        packet[3] |= 0x10; // CD
+    }
+    (packet, id, idn)
+}
+
+/// Whether a response has the TC (truncated) bit set.
+fn is_truncated(resp: &[u8]) -> bool {
+    read_u16(resp, 2) & 0x0200 != 0
+}
+
+/// Frame a message for DNS over TCP: a 2-byte big-endian length, then the message.
+fn tcp_frame(packet: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(packet.len() + 2);
+    framed.extend_from_slice(&(packet.len() as u16).to_be_bytes());
+    framed.extend_from_slice(packet);
+    framed
+}
+
+/// Check a response carries the query's transaction ID.
+fn check_response_id(resp: Vec<u8>, id: u16) -> Result<Vec<u8>, String> {
+    if resp.len() < 2 || read_u16(&resp, 0) != id {
+        return Err("response ID mismatch".to_string());
+    }
+    Ok(resp)
+}
+
+/// Send a query over TCP and read the whole (length-prefixed) response.
+fn query_tcp(packet: &[u8], addr: SocketAddr, timeout: Duration) -> Result<Vec<u8>, String> {
+    let mut stream = TcpStream::connect_timeout(&addr, timeout)
+        .map_err(|e| format!("Failed to connect over TCP: {e}"))?;
+    stream
+        .set_read_timeout(Some(timeout))
+        .and_then(|()| stream.set_write_timeout(Some(timeout)))
+        .map_err(|e| format!("Failed to set timeout: {e}"))?;
+    stream
+        .write_all(&tcp_frame(packet))
+        .map_err(|e| format!("Failed to send query over TCP: {e}"))?;
+    let mut len = [0u8; 2];
+    stream
+        .read_exact(&mut len)
+        .map_err(|e| format!("Failed to receive TCP response: {e}"))?;
+    let mut resp = vec![0u8; u16::from_be_bytes(len) as usize];
+    stream
+        .read_exact(&mut resp)
+        .map_err(|e| format!("Failed to receive TCP response: {e}"))?;
+    check_response_id(resp, read_u16(packet, 0))
+}
+
+/// Send one framed query on an open TCP connection and read the framed response.
+async fn tcp_exchange(
+    stream: &mut tokio::net::TcpStream,
+    packet: &[u8],
+) -> Result<Vec<u8>, String> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    stream
+        .write_all(&tcp_frame(packet))
+        .await
+        .map_err(|e| format!("Failed to send query over TCP: {e}"))?;
+    let len = stream
+        .read_u16()
+        .await
+        .map_err(|e| format!("Failed to receive TCP response: {e}"))?;
+    let mut resp = vec![0u8; len as usize];
+    stream
+        .read_exact(&mut resp)
+        .await
+        .map_err(|e| format!("Failed to receive TCP response: {e}"))?;
+    check_response_id(resp, read_u16(packet, 0))
+}
+
+/// Replies awaited on a client's UDP sockets, by resolver and transaction ID.
+type PendingReplies = std::sync::Mutex<HashMap<(SocketAddr, u16), oneshot::Sender<Vec<u8>>>>;
+
+/// Removes a query's pending entry when it completes, times out or is cancelled.
+struct PendingGuard<'a> {
+    pending: &'a PendingReplies,
+    key: (SocketAddr, u16),
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&self.key);
+        }
+    }
+}
+
+/// A DNS client that reuses one UDP socket per address family, and one TCP
+/// connection per resolver, across queries.
+///
+/// Queries may run concurrently: every query waiting on a shared socket reads
+/// from it and hands each reply to the query with the matching resolver and
+/// transaction ID, so concurrent users never take each other's answers. Replies
+/// nobody is waiting for, e.g. late ones for a timed-out query, are dropped.
+/// TCP queries to the same resolver take turns on its connection.
+#[derive(Debug, Default)]
+pub struct DnsClient {
+    v4: tokio::sync::OnceCell<tokio::net::UdpSocket>,
+    v6: tokio::sync::OnceCell<tokio::net::UdpSocket>,
+    pending: PendingReplies,
+    tcp: std::sync::Mutex<HashMap<SocketAddr, TcpConnection>>,
+}
+
+/// A kept-open TCP connection to one resolver, if any.
+type TcpConnection = Arc<tokio::sync::Mutex<Option<tokio::net::TcpStream>>>;
+
+impl DnsClient {
+    /// A client whose sockets are bound on first use.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Local addresses of the UDP sockets bound so far.
+    pub fn local_addrs(&self) -> Vec<SocketAddr> {
+        [self.v4.get(), self.v6.get()]
+            .into_iter()
+            .flatten()
+            .filter_map(|s| s.local_addr().ok())
+            .collect()
+    }
+
+    /// The shared UDP socket able to reach `addr`.
+    async fn socket_for(&self, addr: SocketAddr) -> Result<&tokio::net::UdpSocket, String> {
+        let cell = if addr.is_ipv4() { &self.v4 } else { &self.v6 };
+        cell.get_or_try_init(|| async {
+            tokio::net::UdpSocket::bind(unspecified_for(&addr))
+                .await
+                .map_err(|e| format!("Failed to bind UDP socket: {e}"))
+        })
+        .await
+    }
+
+    /// Send `packet` over UDP and wait for the reply carrying its ID.
+    async fn exchange_udp(
+        &self,
+        mut packet: Vec<u8>,
+        addr: SocketAddr,
+        timeout: Duration,
+    ) -> Result<Vec<u8>, String> {
+        let socket = self.socket_for(addr).await?;
+        let (tx, mut rx) = oneshot::channel();
+        let key = {
+            let mut pending = self
+                .pending
+                .lock()
+                .map_err(|_| "DNS client state poisoned".to_string())?;
+            // Another in-flight query to this resolver already uses the ID
+            let mut id = read_u16(&packet, 0);
+            while pending.contains_key(&(addr, id)) {
+                id = id.wrapping_add(1);
+            }
+            packet[..2].copy_from_slice(&id.to_be_bytes());
+            pending.insert((addr, id), tx);
+            (addr, id)
+        };
+        let _guard = PendingGuard {
+            pending: &self.pending,
+            key,
+        };
+        socket
+            .send_to(&packet, addr)
+            .await
+            .map_err(|e| format!("Failed to send query: {e}"))?;
+
+        let mut buf = [0u8; 4096];
+        let receive = async {
+            loop {
+                tokio::select! {
+                    biased;
+                    reply = &mut rx => {
+                        return reply
+                            .map_err(|_| "Failed to receive response: cancelled".to_string());
+                    }
+                    received = socket.recv_from(&mut buf) => {
+                        let (len, src) =
+                            received.map_err(|e| format!("Failed to receive response: {e}"))?;
+                        if len >= 2 {
+                            self.dispatch(src, &buf[..len]);
+                        }
+                    }
+                }
+            }
+        };
+        tokio::time::timeout(timeout, receive)
+            .await
+            .map_err(|_| "Failed to receive response: timed out".to_string())?
+    }
+
+    /// Hand a received datagram to the query waiting for it, if any.
+    fn dispatch(&self, src: SocketAddr, resp: &[u8]) {
+        let waiting = self
+            .pending
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.remove(&(src, read_u16(resp, 0))));
+        if let Some(tx) = waiting {
+            let _ = tx.send(resp.to_vec());
+        }
+    }
+
+    /// Send `packet` over this client's TCP connection to `addr`, opening (or
+    /// reopening, if the resolver closed it) the connection as needed.
+    async fn exchange_tcp(
+        &self,
+        packet: &[u8],
+        addr: SocketAddr,
+        timeout: Duration,
+    ) -> Result<Vec<u8>, String> {
+        let conn = self
+            .tcp
+            .lock()
+            .map_err(|_| "DNS client state poisoned".to_string())?
+            .entry(addr)
+            .or_default()
+            .clone();
+        let mut conn = conn.lock().await;
+        let exchange = async {
+            if let Some(stream) = conn.as_mut() {
+                if let Ok(resp) = tcp_exchange(stream, packet).await {
+                    return Ok(resp);
+                }
+            }
+            let mut stream = tokio::net::TcpStream::connect(addr)
+                .await
+                .map_err(|e| format!("Failed to connect over TCP: {e}"))?;
+            let resp = tcp_exchange(&mut stream, packet).await?;
+            *conn = Some(stream);
+            Ok(resp)
+        };
+        let resp = tokio::time::timeout(timeout, exchange)
+            .await
+            .unwrap_or_else(|_| Err("Failed to receive TCP response: timed out".to_string()));
+        if resp.is_err() {
+            // A half-finished exchange leaves the stream unusable
+            *conn = None;
+        }
+        resp
+    }
+
+    /// Perform a DNS query using this client's sockets.
+    pub async fn query_on(&self, config: &DnsConfig) -> Result<DnsResult, String> {
+        let timestamp = crate::timestamp::now();
+        let (packet, _, idn) = query_packet(config);
+        let resolver_addr = resolver_addr(&config.resolver)?;
+
+        let start = Instant::now();
+        let mut resp = if config.tcp {
+            self.exchange_tcp(&packet, resolver_addr, config.timeout)
+                .await?
+        } else {
+            self.exchange_udp(packet.clone(), resolver_addr, config.timeout)
+                .await?
+        };
+        if !config.tcp && is_truncated(&resp) {
+            // The answer didn't fit in a datagram; ask again over TCP for all of it
+            resp = self
+                .exchange_tcp(&packet, resolver_addr, config.timeout)
+                .await?;
+        }
+        let query_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        parse_response(&resp, config, idn, query_time_ms, timestamp)
+    }
+}
+
+/// Perform a DNS query on a one-shot socket.
+pub fn query(config: &DnsConfig) -> Result<DnsResult, String> {
+    let timestamp = crate::timestamp::now();
+    let (packet, id, idn) = query_packet(config);
+    let resolver_addr = resolver_addr(&config.resolver)?;
+
+    let start = Instant::now();
+    if config.tcp {
+        let resp = query_tcp(&packet, resolver_addr, config.timeout)?;
+        let query_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+        return parse_response(&resp, config, idn, query_time_ms, timestamp);
+    }
+    let socket = UdpSocket::bind(unspecified_for(&resolver_addr))
+        .map_err(|e| format!("Failed to bind UDP socket: {e}"))?;
+    socket
+        .send_to(&packet, resolver_addr)
+        .map_err(|e| format!("Failed to send query: {e}"))?;
+
+    let mut resp_buf = [0u8; 4096];
+    let len = loop {
+        let remaining = config
+            .timeout
+            .checked_sub(start.elapsed())
+            .filter(|d| !d.is_zero())
+            .ok_or("Failed to receive response: timed out")?;
+        socket
+            .set_read_timeout(Some(remaining))
+            .map_err(|e| format!("Failed to set timeout: {e}"))?;
+        let (len, src) = socket
+            .recv_from(&mut resp_buf)
+            .map_err(|e| format!("Failed to receive response: {e}"))?;
+        // Stray datagrams from elsewhere are not the answer
+        if src == resolver_addr && len >= 2 && read_u16(&resp_buf, 0) == id {
+            break len;
+        }
+    };
+    if is_truncated(&resp_buf[..len]) {
+        // The answer didn't fit in a datagram; ask again over TCP for all of it
+        let resp = query_tcp(&packet, resolver_addr, config.timeout)?;
+        let query_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+        return parse_response(&resp, config, idn, query_time_ms, timestamp);
+    }
+    let query_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    parse_response(&resp_buf[..len], config, idn, query_time_ms, timestamp)
+}
+
+/// Perform a DNS query without blocking the async runtime, on a one-shot [`DnsClient`].
+///
+/// Dropping the future cancels the query.
+pub async fn query_async(config: &DnsConfig) -> Result<DnsResult, String> {
+    DnsClient::new().query_on(config).await
+}
+
+/// How [`query_multi`] decides it has an answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultiMode {
+    /// Wait for every resolver and report the most common answer.
+    All,
+    /// Return as soon as any resolver answers successfully.
+    FirstResponse,
+    /// Return once this many resolvers agree on the answer.
+    Quorum(usize),
+}
+
+/// One resolver's outcome in a multi-resolver query.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolverAnswer {
+    pub resolver: String,
+    pub result: Option<DnsResult>,
+    pub error: Option<String>,
+}
+
+impl ResolverAnswer {
+    /// Whether the resolver answered with NOERROR. REFUSED, SERVFAIL and
+    /// NXDOMAIN answers are not agreement on an empty record set.
+    pub fn answered(&self) -> bool {
+        self.result
+            .as_ref()
+            .is_some_and(|r| r.response_code == "NOERROR")
+    }
+
+    /// Sorted record values, or `None` if the query failed.
+    pub fn values(&self) -> Option<Vec<String>> {
+        let result = self.result.as_ref()?;
+        let mut values: Vec<String> = result
+            .records
+            .iter()
+            .filter(|r| r.record_type == result.record_type)
+            .map(|r| r.value.clone())
+            .collect();
+        values.sort();
+        Some(values)
+    }
+}
+
+/// Result of querying several resolvers for the same name.
+#[derive(Debug, Clone, Serialize)]
+pub struct MultiDnsResult {
+    pub domain: String,
+    pub record_type: String,
+    /// Answers in resolver order; resolvers cancelled by an early exit are omitted.
+    pub answers: Vec<ResolverAnswer>,
+    /// The agreed (or most common) set of record values.
+    pub consensus: Option<Vec<String>>,
+    /// How many resolvers returned the consensus answer.
+    pub agreeing: usize,
+    /// Required agreement in quorum mode.
+    pub quorum: Option<usize>,
+    /// Resolver whose answer was used in first-response mode.
+    pub answered_by: Option<String>,
+    /// When the queries started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
+}
+
+/// The most common NOERROR answer and how many resolvers gave it.
+fn consensus<'a>(
+    answers: impl IntoIterator<Item = &'a ResolverAnswer>,
+) -> Option<(Vec<String>, usize)> {
+    let mut groups: Vec<(Vec<String>, usize)> = Vec::new();
+    for values in answers
+        .into_iter()
+        .filter(|a| a.answered())
+        .filter_map(|a| a.values())
+    {
+        match groups.iter_mut().find(|(v, _)| *v == values) {
+            Some((_, n)) => *n += 1,
+            None => groups.push((values, 1)),
+        }
+    }
+    // Earliest group wins ties
+    groups.into_iter().rev().max_by_key(|(_, n)| *n)
+}
+
+/// Why no resolver answered: each one's error or response code.
+fn no_answer(answers: &[ResolverAnswer]) -> String {
+    let reasons: Vec<String> = answers
+        .iter()
+        .map(|a| {
+            let why = match (&a.result, &a.error) {
+                (Some(r), _) => r.response_code.as_str(),
+                (None, Some(e)) => e.as_str(),
+                (None, None) => "no answer",
+            };
+            format!("{}: {why}", a.resolver)
+        })
+        .collect();
+    format!("No resolver answered ({})", reasons.join("; "))
+}
+
+/// Query several resolvers concurrently, at most `parallel` at a time (all at
+/// once when `None`).
+pub async fn query_multi(
+    config: &DnsConfig,
+    resolvers: &[String],
+    mode: MultiMode,
+    parallel: Option<usize>,
+) -> Result<MultiDnsResult, String> {
+    let timestamp = crate::timestamp::now();
+    if resolvers.is_empty() {
+        return Err("No resolvers given".to_string());
+    }
+    if let MultiMode::Quorum(n) = mode {
+        if n == 0 || n > resolvers.len() {
+            return Err(format!(
+                "Quorum must be between 1 and {} (the number of resolvers)",
+                resolvers.len()
+            ));
+        }
+    }
+
+    let limit = std::sync::Arc::new(tokio::sync::Semaphore::new(
+        parallel
+            .unwrap_or(resolvers.len())
+            .clamp(1, tokio::sync::Semaphore::MAX_PERMITS),
+    ));
+    let client = Arc::new(DnsClient::new());
+    let mut set = tokio::task::JoinSet::new();
+    for (i, resolver) in resolvers.iter().enumerate() {
+        let config = DnsConfig {
+            resolver: resolver.clone(),
+            ..config.clone()
+        };
+        let limit = limit.clone();
+        let client = client.clone();
+        set.spawn(async move {
+            let _permit = limit.acquire_owned().await;
+            (i, client.query_on(&config).await)
+        });
+    }
+
+    let mut done: Vec<(usize, ResolverAnswer)> = Vec::with_capacity(resolvers.len());
+    let mut first = None;
+    while let Some(joined) = set.join_next().await {
+        let (i, result) = joined.map_err(|e| format!("DNS query task failed: {e}"))?;
+        let (result, error) = match result {
+            Ok(r) => (Some(r), None),
+            Err(e) => (None, Some(e)),
+        };
+        let answer = ResolverAnswer {
+            resolver: resolvers[i].clone(),
+            result,
+            error,
+        };
+        let answered = answer.answered();
+        done.push((i, answer));
+        let finished = match mode {
+            MultiMode::All => false,
+            MultiMode::FirstResponse => answered,
+            MultiMode::Quorum(n) => {
+                consensus(done.iter().map(|(_, a)| a)).is_some_and(|(_, agreeing)| agreeing >= n)
+            }
+        };
+        if finished {
+            first = Some(i);
+            // Dropping the remaining queries cancels them
+            set.abort_all();
+            break;
+        }
+    }
+
+    done.sort_by_key(|(i, _)| *i);
+    let answers: Vec<ResolverAnswer> = done.into_iter().map(|(_, a)| a).collect();
+    let mut answered_by = None;
+    let (consensus, agreeing) = match mode {
+        MultiMode::FirstResponse => {
+            let winner = first
+                .and_then(|i| answers.iter().find(|a| a.resolver == resolvers[i]))
+                .ok_or_else(|| no_answer(&answers))?;
+            answered_by = Some(winner.resolver.clone());
+            (winner.values(), 1)
+        }
+        _ => match consensus(&answers) {
+            Some((values, n)) => (Some(values), n),
+            None => return Err(no_answer(&answers)),
+        },
+    };
+    if let MultiMode::Quorum(n) = mode {
+        if agreeing < n {
+            return Err(format!(
+                "No quorum: at most {agreeing} of {} resolvers agree, {n} required",
+                resolvers.len()
+            ));
+        }
+    }
+
+    Ok(MultiDnsResult {
+        domain: config.domain.clone(),
+        record_type: config.record_type.to_string(),
+        answers,
+        consensus,
+        agreeing,
+        quorum: match mode {
+            MultiMode::Quorum(n) => Some(n),
+            _ => None,
+        },
+        answered_by,
+        timestamp,
+    })
+}
+
+/// Internal and external answers for the same name, from a split-horizon check.
+#[derive(Debug, Clone, Serialize)]
+pub struct SplitHorizonResult {
+    pub domain: String,
+    pub record_type: String,
+    pub internal: ResolverAnswer,
+    pub external: ResolverAnswer,
+    /// Both resolvers answered and their record sets differ.
+    pub split_detected: bool,
+    /// When the check started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
+}
+
+/// Ask `internal` and the configured (public) resolver for the same name and
+/// report whether the answers differ.
+pub async fn split_check(config: &DnsConfig, internal: &str) -> Result<SplitHorizonResult, String> {
+    let timestamp = crate::timestamp::now();
+    let resolvers = [internal.to_string(), config.resolver.clone()];
+    let mut answers = query_multi(config, &resolvers, MultiMode::All, None)
+        .await?
+        .answers
+        .into_iter();
+    let (Some(internal), Some(external)) = (answers.next(), answers.next()) else {
+        return Err("Split-horizon check needs an answer from both resolvers".to_string());
+    };
+    let split_detected = match (internal.values(), external.values()) {
+        (Some(a), Some(b)) => a != b,
+        _ => false,
+    };
+    Ok(SplitHorizonResult {
+        domain: config.domain.clone(),
+        record_type: config.record_type.to_string(),
+        internal,
+        external,
+        split_detected,
+        timestamp,
+    })
+}
+
+/// The PTR query name for an address, e.g. `7.113.0.203.in-addr.arpa`.
+pub fn reverse_name(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, d] = v4.octets();
+            format!("{d}.{c}.{b}.{a}.in-addr.arpa")
+        }
+        IpAddr::V6(v6) => {
+            let nibbles: Vec<String> = v6
+                .octets()
+                .iter()
+                .rev()
+                .flat_map(|b| [b & 0x0F, b >> 4])
+                .map(|n| format!("{n:x}"))
+                .collect();
+            format!("{}.ip6.arpa", nibbles.join("."))
+        }
+    }
+}
+
+/// Look up the PTR names for an address with the configured resolver.
+pub fn reverse_lookup(config: &DnsConfig, ip: IpAddr) -> Result<Vec<String>, String> {
+    Ok(ptr_names(&query(&ptr_query(config, ip))?))
+}
+
+/// [`reverse_lookup`] without blocking the async runtime.
+pub async fn reverse_lookup_async(config: &DnsConfig, ip: IpAddr) -> Result<Vec<String>, String> {
+    Ok(ptr_names(&query_async(&ptr_query(config, ip)).await?))
+}
+
+fn ptr_query(config: &DnsConfig, ip: IpAddr) -> DnsConfig {
+    DnsConfig {
+        domain: reverse_name(ip),
+        record_type: RecordType::PTR,
+        ..config.clone()
+    }
+}
+
+fn ptr_names(result: &DnsResult) -> Vec<String> {
+    result
+        .records_of_type("PTR")
+        .iter()
+        .map(|r| r.value.trim_end_matches('.').to_string())
+        .collect()
+}
+
+/// The first nameserver in `/etc/resolv.conf`, which also knows private
+/// addresses, or the default public resolver.
+pub(crate) fn ptr_resolver() -> String {
+    crate::netif::system_resolvers()
+        .ok()
+        .and_then(|servers| servers.into_iter().next())
+        .unwrap_or_else(|| DnsConfig::default().resolver)
+}
+
+/// Reverse DNS of one forward address, and whether it points back.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReverseCheck {
+    pub ip: IpAddr,
+    pub ptr: Vec<String>,
+    /// A PTR name matches the queried name (or its canonical name).
+    pub verified: bool,
+    pub error: Option<String>,
+}
+
+/// Forward-confirmed reverse DNS for every A/AAAA address of a name.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReverseVerifyResult {
+    pub domain: String,
+    pub resolver: String,
+    pub addresses: Vec<ReverseCheck>,
+    /// When the check started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
+}
+
+impl ReverseVerifyResult {
+    /// Every address's PTR points back to the name.
+    pub fn all_verified(&self) -> bool {
+        self.addresses.iter().all(|a| a.verified)
+    }
+}
+
+/// Resolve A and AAAA for `config.domain`, then check each address's PTR
+/// points back to it (forward-confirmed reverse DNS).
+pub fn verify_reverse(config: &DnsConfig) -> Result<ReverseVerifyResult, String> {
+    let timestamp = crate::timestamp::now();
+    let mut names = vec![config.domain.trim_end_matches('.').to_lowercase()];
+    let mut addrs = Vec::new();
+    for record_type in [RecordType::A, RecordType::AAAA] {
+        let forward = query(&DnsConfig {
+            record_type,
+            ..config.clone()
+        })?;
+        let canonical = forward
+            .canonical_name()
+            .trim_end_matches('.')
+            .to_lowercase();
+        if !names.contains(&canonical) {
+            names.push(canonical);
+        }
+        addrs.extend(
+            forward
+                .final_records()
+                .iter()
+                .filter_map(|r| r.value.parse::<IpAddr>().ok()),
+        );
+    }
+    if addrs.is_empty() {
+        return Err(format!("{} has no A or AAAA records", config.domain));
+    }
+
+    let addresses = addrs
+        .into_iter()
+        .map(|ip| match reverse_lookup(config, ip) {
+            Ok(ptr) => ReverseCheck {
+                verified: ptr.iter().any(|p| names.contains(&p.to_lowercase())),
+                ip,
+                ptr,
+                error: None,
+            },
+            Err(e) => ReverseCheck {
+                ip,
+                ptr: Vec::new(),
+                verified: false,
+                error: Some(e),
+            },
+        })
+        .collect();
+    Ok(ReverseVerifyResult {
+        domain: config.domain.clone(),
+        resolver: config.resolver.clone(),
+        addresses,
+        timestamp,
+    })
+}
+
+/// Async [`verify_reverse`]; the queries run on the blocking pool.
+pub async fn verify_reverse_async(config: &DnsConfig) -> Result<ReverseVerifyResult, String> {
+    let config = config.clone();
+    crate::runtime::blocking(move || verify_reverse(&config)).await
+}
+
+/// Parse a comma-separated list of record types, e.g. `A,AAAA,MX,NS`.
+pub fn parse_record_types(list: &str) -> Result<Vec<RecordType>, String> {
+    let types: Vec<RecordType> = list
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| RecordType::from_str_loose(t).ok_or_else(|| format!("Unknown record type: {t}")))
+        .collect::<Result<_, _>>()?;
+    if types.is_empty() {
+        return Err("No record types given".to_string());
+    }
+    Ok(types)
+}
+
+/// Records of several types for one name, normalized so saved runs diff cleanly:
+/// no timings or timestamp, and everything in a stable order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsMonitorSet {
+    pub domain: String,
+    pub resolver: String,
+    /// Queried types, sorted.
+    pub types: Vec<String>,
+    /// Response code for each queried type.
+    pub status: BTreeMap<String, String>,
+    /// Records sorted by type, then value, then name; duplicates merged keeping
+    /// the lowest TTL.
+    pub records: Vec<DnsRecord>,
+}
+
+impl DnsMonitorSet {
+    /// Merge per-type query results, given in any order.
+    pub fn from_results(domain: &str, resolver: &str, results: &[DnsResult]) -> Self {
+        let mut status = BTreeMap::new();
+        let mut records = Vec::new();
+        for result in results {
+            status.insert(
+                result.record_type.to_uppercase(),
+                result.response_code.clone(),
+            );
+            records.extend(result.records.iter().map(|r| DnsRecord {
+                name: r.name.trim_end_matches('.').to_lowercase(),
+                record_type: r.record_type.to_uppercase(),
+                ..r.clone()
+            }));
+        }
+        records.sort_by(|a, b| {
+            (&a.record_type, &a.value, &a.name, a.ttl).cmp(&(
+                &b.record_type,
+                &b.value,
+                &b.name,
+                b.ttl,
+            ))
+        });
+        // Sorted by TTL last, so the first of each run of duplicates has the lowest
+        records.dedup_by(|later, first| {
+            (&later.record_type, &later.value, &later.name)
+                == (&first.record_type, &first.value, &first.name)
+        });
+        Self {
+            domain: domain.trim_end_matches('.').to_lowercase(),
+            resolver: resolver.to_string(),
+            types: status.keys().cloned().collect(),
+            status,
+            records,
+        }
+    }
+}
+
+/// Query each of `types` for `config.domain` and merge the answers into a
+/// [`DnsMonitorSet`].
+pub async fn monitor_set(
+    config: &DnsConfig,
+    types: &[RecordType],
+) -> Result<DnsMonitorSet, String> {
+    let client = DnsClient::new();
+    let mut results = Vec::with_capacity(types.len());
+    for &record_type in types {
+        let config = DnsConfig {
+            record_type,
+            ..config.clone()
+        };
+        results.push(client.query_on(&config).await?);
+    }
+    Ok(DnsMonitorSet::from_results(
+        &config.domain,
+        &config.resolver,
+        &results,
+    ))
+}
+
+/// Names queried by [`fastest_resolver`] when none are given.
+pub const BENCH_DOMAINS: &[&str] = &["google.com", "cloudflare.com", "wikipedia.org"];
+
+/// One resolver's latency across a resolver benchmark.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolverLatency {
+    pub resolver: String,
+    /// Queries answered with NOERROR.
+    pub answered: usize,
+    pub queries: usize,
+    pub avg_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    /// First failure, if any query went unanswered or got an error rcode.
+    pub error: Option<String>,
+}
+
+/// Resolvers ranked by how quickly they answered the same set of names.
+#[derive(Debug, Clone, Serialize)]
+pub struct FastestResolverResult {
+    pub domains: Vec<String>,
+    pub record_type: String,
+    /// Fastest first; resolvers with any failed query are ranked last.
+    pub ranking: Vec<ResolverLatency>,
+    /// The recommended resolver: the fastest one that answered every query.
+    pub fastest: Option<String>,
+    /// When the benchmark started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
+}
+
+/// Query every name in `domains` through each resolver and rank them by average latency.
+///
+/// Resolvers run concurrently; each one's names are queried in turn so its
+/// samples don't compete with each other.
+pub async fn fastest_resolver(
+    config: &DnsConfig,
+    resolvers: &[String],
+    domains: &[String],
+) -> Result<FastestResolverResult, String> {
+    let timestamp = crate::timestamp::now();
+    if resolvers.is_empty() {
+        return Err("No resolvers given".to_string());
+    }
+    if domains.is_empty() {
+        return Err("No domains given".to_string());
+    }
+
+    let client = Arc::new(DnsClient::new());
+    let mut set = tokio::task::JoinSet::new();
+    for resolver in resolvers {
+        let configs: Vec<DnsConfig> = domains
+            .iter()
+            .map(|domain| DnsConfig {
+                domain: domain.clone(),
+                resolver: resolver.clone(),
+                ..config.clone()
+            })
+            .collect();
+        let resolver = resolver.clone();
+        let client = client.clone();
+        set.spawn(async move {
+            let mut samples = Vec::new();
+            let mut error = None;
+            for config in &configs {
+                match client.query_on(config).await {
+                    Ok(r) if r.response_code == "NOERROR" => samples.push(r.query_time_ms),
+                    // A fast REFUSED or SERVFAIL is not a usable answer
+                    Ok(r) => {
+                        error.get_or_insert(format!("{}: {}", config.domain, r.response_code));
+                    }
+                    Err(e) => {
+                        error.get_or_insert(format!("{}: {e}", config.domain));
+                    }
+                }
+            }
+            ResolverLatency {
+                resolver,
+                answered: samples.len(),
+                queries: configs.len(),
+                avg_ms: crate::stats::mean(&samples),
+                p95_ms: crate::stats::percentile(&samples, 95.0),
+                error,
+            }
+        });
+    }
+
+    let mut ranking = Vec::with_capacity(resolvers.len());
+    while let Some(joined) = set.join_next().await {
+        ranking.push(joined.map_err(|e| format!("DNS query task failed: {e}"))?);
+    }
+    ranking.sort_by(|a, b| {
+        (a.error.is_some(), a.avg_ms.unwrap_or(f64::INFINITY))
+            .partial_cmp(&(b.error.is_some(), b.avg_ms.unwrap_or(f64::INFINITY)))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let fastest = ranking
+        .first()
+        .filter(|r| r.error.is_none())
+        .map(|r| r.resolver.clone());
+
+    Ok(FastestResolverResult {
+        domains: domains.to_vec(),
+        record_type: config.record_type.to_string(),
+        ranking,
+        fastest,
+        timestamp,
+    })
+}
+
+/// DNSSEC verdict for a name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DnssecStatus {
+    /// Signed, and the resolver validated the answer (AD bit set).
+    Validated,
+    /// Signatures are present but the resolver did not vouch for them: it doesn't
+    /// validate, or validation failed.
+    SignedNotValidated,
+    /// No DNSSEC records were returned.
+    Unsigned,
+}
+
+impl std::fmt::Display for DnssecStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Validated => "validated",
+            Self::SignedNotValidated => "signed, not validated",
+            Self::Unsigned => "unsigned",
+        })
+    }
+}
+
+/// DNSSEC records and the resolver's verdict for a name.
+#[derive(Debug, Clone, Serialize)]
+pub struct DnssecResult {
+    pub domain: String,
+    pub resolver: String,
+    pub record_type: String,
+    pub status: DnssecStatus,
+    /// The resolver set the AD (authenticated data) bit on the answer.
+    pub authenticated_data: bool,
+    /// RRSIG records covering the answer.
+    pub rrsig: Vec<DnsRecord>,
+    pub dnskey: Vec<DnsRecord>,
+    pub ds: Vec<DnsRecord>,
+    /// The answer itself, queried with the DO bit set.
+    pub answer: DnsResult,
+    /// When the check started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
+}
+
+impl DnssecResult {
+    /// Combine the DO-bit answer with the name's DNSKEY and DS lookups.
+    pub fn from_answers(
+        answer: DnsResult,
+        dnskey: Option<&DnsResult>,
+        ds: Option<&DnsResult>,
+    ) -> Self {
+        let of_type = |r: Option<&DnsResult>, t: &str| -> Vec<DnsRecord> {
+            r.map(|r| r.records_of_type(t).into_iter().cloned().collect())
+                .unwrap_or_default()
+        };
+        let rrsig = of_type(Some(&answer), "RRSIG");
+        let dnskey = of_type(dnskey, "DNSKEY");
+        let ds = of_type(ds, "DS");
+        let signed = !rrsig.is_empty() || !dnskey.is_empty() || !ds.is_empty();
+        let status = if answer.authenticated_data {
+            DnssecStatus::Validated
+        } else if signed {
+            DnssecStatus::SignedNotValidated
+        } else {
+            DnssecStatus::Unsigned
+        };
+        Self {
+            timestamp: answer.timestamp.clone(),
+            domain: answer.domain.clone(),
+            resolver: answer.resolver.clone(),
+            record_type: answer.record_type.clone(),
+            status,
+            authenticated_data: answer.authenticated_data,
+            rrsig,
+            dnskey,
+            ds,
+            answer,
+        }
+    }
+
+    /// Whether any DNSSEC records were returned for the name.
+    pub fn is_signed(&self) -> bool {
+        self.status != DnssecStatus::Unsigned
+    }
+}
+
+/// Query a name with the DO bit, plus its DNSKEY and DS records, and report
+/// whether it is signed and whether the resolver validated it.
+///
+/// Signatures are not checked locally; the verdict relies on the resolver's AD bit.
+pub fn dnssec_check(config: &DnsConfig) -> Result<DnssecResult, String> {
+    let config = DnsConfig {
+        dnssec: true,
+        ..config.clone()
+    };
+    let answer = query(&config)?;
+    let lookup = |record_type| {
+        query(&DnsConfig {
+            record_type,
+            ..config.clone()
+        })
+        .ok()
+    };
+    let dnskey = lookup(RecordType::DNSKEY);
+    let ds = lookup(RecordType::DS);
+    Ok(DnssecResult::from_answers(
+        answer,
+        dnskey.as_ref(),
+        ds.as_ref(),
+    ))
+}
+
+/// Async [`dnssec_check`]; the answer, DNSKEY and DS lookups run concurrently
+/// on one [`DnsClient`].
+pub async fn dnssec_check_async(config: &DnsConfig) -> Result<DnssecResult, String> {
+    let config = DnsConfig {
+        dnssec: true,
+        ..config.clone()
+    };
+    let with_type = |record_type| DnsConfig {
+        record_type,
+        ..config.clone()
+    };
+    let (dnskey_config, ds_config) = (with_type(RecordType::DNSKEY), with_type(RecordType::DS));
+    let client = DnsClient::new();
+    let (answer, dnskey, ds) = tokio::join!(
+        client.query_on(&config),
+        client.query_on(&dnskey_config),
+        client.query_on(&ds_config),
+    );
+    Ok(DnssecResult::from_answers(
+        answer?,
+        dnskey.ok().as_ref(),
+        ds.ok().as_ref(),
+    ))
+}
+
+/// Addresses for a name as the operating system resolves it.
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemDnsResult {
+    pub domain: String,
+    /// Always `"system"`, to tell these apart from direct queries.
+    pub resolver: String,
+    /// `A`, `AAAA`, or `A/AAAA` when both families were requested.
+    pub record_type: String,
+    pub addresses: Vec<IpAddr>,
+    pub query_time_ms: f64,
+    /// When the lookup started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
+}
+
+/// Resolve a name through the OS stub resolver (`/etc/hosts`, nsswitch, split DNS).
+///
+/// `record_type` may be `A` or `AAAA` to keep one address family; `None` keeps both.
+pub fn system_resolve(
+    domain: &str,
+    record_type: Option<RecordType>,
+) -> Result<SystemDnsResult, String> {
+    let timestamp = crate::timestamp::now();
+    let keep: fn(&IpAddr) -> bool = match record_type {
+        None => |_| true,
+        Some(RecordType::A) => IpAddr::is_ipv4,
+        Some(RecordType::AAAA) => IpAddr::is_ipv6,
+        Some(other) => {
+            return Err(format!(
+                "System resolution only supports A and AAAA records, not {other}"
+            ))
+        }
+    };
     let start = Instant::now();
-    socket
-        .send_to(&packet, resolver_addr)
-        .map_err(|e| format!("Failed to send query: {e}"))?;
-
-    let mut resp_buf = [0u8; 4096];
-    let (len, _) = socket
-        .recv_from(&mut resp_buf)
-        .map_err(|e| format!("Failed to receive response: {e}"))?;
+    let resolved = (domain, 0)
+        .to_socket_addrs()
+        .map_err(|e| format!("System resolver failed for {domain}: {e}"))?;
     let query_time_ms = start.elapsed().as_secs_f64() * 1000.0;
 
-    let resp = &resp_buf[..len];
+    let mut addresses: Vec<IpAddr> = Vec::new();
+    for ip in resolved.map(|a| a.ip()).filter(keep) {
+        if !addresses.contains(&ip) {
+            addresses.push(ip);
+        }
+    }
+    if addresses.is_empty() {
+        return Err(format!(
+            "System resolver returned no {} addresses for {domain}",
+            record_type.map_or("A/AAAA".to_string(), |t| t.to_string())
+        ));
+    }
+    Ok(SystemDnsResult {
+        domain: domain.to_string(),
+        resolver: "system".to_string(),
+        record_type: record_type.map_or("A/AAAA".to_string(), |t| t.to_string()),
+        addresses,
+        query_time_ms,
+        timestamp,
+    })
+}
+
+/// Async [`system_resolve`]; the OS resolver call runs on the blocking pool.
+pub async fn system_resolve_async(
+    domain: &str,
+    record_type: Option<RecordType>,
+) -> Result<SystemDnsResult, String> {
+    let domain = domain.to_string();
+    crate::runtime::blocking(move || system_resolve(&domain, record_type)).await
+}
+
+/// Parse a DNS response packet into a result.
+fn parse_response(
+    resp: &[u8],
+    config: &DnsConfig,
+    idn: crate::idn::IdnInfo,
+    query_time_ms: f64,
+    timestamp: String,
+) -> Result<DnsResult, String> {
+    let len = resp.len();
     if len < 12 {
         return Err("Response too short".to_string());
     }
@@ -323,29 +1702,43 @@ pub fn query(config: &DnsConfig) -> Result<DnsResult, String> {
         offset += 4;
         let rdlength = read_u16(resp, offset);
         offset += 2;
-
-        let rtype_name = match rtype {
-            1 => "A",
-            28 => "AAAA",
-            5 => "CNAME",
-            2 => "NS",
-            15 => "MX",
-            16 => "TXT",
-            6 => "SOA",
-            12 => "PTR",
-            _ => "UNKNOWN",
-        };
+        if offset + rdlength as usize > len {
+            break;
+        }
 
         let value = parse_rdata(resp, &mut offset, rdlength, rtype);
 
         records.push(DnsRecord {
             name,
-            record_type: rtype_name.to_string(),
+            record_type: rtype_name(rtype).to_string(),
             ttl,
             value,
         });
     }
 
+    // Skip the authority section and look for an OPT record in the additional section
+    let nscount = read_u16(resp, 8) as usize;
+    let arcount = read_u16(resp, 10) as usize;
+    let mut client_subnet_scope = None;
+    for i in 0..(nscount + arcount) {
+        if offset >= len {
+            break;
+        }
+        parse_name(resp, &mut offset);
+        if offset + 10 > len {
+            break;
+        }
+        let rtype = read_u16(resp, offset);
+        let rdlength = read_u16(resp, offset + 8) as usize;
+        offset += 10;
+        let end = (offset + rdlength).min(len);
+        if i >= nscount && rtype == 41 {
+            client_subnet_scope = parse_ecs_scope(&resp[offset..end]);
+        }
+        offset = end;
+    }
+
+    let cname_chain = cname_chain(&idn.ascii, &records);
     Ok(DnsResult {
         domain: config.domain.clone(),
         resolver: config.resolver.clone(),
@@ -356,23 +1749,163 @@ pub fn query(config: &DnsConfig) -> Result<DnsResult, String> {
         truncated,
         recursion_available,
         authenticated_data,
+        client_subnet: config.client_subnet.map(|s| s.to_string()),
+        client_subnet_scope,
+        idn: idn.is_idn.then_some(idn),
+        cname_chain,
+        timestamp,
     })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::DnsResponseBuilder;
+
+    #[test]
+    fn test_tcp_frame_prefixes_length() {
+        let config = DnsConfig {
+            domain: "example.com".into(),
+            ..Default::default()
+        };
+        let (pkt, _, _) = query_packet(&config);
+        let framed = tcp_frame(&pkt);
+        assert_eq!(framed.len(), pkt.len() + 2);
+        assert_eq!(
+            u16::from_be_bytes([framed[0], framed[1]]) as usize,
+            pkt.len()
+        );
+        assert_eq!(&framed[2..], &pkt[..]);
+        assert_eq!(&tcp_frame(&[0xAB; 300])[..2], &[0x01, 0x2C]);
+    }
+
+    /// Serve one truncated UDP reply and the full answer over TCP on the same port.
+    fn spawn_truncating_resolver() -> SocketAddr {
+        let tcp = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = tcp.local_addr().unwrap();
+        let udp = UdpSocket::bind(addr).unwrap();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 512];
+            let (_, src) = udp.recv_from(&mut buf).unwrap();
+            let id = read_u16(&buf, 0);
+            let truncated = DnsResponseBuilder::new(id)
+                .flags(0x8380)
+                .question("example.com", 16)
+                .build();
+            udp.send_to(&truncated, src).unwrap();
+        });
+        std::thread::spawn(move || {
+            let (mut stream, _) = tcp.accept().unwrap();
+            let mut len = [0u8; 2];
+            stream.read_exact(&mut len).unwrap();
+            let mut query = vec![0u8; u16::from_be_bytes(len) as usize];
+            stream.read_exact(&mut query).unwrap();
+            let mut full = DnsResponseBuilder::new(read_u16(&query, 0)).question("example.com", 16);
+            for i in 0..3 {
+                full = full.answer(
+                    "example.com",
+                    16,
+                    60,
+                    &[5, b'r', b'e', b'c', b'-', b'0' + i],
+                );
+            }
+            stream.write_all(&tcp_frame(&full.build())).unwrap();
+        });
+        addr
+    }
+
+    #[test]
+    fn test_truncated_udp_answer_retries_over_tcp() {
+        let config = DnsConfig {
+            domain: "example.com".into(),
+            record_type: RecordType::TXT,
+            resolver: spawn_truncating_resolver().to_string(),
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let result = query(&config).unwrap();
+        assert!(!result.truncated);
+        assert_eq!(result.records.len(), 3);
+        assert_eq!(result.records[2].value, "rec-2");
+    }
+
+    #[tokio::test]
+    async fn test_async_query_retries_over_tcp() {
+        let config = DnsConfig {
+            domain: "example.com".into(),
+            record_type: RecordType::TXT,
+            resolver: spawn_truncating_resolver().to_string(),
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let result = query_async(&config).await.unwrap();
+        assert_eq!(result.records.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_dns_client_reuses_tcp_connection() {
+        // Accept a single connection and answer two queries on it
+        let tcp = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let resolver = tcp.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = tcp.accept().unwrap();
+            for _ in 0..2 {
+                let mut len = [0u8; 2];
+                stream.read_exact(&mut len).unwrap();
+                let mut query = vec![0u8; u16::from_be_bytes(len) as usize];
+                stream.read_exact(&mut query).unwrap();
+                let resp = DnsResponseBuilder::new(read_u16(&query, 0))
+                    .question("example.com", 1)
+                    .answer("example.com", 1, 60, &[192, 0, 2, 9])
+                    .build();
+                stream.write_all(&tcp_frame(&resp)).unwrap();
+            }
+        });
+        let client = DnsClient::new();
+        let config = DnsConfig {
+            domain: "example.com".into(),
+            resolver: resolver.to_string(),
+            tcp: true,
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        for _ in 0..2 {
+            let result = client.query_on(&config).await.unwrap();
+            assert_eq!(result.records[0].value, "192.0.2.9");
+        }
+    }
+
+    #[test]
+    fn test_query_packet_header_flags() {
+        let config = DnsConfig {
+            domain: "example.com".into(),
+            ..Default::default()
+        };
+        let (pkt, _, _) = query_packet(&config);
+        assert_eq!(&pkt[2..4], &[0x01, 0x00]);
+
+        let config = DnsConfig {
+            recursion_desired: false,
+            checking_disabled: true,
+            ..config
+        };
+        let (pkt, _, _) = query_packet(&config);
+        // RD=0 in the first flags byte, CD=1 in the second
+        assert_eq!(pkt[2] & 0x01, 0);
+        assert_eq!(pkt[3] & 0x10, 0x10);
+        assert_eq!(&pkt[2..4], &[0x00, 0x10]);
+    }
 
     #[test]
     fn test_build_query_structure() {
-        let pkt = build_query("example.com", 1);
-        // Header is 12 bytes
-        assert_eq!(pkt[0], 0xAB);
-        assert_eq!(pkt[1], 0xCD);
+        let (pkt, id) = build_query("example.com", 1);
+        // Header is 12 bytes, starting with the returned ID
+        assert_eq!(read_u16(&pkt, 0), id);
         // RD flag set
         assert_eq!(pkt[2], 0x01);
-        // QDCOUNT = 1
-        assert_eq!(pkt[5], 0x01);
+        // QDCOUNT = 1, ANCOUNT = NSCOUNT = ARCOUNT = 0
+        assert_eq!(read_u16(&pkt, 4), 1);
+        assert_eq!(&pkt[6..12], &[0; 6]);
         // First label: "example" (7 bytes)
         assert_eq!(pkt[12], 7);
         assert_eq!(&pkt[13..20], b"example");
@@ -383,9 +1916,26 @@ mod tests {
         assert_eq!(pkt[24], 0);
     }
 
+    #[test]
+    fn test_build_query_ids_are_random() {
+        let ids: std::collections::HashSet<u16> =
+            (0..16).map(|_| build_query("example.com", 1).1).collect();
+        assert!(ids.len() > 1);
+    }
+
+    #[test]
+    fn test_check_response_id() {
+        assert!(check_response_id(vec![0x12, 0x34, 0x81, 0x80], 0x1234).is_ok());
+        assert_eq!(
+            check_response_id(vec![0x12, 0x35, 0x81, 0x80], 0x1234).unwrap_err(),
+            "response ID mismatch"
+        );
+        assert!(check_response_id(vec![0x12], 0x1234).is_err());
+    }
+
     #[test]
     fn test_build_query_single_label() {
-        let pkt = build_query("localhost", 1);
+        let (pkt, _) = build_query("localhost", 1);
         assert_eq!(pkt[12], 9); // "localhost" length
         assert_eq!(&pkt[13..22], b"localhost");
         assert_eq!(pkt[22], 0); // Root label
@@ -393,7 +1943,7 @@ mod tests {
 
     #[test]
     fn test_build_query_subdomain() {
-        let pkt = build_query("www.sub.example.com", 28);
+        let (pkt, _) = build_query("www.sub.example.com", 28);
         assert_eq!(pkt[12], 3); // "www"
         assert_eq!(&pkt[13..16], b"www");
         assert_eq!(pkt[16], 3); // "sub"
@@ -446,8 +1996,34 @@ mod tests {
         assert_eq!(offset, 9);
     }
 
-    // Note: DNS name compression is not fully implemented in this simplified version.
-    // A full implementation would properly handle compression pointers.
+    #[test]
+    fn test_parse_name_compression_pointer() {
+        // "example.com" at 0, then "www" + pointer to offset 0 at 13
+        let buf = b"\x07example\x03com\x00\x03www\xC0\x00\xFF";
+        let mut offset = 13;
+        assert_eq!(parse_name(buf, &mut offset), "www.example.com");
+        assert_eq!(offset, 19);
+
+        // A bare pointer advances past its two bytes only
+        let mut offset = 17;
+        assert_eq!(parse_name(buf, &mut offset), "example.com");
+        assert_eq!(offset, 19);
+    }
+
+    #[test]
+    fn test_parse_name_pointer_loops_terminate() {
+        // A pointer to itself
+        let buf = b"\xC0\x00";
+        let mut offset = 0;
+        assert_eq!(parse_name(buf, &mut offset), "");
+        assert_eq!(offset, 2);
+
+        // Two names pointing at each other
+        let buf = b"\x01a\xC0\x05\x00\x01b\xC0\x00";
+        let mut offset = 0;
+        parse_name(buf, &mut offset);
+        assert_eq!(offset, 4);
+    }
 
     #[test]
     fn test_parse_name_empty_buffer() {
@@ -556,6 +2132,16 @@ mod tests {
         assert_eq!(offset, 4);
     }
 
+    #[test]
+    fn test_parse_rdata_truncated() {
+        // RDLENGTH claims more bytes than the packet has left
+        for rtype in [1, 28, 15, 16, 43, 44, 46, 48, 52, 257, 999] {
+            let mut offset = 0;
+            assert_eq!(parse_rdata(&[1, 2], &mut offset, 20, rtype), "");
+            assert_eq!(offset, 2);
+        }
+    }
+
     #[test]
     fn test_dns_config_default() {
         let config = DnsConfig::default();
@@ -563,6 +2149,7 @@ mod tests {
         assert_eq!(config.record_type, RecordType::A);
         assert_eq!(config.resolver, "8.8.8.8");
         assert_eq!(config.timeout, Duration::from_secs(5));
+        assert!(config.client_subnet.is_none());
     }
 
     #[test]
@@ -591,6 +2178,11 @@ mod tests {
             truncated: false,
             recursion_available: true,
             authenticated_data: false,
+            client_subnet: None,
+            client_subnet_scope: None,
+            idn: None,
+            cname_chain: vec![],
+            timestamp: String::new(),
         };
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("example.com"));
@@ -598,6 +2190,913 @@ mod tests {
         assert!(json.contains("NOERROR"));
     }
 
+    #[test]
+    fn test_client_subnet_parse() {
+        let s = ClientSubnet::parse("203.0.113.0/24").unwrap();
+        assert_eq!(s.addr, "203.0.113.0".parse::<IpAddr>().unwrap());
+        assert_eq!(s.prefix_len, 24);
+        assert_eq!(s.to_string(), "203.0.113.0/24");
+        assert_eq!(ClientSubnet::parse("10.0.0.1").unwrap().prefix_len, 32);
+        assert_eq!(ClientSubnet::parse("2001:db8::/56").unwrap().prefix_len, 56);
+        assert!(ClientSubnet::parse("10.0.0.0/33").is_err());
+        assert!(ClientSubnet::parse("not-an-ip/24").is_err());
+    }
+
+    #[test]
+    fn test_client_subnet_truncated_addr() {
+        let s = ClientSubnet::parse("203.0.113.77/20").unwrap();
+        assert_eq!(s.truncated_addr(), vec![203, 0, 112]);
+        let s = ClientSubnet::parse("0.0.0.0/0").unwrap();
+        assert!(s.truncated_addr().is_empty());
+    }
+
+    #[test]
+    fn test_build_query_with_ecs_option() {
+        let (mut pkt, _) = build_query("example.com", 1);
+        let qlen = pkt.len();
+        let subnet = ClientSubnet::parse("203.0.113.0/24").unwrap();
+        append_edns(&mut pkt, Some(&subnet), false);
+        // ARCOUNT = 1
+        assert_eq!(read_u16(&pkt, 10), 1);
+        let opt = &pkt[qlen..];
+        assert_eq!(opt[0], 0); // root name
+        assert_eq!(read_u16(opt, 1), 41); // TYPE=OPT
+        assert_eq!(read_u16(opt, 3), 4096); // payload size
+        assert_eq!(read_u16(opt, 9), 11); // RDLENGTH
+        let rdata = &opt[11..];
+        assert_eq!(read_u16(rdata, 0), 8); // ECS option code
+        assert_eq!(read_u16(rdata, 2), 7); // option length
+        assert_eq!(read_u16(rdata, 4), 1); // family IPv4
+        assert_eq!(rdata[6], 24); // source prefix
+        assert_eq!(rdata[7], 0); // scope prefix
+        assert_eq!(&rdata[8..], &[203, 0, 113]);
+    }
+
+    #[test]
+    fn test_query_packet_sets_do_bit() {
+        let config = DnsConfig {
+            domain: "example.com".to_string(),
+            dnssec: true,
+            ..Default::default()
+        };
+        let (pkt, _, _) = query_packet(&config);
+        assert_eq!(read_u16(&pkt, 10), 1); // ARCOUNT
+        let opt = &pkt[pkt.len() - 11..];
+        assert_eq!(read_u16(opt, 1), 41); // TYPE=OPT
+        assert_eq!(read_u16(opt, 7), 0x8000); // DO
+        assert_eq!(read_u16(opt, 9), 0); // no options
+
+        let (plain, _, _) = query_packet(&DnsConfig::default());
+        assert_eq!(read_u16(&plain, 10), 0);
+    }
+
+    /// A response to `config` with an A record, optionally signed and with the AD bit.
+    fn dnssec_response(config: &DnsConfig, signed: bool, ad: bool) -> DnsResult {
+        let (mut resp, _, idn) = query_packet(config);
+        resp.truncate(12 + encode_name(&config.domain).len() + 4);
+        resp[2] = 0x81;
+        resp[3] = if ad { 0xA0 } else { 0x80 }; // RA (+ AD)
+        resp[7] = if signed { 2 } else { 1 }; // ANCOUNT
+        resp[10] = 0;
+        resp[11] = 0;
+        resp.extend(answer(&config.domain, 1, &[192, 0, 2, 1]));
+        if signed {
+            let mut rrsig = vec![0, 1, 13, 2]; // covers A, ECDSAP256SHA256, 2 labels
+            rrsig.extend_from_slice(&[0, 0, 1, 44]); // original TTL
+            rrsig.extend_from_slice(&[0; 8]); // expiration, inception
+            rrsig.extend_from_slice(&12345u16.to_be_bytes()); // key tag
+            rrsig.extend(encode_name(&config.domain));
+            rrsig.extend_from_slice(&[0xAB; 64]); // signature
+            resp.extend(answer(&config.domain, 46, &rrsig));
+        }
+        parse_response(&resp, config, idn, 1.0, crate::timestamp::now()).unwrap()
+    }
+
+    #[test]
+    fn test_dnssec_ad_bit_reported_as_validated() {
+        let config = DnsConfig {
+            domain: "example.com".to_string(),
+            dnssec: true,
+            ..Default::default()
+        };
+        let validated =
+            DnssecResult::from_answers(dnssec_response(&config, true, true), None, None);
+        assert_eq!(validated.status, DnssecStatus::Validated);
+        assert!(validated.authenticated_data);
+        assert_eq!(validated.rrsig.len(), 1);
+        assert_eq!(
+            validated.rrsig[0].value,
+            "A 13 keytag=12345 signer=example.com"
+        );
+
+        let not_validated =
+            DnssecResult::from_answers(dnssec_response(&config, true, false), None, None);
+        assert_eq!(not_validated.status, DnssecStatus::SignedNotValidated);
+        assert!(!not_validated.authenticated_data);
+        assert!(not_validated.is_signed());
+
+        let unsigned =
+            DnssecResult::from_answers(dnssec_response(&config, false, false), None, None);
+        assert_eq!(unsigned.status, DnssecStatus::Unsigned);
+        assert!(!unsigned.is_signed());
+    }
+
+    #[tokio::test]
+    async fn test_dnssec_check_async_matches_sync() {
+        let check = |resolver: SocketAddr| DnsConfig {
+            domain: "example.com".to_string(),
+            resolver: resolver.to_string(),
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let (sync_addr, sync_server) = mock_resolver_answering(3, [192, 0, 2, 7], Duration::ZERO);
+        let sync_result = tokio::task::spawn_blocking(move || dnssec_check(&check(sync_addr)))
+            .await
+            .unwrap()
+            .unwrap();
+        let (async_addr, async_server) = mock_resolver_answering(3, [192, 0, 2, 7], Duration::ZERO);
+        let async_result = dnssec_check_async(&check(async_addr)).await.unwrap();
+        sync_server.join().unwrap();
+        async_server.join().unwrap();
+
+        assert_eq!(async_result.status, sync_result.status);
+        assert_eq!(async_result.domain, sync_result.domain);
+        let values = |r: &DnssecResult| -> Vec<String> {
+            r.answer
+                .records
+                .iter()
+                .map(|rec| rec.value.clone())
+                .collect()
+        };
+        assert_eq!(values(&async_result), values(&sync_result));
+        assert_eq!(async_result.status, DnssecStatus::Unsigned);
+    }
+
+    #[test]
+    fn test_parse_dnskey_and_ds_rdata() {
+        let dnskey = [1, 1, 3, 13, 0xAA, 0xBB];
+        assert_eq!(parse_rdata(&dnskey, &mut 0, 6, 48), "257 3 13");
+        let ds = [0x30, 0x39, 13, 2, 0xDE, 0xAD];
+        assert_eq!(parse_rdata(&ds, &mut 0, 6, 43), "12345 13 2 dead");
+        assert_eq!(
+            RecordType::from_str_loose("dnskey"),
+            Some(RecordType::DNSKEY)
+        );
+        assert_eq!(RecordType::DS.to_qtype(), 43);
+    }
+
+    #[test]
+    fn test_parse_caa_rdata() {
+        // 0 issue "letsencrypt.org" as it appears on the wire
+        let mut caa = vec![0, 5];
+        caa.extend_from_slice(b"issue");
+        caa.extend_from_slice(b"letsencrypt.org");
+        let len = caa.len() as u16;
+        let mut offset = 0;
+        assert_eq!(
+            parse_rdata(&caa, &mut offset, len, 257),
+            "0 issue \"letsencrypt.org\""
+        );
+        assert_eq!(offset, caa.len());
+
+        let mut iodef = vec![128, 5];
+        iodef.extend_from_slice(b"iodefmailto:security@example.com");
+        assert_eq!(
+            parse_rdata(&iodef, &mut 0, iodef.len() as u16, 257),
+            "128 iodef \"mailto:security@example.com\""
+        );
+        // A tag length running past the record falls back to hex
+        assert_eq!(parse_rdata(&[0, 9, b'x'], &mut 0, 3, 257), "000978");
+
+        assert_eq!(RecordType::from_str_loose("caa"), Some(RecordType::CAA));
+        assert_eq!(RecordType::from_str_loose("Caa"), Some(RecordType::CAA));
+        assert_eq!(RecordType::CAA.to_qtype(), 257);
+        assert_eq!(rtype_name(257), "CAA");
+    }
+
+    #[test]
+    fn test_parse_tlsa_and_sshfp_rdata() {
+        let tlsa = [3, 1, 1, 0xAB, 0xCD];
+        assert_eq!(parse_rdata(&tlsa, &mut 0, 5, 52), "3 1 1 abcd");
+        let sshfp = [4, 2, 0x12, 0x34];
+        assert_eq!(parse_rdata(&sshfp, &mut 0, 4, 44), "4 2 1234");
+        assert_eq!(RecordType::from_str_loose("tlsa"), Some(RecordType::TLSA));
+        assert_eq!(RecordType::SSHFP.to_qtype(), 44);
+    }
+
+    #[test]
+    fn test_parse_ecs_scope() {
+        // ECS option: family 1, source /24, scope /16, 3 address bytes
+        let rdata = [0, 8, 0, 7, 0, 1, 24, 16, 203, 0, 113];
+        assert_eq!(parse_ecs_scope(&rdata), Some(16));
+        // Unrelated option (cookie) followed by nothing
+        let rdata = [0, 10, 0, 2, 0xAA, 0xBB];
+        assert_eq!(parse_ecs_scope(&rdata), None);
+        assert_eq!(parse_ecs_scope(&[]), None);
+    }
+
+    /// Answer `n` A queries with 127.0.0.1, returning the client address of each.
+    fn mock_resolver(n: usize) -> (SocketAddr, std::thread::JoinHandle<Vec<SocketAddr>>) {
+        mock_resolver_answering(n, [127, 0, 0, 1], Duration::ZERO)
+    }
+
+    /// Answer `n` A queries with `ip` after `delay`.
+    fn mock_resolver_answering(
+        n: usize,
+        ip: [u8; 4],
+        delay: Duration,
+    ) -> (SocketAddr, std::thread::JoinHandle<Vec<SocketAddr>>) {
+        let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = sock.local_addr().unwrap();
+        let handle = std::thread::spawn(move || {
+            let mut clients = Vec::new();
+            let mut buf = [0u8; 512];
+            for _ in 0..n {
+                let (len, src) = sock.recv_from(&mut buf).unwrap();
+                std::thread::sleep(delay);
+                let mut resp = buf[..len].to_vec();
+                resp[2] = 0x81; // QR + RD
+                resp[3] = 0x80; // RA
+                resp[7] = 1; // ANCOUNT
+                resp[10] = 0; // drop any OPT record count
+                resp[11] = 0;
+                resp.extend_from_slice(&[0xC0, 0x0C, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4]);
+                resp.extend_from_slice(&ip);
+                sock.send_to(&resp, src).unwrap();
+                clients.push(src);
+            }
+            clients
+        });
+        (addr, handle)
+    }
+
+    /// Refuse `n` queries (rcode 5, no records).
+    fn mock_resolver_refusing(n: usize) -> SocketAddr {
+        let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = sock.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 512];
+            for _ in 0..n {
+                let (len, src) = sock.recv_from(&mut buf).unwrap();
+                let mut resp = buf[..len].to_vec();
+                resp[2] = 0x81; // QR + RD
+                resp[3] = 0x85; // RA, REFUSED
+                resp[10] = 0; // drop any OPT record count
+                resp[11] = 0;
+                sock.send_to(&resp, src).unwrap();
+            }
+        });
+        addr
+    }
+
+    /// Answer `n` queries from a fixed zone: `(name, qtype)` → A addresses or
+    /// PTR targets. Unknown questions get an empty NOERROR answer.
+    fn mock_zone(
+        n: usize,
+        zone: Vec<(&'static str, u16, &'static str)>,
+    ) -> (SocketAddr, std::thread::JoinHandle<()>) {
+        let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = sock.local_addr().unwrap();
+        let handle = std::thread::spawn(move || {
+            let mut buf = [0u8; 512];
+            for _ in 0..n {
+                let (len, src) = sock.recv_from(&mut buf).unwrap();
+                let id = u16::from_be_bytes([buf[0], buf[1]]);
+                let mut labels = Vec::new();
+                let mut pos = 12;
+                while pos < len && buf[pos] != 0 {
+                    let l = usize::from(buf[pos]);
+                    labels.push(String::from_utf8_lossy(&buf[pos + 1..pos + 1 + l]).into_owned());
+                    pos += 1 + l;
+                }
+                let qtype = u16::from_be_bytes([buf[pos + 1], buf[pos + 2]]);
+                let qname = labels.join(".");
+                let mut resp = DnsResponseBuilder::new(id).question(&qname, qtype);
+                for &(name, rtype, value) in &zone {
+                    if name != qname || rtype != qtype {
+                        continue;
+                    }
+                    resp = match value.parse::<std::net::Ipv4Addr>() {
+                        Ok(ip) => resp.answer(name, rtype, 60, &ip.octets()),
+                        Err(_) => resp.answer_name(name, rtype, 60, value),
+                    };
+                }
+                sock.send_to(&resp.build(), src).unwrap();
+            }
+        });
+        (addr, handle)
+    }
+
+    fn encode_name(name: &str) -> Vec<u8> {
+        let mut out = Vec::new();
+        for label in name.split('.') {
+            out.push(label.len() as u8);
+            out.extend_from_slice(label.as_bytes());
+        }
+        out.push(0);
+        out
+    }
+
+    fn answer(name: &str, rtype: u16, rdata: &[u8]) -> Vec<u8> {
+        let mut out = encode_name(name);
+        out.extend_from_slice(&rtype.to_be_bytes());
+        out.extend_from_slice(&[0, 1, 0, 0, 1, 44]); // IN, TTL 300
+        out.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        out.extend_from_slice(rdata);
+        out
+    }
+
+    #[test]
+    fn test_parse_record_types() {
+        assert_eq!(
+            parse_record_types("a, AAAA,mx,").unwrap(),
+            vec![RecordType::A, RecordType::AAAA, RecordType::MX]
+        );
+        assert_eq!(
+            parse_record_types("A,BOGUS").unwrap_err(),
+            "Unknown record type: BOGUS"
+        );
+        assert!(parse_record_types(" , ").is_err());
+    }
+
+    #[test]
+    fn test_monitor_set_is_order_independent() {
+        let record = |rtype: &str, ttl, value: &str| DnsRecord {
+            name: "Example.com.".to_string(),
+            record_type: rtype.to_string(),
+            ttl,
+            value: value.to_string(),
+        };
+        let result = |rtype: &str, records: Vec<DnsRecord>| DnsResult {
+            domain: "example.com".to_string(),
+            resolver: "192.0.2.53".to_string(),
+            record_type: rtype.to_string(),
+            records,
+            query_time_ms: 12.5,
+            response_code: "NOERROR".to_string(),
+            truncated: false,
+            recursion_available: true,
+            authenticated_data: false,
+            client_subnet: None,
+            client_subnet_scope: None,
+            idn: None,
+            cname_chain: Vec::new(),
+            timestamp: "2026-10-16T00:00:00.000Z".to_string(),
+        };
+        let a1 = record("A", 300, "192.0.2.1");
+        let a2 = record("A", 300, "192.0.2.2");
+        let mx1 = record("MX", 3600, "10 mx1.example.com");
+        let mx2 = record("MX", 3600, "20 mx2.example.com");
+
+        let first = DnsMonitorSet::from_results(
+            "example.com",
+            "192.0.2.53",
+            &[
+                result("A", vec![a1.clone(), a2.clone()]),
+                result("MX", vec![mx1.clone(), mx2.clone()]),
+            ],
+        );
+        let mut late_a = result("A", vec![a2, a1.clone(), a1]);
+        late_a.query_time_ms = 80.0;
+        late_a.timestamp = "2026-10-17T00:00:00.000Z".to_string();
+        let second = DnsMonitorSet::from_results(
+            "example.com",
+            "192.0.2.53",
+            &[result("MX", vec![mx2, mx1]), late_a],
+        );
+
+        let first_json = serde_json::to_string_pretty(&first).unwrap();
+        assert_eq!(first_json, serde_json::to_string_pretty(&second).unwrap());
+        assert_eq!(first.types, vec!["A", "MX"]);
+        let values: Vec<&str> = first.records.iter().map(|r| r.value.as_str()).collect();
+        assert_eq!(
+            values,
+            vec![
+                "192.0.2.1",
+                "192.0.2.2",
+                "10 mx1.example.com",
+                "20 mx2.example.com"
+            ]
+        );
+        assert!(first.records.iter().all(|r| r.name == "example.com"));
+    }
+
+    #[test]
+    fn test_reverse_name() {
+        assert_eq!(
+            reverse_name("203.0.113.7".parse().unwrap()),
+            "7.113.0.203.in-addr.arpa"
+        );
+        let v6 = reverse_name("2001:db8::1".parse().unwrap());
+        assert!(v6.starts_with("1.0.0.0.0.0.0.0."));
+        assert!(v6.ends_with(".8.b.d.0.1.0.0.2.ip6.arpa"));
+        assert_eq!(v6.split('.').count(), 34);
+    }
+
+    #[test]
+    fn test_verify_reverse_per_address_verdicts() {
+        // A, AAAA, then one PTR per address
+        let (resolver, server) = mock_zone(
+            4,
+            vec![
+                ("www.example.com", 1, "192.0.2.1"),
+                ("www.example.com", 1, "192.0.2.2"),
+                ("1.2.0.192.in-addr.arpa", 12, "www.example.com."),
+                ("2.2.0.192.in-addr.arpa", 12, "host-2.isp.example.net."),
+            ],
+        );
+        let config = DnsConfig {
+            domain: "www.example.com".to_string(),
+            resolver: resolver.to_string(),
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let result = verify_reverse(&config).unwrap();
+        server.join().unwrap();
+
+        assert_eq!(result.addresses.len(), 2);
+        let first = &result.addresses[0];
+        assert_eq!(first.ip.to_string(), "192.0.2.1");
+        assert_eq!(first.ptr, vec!["www.example.com"]);
+        assert!(first.verified);
+        let second = &result.addresses[1];
+        assert_eq!(second.ip.to_string(), "192.0.2.2");
+        assert_eq!(second.ptr, vec!["host-2.isp.example.net"]);
+        assert!(!second.verified);
+        assert!(!result.all_verified());
+    }
+
+    #[test]
+    fn test_verify_reverse_without_addresses() {
+        let (resolver, server) = mock_zone(2, Vec::new());
+        let config = DnsConfig {
+            domain: "empty.example".to_string(),
+            resolver: resolver.to_string(),
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let err = verify_reverse(&config).unwrap_err();
+        server.join().unwrap();
+        assert_eq!(err, "empty.example has no A or AAAA records");
+    }
+
+    /// Parse a synthetic response as the answer to `config`.
+    fn parse_built(config: &DnsConfig, packet: &[u8]) -> DnsResult {
+        let idn = crate::idn::analyze_idn(&config.domain);
+        parse_response(packet, config, idn, 1.0, crate::timestamp::now()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_built_a_response() {
+        let config = DnsConfig {
+            domain: "example.com".to_string(),
+            ..Default::default()
+        };
+        let packet = DnsResponseBuilder::new(7)
+            .question("example.com", 1)
+            .answer("example.com", 1, 300, &[93, 184, 216, 34])
+            .build();
+        let result = parse_built(&config, &packet);
+        assert_eq!(result.response_code, "NOERROR");
+        assert!(result.recursion_available);
+        assert!(!result.truncated);
+        assert_eq!(result.records.len(), 1);
+        let record = &result.records[0];
+        assert_eq!(record.name, "example.com");
+        assert_eq!(record.record_type, "A");
+        assert_eq!(record.ttl, 300);
+        assert_eq!(record.value, "93.184.216.34");
+
+        let nxdomain = DnsResponseBuilder::new(7)
+            .question("example.com", 1)
+            .rcode(3)
+            .build();
+        let result = parse_built(&config, &nxdomain);
+        assert_eq!(result.response_code, "NXDOMAIN");
+        assert!(result.records.is_empty());
+    }
+
+    #[test]
+    fn test_parse_response_with_oversized_section_counts() {
+        let config = DnsConfig {
+            domain: "example.com".to_string(),
+            ..Default::default()
+        };
+        let mut packet = DnsResponseBuilder::new(7)
+            .question("example.com", 1)
+            .answer("example.com", 1, 300, &[93, 184, 216, 34])
+            .build();
+        // NSCOUNT and ARCOUNT claim far more records than the packet holds
+        packet[8..12].copy_from_slice(&[0xFF; 4]);
+        let result = parse_built(&config, &packet);
+        assert_eq!(result.records.len(), 1);
+        assert_eq!(result.client_subnet_scope, None);
+    }
+
+    #[test]
+    fn test_parse_built_response_follows_compression_pointers() {
+        let config = DnsConfig {
+            domain: "example.com".to_string(),
+            record_type: RecordType::MX,
+            ..Default::default()
+        };
+        let builder = DnsResponseBuilder::new(7)
+            .question("example.com", 15)
+            .answer_mx("example.com", 300, 10, "mx1.example.com")
+            .answer_mx("example.com", 300, 20, "mx2.example.com");
+        let compressed = builder.build();
+        let uncompressed = builder.clone().compress(false).build();
+        // Owner names and the exchanges' "example.com" suffix become pointers
+        assert!(compressed.len() < uncompressed.len());
+        assert!(compressed.contains(&0xC0));
+
+        for packet in [compressed, uncompressed] {
+            let result = parse_built(&config, &packet);
+            let values: Vec<&str> = result.records.iter().map(|r| r.value.as_str()).collect();
+            assert_eq!(values, ["10 mx1.example.com", "20 mx2.example.com"]);
+            assert!(result.records.iter().all(|r| r.name == "example.com"));
+        }
+    }
+
+    #[test]
+    fn test_parse_response_reconstructs_cname_chain() {
+        let config = DnsConfig {
+            domain: "www.example.com".to_string(),
+            ..Default::default()
+        };
+        let packet = DnsResponseBuilder::new(7)
+            .question("www.example.com", 1)
+            .answer_name("www.example.com", 5, 300, "cdn.example.net")
+            .answer("cdn.example.net", 1, 300, &[192, 0, 2, 1])
+            .answer("cdn.example.net", 1, 300, &[192, 0, 2, 2])
+            .build();
+
+        let result = parse_built(&config, &packet);
+        assert_eq!(result.records.len(), 3);
+        assert_eq!(result.cname_chain, vec!["cdn.example.net"]);
+        assert_eq!(result.canonical_name(), "cdn.example.net");
+        let finals: Vec<&str> = result
+            .final_records()
+            .iter()
+            .map(|r| r.value.as_str())
+            .collect();
+        assert_eq!(finals, ["192.0.2.1", "192.0.2.2"]);
+    }
+
+    #[test]
+    fn test_cname_chain_multi_hop_and_loop() {
+        let record = |name: &str, rtype: &str, value: &str| DnsRecord {
+            name: name.to_string(),
+            record_type: rtype.to_string(),
+            ttl: 60,
+            value: value.to_string(),
+        };
+        let records = [
+            record("b.example.", "CNAME", "c.example."),
+            record("a.example.", "CNAME", "b.example."),
+            record("c.example.", "A", "192.0.2.9"),
+        ];
+        assert_eq!(
+            cname_chain("A.example", &records),
+            ["b.example", "c.example"]
+        );
+        assert!(cname_chain("c.example", &records).is_empty());
+        let looped = [
+            record("x.example", "CNAME", "y.example"),
+            record("y.example", "CNAME", "x.example"),
+        ];
+        assert_eq!(cname_chain("x.example", &looped), ["y.example"]);
+    }
+
+    #[test]
+    fn test_system_resolve_localhost() {
+        let result = system_resolve("localhost", None).unwrap();
+        assert_eq!(result.resolver, "system");
+        assert_eq!(result.record_type, "A/AAAA");
+        assert!(result.addresses.iter().all(|ip| ip.is_loopback()));
+        let loopback = [
+            IpAddr::from([127, 0, 0, 1]),
+            IpAddr::from(std::net::Ipv6Addr::LOCALHOST),
+        ];
+        assert!(result.addresses.iter().any(|ip| loopback.contains(ip)));
+
+        let v4 = system_resolve("localhost", Some(RecordType::A)).unwrap();
+        assert_eq!(v4.addresses, [IpAddr::from([127, 0, 0, 1])]);
+        assert!(system_resolve("localhost", Some(RecordType::MX)).is_err());
+    }
+
+    #[test]
+    fn test_resolver_addr_default_port() {
+        assert_eq!(resolver_addr("8.8.8.8").unwrap().port(), 53);
+        assert_eq!(resolver_addr("127.0.0.1:5353").unwrap().port(), 5353);
+        assert_eq!(
+            resolver_addr("2001:4860:4860::8888").unwrap(),
+            "[2001:4860:4860::8888]:53".parse().unwrap()
+        );
+        assert_eq!(resolver_addr("[::1]:5353").unwrap().port(), 5353);
+        assert!(resolver_addr("not a resolver").is_err());
+        assert!(resolver_addr("127.0.0.1:99999").is_err());
+    }
+
+    #[test]
+    fn test_query_ipv6_resolver_with_port() {
+        let Ok(sock) = UdpSocket::bind("[::1]:0") else {
+            return; // no IPv6 loopback here
+        };
+        let addr = sock.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 512];
+            let (_, src) = sock.recv_from(&mut buf).unwrap();
+            let resp = DnsResponseBuilder::new(read_u16(&buf, 0))
+                .question("example.com", 1)
+                .answer("example.com", 1, 60, &[192, 0, 2, 6])
+                .build();
+            sock.send_to(&resp, src).unwrap();
+        });
+        let config = DnsConfig {
+            domain: "example.com".into(),
+            resolver: addr.to_string(),
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        assert_eq!(query(&config).unwrap().records[0].value, "192.0.2.6");
+    }
+
+    #[tokio::test]
+    async fn test_dns_client_reuses_socket() {
+        let (resolver, server) = mock_resolver(3);
+        let client = DnsClient::new();
+        for domain in ["a.example", "b.example", "c.example"] {
+            let config = DnsConfig {
+                domain: domain.to_string(),
+                resolver: resolver.to_string(),
+                timeout: Duration::from_secs(2),
+                ..Default::default()
+            };
+            let result = client.query_on(&config).await.unwrap();
+            assert_eq!(result.records.len(), 1);
+            assert_eq!(result.records[0].value, "127.0.0.1");
+            assert_eq!(result.records[0].name, domain);
+        }
+        let clients = server.join().unwrap();
+        let local = client.local_addrs();
+        assert_eq!(local.len(), 1);
+        assert!(clients.iter().all(|c| c.port() == local[0].port()));
+    }
+
+    #[tokio::test]
+    async fn test_dns_client_dispatches_concurrent_replies_by_id() {
+        // Collect three queries, then answer them in reverse order
+        let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let resolver = sock.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let mut queries = Vec::new();
+            for _ in 0..3 {
+                let mut buf = [0u8; 512];
+                let (len, src) = sock.recv_from(&mut buf).unwrap();
+                queries.push((buf[..len].to_vec(), src));
+            }
+            for (query, src) in queries.iter().rev() {
+                // The first letter of the name picks the address
+                let mut resp = query.clone();
+                resp[2] = 0x81;
+                resp[3] = 0x80;
+                resp[7] = 1;
+                resp[10] = 0;
+                resp[11] = 0;
+                resp.extend_from_slice(&[0xC0, 0x0C, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4]);
+                resp.extend_from_slice(&[10, 0, 0, query[13]]);
+                sock.send_to(&resp, src).unwrap();
+            }
+        });
+        let client = DnsClient::new();
+        let config = |domain: &str| DnsConfig {
+            domain: domain.to_string(),
+            resolver: resolver.to_string(),
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let (a, b, c) = (
+            config("a.example"),
+            config("b.example"),
+            config("c.example"),
+        );
+        let (a, b, c) = tokio::join!(
+            client.query_on(&a),
+            client.query_on(&b),
+            client.query_on(&c)
+        );
+        server.join().unwrap();
+        assert_eq!(a.unwrap().records[0].value, format!("10.0.0.{}", b'a'));
+        assert_eq!(b.unwrap().records[0].value, format!("10.0.0.{}", b'b'));
+        assert_eq!(c.unwrap().records[0].value, format!("10.0.0.{}", b'c'));
+        assert_eq!(client.local_addrs().len(), 1);
+    }
+
+    fn multi_config() -> DnsConfig {
+        DnsConfig {
+            domain: "multi.example".to_string(),
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_multi_first_response_returns_fast_resolver() {
+        let (slow, _) = mock_resolver_answering(1, [10, 0, 0, 2], Duration::from_millis(800));
+        let (fast, _) = mock_resolver_answering(1, [10, 0, 0, 1], Duration::ZERO);
+        let resolvers = vec![slow.to_string(), fast.to_string()];
+        let start = Instant::now();
+        let result = query_multi(&multi_config(), &resolvers, MultiMode::FirstResponse, None)
+            .await
+            .unwrap();
+        assert!(start.elapsed() < Duration::from_millis(600));
+        assert_eq!(result.answered_by, Some(fast.to_string()));
+        assert_eq!(result.consensus, Some(vec!["10.0.0.1".to_string()]));
+        assert_eq!(result.answers.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_query_multi_quorum() {
+        let spawn = || {
+            [[10, 0, 0, 1], [10, 0, 0, 1], [10, 0, 0, 9]]
+                .map(|ip| mock_resolver_answering(1, ip, Duration::ZERO).0.to_string())
+                .to_vec()
+        };
+        let result = query_multi(&multi_config(), &spawn(), MultiMode::Quorum(2), None)
+            .await
+            .unwrap();
+        assert_eq!(result.consensus, Some(vec!["10.0.0.1".to_string()]));
+        assert_eq!(result.agreeing, 2);
+        assert_eq!(result.quorum, Some(2));
+
+        let err = query_multi(&multi_config(), &spawn(), MultiMode::Quorum(3), None)
+            .await
+            .unwrap_err();
+        assert!(err.contains("No quorum"));
+    }
+
+    #[tokio::test]
+    async fn test_query_multi_ignores_refused_answers() {
+        let refusing = || mock_resolver_refusing(1).to_string();
+        let err = query_multi(
+            &multi_config(),
+            &[refusing(), refusing()],
+            MultiMode::Quorum(2),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.starts_with("No resolver answered"));
+        assert!(err.contains("REFUSED"));
+
+        let (slow, _) = mock_resolver_answering(1, [10, 0, 0, 1], Duration::from_millis(100));
+        let resolvers = vec![refusing(), slow.to_string()];
+        let result = query_multi(&multi_config(), &resolvers, MultiMode::FirstResponse, None)
+            .await
+            .unwrap();
+        assert_eq!(result.answered_by, Some(slow.to_string()));
+        assert_eq!(result.consensus, Some(vec!["10.0.0.1".to_string()]));
+
+        let resolvers = vec![refusing(), refusing()];
+        let err = query_multi(&multi_config(), &resolvers, MultiMode::FirstResponse, None)
+            .await
+            .unwrap_err();
+        assert!(err.contains("REFUSED"));
+    }
+
+    #[tokio::test]
+    async fn test_query_multi_parallel_limit() {
+        let resolvers: Vec<String> = (0..2)
+            .map(|_| {
+                mock_resolver_answering(1, [10, 0, 0, 1], Duration::from_millis(150))
+                    .0
+                    .to_string()
+            })
+            .collect();
+        let start = Instant::now();
+        let result = query_multi(&multi_config(), &resolvers, MultiMode::All, Some(1))
+            .await
+            .unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert_eq!(result.agreeing, 2);
+    }
+
+    #[tokio::test]
+    async fn test_fastest_resolver_ranks_by_latency() {
+        let domains = vec!["a.example".to_string(), "b.example".to_string()];
+        let (slow, _) = mock_resolver_answering(2, [10, 0, 0, 2], Duration::from_millis(120));
+        let (fast, _) = mock_resolver_answering(2, [10, 0, 0, 1], Duration::from_millis(10));
+        let (medium, _) = mock_resolver_answering(2, [10, 0, 0, 3], Duration::from_millis(60));
+        // Bound but never answers
+        let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let resolvers = vec![
+            silent.local_addr().unwrap().to_string(),
+            slow.to_string(),
+            fast.to_string(),
+            medium.to_string(),
+        ];
+        let config = DnsConfig {
+            timeout: Duration::from_millis(400),
+            ..Default::default()
+        };
+        let result = fastest_resolver(&config, &resolvers, &domains)
+            .await
+            .unwrap();
+        let order: Vec<&str> = result.ranking.iter().map(|r| r.resolver.as_str()).collect();
+        assert_eq!(
+            order,
+            [&resolvers[2], &resolvers[3], &resolvers[1], &resolvers[0]]
+        );
+        assert_eq!(result.fastest.as_deref(), Some(resolvers[2].as_str()));
+        assert_eq!(result.ranking[0].answered, 2);
+        assert!(result.ranking[0].avg_ms.unwrap() >= 10.0);
+        let failed = &result.ranking[3];
+        assert_eq!(failed.answered, 0);
+        assert!(failed.avg_ms.is_none());
+        assert!(failed.error.as_deref().unwrap().starts_with("a.example: "));
+
+        assert!(fastest_resolver(&config, &[], &domains).await.is_err());
+        assert!(fastest_resolver(&config, &resolvers, &[]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fastest_resolver_ranks_refusing_resolver_last() {
+        let domains = vec!["a.example".to_string()];
+        let refusing = mock_resolver_refusing(1);
+        let (slow, _) = mock_resolver_answering(1, [10, 0, 0, 1], Duration::from_millis(50));
+        let resolvers = vec![refusing.to_string(), slow.to_string()];
+        let config = DnsConfig {
+            timeout: Duration::from_millis(400),
+            ..Default::default()
+        };
+        let result = fastest_resolver(&config, &resolvers, &domains)
+            .await
+            .unwrap();
+        assert_eq!(result.fastest.as_deref(), Some(resolvers[1].as_str()));
+        let last = &result.ranking[1];
+        assert_eq!(last.resolver, resolvers[0]);
+        assert_eq!(last.answered, 0);
+        assert_eq!(last.error.as_deref(), Some("a.example: REFUSED"));
+    }
+
+    #[tokio::test]
+    async fn test_query_multi_all_and_validation() {
+        let resolvers: Vec<String> = [[10, 0, 0, 1], [10, 0, 0, 2], [10, 0, 0, 2]]
+            .map(|ip| mock_resolver_answering(1, ip, Duration::ZERO).0.to_string())
+            .to_vec();
+        let result = query_multi(&multi_config(), &resolvers, MultiMode::All, None)
+            .await
+            .unwrap();
+        assert_eq!(result.answers.len(), 3);
+        assert_eq!(result.consensus, Some(vec!["10.0.0.2".to_string()]));
+        assert_eq!(result.agreeing, 2);
+
+        assert!(query_multi(&multi_config(), &[], MultiMode::All, None)
+            .await
+            .is_err());
+        assert!(
+            query_multi(&multi_config(), &resolvers, MultiMode::Quorum(4), None)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_check_detects_differing_answers() {
+        let (internal, _) = mock_resolver_answering(1, [10, 1, 2, 3], Duration::ZERO);
+        let (external, _) = mock_resolver_answering(1, [203, 0, 113, 7], Duration::ZERO);
+        let config = DnsConfig {
+            resolver: external.to_string(),
+            ..multi_config()
+        };
+        let result = split_check(&config, &internal.to_string()).await.unwrap();
+        assert!(result.split_detected);
+        assert_eq!(result.internal.resolver, internal.to_string());
+        assert_eq!(result.internal.values(), Some(vec!["10.1.2.3".to_string()]));
+        assert_eq!(
+            result.external.values(),
+            Some(vec!["203.0.113.7".to_string()])
+        );
+
+        let human = crate::output::HumanReadable::to_human(&result);
+        assert!(human.contains("Split-horizon detected"));
+        assert!(human.contains("10.1.2.3"));
+        assert!(human.contains("203.0.113.7"));
+    }
+
+    #[tokio::test]
+    async fn test_split_check_same_answer() {
+        let (internal, _) = mock_resolver_answering(1, [192, 0, 2, 1], Duration::ZERO);
+        let (external, _) = mock_resolver_answering(1, [192, 0, 2, 1], Duration::ZERO);
+        let config = DnsConfig {
+            resolver: external.to_string(),
+            ..multi_config()
+        };
+        let result = split_check(&config, &internal.to_string()).await.unwrap();
+        assert!(!result.split_detected);
+    }
+
     #[test]
     fn test_hex_encode() {
         assert_eq!(hex::encode(&[0xAB, 0xCD, 0xEF]), "abcdef");
@@ -651,6 +3150,27 @@ impl DnsResult {
         self.response_code == "NXDOMAIN"
     }
 
+    /// The name the query finally resolved to: the end of the CNAME chain, or the queried name.
+    pub fn canonical_name(&self) -> &str {
+        match (self.cname_chain.last(), &self.idn) {
+            (Some(target), _) => target,
+            (None, Some(idn)) => &idn.ascii,
+            (None, None) => &self.domain,
+        }
+    }
+
+    /// Records of the queried type owned by the canonical name.
+    pub fn final_records(&self) -> Vec<&DnsRecord> {
+        let canonical = self.canonical_name().trim_end_matches('.');
+        self.records
+            .iter()
+            .filter(|r| {
+                r.is_type(&self.record_type)
+                    && r.name.trim_end_matches('.').eq_ignore_ascii_case(canonical)
+            })
+            .collect()
+    }
+
     /// Return the minimum TTL across all records, or None if empty.
     pub fn min_ttl(&self) -> Option<u32> {
         self.records.iter().map(|r| r.ttl).min()
@@ -703,6 +3223,11 @@ mod display_tests {
             truncated: false,
             recursion_available: true,
             authenticated_data: false,
+            client_subnet: None,
+            client_subnet_scope: None,
+            idn: None,
+            cname_chain: vec![],
+            timestamp: String::new(),
         };
         let s = format!("{result}");
         assert!(s.contains("QUERY: example.com A @8.8.8.8"));
@@ -723,6 +3248,11 @@ mod display_tests {
             truncated: true,
             recursion_available: true,
             authenticated_data: false,
+            client_subnet: None,
+            client_subnet_scope: None,
+            idn: None,
+            cname_chain: vec![],
+            timestamp: String::new(),
         };
         let s = format!("{result}");
         assert!(s.contains("truncated"));
@@ -740,6 +3270,11 @@ mod display_tests {
             truncated: false,
             recursion_available: true,
             authenticated_data: false,
+            client_subnet: None,
+            client_subnet_scope: None,
+            idn: None,
+            cname_chain: vec![],
+            timestamp: String::new(),
         };
         assert!(result.is_success());
         assert!(!result.is_nxdomain());
@@ -757,6 +3292,11 @@ mod display_tests {
             truncated: false,
             recursion_available: true,
             authenticated_data: false,
+            client_subnet: None,
+            client_subnet_scope: None,
+            idn: None,
+            cname_chain: vec![],
+            timestamp: String::new(),
         };
         assert!(!result.is_success());
         assert!(result.is_nxdomain());
@@ -793,6 +3333,11 @@ mod display_tests {
             truncated: false,
             recursion_available: true,
             authenticated_data: false,
+            client_subnet: None,
+            client_subnet_scope: None,
+            idn: None,
+            cname_chain: vec![],
+            timestamp: String::new(),
         };
         let a_records = result.records_of_type("A");
         assert_eq!(a_records.len(), 2);
@@ -827,6 +3372,11 @@ mod display_tests {
             truncated: false,
             recursion_available: true,
             authenticated_data: false,
+            client_subnet: None,
+            client_subnet_scope: None,
+            idn: None,
+            cname_chain: vec![],
+            timestamp: String::new(),
         };
         assert_eq!(result.min_ttl(), Some(60));
     }
@@ -843,6 +3393,11 @@ mod display_tests {
             truncated: false,
             recursion_available: true,
             authenticated_data: false,
+            client_subnet: None,
+            client_subnet_scope: None,
+            idn: None,
+            cname_chain: vec![],
+            timestamp: String::new(),
         };
         assert_eq!(result.min_ttl(), None);
     }
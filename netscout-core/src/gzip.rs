@@ -0,0 +1,380 @@
+//! Minimal DEFLATE, zlib and gzip decoding (RFC 1951, 1950, 1952), enough to
+//! measure how much a compressed HTTP response expands.
+
+/// Refuse to inflate bodies beyond this size (decompression bombs).
+pub const MAX_OUTPUT: usize = 64 * 1024 * 1024;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order in which code-length code lengths are sent in a dynamic block header.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// LSB-first bit reader over a byte slice.
+struct Bits<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buf: u32,
+    count: u32,
+}
+
+impl<'a> Bits<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            buf: 0,
+            count: 0,
+        }
+    }
+
+    fn take(&mut self, n: u32) -> Result<u32, String> {
+        while self.count < n {
+            let byte = *self.data.get(self.pos).ok_or("Truncated deflate stream")?;
+            self.pos += 1;
+            self.buf |= (byte as u32) << self.count;
+            self.count += 8;
+        }
+        let value = self.buf & ((1u32 << n) - 1);
+        self.buf >>= n;
+        self.count -= n;
+        Ok(value)
+    }
+
+    /// Drop the rest of the current byte (stored blocks start byte-aligned).
+    fn align(&mut self) {
+        self.buf = 0;
+        self.count = 0;
+    }
+}
+
+/// Canonical Huffman code: symbol counts per length and symbols in code order.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        let mut offsets = [0u16; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= bits.take(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("Invalid Huffman code in deflate stream".to_string())
+    }
+}
+
+/// Inflate a raw DEFLATE stream, returning the output and the input bytes consumed.
+pub fn inflate(data: &[u8]) -> Result<(Vec<u8>, usize), String> {
+    let mut bits = Bits::new(data);
+    let mut out = Vec::new();
+    loop {
+        let last = bits.take(1)? == 1;
+        match bits.take(2)? {
+            0 => {
+                bits.align();
+                let header = data
+                    .get(bits.pos..bits.pos + 4)
+                    .ok_or("Truncated stored block")?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                let nlen = u16::from_le_bytes([header[2], header[3]]);
+                if len != !nlen {
+                    return Err("Corrupt stored block length".to_string());
+                }
+                let start = bits.pos + 4;
+                let block = data
+                    .get(start..start + len as usize)
+                    .ok_or("Truncated stored block")?;
+                out.extend_from_slice(block);
+                bits.pos = start + len as usize;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let lit = Huffman::new(&lengths);
+                let dist = Huffman::new(&[5; 30]);
+                inflate_block(&mut bits, &lit, &dist, &mut out)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_tables(&mut bits)?;
+                inflate_block(&mut bits, &lit, &dist, &mut out)?;
+            }
+            _ => return Err("Invalid deflate block type".to_string()),
+        }
+        if out.len() > MAX_OUTPUT {
+            return Err(format!(
+                "Decompressed body exceeds {} MB",
+                MAX_OUTPUT / 1024 / 1024
+            ));
+        }
+        if last {
+            return Ok((out, bits.pos));
+        }
+    }
+}
+
+/// Read the code tables of a dynamic Huffman block.
+fn dynamic_tables(bits: &mut Bits) -> Result<(Huffman, Huffman), String> {
+    let nlit = bits.take(5)? as usize + 257;
+    let ndist = bits.take(5)? as usize + 1;
+    let ncode = bits.take(4)? as usize + 4;
+    if nlit > 286 || ndist > 30 {
+        return Err("Too many codes in dynamic block".to_string());
+    }
+    let mut code_lengths = [0u8; 19];
+    for &i in &CODE_LENGTH_ORDER[..ncode] {
+        code_lengths[i] = bits.take(3)? as u8;
+    }
+    let code_huffman = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(nlit + ndist);
+    while lengths.len() < nlit + ndist {
+        let (value, repeat) = match code_huffman.decode(bits)? {
+            sym @ 0..=15 => (sym as u8, 1),
+            16 => {
+                let prev = *lengths.last().ok_or("Repeat with no previous length")?;
+                (prev, 3 + bits.take(2)?)
+            }
+            17 => (0, 3 + bits.take(3)?),
+            _ => (0, 11 + bits.take(7)?),
+        };
+        if lengths.len() + repeat as usize > nlit + ndist {
+            return Err("Code lengths overrun dynamic block".to_string());
+        }
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    Ok((
+        Huffman::new(&lengths[..nlit]),
+        Huffman::new(&lengths[nlit..]),
+    ))
+}
+
+/// Decode literals and back-references until the end-of-block symbol.
+fn inflate_block(
+    bits: &mut Bits,
+    lit: &Huffman,
+    dist: &Huffman,
+    out: &mut Vec<u8>,
+) -> Result<(), String> {
+    loop {
+        let symbol = lit.decode(bits)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let i = symbol - 257;
+                if i >= LENGTH_BASE.len() {
+                    return Err("Invalid length code in deflate stream".to_string());
+                }
+                let len = LENGTH_BASE[i] as usize + bits.take(LENGTH_EXTRA[i] as u32)? as usize;
+                let d = dist.decode(bits)? as usize;
+                if d >= DIST_BASE.len() {
+                    return Err("Invalid distance code in deflate stream".to_string());
+                }
+                let distance = DIST_BASE[d] as usize + bits.take(DIST_EXTRA[d] as u32)? as usize;
+                if distance > out.len() {
+                    return Err("Back-reference before start of output".to_string());
+                }
+                let start = out.len() - distance;
+                for k in 0..len {
+                    out.push(out[start + k]);
+                }
+                if out.len() > MAX_OUTPUT {
+                    return Err(format!(
+                        "Decompressed body exceeds {} MB",
+                        MAX_OUTPUT / 1024 / 1024
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// CRC-32 (IEEE) as used in the gzip trailer.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Decompress a gzip member, checking its CRC and length trailer.
+pub fn gunzip(data: &[u8]) -> Result<Vec<u8>, String> {
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+
+    if data.len() < 18 || data[..3] != [0x1f, 0x8b, 8] {
+        return Err("Not a gzip stream".to_string());
+    }
+    let flags = data[3];
+    let mut pos = 10;
+    if flags & FEXTRA != 0 {
+        let xlen = data.get(pos..pos + 2).ok_or("Truncated gzip header")?;
+        pos += 2 + u16::from_le_bytes([xlen[0], xlen[1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let end = data
+                .get(pos..)
+                .and_then(|rest| rest.iter().position(|&b| b == 0))
+                .ok_or("Truncated gzip header")?;
+            pos += end + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+
+    let (out, used) = inflate(data.get(pos..).ok_or("Truncated gzip header")?)?;
+    let trailer = data
+        .get(pos + used..pos + used + 8)
+        .ok_or("Truncated gzip trailer")?;
+    let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+    if crc != crc32(&out) {
+        return Err("gzip CRC mismatch".to_string());
+    }
+    if size != out.len() as u32 {
+        return Err("gzip length mismatch".to_string());
+    }
+    Ok(out)
+}
+
+/// Decompress a zlib stream (HTTP's `deflate` encoding), tolerating servers
+/// that send raw DEFLATE without the zlib header.
+pub fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    let has_header = data.len() >= 2
+        && data[0] & 0x0f == 8
+        && u16::from_be_bytes([data[0], data[1]]).is_multiple_of(31);
+    if has_header {
+        inflate(&data[2..]).map(|(out, _)| out)
+    } else {
+        inflate(data).map(|(out, _)| out)
+    }
+}
+
+/// Decode a body sent with `Content-Encoding: encoding`.
+///
+/// Returns `None` for encodings this module can't decode (such as `br`).
+pub fn decode(encoding: &str, body: &[u8]) -> Option<Result<Vec<u8>, String>> {
+    match encoding.trim().to_ascii_lowercase().as_str() {
+        "gzip" | "x-gzip" => Some(gunzip(body)),
+        "deflate" => Some(zlib_decompress(body)),
+        "identity" => Some(Ok(body.to_vec())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `gzip.compress(b"hello hello hello hello\n", mtime=0)`: one fixed-Huffman block.
+    const HELLO_GZ: [u8; 29] = [
+        0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9, 0xc9,
+        0x57, 0xc8, 0x40, 0x27, 0xb9, 0x00, 0x00, 0x88, 0x59, 0x0b, 0x18, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_gunzip_fixed_huffman() {
+        assert_eq!(gunzip(&HELLO_GZ).unwrap(), b"hello hello hello hello\n");
+    }
+
+    #[test]
+    fn test_gunzip_dynamic_huffman() {
+        let gz = std::fs::read(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/repeat.txt.gz"),
+        )
+        .unwrap();
+        let out = gunzip(&gz).unwrap();
+        assert_eq!(out, b"netscout compression test\n".repeat(500));
+    }
+
+    #[test]
+    fn test_inflate_stored_block() {
+        let raw = [
+            0x01, 0x0c, 0x00, 0xf3, 0xff, b's', b't', b'o', b'r', b'e', b'd', b' ', b'b', b'l',
+            b'o', b'c', b'k',
+        ];
+        let (out, used) = inflate(&raw).unwrap();
+        assert_eq!(out, b"stored block");
+        assert_eq!(used, raw.len());
+    }
+
+    #[test]
+    fn test_gunzip_rejects_corruption() {
+        let mut bad_crc = HELLO_GZ;
+        bad_crc[22] ^= 0xff;
+        assert_eq!(gunzip(&bad_crc).unwrap_err(), "gzip CRC mismatch");
+        assert!(gunzip(&HELLO_GZ[..20]).is_err());
+        assert!(gunzip(b"plain text, not gzip").is_err());
+    }
+
+    #[test]
+    fn test_decode_by_encoding() {
+        assert_eq!(
+            decode("GZIP", &HELLO_GZ).unwrap().unwrap(),
+            b"hello hello hello hello\n"
+        );
+        assert_eq!(decode("identity", b"abc").unwrap().unwrap(), b"abc");
+        assert!(decode("br", b"abc").is_none());
+    }
+
+    #[test]
+    fn test_crc32_known_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+}
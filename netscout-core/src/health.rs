@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+/// Certificates expiring in fewer days than this are a warning.
+pub const CERT_WARNING_DAYS: i64 = 30;
+/// Certificates expiring in fewer days than this are critical.
+pub const CERT_CRITICAL_DAYS: i64 = 7;
+
+/// Health-check status, as used by Consul and similar service-discovery systems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Passing,
+    Warning,
+    Critical,
+}
+
+impl HealthStatus {
+    /// Exit code for script checks: 0 passing, 1 warning, 2 critical.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Passing => 0,
+            Self::Warning => 1,
+            Self::Critical => 2,
+        }
+    }
+}
+
+/// A result summarised as a health check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthCheck {
+    pub status: HealthStatus,
+    pub output: String,
+    pub notes: String,
+}
+
+impl HealthCheck {
+    pub fn new(status: HealthStatus, output: impl Into<String>, notes: impl Into<String>) -> Self {
+        Self {
+            status,
+            output: output.into(),
+            notes: notes.into(),
+        }
+    }
+
+    /// A failed command is always critical.
+    pub fn from_error(error: &str) -> Self {
+        Self::new(HealthStatus::Critical, error, "command failed")
+    }
+
+    /// Render as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|e| format!("JSON error: {e}"))
+    }
+}
+
+/// Status for a certificate expiring in `days` days.
+pub fn cert_expiry_status(days: i64) -> HealthStatus {
+    if days < CERT_CRITICAL_DAYS {
+        HealthStatus::Critical
+    } else if days < CERT_WARNING_DAYS {
+        HealthStatus::Warning
+    } else {
+        HealthStatus::Passing
+    }
+}
+
+/// Merge several checks into one with the worst status.
+pub fn combine(checks: &[HealthCheck]) -> HealthCheck {
+    let status = checks
+        .iter()
+        .map(|c| c.status)
+        .max()
+        .unwrap_or(HealthStatus::Passing);
+    let join = |f: fn(&HealthCheck) -> &str| {
+        checks
+            .iter()
+            .map(f)
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("; ")
+    };
+    HealthCheck {
+        status,
+        output: join(|c| &c.output),
+        notes: join(|c| &c.notes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_serializes_lowercase() {
+        let check = HealthCheck::new(HealthStatus::Warning, "slow", "");
+        let json: serde_json::Value = serde_json::from_str(&check.to_json()).unwrap();
+        assert_eq!(json["status"], "warning");
+        assert_eq!(json["output"], "slow");
+    }
+
+    #[test]
+    fn test_cert_expiry_status_thresholds() {
+        assert_eq!(cert_expiry_status(-1), HealthStatus::Critical);
+        assert_eq!(cert_expiry_status(3), HealthStatus::Critical);
+        assert_eq!(cert_expiry_status(7), HealthStatus::Warning);
+        assert_eq!(cert_expiry_status(29), HealthStatus::Warning);
+        assert_eq!(cert_expiry_status(30), HealthStatus::Passing);
+    }
+
+    #[test]
+    fn test_combine_takes_worst_status() {
+        let checks = [
+            HealthCheck::new(HealthStatus::Passing, "a ok", ""),
+            HealthCheck::from_error("b: connection refused"),
+            HealthCheck::new(HealthStatus::Warning, "c lossy", ""),
+        ];
+        let combined = combine(&checks);
+        assert_eq!(combined.status, HealthStatus::Critical);
+        assert_eq!(combined.output, "a ok; b: connection refused; c lossy");
+        assert_eq!(combine(&[]).status, HealthStatus::Passing);
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(HealthStatus::Passing.exit_code(), 0);
+        assert_eq!(HealthStatus::Warning.exit_code(), 1);
+        assert_eq!(HealthStatus::Critical.exit_code(), 2);
+    }
+}
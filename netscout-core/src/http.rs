@@ -1,9 +1,22 @@
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::io::{Read, Write};
-use std::net::{TcpStream, ToSocketAddrs};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, Instant};
 
+/// How the `Connection` request header is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ConnectionMode {
+    /// Send `Connection: close`.
+    Close,
+    /// Send `Connection: keep-alive`.
+    KeepAlive,
+    /// Send no `Connection` header (HTTP/1.1 defaults to keep-alive).
+    Unset,
+}
+
 /// Configuration for an HTTP probe.
 #[derive(Debug, Clone)]
 pub struct HttpConfig {
@@ -14,8 +27,32 @@ pub struct HttpConfig {
     pub follow_redirects: bool,
     pub max_redirects: u32,
     pub timeout: Duration,
+    pub connection: ConnectionMode,
+    /// Time-to-first-byte limit; exceeding it marks the probe as failed.
+    pub max_ttfb: Option<Duration>,
+    /// Offer `h2` via ALPN (HTTPS only); falls back to HTTP/1.1 if the server declines.
+    pub http2: bool,
+    /// Extra PEM CA certificates to trust for HTTPS, alongside the bundled roots.
+    pub ca_file: Option<PathBuf>,
+    /// Report TLS version, cipher, ALPN and session resumption for HTTPS probes.
+    pub tls_info: bool,
+    /// Send `Accept-Encoding: gzip, br` and report how the response was compressed.
+    pub compression: bool,
+    /// Make a discarded request first, then measure a second one on the warmed-up connection.
+    pub warmup: bool,
+    /// Report the body download rate over the transfer phase.
+    pub throughput: bool,
+    /// Largest response body to hold in memory, in bytes, when it is kept (see
+    /// `keep_body` and `compression`); a bigger body fails the probe. Bodies that
+    /// are not kept are only counted and may be any size.
+    pub max_body: usize,
+    /// Return the final response body in [`HttpResult::body`].
+    pub keep_body: bool,
 }
 
+/// Default [`HttpConfig::max_body`]: 64 MiB.
+pub const DEFAULT_MAX_BODY: usize = 64 * 1024 * 1024;
+
 impl Default for HttpConfig {
     fn default() -> Self {
         Self {
@@ -26,6 +63,16 @@ impl Default for HttpConfig {
             follow_redirects: false,
             max_redirects: 10,
             timeout: Duration::from_secs(10),
+            connection: ConnectionMode::Close,
+            max_ttfb: None,
+            http2: false,
+            ca_file: None,
+            tls_info: false,
+            compression: false,
+            warmup: false,
+            throughput: false,
+            max_body: DEFAULT_MAX_BODY,
+            keep_body: false,
         }
     }
 }
@@ -46,6 +93,10 @@ pub struct HttpTiming {
 pub struct HttpRedirect {
     pub url: String,
     pub status: u16,
+    /// Unicode/punycode forms if the redirect target is an internationalized domain.
+    pub idn: Option<crate::idn::IdnInfo>,
+    /// Timing of the request that returned this redirect.
+    pub timing: HttpTiming,
 }
 
 /// Result of an HTTP probe.
@@ -59,7 +110,174 @@ pub struct HttpResult {
     pub body_size: usize,
     pub timing: HttpTiming,
     pub redirects: Vec<HttpRedirect>,
+    /// Total time across every request in the redirect chain, including the final one.
+    pub redirect_chain_ms: f64,
+    /// The URL actually fetched last; equals `url` when no redirect was followed.
+    pub final_url: String,
     pub tls: bool,
+    /// Unicode/punycode forms if the host is an internationalized domain.
+    pub idn: Option<crate::idn::IdnInfo>,
+    /// `(declared, actual)` body bytes when they disagree with `Content-Length`.
+    pub content_length_mismatch: Option<(usize, usize)>,
+    /// Allowed methods and CORS headers (OPTIONS requests only).
+    pub options: Option<OptionsInfo>,
+    /// The `--max-ttfb` limit this probe was checked against.
+    pub max_ttfb_ms: Option<f64>,
+    /// ALPN-negotiated protocol (`h2` or `http/1.1`) for `--http2` probes.
+    pub protocol: Option<String>,
+    /// TLS-layer details when `tls_info` was requested.
+    pub tls_info: Option<HttpTlsInfo>,
+    /// Response compression when `compression` was requested.
+    pub compression: Option<CompressionInfo>,
+    /// Timing of the discarded first request when `warmup` was requested.
+    pub warmup: Option<WarmupInfo>,
+    /// Body download rate when `throughput` was requested and the transfer was long enough to measure.
+    pub body_throughput_mbps: Option<f64>,
+    /// When the probe started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
+    /// The final response body when `keep_body` was set; never serialized.
+    #[serde(skip)]
+    pub body: Option<Vec<u8>>,
+}
+
+/// The cold request made before the measured one by `--warmup`.
+#[derive(Debug, Clone, Serialize)]
+pub struct WarmupInfo {
+    /// Timing of the first request, including DNS, connect and TLS setup.
+    pub cold: HttpTiming,
+    /// Whether the measured request ran on the cold request's connection.
+    pub connection_reused: bool,
+}
+
+impl HttpResult {
+    /// Whether the time to first byte went over the configured limit.
+    pub fn ttfb_exceeded(&self) -> bool {
+        self.max_ttfb_ms
+            .is_some_and(|max| self.timing.ttfb_ms > max)
+    }
+}
+
+/// TLS details of the connection an HTTPS probe ran over.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HttpTlsInfo {
+    pub version: String,
+    pub cipher_suite: String,
+    /// Protocol the server selected via ALPN, if any.
+    pub alpn: Option<String>,
+    /// Whether the handshake resumed a session from an earlier probe.
+    pub resumed: bool,
+}
+
+/// How a response body was compressed, measured from the bytes received.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompressionInfo {
+    /// `Content-Encoding` of the response; `None` if it was sent uncompressed.
+    pub encoding: Option<String>,
+    /// Body bytes on the wire.
+    pub compressed_bytes: usize,
+    /// Body bytes after decoding; `None` for encodings that can't be decoded here (`br`).
+    pub decompressed_bytes: Option<usize>,
+    /// `decompressed_bytes / compressed_bytes`.
+    pub ratio: Option<f64>,
+    /// Why the body couldn't be decoded, if it couldn't.
+    pub error: Option<String>,
+}
+
+impl CompressionInfo {
+    /// Measure a response body sent with the given headers.
+    pub fn measure(headers: &HashMap<String, String>, body: &[u8]) -> Self {
+        let encoding = headers
+            .get("content-encoding")
+            .map(|e| e.trim().to_ascii_lowercase())
+            .filter(|e| !e.is_empty() && e != "identity");
+        let Some(encoding) = encoding else {
+            return Self {
+                encoding: None,
+                compressed_bytes: body.len(),
+                decompressed_bytes: Some(body.len()),
+                ratio: None,
+                error: None,
+            };
+        };
+        let (decompressed_bytes, error) = match crate::gzip::decode(&encoding, body) {
+            Some(Ok(decoded)) => (Some(decoded.len()), None),
+            Some(Err(e)) => (None, Some(e)),
+            None => (None, None),
+        };
+        Self {
+            ratio: decompressed_bytes
+                .filter(|_| !body.is_empty())
+                .map(|n| n as f64 / body.len() as f64),
+            encoding: Some(encoding),
+            compressed_bytes: body.len(),
+            decompressed_bytes,
+            error,
+        }
+    }
+}
+
+/// Methods and CORS policy advertised in an OPTIONS response.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct OptionsInfo {
+    /// Methods from the `Allow` header.
+    pub allow: Vec<String>,
+    /// `Access-Control-*` headers, keyed by lowercase name.
+    pub cors: BTreeMap<String, String>,
+}
+
+/// Extract `Allow` and CORS headers from response headers (lowercase keys).
+pub fn parse_options(headers: &HashMap<String, String>) -> OptionsInfo {
+    let allow = headers
+        .get("allow")
+        .map(|v| {
+            v.split(',')
+                .map(|m| m.trim().to_uppercase())
+                .filter(|m| !m.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    let cors = headers
+        .iter()
+        .filter(|(k, _)| k.starts_with("access-control-"))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    OptionsInfo { allow, cors }
+}
+
+/// Parse a `Key: Value` header line.
+pub fn parse_header(line: &str) -> Option<(String, String)> {
+    line.split_once(':')
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+}
+
+/// Parse a headers file with one `Key: Value` per line, skipping blank lines and `#` comments.
+pub fn parse_headers_file(content: &str) -> Result<Vec<(String, String)>, String> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .map(|(i, l)| {
+            parse_header(l).ok_or_else(|| format!("Invalid header on line {}: {}", i + 1, l.trim()))
+        })
+        .collect()
+}
+
+/// Merge two header lists; `overrides` replace headers of the same name (case-insensitive).
+pub fn merge_headers(
+    base: Vec<(String, String)>,
+    overrides: Vec<(String, String)>,
+) -> Vec<(String, String)> {
+    let mut merged = base;
+    for (key, value) in overrides {
+        match merged
+            .iter_mut()
+            .find(|(k, _)| k.eq_ignore_ascii_case(&key))
+        {
+            Some(existing) => *existing = (key, value),
+            None => merged.push((key, value)),
+        }
+    }
+    merged
 }
 
 /// Parse a URL into (scheme, host, port, path).
@@ -77,13 +295,8 @@ fn parse_url(url: &str) -> Result<(bool, String, u16, String), String> {
         None => (rest, "/"),
     };
 
-    let (host, port) = match host_port.rsplit_once(':') {
-        Some((h, p)) => {
-            let port: u16 = p.parse().map_err(|_| format!("Invalid port: {p}"))?;
-            (h.to_string(), port)
-        }
-        None => (host_port.to_string(), if tls { 443 } else { 80 }),
-    };
+    let (host, port) = crate::addr::split_host_port(host_port)?;
+    let (host, port) = (host.to_string(), port.unwrap_or(if tls { 443 } else { 80 }));
 
     Ok((tls, host, port, path.to_string()))
 }
@@ -93,96 +306,518 @@ type HttpRawResponse = (
     u16,
     String,
     HashMap<String, String>,
-    usize,
+    ResponseBody,
     f64,
     f64,
     f64,
     f64,
+    Option<(usize, usize)>,
 );
 
-/// Perform a simple HTTP request (no TLS — for HTTP only).
-fn http_request_plain(
-    host: &str,
-    port: u16,
-    path: &str,
+/// Response bytes (the head and any body kept), the body size, and any
+/// `(declared, actual)` Content-Length mismatch.
+type RawResponse = (Vec<u8>, usize, Option<(usize, usize)>);
+
+/// A response body: its bytes when kept (see [`body_limit`]) and its full size.
+#[derive(Debug, Default)]
+struct ResponseBody {
+    bytes: Vec<u8>,
+    size: usize,
+}
+
+impl ResponseBody {
+    /// The body following the head in `response`.
+    fn after_head(response: &[u8], size: usize) -> Self {
+        let bytes = find_head_end(response).map_or_else(Vec::new, |end| response[end..].to_vec());
+        Self { bytes, size }
+    }
+}
+
+/// How many body bytes to keep in memory: up to `max_body` when the body is
+/// returned or decompressed, otherwise none.
+fn body_limit(config: &HttpConfig) -> Option<usize> {
+    (config.keep_body || config.compression).then_some(config.max_body)
+}
+
+/// Build the request head and body to send.
+fn build_request(host: &str, path: &str, config: &HttpConfig) -> String {
+    let mut req = format!("{} {path} HTTP/1.1\r\nHost: {host}\r\n", config.method);
+    match config.connection {
+        ConnectionMode::Close => req.push_str("Connection: close\r\n"),
+        ConnectionMode::KeepAlive => req.push_str("Connection: keep-alive\r\n"),
+        ConnectionMode::Unset => {}
+    }
+    for (k, v) in &config.headers {
+        req.push_str(&format!("{k}: {v}\r\n"));
+    }
+    if let Some(value) = accept_encoding(config) {
+        req.push_str(&format!("Accept-Encoding: {value}\r\n"));
+    }
+    if let Some(ref b) = config.body {
+        req.push_str(&format!("Content-Length: {}\r\n", b.len()));
+    }
+    req.push_str("\r\n");
+    if let Some(ref b) = config.body {
+        req.push_str(b);
+    }
+    req
+}
+
+/// The `Accept-Encoding` to add for `--compression`, unless the user set their own.
+fn accept_encoding(config: &HttpConfig) -> Option<&'static str> {
+    let user_set = config
+        .headers
+        .iter()
+        .any(|(k, _)| k.eq_ignore_ascii_case("accept-encoding"));
+    (config.compression && !user_set).then_some("gzip, br")
+}
+
+/// Find the end of the header block (index just past `\r\n\r\n`).
+fn find_head_end(buf: &[u8]) -> Option<usize> {
+    buf.windows(4).position(|w| w == b"\r\n\r\n").map(|i| i + 4)
+}
+
+/// Incremental decoder for a chunked body. Each call resumes at the first chunk
+/// not yet decoded, so a large body is parsed once rather than on every read.
+struct ChunkedBody {
+    pos: usize,
+    decoded: Vec<u8>,
+    /// Decoded size, counted whether or not the data is kept.
+    size: usize,
+    keep: bool,
+}
+
+impl ChunkedBody {
+    fn new(keep: bool) -> Self {
+        Self {
+            pos: 0,
+            decoded: Vec::new(),
+            size: 0,
+            keep,
+        }
+    }
+
+    /// Decode the complete chunks of `data` (the raw body received so far).
+    /// Returns `true` once the terminating chunk has arrived.
+    fn advance(&mut self, data: &[u8]) -> bool {
+        loop {
+            let Some(rest) = data.get(self.pos..) else {
+                return false;
+            };
+            let Some(line_len) = rest.windows(2).position(|w| w == b"\r\n") else {
+                return false;
+            };
+            let Some(size) = std::str::from_utf8(&rest[..line_len])
+                .ok()
+                .and_then(|line| line.split(';').next())
+                .and_then(|hex| usize::from_str_radix(hex.trim(), 16).ok())
+            else {
+                return false;
+            };
+            if size == 0 {
+                return true;
+            }
+            let start = line_len + 2;
+            // Wait for the chunk's data and its trailing CRLF
+            let Some(chunk) = rest.get(start..start + size + 2) else {
+                return false;
+            };
+            if self.keep {
+                self.decoded.extend_from_slice(&chunk[..size]);
+            }
+            self.size += size;
+            self.pos += start + size + 2;
+        }
+    }
+}
+
+/// Read a full HTTP response.
+///
+/// The body is framed by `Content-Length` or chunked encoding when present, so a
+/// kept-alive connection does not have to close; otherwise it is read until EOF.
+/// With `keep` set, the body is kept (decoded, if chunked) and the read fails once
+/// it grows past that many bytes; otherwise body bytes are counted and dropped,
+/// so any size can be measured. Also returns `(declared, actual)` body lengths
+/// when the bytes received disagree with `Content-Length`.
+fn read_response<R: Read>(
+    stream: &mut R,
     method: &str,
-    extra_headers: &[(String, String)],
-    body: Option<&str>,
-    timeout_dur: Duration,
-) -> Result<HttpRawResponse, String> {
-    let t_start = Instant::now();
+    keep: Option<usize>,
+) -> Result<RawResponse, String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let head_end = loop {
+        if let Some(end) = find_head_end(&buf) {
+            break end;
+        }
+        let n = read_some(stream, &mut chunk)?;
+        if n == 0 {
+            return Ok((buf, 0, None));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_lowercase();
+    let status: u16 = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    let header = |name: &str| {
+        head.lines()
+            .filter_map(|l| l.split_once(':'))
+            .find(|(k, _)| k.trim() == name)
+            .map(|(_, v)| v.trim().to_string())
+    };
+    if method.eq_ignore_ascii_case("HEAD")
+        || (100..200).contains(&status)
+        || status == 204
+        || status == 304
+    {
+        buf.truncate(head_end);
+        return Ok((buf, 0, None));
+    }
+    let chunked = header("transfer-encoding").is_some_and(|v| v.contains("chunked"));
+    let content_length = header("content-length").and_then(|v| v.parse::<usize>().ok());
+    // An unframed OPTIONS response carries no body; don't wait for the server to close
+    if method.eq_ignore_ascii_case("OPTIONS") && !chunked && content_length.is_none() {
+        buf.truncate(head_end);
+        return Ok((buf, 0, None));
+    }
+
+    // Transfer-Encoding takes precedence over Content-Length (RFC 9112 §6.3)
+    let declared = content_length.filter(|_| !chunked);
+    if let (Some(len), Some(max)) = (declared, keep) {
+        if len > max {
+            return Err(body_too_large(max));
+        }
+    }
+    let mismatch = |actual: usize| {
+        declared
+            .filter(|&len| len != actual)
+            .map(|len| (len, actual))
+    };
 
-    // DNS
-    let addr = format!("{host}:{port}")
-        .to_socket_addrs()
+    // Raw body bytes not yet dropped, and how many were dropped before them
+    let mut body = buf.split_off(head_end);
+    let mut dropped = 0;
+    let mut chunks = ChunkedBody::new(keep.is_some());
+    loop {
+        if chunked {
+            if chunks.advance(&body) {
+                buf.extend_from_slice(&chunks.decoded);
+                return Ok((buf, chunks.size, None));
+            }
+            if keep.is_none() {
+                // Only the chunk still being received is needed
+                body.drain(..chunks.pos);
+                dropped += chunks.pos;
+                chunks.pos = 0;
+            }
+        } else if let Some(len) = declared {
+            let received = dropped + body.len();
+            if received >= len {
+                let extra = mismatch(received);
+                if keep.is_some() {
+                    buf.extend_from_slice(&body[..len]);
+                }
+                return Ok((buf, len, extra));
+            }
+        }
+        match keep {
+            // Chunk framing counts towards the limit; it is a guard, not a measurement
+            Some(max) if body.len() > max => return Err(body_too_large(max)),
+            None if !chunked => {
+                dropped += body.len();
+                body.clear();
+            }
+            _ => {}
+        }
+        let n = read_some(stream, &mut chunk)?;
+        if n == 0 {
+            let received = dropped + body.len();
+            if let Some(max) = keep.filter(|&max| received > max) {
+                return Err(body_too_large(max));
+            }
+            let short = mismatch(received);
+            if keep.is_some() {
+                buf.extend_from_slice(&body);
+            }
+            return Ok((buf, received, short));
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+}
+
+fn body_too_large(max_body: usize) -> String {
+    format!("Response body exceeds the {max_body}-byte limit (raise it with --max-body)")
+}
+
+/// Reader that notes when the first response bytes arrived.
+struct FirstByte<R> {
+    inner: R,
+    at: Option<Instant>,
+}
+
+impl<R: Read> Read for FirstByte<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n > 0 && self.at.is_none() {
+            self.at = Some(Instant::now());
+        }
+        Ok(n)
+    }
+}
+
+/// [`read_response`], also returning the time from `sent` to the first byte.
+fn read_response_timed<R: Read>(
+    stream: &mut R,
+    config: &HttpConfig,
+    sent: Instant,
+) -> Result<(RawResponse, f64), String> {
+    let mut timed = FirstByte {
+        inner: stream,
+        at: None,
+    };
+    let response = read_response(&mut timed, &config.method, body_limit(config))?;
+    let first = timed.at.unwrap_or_else(Instant::now);
+    Ok((response, first.duration_since(sent).as_secs_f64() * 1000.0))
+}
+
+/// Read into `buf`, treating a TLS peer that closes without `close_notify` as EOF.
+fn read_some<R: Read>(stream: &mut R, buf: &mut [u8]) -> Result<usize, String> {
+    match stream.read(buf) {
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(0),
+        r => r.map_err(|e| format!("Read failed: {e}")),
+    }
+}
+
+/// Resolve `host` and connect, returning the socket with DNS and connect times.
+fn connect(host: &str, port: u16, timeout: Duration) -> Result<(TcpStream, f64, f64), String> {
+    let t_start = Instant::now();
+    let addr = crate::addr::resolve(host, port)
         .map_err(|e| format!("DNS failed: {e}"))?
+        .into_iter()
         .next()
         .ok_or("No address")?;
     let dns_ms = t_start.elapsed().as_secs_f64() * 1000.0;
 
-    // Connect
     let t_conn = Instant::now();
-    let mut stream = TcpStream::connect_timeout(&addr, timeout_dur)
-        .map_err(|e| format!("Connect failed: {e}"))?;
+    let stream =
+        TcpStream::connect_timeout(&addr, timeout).map_err(|e| format!("Connect failed: {e}"))?;
     stream
-        .set_read_timeout(Some(timeout_dur))
+        .set_read_timeout(Some(timeout))
         .map_err(|e| format!("Set timeout: {e}"))?;
-    let connect_ms = t_conn.elapsed().as_secs_f64() * 1000.0;
+    stream
+        .set_write_timeout(Some(timeout))
+        .map_err(|e| format!("Set timeout: {e}"))?;
+    Ok((stream, dns_ms, t_conn.elapsed().as_secs_f64() * 1000.0))
+}
 
-    // Send request
-    let mut req = format!("{method} {path} HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n");
-    for (k, v) in extra_headers {
-        req.push_str(&format!("{k}: {v}\r\n"));
-    }
-    if let Some(b) = body {
-        req.push_str(&format!("Content-Length: {}\r\n", b.len()));
-    }
-    req.push_str("\r\n");
-    if let Some(b) = body {
-        req.push_str(b);
-    }
+/// Status, reason, headers, body, time to first byte and any `(declared,
+/// actual)` Content-Length mismatch of one response.
+type Exchange = (
+    u16,
+    String,
+    HashMap<String, String>,
+    ResponseBody,
+    f64,
+    Option<(usize, usize)>,
+);
+
+/// Send one HTTP/1.1 request on `stream` and read the response.
+fn exchange<S: Read + Write>(
+    stream: &mut S,
+    host: &str,
+    path: &str,
+    config: &HttpConfig,
+) -> Result<Exchange, String> {
+    let req = build_request(host, path, config);
 
     let t_send = Instant::now();
     stream
         .write_all(req.as_bytes())
         .map_err(|e| format!("Write failed: {e}"))?;
 
-    // Read response
-    let mut response = Vec::new();
-    stream
-        .read_to_end(&mut response)
-        .map_err(|e| format!("Read failed: {e}"))?;
-    let ttfb_ms = t_send.elapsed().as_secs_f64() * 1000.0;
-    let total_ms = t_start.elapsed().as_secs_f64() * 1000.0;
-    let transfer_ms = total_ms - dns_ms - connect_ms - ttfb_ms;
-
+    let ((response, body_size, content_length_mismatch), ttfb_ms) =
+        read_response_timed(stream, config, t_send)?;
     let response_str = String::from_utf8_lossy(&response);
-    let (status, status_text, headers, body_size) = parse_response(&response_str)?;
-
+    let (status, status_text, headers, _) = parse_response(&response_str)?;
+    // Keep raw bytes; the lossy string may differ for non-UTF-8 bodies
+    let body = ResponseBody::after_head(&response, body_size);
     Ok((
         status,
         status_text,
         headers,
-        body_size,
+        body,
+        ttfb_ms,
+        content_length_mismatch,
+    ))
+}
+
+/// Combine a response with the setup times of its connection; the transfer
+/// time is whatever remains of the time since `t_start`.
+fn raw_response(
+    exchange: Exchange,
+    t_start: Instant,
+    dns_ms: f64,
+    connect_ms: f64,
+    tls_ms: f64,
+) -> HttpRawResponse {
+    let (status, status_text, headers, body, ttfb_ms, content_length_mismatch) = exchange;
+    let total_ms = t_start.elapsed().as_secs_f64() * 1000.0;
+    let transfer_ms = total_ms - dns_ms - connect_ms - tls_ms - ttfb_ms;
+    (
+        status,
+        status_text,
+        headers,
+        body,
         dns_ms,
         connect_ms,
         ttfb_ms,
         transfer_ms.max(0.0),
+        content_length_mismatch,
+    )
+}
+
+/// A connection left open for the next request to the same origin.
+enum Conn {
+    Plain(TcpStream),
+    /// HTTP/1.1 over TLS, with the session it was opened with.
+    Tls(
+        Box<rustls::StreamOwned<rustls::ClientConnection, TcpStream>>,
+        TlsSession,
+    ),
+    H2(H2Conn),
+}
+
+/// An HTTP/2 connection. Its driver task runs on `runtime` whenever a request
+/// blocks on it.
+struct H2Conn {
+    runtime: tokio::runtime::Runtime,
+    send: h2::client::SendRequest<bytes::Bytes>,
+    authority: String,
+    session: TlsSession,
+}
+
+/// Connect to `host`, then complete the TLS handshake when `tls` is set and,
+/// for `--http2`, the HTTP/2 one if the server selects `h2`. Returns the
+/// connection, the DNS and connect times and the TLS session.
+fn open_conn(
+    tls: bool,
+    host: &str,
+    port: u16,
+    config: &HttpConfig,
+) -> Result<(Conn, f64, f64), String> {
+    let (mut sock, dns_ms, connect_ms) = connect(host, port, config.timeout)?;
+    if !tls {
+        return Ok((Conn::Plain(sock), dns_ms, connect_ms));
+    }
+    if config.http2 {
+        return Ok((http2_connect(host, port, config, sock)?, dns_ms, connect_ms));
+    }
+    let (conn, ms, info) = tls_handshake(config, host, &mut sock)?;
+    let session = TlsSession {
+        ms,
+        info,
+        protocol: None,
+    };
+    let stream = Box::new(rustls::StreamOwned::new(conn, sock));
+    Ok((Conn::Tls(stream, session), dns_ms, connect_ms))
+}
+
+/// Perform an HTTP request over TLS or plain TCP, with the TLS session details
+/// when `tls` is set.
+///
+/// An open connection in `conn` is used instead of connecting (DNS, connect
+/// and TLS times are then zero). If both sides allow it, the connection is
+/// left in `conn` afterwards for the next request; HTTP/2 connections always are.
+fn http_request(
+    tls: bool,
+    host: &str,
+    port: u16,
+    path: &str,
+    config: &HttpConfig,
+    conn: &mut Option<Conn>,
+) -> Result<(HttpRawResponse, Option<TlsSession>), String> {
+    let t_start = Instant::now();
+
+    let (mut open, dns_ms, connect_ms, reused) = match conn.take() {
+        Some(open) => (open, 0.0, 0.0, true),
+        None => {
+            let (open, dns_ms, connect_ms) = open_conn(tls, host, port, config)?;
+            (open, dns_ms, connect_ms, false)
+        }
+    };
+    let session = match &open {
+        Conn::Plain(_) => None,
+        Conn::Tls(_, session) => Some(session),
+        Conn::H2(h2) => Some(&h2.session),
+    }
+    .map(|s| TlsSession {
+        ms: if reused { 0.0 } else { s.ms },
+        ..s.clone()
+    });
+
+    let exchange = match &mut open {
+        Conn::Plain(stream) => exchange(stream, host, path, config)?,
+        Conn::Tls(stream, _) => exchange(stream.as_mut(), host, path, config)?,
+        Conn::H2(h2) => {
+            let send = h2.send.clone();
+            h2.runtime
+                .block_on(h2_exchange(send, &h2.authority, path, config))?
+        }
+    };
+    let server_closes = exchange
+        .2
+        .get("connection")
+        .is_some_and(|v| v.eq_ignore_ascii_case("close"));
+    let keep = matches!(open, Conn::H2(_))
+        || (config.connection != ConnectionMode::Close && !server_closes);
+    if keep {
+        *conn = Some(open);
+    }
+    let tls_ms = session.as_ref().map_or(0.0, |s| s.ms);
+    Ok((
+        raw_response(exchange, t_start, dns_ms, connect_ms, tls_ms),
+        session,
     ))
 }
 
+/// Body download rate over the transfer phase, or `None` when it was too short
+/// to measure (see [`crate::speed::MIN_SAMPLE_MS`]).
+pub fn body_throughput_mbps(body_size: usize, transfer_ms: f64) -> Option<f64> {
+    crate::speed::throughput_mbps(body_size as u64, transfer_ms)
+}
+
+/// TLS side of a request: the handshake time, the `--tls-info` details and,
+/// for `--http2`, the protocol the server selected via ALPN.
+#[derive(Clone)]
+struct TlsSession {
+    ms: f64,
+    info: Option<HttpTlsInfo>,
+    protocol: Option<&'static str>,
+}
+
 /// Parse an HTTP response string into components.
 fn parse_response(resp: &str) -> Result<(u16, String, HashMap<String, String>, usize), String> {
     let (header_section, body) = resp.split_once("\r\n\r\n").unwrap_or((resp, ""));
 
     let mut lines = header_section.lines();
     let status_line = lines.next().ok_or("Empty response")?;
-    let parts: Vec<&str> = status_line.splitn(3, ' ').collect();
-    if parts.len() < 2 {
+    if !status_line.starts_with("HTTP/") {
+        return Err(not_http1(status_line));
+    }
+    // Tolerate runs of spaces or tabs between the version, code and reason
+    let rest = status_line
+        .split_once(char::is_whitespace)
+        .map_or("", |(_, rest)| rest.trim());
+    let (code, reason) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    if code.is_empty() {
         return Err(format!("Invalid status line: {status_line}"));
     }
-    let status: u16 = parts[1].parse().map_err(|_| "Invalid status code")?;
-    let status_text = parts.get(2).unwrap_or(&"").to_string();
+    let status: u16 = code.parse().map_err(|_| "Invalid status code")?;
+    let status_text = reason.trim().to_string();
 
     let mut headers = HashMap::new();
     for line in lines {
@@ -194,38 +829,140 @@ fn parse_response(resp: &str) -> Result<(u16, String, HashMap<String, String>, u
     Ok((status, status_text, headers, body.len()))
 }
 
+/// Error for a response whose first line isn't an HTTP/1.x status line, such as
+/// an HTTP/2 server's binary SETTINGS or GOAWAY frame.
+fn not_http1(first_line: &str) -> String {
+    let binary = first_line
+        .chars()
+        .any(|c| c == char::REPLACEMENT_CHARACTER || (c.is_control() && c != '\t'));
+    let got = if binary {
+        "binary data".to_string()
+    } else {
+        format!("{:?}", first_line.chars().take(40).collect::<String>())
+    };
+    format!(
+        "Server spoke HTTP/2 or a non-HTTP protocol (got {got} instead of an HTTP/1.x \
+         status line); for an HTTP/2-only server try --http2"
+    )
+}
+
 /// Probe an HTTP(S) URL.
 pub fn probe(config: &HttpConfig) -> Result<HttpResult, String> {
-    let (tls, host, port, path) = parse_url(&config.url)?;
+    let timestamp = crate::timestamp::now();
+    let (mut tls, host, port, path) = parse_url(&config.url)?;
 
-    if tls {
-        // For HTTPS, we report that TLS is required but use a simplified approach
-        // A full TLS implementation would use rustls here
-        return Err("HTTPS probing requires the cert module. Use `netscout http` with http:// URLs, or use `netscout cert` for TLS inspection.".to_string());
+    if config.http2 && !tls {
+        return Err("--http2 requires an https:// URL (h2 is negotiated via TLS ALPN)".to_string());
     }
-
-    let (status, status_text, headers, body_size, dns_ms, connect_ms, ttfb_ms, transfer_ms) =
-        http_request_plain(
-            &host,
-            port,
-            &path,
-            &config.method,
-            &config.headers,
-            config.body.as_deref(),
-            config.timeout,
-        )?;
-
-    let total_ms = dns_ms + connect_ms + ttfb_ms + transfer_ms;
-
+    let idn = crate::idn::analyze_idn(&host);
+    let mut request = config.clone();
+    let (mut host, mut port, mut path) = (idn.ascii.clone(), port, path);
+    let mut current_url = config.url.clone();
+    let mut conn = None;
+    let cold = if config.warmup {
+        let mut prime = request.clone();
+        prime.connection = ConnectionMode::KeepAlive;
+        let ((_, _, _, _, dns_ms, connect_ms, ttfb_ms, transfer_ms, _), session) =
+            http_request(tls, &host, port, &path, &prime, &mut conn)?;
+        let tls_ms = session.map(|s| s.ms);
+        Some(HttpTiming {
+            dns_ms,
+            connect_ms,
+            tls_ms,
+            ttfb_ms,
+            transfer_ms,
+            total_ms: dns_ms + connect_ms + tls_ms.unwrap_or(0.0) + ttfb_ms + transfer_ms,
+        })
+    } else {
+        None
+    };
+    let mut connection_reused = conn.is_some();
     let mut redirects = Vec::new();
-    if config.follow_redirects && (300..400).contains(&status) {
-        if let Some(location) = headers.get("location") {
-            redirects.push(HttpRedirect {
-                url: location.clone(),
+    let (status, status_text, headers, body, timing, content_length_mismatch, session) = loop {
+        let (raw, session) = match http_request(tls, &host, port, &path, &request, &mut conn) {
+            // The server dropped the warmed-up connection; measure on a fresh one
+            Err(_) if connection_reused && redirects.is_empty() => {
+                connection_reused = false;
+                http_request(tls, &host, port, &path, &request, &mut conn)?
+            }
+            raw => raw?,
+        };
+        let (
+            status,
+            status_text,
+            headers,
+            body,
+            dns_ms,
+            connect_ms,
+            ttfb_ms,
+            transfer_ms,
+            content_length_mismatch,
+        ) = raw;
+        let tls_ms = session.as_ref().map(|s| s.ms);
+        let timing = HttpTiming {
+            dns_ms,
+            connect_ms,
+            tls_ms,
+            ttfb_ms,
+            transfer_ms,
+            total_ms: dns_ms + connect_ms + tls_ms.unwrap_or(0.0) + ttfb_ms + transfer_ms,
+        };
+
+        let location = headers
+            .get("location")
+            .filter(|_| config.follow_redirects && (300..400).contains(&status))
+            .map(|l| resolve_location(&current_url, l));
+        let next = location.and_then(|url| {
+            let (next_tls, next_host, next_port, next_path) = parse_url(&url).ok()?;
+            Some((url, next_tls, next_host, next_port, next_path))
+        });
+        let Some((url, next_tls, next_host, next_port, next_path)) = next else {
+            break (
                 status,
-            });
+                status_text,
+                headers,
+                body,
+                timing,
+                content_length_mismatch,
+                session,
+            );
+        };
+        if redirects.len() >= config.max_redirects as usize {
+            return Err(format!("Too many redirects (max {})", config.max_redirects));
         }
-    }
+
+        redirects.push(HttpRedirect {
+            idn: redirect_idn(&url),
+            url: url.clone(),
+            status,
+            timing,
+        });
+        if status == 303 {
+            request.method = "GET".to_string();
+            request.body = None;
+        }
+        tls = next_tls;
+        host = crate::idn::analyze_idn(&next_host).ascii;
+        port = next_port;
+        path = next_path;
+        current_url = url;
+        conn = None;
+    };
+    let (tls_info, protocol) = session.map_or((None, None), |s| (s.info, s.protocol));
+    let redirect_chain_ms =
+        redirects.iter().map(|r| r.timing.total_ms).sum::<f64>() + timing.total_ms;
+
+    let options = config
+        .method
+        .eq_ignore_ascii_case("OPTIONS")
+        .then(|| parse_options(&headers));
+    let compression = config
+        .compression
+        .then(|| CompressionInfo::measure(&headers, &body.bytes));
+    let body_throughput_mbps = config
+        .throughput
+        .then(|| body_throughput_mbps(body.size, timing.transfer_ms))
+        .flatten();
 
     Ok(HttpResult {
         url: config.url.clone(),
@@ -233,24 +970,347 @@ pub fn probe(config: &HttpConfig) -> Result<HttpResult, String> {
         status,
         status_text,
         headers,
-        body_size,
-        timing: HttpTiming {
-            dns_ms,
-            connect_ms,
-            tls_ms: None,
-            ttfb_ms,
-            transfer_ms,
-            total_ms,
-        },
+        body_size: body.size,
+        timing,
         redirects,
+        redirect_chain_ms,
+        final_url: current_url,
         tls,
+        idn: idn.is_idn.then_some(idn),
+        content_length_mismatch,
+        options,
+        max_ttfb_ms: config.max_ttfb.map(|d| d.as_secs_f64() * 1000.0),
+        protocol: protocol.map(str::to_string),
+        tls_info,
+        compression,
+        warmup: cold.map(|cold| WarmupInfo {
+            cold,
+            connection_reused,
+        }),
+        body_throughput_mbps,
+        timestamp,
+        body: config.keep_body.then_some(body.bytes),
     })
 }
 
+/// Fetch an HTTP(S) URL and return the status and body, e.g. from an IP echo service.
+pub fn fetch(config: &HttpConfig) -> Result<(u16, Vec<u8>), String> {
+    let (tls, host, port, path) = parse_url(&config.url)?;
+    let config = HttpConfig {
+        keep_body: true,
+        ..config.clone()
+    };
+    let ((status, _, _, body, ..), ..) = http_request(tls, &host, port, &path, &config, &mut None)?;
+    Ok((status, body.bytes))
+}
+
+/// Async [`probe`]; the request runs on the blocking pool.
+pub async fn probe_async(config: &HttpConfig) -> Result<HttpResult, String> {
+    let config = config.clone();
+    crate::runtime::blocking(move || probe(&config)).await
+}
+
+/// Headers that are connection-specific in HTTP/1.1 and not allowed in HTTP/2.
+fn is_connection_header(name: &str) -> bool {
+    [
+        "connection",
+        "host",
+        "keep-alive",
+        "proxy-connection",
+        "transfer-encoding",
+        "upgrade",
+    ]
+    .iter()
+    .any(|h| name.eq_ignore_ascii_case(h))
+}
+
+/// Await `fut`, failing once `timeout` passes.
+async fn within<F: std::future::Future>(timeout: Duration, fut: F) -> Result<F::Output, String> {
+    tokio::time::timeout(timeout, fut)
+        .await
+        .map_err(|_| format!("Timed out after {}ms", timeout.as_millis()))
+}
+
+fn h2_error(e: h2::Error) -> String {
+    format!("HTTP/2 request failed: {e}")
+}
+
+/// Complete a TLS handshake on `sock` offering `h2` via ALPN, then the HTTP/2
+/// handshake if the server selects it; otherwise the connection is kept for HTTP/1.1.
+fn http2_connect(
+    host: &str,
+    port: u16,
+    config: &HttpConfig,
+    sock: TcpStream,
+) -> Result<Conn, String> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("Runtime setup failed: {e}"))?;
+    let t_tls = Instant::now();
+    let tls = runtime.block_on(async {
+        sock.set_nonblocking(true)
+            .map_err(|e| format!("Socket setup failed: {e}"))?;
+        let sock = tokio::net::TcpStream::from_std(sock)
+            .map_err(|e| format!("Socket setup failed: {e}"))?;
+        let tls_config = tls_client_config(config.ca_file.as_deref(), true)?;
+        let handshake =
+            tokio_rustls::TlsConnector::from(tls_config).connect(server_name(host)?, sock);
+        within(config.timeout, handshake)
+            .await?
+            .map_err(|e| format!("TLS handshake failed: {e}"))
+    })?;
+    let ms = t_tls.elapsed().as_secs_f64() * 1000.0;
+    let conn = tls.get_ref().1;
+    let h2 = conn.alpn_protocol() == Some(b"h2");
+    let session = TlsSession {
+        ms,
+        info: config.tls_info.then(|| tls_details(conn)),
+        protocol: Some(if h2 { "h2" } else { "http/1.1" }),
+    };
+
+    if !h2 {
+        let (sock, conn) = tls.into_inner();
+        let sock = sock
+            .into_std()
+            .and_then(|sock| sock.set_nonblocking(false).map(|()| sock))
+            .map_err(|e| format!("Socket setup failed: {e}"))?;
+        let stream = Box::new(rustls::StreamOwned::new(conn, sock));
+        return Ok(Conn::Tls(stream, session));
+    }
+    let send = runtime.block_on(async {
+        let (send, connection) = within(config.timeout, h2::client::handshake(tls))
+            .await?
+            .map_err(h2_error)?;
+        tokio::spawn(connection);
+        Ok::<_, String>(send)
+    })?;
+    let authority = if port == 443 {
+        host.to_string()
+    } else {
+        format!("{host}:{port}")
+    };
+    Ok(Conn::H2(H2Conn {
+        runtime,
+        send,
+        authority,
+        session,
+    }))
+}
+
+/// Send one request on an HTTP/2 connection and read the response. HTTP/2
+/// has no reason phrase and no Content-Length mismatch is reported.
+async fn h2_exchange(
+    send: h2::client::SendRequest<bytes::Bytes>,
+    authority: &str,
+    path: &str,
+    config: &HttpConfig,
+) -> Result<Exchange, String> {
+    let mut request = http::Request::builder()
+        .method(config.method.as_str())
+        .uri(format!("https://{authority}{path}"));
+    for (k, v) in config
+        .headers
+        .iter()
+        .filter(|(k, _)| !is_connection_header(k))
+    {
+        request = request.header(k.as_str(), v.as_str());
+    }
+    if let Some(value) = accept_encoding(config) {
+        request = request.header("accept-encoding", value);
+    }
+    if let Some(body) = &config.body {
+        request = request.header("content-length", body.len());
+    }
+    let request = request
+        .body(())
+        .map_err(|e| format!("Invalid request: {e}"))?;
+
+    let mut send = within(config.timeout, send.ready())
+        .await?
+        .map_err(h2_error)?;
+    let t_send = Instant::now();
+    let (response, mut stream) = send
+        .send_request(request, config.body.is_none())
+        .map_err(h2_error)?;
+    if let Some(body) = &config.body {
+        stream
+            .send_data(body.clone().into(), true)
+            .map_err(h2_error)?;
+    }
+    // Interim 1xx responses are skipped by the h2 client
+    let response = within(config.timeout, response).await?.map_err(h2_error)?;
+    let ttfb_ms = t_send.elapsed().as_secs_f64() * 1000.0;
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(k, v)| {
+            let value = String::from_utf8_lossy(v.as_bytes()).into_owned();
+            (k.as_str().to_string(), value)
+        })
+        .collect();
+
+    let mut data = response.into_body();
+    let mut body = ResponseBody::default();
+    let keep = body_limit(config);
+    while let Some(chunk) = within(config.timeout, data.data()).await? {
+        let chunk = chunk.map_err(h2_error)?;
+        body.size += chunk.len();
+        let _ = data.flow_control().release_capacity(chunk.len());
+        if let Some(max) = keep {
+            body.bytes.extend_from_slice(&chunk);
+            if body.size > max {
+                return Err(body_too_large(max));
+            }
+        }
+    }
+    Ok((status, String::new(), headers, body, ttfb_ms, None))
+}
+
+/// Server name to verify `host`'s certificate against.
+fn server_name(host: &str) -> Result<rustls::pki_types::ServerName<'static>, String> {
+    rustls::pki_types::ServerName::try_from(crate::addr::split_zone(host).0.to_string())
+        .map_err(|e| format!("Invalid server name: {e}"))
+}
+
+/// TLS version, cipher suite, ALPN protocol and resumption of a completed handshake.
+fn tls_details(conn: &rustls::ClientConnection) -> HttpTlsInfo {
+    HttpTlsInfo {
+        version: match conn.protocol_version() {
+            Some(rustls::ProtocolVersion::TLSv1_2) => "TLSv1.2",
+            Some(rustls::ProtocolVersion::TLSv1_3) => "TLSv1.3",
+            _ => "unknown",
+        }
+        .to_string(),
+        cipher_suite: conn
+            .negotiated_cipher_suite()
+            .map(|cs| format!("{:?}", cs.suite()))
+            .unwrap_or_else(|| "unknown".to_string()),
+        alpn: conn
+            .alpn_protocol()
+            .map(|p| String::from_utf8_lossy(p).into_owned()),
+        resumed: conn.handshake_kind() == Some(rustls::HandshakeKind::Resumed),
+    }
+}
+
+/// Complete a TLS handshake on `sock`, offering only `http/1.1` via ALPN.
+/// Returns the connection, the handshake time and, if `tls_info` is set, its details.
+fn tls_handshake(
+    config: &HttpConfig,
+    host: &str,
+    sock: &mut TcpStream,
+) -> Result<(rustls::ClientConnection, f64, Option<HttpTlsInfo>), String> {
+    let t_tls = Instant::now();
+    let tls_config = tls_client_config(config.ca_file.as_deref(), false)?;
+    let mut conn = rustls::ClientConnection::new(tls_config, server_name(host)?)
+        .map_err(|e| format!("TLS setup failed: {e}"))?;
+    while conn.is_handshaking() {
+        conn.complete_io(sock)
+            .map_err(|e| format!("TLS handshake failed: {e}"))?;
+    }
+    let tls_ms = t_tls.elapsed().as_secs_f64() * 1000.0;
+    let tls_info = config.tls_info.then(|| tls_details(&conn));
+    Ok((conn, tls_ms, tls_info))
+}
+
+/// Client config for HTTPS probes, shared per CA file and ALPN offer so later
+/// probes can resume sessions.
+fn tls_client_config(
+    ca_file: Option<&Path>,
+    http2: bool,
+) -> Result<Arc<rustls::ClientConfig>, String> {
+    type Key = (Option<PathBuf>, bool);
+    static CONFIGS: OnceLock<Mutex<HashMap<Key, Arc<rustls::ClientConfig>>>> = OnceLock::new();
+    let mut configs = CONFIGS
+        .get_or_init(Default::default)
+        .lock()
+        .map_err(|_| "TLS config cache poisoned".to_string())?;
+    let key = (ca_file.map(Path::to_path_buf), http2);
+    if let Some(config) = configs.get(&key) {
+        return Ok(config.clone());
+    }
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut tls_config = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("TLS setup failed: {e}"))?
+        .with_root_certificates(crate::cert::root_store(ca_file)?)
+        .with_no_client_auth();
+    tls_config.alpn_protocols = if http2 {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    } else {
+        vec![b"http/1.1".to_vec()]
+    };
+    let tls_config = Arc::new(tls_config);
+    configs.insert(key, tls_config.clone());
+    Ok(tls_config)
+}
+
+/// Resolve a `Location` header against the URL that returned it.
+fn resolve_location(base: &str, location: &str) -> String {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        return location.to_string();
+    }
+    let (scheme, rest) = base.split_once("://").unwrap_or(("http", base));
+    if let Some(authority) = location.strip_prefix("//") {
+        return format!("{scheme}://{authority}");
+    }
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    if location.starts_with('/') {
+        return format!("{scheme}://{authority}{location}");
+    }
+    let path = path.split(['?', '#']).next().unwrap_or("/");
+    let dir = &path[..path.rfind('/').map_or(0, |i| i + 1)];
+    let dir = if dir.is_empty() { "/" } else { dir };
+    format!("{scheme}://{authority}{dir}{location}")
+}
+
+/// IDN analysis of an absolute redirect target; `None` for relative or ASCII targets.
+fn redirect_idn(location: &str) -> Option<crate::idn::IdnInfo> {
+    let (_, host, _, _) = parse_url(location).ok()?;
+    let idn = crate::idn::analyze_idn(&host);
+    idn.is_idn.then_some(idn)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_headers_file_skips_comments_and_blanks() {
+        let content = "# auth\nAuthorization: Bearer abc\n\nAccept: application/json\n";
+        let headers = parse_headers_file(content).unwrap();
+        assert_eq!(
+            headers,
+            vec![
+                ("Authorization".to_string(), "Bearer abc".to_string()),
+                ("Accept".to_string(), "application/json".to_string()),
+            ]
+        );
+        let err = parse_headers_file("Accept: */*\nbogus\n").unwrap_err();
+        assert!(err.contains("line 2"));
+    }
+
+    #[test]
+    fn test_merge_headers_file_with_flag_override() {
+        let file =
+            parse_headers_file("X-Api-Key: one\nAccept: text/html\nUser-Agent: netscout-test\n")
+                .unwrap();
+        let flags = vec![parse_header("accept: application/json").unwrap()];
+        let merged = merge_headers(file, flags);
+        assert_eq!(
+            merged,
+            vec![
+                ("X-Api-Key".to_string(), "one".to_string()),
+                ("accept".to_string(), "application/json".to_string()),
+                ("User-Agent".to_string(), "netscout-test".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn test_parse_url_http() {
         let (tls, host, port, path) = parse_url("http://example.com/path").unwrap();
@@ -415,6 +1475,45 @@ mod tests {
         assert_eq!(body_size, 4);
     }
 
+    #[test]
+    fn test_parse_response_extra_whitespace() {
+        let resp = "HTTP/1.1  404 \t Not   Found  \r\nServer: x\r\n\r\n";
+        let (status, text, headers, _) = parse_response(resp).unwrap();
+        assert_eq!(status, 404);
+        assert_eq!(text, "Not   Found");
+        assert_eq!(headers.get("server").map(String::as_str), Some("x"));
+
+        let (status, text, _, _) = parse_response("HTTP/1.1 204 \r\n\r\n").unwrap();
+        assert_eq!(status, 204);
+        assert_eq!(text, "");
+        assert!(parse_response("HTTP/1.1\r\n\r\n")
+            .unwrap_err()
+            .starts_with("Invalid status line"));
+    }
+
+    #[test]
+    fn test_parse_response_http2_or_binary() {
+        // An h2 server's reply to an HTTP/1.1 request: SETTINGS, then GOAWAY
+        let frames = [
+            &[0, 0, 0, 4, 0, 0, 0, 0, 0][..],
+            &[0, 0, 8, 7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1][..],
+        ]
+        .concat();
+        let err = parse_response(&String::from_utf8_lossy(&frames)).unwrap_err();
+        assert!(err.contains("HTTP/2 or a non-HTTP protocol"), "{err}");
+        assert!(err.contains("binary data"), "{err}");
+        assert!(err.contains("--http2"), "{err}");
+
+        let preface = "PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+        assert!(parse_response(preface)
+            .unwrap_err()
+            .contains("\"PRI * HTTP/2.0\""));
+        let ssh = "SSH-2.0-OpenSSH_9.6\r\n";
+        assert!(parse_response(ssh)
+            .unwrap_err()
+            .contains("non-HTTP protocol"));
+    }
+
     #[test]
     fn test_parse_response_empty() {
         assert!(parse_response("").is_err());
@@ -430,6 +1529,7 @@ mod tests {
         assert!(!config.follow_redirects);
         assert_eq!(config.max_redirects, 10);
         assert_eq!(config.timeout, Duration::from_secs(10));
+        assert_eq!(config.connection, ConnectionMode::Close);
     }
 
     #[test]
@@ -442,6 +1542,16 @@ mod tests {
             follow_redirects: true,
             max_redirects: 5,
             timeout: Duration::from_secs(30),
+            connection: ConnectionMode::KeepAlive,
+            max_ttfb: None,
+            http2: false,
+            ca_file: None,
+            tls_info: false,
+            compression: false,
+            warmup: false,
+            throughput: false,
+            max_body: 1024,
+            keep_body: false,
         };
         assert_eq!(config.method, "POST");
         assert_eq!(config.headers.len(), 1);
@@ -471,6 +1581,15 @@ mod tests {
         let redirect = HttpRedirect {
             url: "https://example.com/new".to_string(),
             status: 301,
+            idn: None,
+            timing: HttpTiming {
+                dns_ms: 1.0,
+                connect_ms: 2.0,
+                tls_ms: None,
+                ttfb_ms: 3.0,
+                transfer_ms: 0.5,
+                total_ms: 6.5,
+            },
         };
         let json = serde_json::to_string(&redirect).unwrap();
         assert!(json.contains("example.com/new"));
@@ -495,7 +1614,20 @@ mod tests {
                 total_ms: 42.0,
             },
             redirects: vec![],
+            redirect_chain_ms: 0.0,
+            final_url: "http://example.com".to_string(),
             tls: false,
+            idn: None,
+            content_length_mismatch: None,
+            options: None,
+            max_ttfb_ms: None,
+            protocol: None,
+            tls_info: None,
+            compression: None,
+            warmup: None,
+            body_throughput_mbps: None,
+            timestamp: String::new(),
+            body: None,
         };
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("example.com"));
@@ -504,14 +1636,787 @@ mod tests {
     }
 
     #[test]
-    fn test_https_probe_fails() {
+    fn test_build_request_connection_modes() {
+        let mut config = HttpConfig::default();
+        let req = build_request("example.com", "/", &config);
+        assert!(req.starts_with("GET / HTTP/1.1\r\nHost: example.com\r\n"));
+        assert!(req.contains("Connection: close\r\n"));
+
+        config.connection = ConnectionMode::KeepAlive;
+        let req = build_request("example.com", "/", &config);
+        assert!(!req.contains("Connection: close"));
+        assert!(req.contains("Connection: keep-alive\r\n"));
+
+        config.connection = ConnectionMode::Unset;
+        let req = build_request("example.com", "/", &config);
+        assert!(!req.contains("Connection:"));
+        assert!(req.ends_with("\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_build_request_with_body() {
         let config = HttpConfig {
-            url: "https://example.com".to_string(),
+            method: "POST".to_string(),
+            body: Some("{}".to_string()),
+            headers: vec![("X-Test".to_string(), "1".to_string())],
             ..Default::default()
         };
-        let result = probe(&config);
-        assert!(result.is_err());
-        let err_msg = result.unwrap_err();
-        assert!(err_msg.contains("HTTPS probing requires the cert module"));
+        let req = build_request("example.com", "/api", &config);
+        assert!(req.starts_with("POST /api HTTP/1.1\r\n"));
+        assert!(req.contains("X-Test: 1\r\n"));
+        assert!(req.contains("Content-Length: 2\r\n"));
+        assert!(req.ends_with("\r\n\r\n{}"));
+    }
+
+    #[test]
+    fn test_decode_chunked() {
+        let mut chunks = ChunkedBody::new(true);
+        assert!(chunks.advance(b"5\r\nhello\r\n6;ext=1\r\n world\r\n0\r\n\r\n"));
+        assert_eq!(chunks.decoded, b"hello world");
+        // Incomplete: no terminating chunk yet
+        assert!(!ChunkedBody::new(true).advance(b"5\r\nhello\r\n"));
+        assert!(!ChunkedBody::new(true).advance(b"5\r\nhel"));
+    }
+
+    #[test]
+    fn test_decode_chunked_incrementally() {
+        let raw = b"5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+        let mut chunks = ChunkedBody::new(true);
+        // Feed the body a byte at a time; each chunk is decoded exactly once
+        for end in 1..raw.len() {
+            let done = chunks.advance(&raw[..end]);
+            assert_eq!(done, end >= raw.len() - 2);
+        }
+        assert_eq!(chunks.decoded, b"hello world");
+        assert_eq!(chunks.pos, 21);
+    }
+
+    #[test]
+    fn test_read_response_content_length() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhelloEXTRA";
+        let (resp, _, _) = read_response(&mut &raw[..], "GET", Some(DEFAULT_MAX_BODY)).unwrap();
+        assert!(resp.ends_with(b"\r\n\r\nhello"));
+    }
+
+    #[test]
+    fn test_read_response_chunked() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n";
+        let (resp, _, _) = read_response(&mut &raw[..], "GET", Some(DEFAULT_MAX_BODY)).unwrap();
+        let (_, _, _, body_size) = parse_response(&String::from_utf8_lossy(&resp)).unwrap();
+        assert_eq!(body_size, 3);
+    }
+
+    #[test]
+    fn test_read_response_head_has_no_body() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n";
+        let (resp, _, mismatch) =
+            read_response(&mut &raw[..], "HEAD", Some(DEFAULT_MAX_BODY)).unwrap();
+        assert_eq!(resp.len(), raw.len());
+        assert!(mismatch.is_none());
+    }
+
+    #[test]
+    fn test_read_response_content_length_mismatch() {
+        let mut raw = b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n".to_vec();
+        raw.extend_from_slice(&[b'x'; 50]);
+        let (resp, _, mismatch) =
+            read_response(&mut &raw[..], "GET", Some(DEFAULT_MAX_BODY)).unwrap();
+        assert_eq!(mismatch, Some((100, 50)));
+        assert_eq!(resp.len(), raw.len());
+
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhelloEXTRA";
+        let (_, _, mismatch) = read_response(&mut &raw[..], "GET", Some(DEFAULT_MAX_BODY)).unwrap();
+        assert_eq!(mismatch, Some((5, 10)));
+
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello";
+        let (_, _, mismatch) = read_response(&mut &raw[..], "GET", Some(DEFAULT_MAX_BODY)).unwrap();
+        assert!(mismatch.is_none());
+    }
+
+    #[test]
+    fn test_read_response_chunked_ignores_content_length() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n";
+        let (_, _, mismatch) = read_response(&mut &raw[..], "GET", Some(DEFAULT_MAX_BODY)).unwrap();
+        assert!(mismatch.is_none());
+    }
+
+    #[test]
+    fn test_probe_keep_alive_does_not_wait_for_close() {
+        use std::net::TcpListener;
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut sock, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let n = sock.read(&mut buf).unwrap();
+            let req = String::from_utf8_lossy(&buf[..n]).to_string();
+            sock.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+                .unwrap();
+            // Hold the connection open past the client's timeout
+            std::thread::sleep(Duration::from_millis(1500));
+            req
+        });
+        let config = HttpConfig {
+            url: format!("http://127.0.0.1:{port}/"),
+            connection: ConnectionMode::KeepAlive,
+            timeout: Duration::from_secs(1),
+            ..Default::default()
+        };
+        let result = probe(&config).unwrap();
+        assert_eq!(result.status, 200);
+        assert_eq!(result.body_size, 2);
+        let req = server.join().unwrap();
+        assert!(req.contains("Connection: keep-alive"));
+    }
+
+    #[test]
+    fn test_body_throughput_mbps() {
+        // 1.25 MB in 100 ms is 10 MB/s, i.e. 100 Mbps
+        let mbps = body_throughput_mbps(1_250_000, 100.0).unwrap();
+        assert!((mbps - 100.0).abs() < 1e-9);
+        let mbps = body_throughput_mbps(500_000, 400.0).unwrap();
+        assert!((mbps - 10.0).abs() < 1e-9);
+        assert!(body_throughput_mbps(1_250_000, 5.0).is_none());
+    }
+
+    #[test]
+    fn test_probe_throughput_times_body_separately() {
+        use std::net::TcpListener;
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut sock, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = sock.read(&mut buf).unwrap();
+            sock.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 200000\r\n\r\n")
+                .unwrap();
+            for _ in 0..4 {
+                std::thread::sleep(Duration::from_millis(40));
+                sock.write_all(&[b'x'; 50_000]).unwrap();
+            }
+        });
+        let config = HttpConfig {
+            url: format!("http://127.0.0.1:{port}/"),
+            timeout: Duration::from_secs(2),
+            throughput: true,
+            ..Default::default()
+        };
+        let result = probe(&config).unwrap();
+        server.join().unwrap();
+        assert_eq!(result.body_size, 200_000);
+        assert!(result.timing.ttfb_ms < result.timing.transfer_ms);
+        assert!(result.timing.transfer_ms >= 100.0);
+        let expected = body_throughput_mbps(200_000, result.timing.transfer_ms);
+        assert_eq!(result.body_throughput_mbps, expected);
+        assert!(expected.is_some());
+    }
+
+    #[test]
+    fn test_probe_reports_truncated_body() {
+        use std::net::TcpListener;
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut sock, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = sock.read(&mut buf).unwrap();
+            sock.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n")
+                .unwrap();
+            sock.write_all(&[b'x'; 50]).unwrap();
+        });
+        let config = HttpConfig {
+            url: format!("http://127.0.0.1:{port}/"),
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let result = probe(&config).unwrap();
+        server.join().unwrap();
+        assert_eq!(result.body_size, 50);
+        assert_eq!(result.content_length_mismatch, Some((100, 50)));
+        let human = crate::output::HumanReadable::to_human(&result);
+        assert!(human.contains("declared 100 bytes but 50 were received"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_probe_keeps_body() {
+        use crate::testutil::{response, spawn_test_http_server};
+        let body: Vec<u8> = (0..=255u8).cycle().take(20_000).collect();
+        let served = body.clone();
+        let addr = spawn_test_http_server(move |_| response(200, "OK", &[], &served)).await;
+        let config = HttpConfig {
+            url: format!("http://{addr}/file"),
+            keep_body: true,
+            ..Default::default()
+        };
+        let probe_with = |config: HttpConfig| tokio::task::spawn_blocking(move || probe(&config));
+        let result = probe_with(config.clone()).await.unwrap().unwrap();
+        assert_eq!(result.body_size, body.len());
+        assert_eq!(result.body, Some(body.clone()));
+
+        // Without keep_body the body is only counted, so the cap doesn't apply
+        let config = HttpConfig {
+            keep_body: false,
+            max_body: 100,
+            ..config
+        };
+        let result = probe_with(config).await.unwrap().unwrap();
+        assert_eq!(result.body_size, body.len());
+        assert!(result.body.is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_probe_headers_output_sorted() {
+        use crate::output::HumanReadable;
+        use crate::testutil::{response, spawn_test_http_server};
+        let addr = spawn_test_http_server(|_| {
+            response(200, "OK", &[("X-Zeta", "last"), ("Age", "5")], b"ok")
+        })
+        .await;
+        let config = HttpConfig {
+            url: format!("http://{addr}/"),
+            ..Default::default()
+        };
+        let result = tokio::task::spawn_blocking(move || probe(&config))
+            .await
+            .unwrap()
+            .unwrap();
+        let table = result.to_table_with_headers();
+        let age = table.find("  age: 5\n").unwrap();
+        let length = table.find("  content-length: 2\n").unwrap();
+        let zeta = table.find("  x-zeta: last\n").unwrap();
+        assert!(age < length && length < zeta);
+
+        assert!(result.to_human_with_headers().contains("x-zeta"));
+        assert!(!result.to_table().contains("x-zeta"));
+        assert!(!result.to_human().contains("x-zeta"));
+    }
+
+    #[test]
+    fn test_read_response_enforces_max_body() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n0123456789";
+        assert!(read_response(&mut &raw[..], "GET", Some(10)).is_ok());
+        let err = read_response(&mut &raw[..], "GET", Some(9)).unwrap_err();
+        assert!(err.contains("9-byte limit"));
+        // Unframed bodies are cut off while reading
+        let raw = b"HTTP/1.1 200 OK\r\n\r\n0123456789";
+        assert!(read_response(&mut &raw[..], "GET", Some(4)).is_err());
+        assert!(read_response(&mut &raw[..], "GET", Some(10)).is_ok());
+    }
+
+    #[test]
+    fn test_read_response_counts_dropped_body() {
+        let head = b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n";
+        let raw = [&head[..], b"0123456789"].concat();
+        let (resp, size, mismatch) = read_response(&mut &raw[..], "GET", None).unwrap();
+        assert_eq!(resp, head);
+        assert_eq!(size, 10);
+        assert!(mismatch.is_none());
+
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2\r\nde\r\n0\r\n\r\n";
+        let (resp, size, _) = read_response(&mut &raw[..], "GET", None).unwrap();
+        assert!(resp.ends_with(b"\r\n\r\n"));
+        assert_eq!(size, 5);
+        // Chunks split across reads are still counted once each
+        struct Trickle<'a>(&'a [u8]);
+        impl Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = self.0.len().min(buf.len()).min(3);
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+        let (_, size, _) = read_response(&mut Trickle(raw), "GET", None).unwrap();
+        assert_eq!(size, 5);
+
+        let raw = b"HTTP/1.1 200 OK\r\n\r\n0123456789";
+        let (_, size, _) = read_response(&mut &raw[..], "GET", None).unwrap();
+        assert_eq!(size, 10);
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let headers: HashMap<String, String> = [
+            ("allow", "get, POST,,options"),
+            ("access-control-allow-origin", "*"),
+            ("access-control-max-age", "600"),
+            ("content-type", "text/plain"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let info = parse_options(&headers);
+        assert_eq!(info.allow, vec!["GET", "POST", "OPTIONS"]);
+        assert_eq!(info.cors.len(), 2);
+        assert_eq!(info.cors["access-control-allow-origin"], "*");
+        assert_eq!(parse_options(&HashMap::new()), OptionsInfo::default());
+    }
+
+    #[test]
+    fn test_read_response_options_without_framing() {
+        let raw = b"HTTP/1.1 204 No Content\r\nAllow: GET\r\n\r\n";
+        let (resp, _, _) = read_response(&mut &raw[..], "OPTIONS", Some(DEFAULT_MAX_BODY)).unwrap();
+        assert_eq!(resp.len(), raw.len());
+        let raw = b"HTTP/1.1 200 OK\r\nAllow: GET\r\nContent-Length: 2\r\n\r\nok";
+        let (resp, _, _) = read_response(&mut &raw[..], "OPTIONS", Some(DEFAULT_MAX_BODY)).unwrap();
+        assert!(resp.ends_with(b"ok"));
+    }
+
+    #[test]
+    fn test_redirect_idn() {
+        assert!(redirect_idn("/relative/path").is_none());
+        assert!(redirect_idn("https://example.com/").is_none());
+        let idn = redirect_idn("https://p\u{0430}ypal.com/login").unwrap();
+        assert!(idn.mixed_script);
+        assert!(idn.ascii.starts_with("xn--"));
+    }
+
+    #[test]
+    fn test_resolve_location() {
+        let base = "http://example.com:8080/a/b?q=1";
+        assert_eq!(
+            resolve_location(base, "https://other.com/x"),
+            "https://other.com/x"
+        );
+        assert_eq!(resolve_location(base, "//cdn.com/x"), "http://cdn.com/x");
+        assert_eq!(resolve_location(base, "/c"), "http://example.com:8080/c");
+        assert_eq!(resolve_location(base, "c"), "http://example.com:8080/a/c");
+        assert_eq!(resolve_location("http://h", "c"), "http://h/c");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_probe_redirect_chain_timing() {
+        use crate::testutil::{response, spawn_test_http_server};
+        let addr = spawn_test_http_server(|req| match req.path.as_str() {
+            "/one" => response(301, "Moved Permanently", &[("Location", "/two")], b""),
+            "/two" => response(302, "Found", &[("Location", "three")], b""),
+            _ => response(200, "OK", &[], b"done"),
+        })
+        .await;
+        let config = HttpConfig {
+            url: format!("http://{addr}/one"),
+            follow_redirects: true,
+            ..Default::default()
+        };
+        let result = tokio::task::spawn_blocking(move || probe(&config))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.status, 200);
+        assert_eq!(result.body_size, 4);
+        assert_eq!(result.redirects.len(), 2);
+        assert_eq!(result.redirects[0].status, 301);
+        assert_eq!(result.redirects[0].url, format!("http://{addr}/two"));
+        assert_eq!(result.redirects[1].url, format!("http://{addr}/three"));
+        assert_eq!(result.final_url, format!("http://{addr}/three"));
+        let sum: f64 = result
+            .redirects
+            .iter()
+            .map(|r| r.timing.total_ms)
+            .sum::<f64>()
+            + result.timing.total_ms;
+        assert!((result.redirect_chain_ms - sum).abs() < 1e-9);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_probe_final_url_without_redirect() {
+        use crate::testutil::{response, spawn_test_http_server};
+        let addr = spawn_test_http_server(|_| response(200, "OK", &[], b"ok")).await;
+        let config = HttpConfig {
+            url: format!("http://{addr}/page"),
+            follow_redirects: true,
+            ..Default::default()
+        };
+        let result = tokio::task::spawn_blocking(move || probe(&config))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(result.redirects.is_empty());
+        assert_eq!(result.final_url, result.url);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_probe_reports_gzip_compression() {
+        use crate::testutil::{response, spawn_test_http_server};
+        let gz = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/repeat.txt.gz"
+        ))
+        .unwrap();
+        let wire = gz.len();
+        let addr = spawn_test_http_server(move |req| {
+            let offered = req.header("accept-encoding").unwrap_or("");
+            if offered.contains("gzip") && offered.contains("br") {
+                response(200, "OK", &[("Content-Encoding", "gzip")], &gz)
+            } else {
+                response(406, "Not Acceptable", &[], b"")
+            }
+        })
+        .await;
+        let config = HttpConfig {
+            url: format!("http://{addr}/"),
+            compression: true,
+            ..Default::default()
+        };
+        let result = tokio::task::spawn_blocking(move || probe(&config))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.status, 200);
+        let c = result.compression.expect("compression info");
+        assert_eq!(c.encoding.as_deref(), Some("gzip"));
+        assert_eq!(c.compressed_bytes, wire);
+        assert_eq!(c.decompressed_bytes, Some(13_000));
+        let ratio = c.ratio.unwrap();
+        assert!((ratio - 13_000.0 / wire as f64).abs() < 1e-9);
+        assert!(c.error.is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_probe_reports_uncompressed_body() {
+        use crate::testutil::{response, spawn_test_http_server};
+        let addr = spawn_test_http_server(|_| response(200, "OK", &[], b"plain")).await;
+        let config = HttpConfig {
+            url: format!("http://{addr}/"),
+            compression: true,
+            ..Default::default()
+        };
+        let result = tokio::task::spawn_blocking(move || probe(&config))
+            .await
+            .unwrap()
+            .unwrap();
+        let c = result.compression.expect("compression info");
+        assert_eq!(c.encoding, None);
+        assert_eq!(c.compressed_bytes, 5);
+        assert_eq!(c.ratio, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_warmup_reuses_connection_for_measured_request() {
+        use crate::testutil::{response, spawn_test_http_server};
+        let addr = spawn_test_http_server(|_| response(200, "OK", &[], b"warm")).await;
+        let config = HttpConfig {
+            url: format!("http://{addr}/"),
+            warmup: true,
+            ..Default::default()
+        };
+        let result = tokio::task::spawn_blocking(move || probe(&config))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.status, 200);
+        let warmup = result.warmup.expect("warmup timing");
+        assert!(warmup.connection_reused);
+        assert!(warmup.cold.connect_ms > 0.0);
+        assert_eq!(result.timing.dns_ms, 0.0);
+        assert_eq!(result.timing.connect_ms, 0.0);
+        assert!(result.timing.connect_ms < warmup.cold.connect_ms);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_warmup_reconnects_when_server_closes() {
+        use crate::testutil::{response, spawn_test_http_server};
+        let addr =
+            spawn_test_http_server(|_| response(200, "OK", &[("Connection", "close")], b"bye"))
+                .await;
+        let config = HttpConfig {
+            url: format!("http://{addr}/"),
+            warmup: true,
+            ..Default::default()
+        };
+        let result = tokio::task::spawn_blocking(move || probe(&config))
+            .await
+            .unwrap()
+            .unwrap();
+        let warmup = result.warmup.expect("warmup timing");
+        assert!(!warmup.connection_reused);
+        assert!(result.timing.connect_ms > 0.0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_probe_too_many_redirects() {
+        use crate::testutil::{response, spawn_test_http_server};
+        let addr =
+            spawn_test_http_server(|_| response(302, "Found", &[("Location", "/loop")], b"")).await;
+        let config = HttpConfig {
+            url: format!("http://{addr}/loop"),
+            follow_redirects: true,
+            max_redirects: 3,
+            ..Default::default()
+        };
+        let err = tokio::task::spawn_blocking(move || probe(&config))
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err, "Too many redirects (max 3)");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_probe_max_ttfb() {
+        use crate::testutil::{response, spawn_test_http_server};
+        let addr = spawn_test_http_server(|req| {
+            if req.path == "/slow" {
+                std::thread::sleep(Duration::from_millis(200));
+            }
+            response(200, "OK", &[], b"ok")
+        })
+        .await;
+        let check = |path: &str, max_ms: u64| {
+            let config = HttpConfig {
+                url: format!("http://{addr}{path}"),
+                max_ttfb: Some(Duration::from_millis(max_ms)),
+                ..Default::default()
+            };
+            tokio::task::spawn_blocking(move || probe(&config))
+        };
+        let slow = check("/slow", 100).await.unwrap().unwrap();
+        assert_eq!(slow.max_ttfb_ms, Some(100.0));
+        assert!(slow.ttfb_exceeded());
+        let fast = check("/fast", 1000).await.unwrap().unwrap();
+        assert!(!fast.ttfb_exceeded());
+    }
+
+    /// TLS server on 127.0.0.1 using the test CA-signed certificate, offering
+    /// only `h2` or `http/1.1`; sessions persist across its `connections`. Over
+    /// HTTP/2 it answers every request on a connection, redirecting
+    /// `/redirect` to `/h2` and sending "hello" otherwise. Over HTTP/1.1 it
+    /// answers "ok" until a request asks it to close the connection.
+    fn spawn_alpn_server(h2: bool, connections: usize) -> u16 {
+        use rustls::pki_types::pem::PemObject;
+        use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+        let testdata = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata");
+        let certs = CertificateDer::pem_file_iter(testdata.join("test-server.pem"))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let key = PrivateKeyDer::from_pem_file(testdata.join("test-server.key")).unwrap();
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let mut config = rustls::ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .unwrap();
+        config.alpn_protocols = if h2 {
+            vec![b"h2".to_vec()]
+        } else {
+            vec![b"http/1.1".to_vec()]
+        };
+        let config = Arc::new(config);
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for _ in 0..connections {
+                let (sock, _) = listener.accept().unwrap();
+                if h2 {
+                    serve_h2(config.clone(), sock);
+                    continue;
+                }
+                let conn = rustls::ServerConnection::new(config.clone()).unwrap();
+                let mut tls = rustls::StreamOwned::new(conn, sock);
+                loop {
+                    let mut buf = Vec::new();
+                    let mut byte = [0u8; 1];
+                    while !buf.ends_with(b"\r\n\r\n") && matches!(tls.read(&mut byte), Ok(1)) {
+                        buf.push(byte[0]);
+                    }
+                    if !buf.ends_with(b"\r\n\r\n") {
+                        break;
+                    }
+                    tls.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+                        .unwrap();
+                    let head = String::from_utf8_lossy(&buf).to_ascii_lowercase();
+                    if head.contains("connection: close") {
+                        tls.conn.send_close_notify();
+                        let _ = tls.flush();
+                        break;
+                    }
+                    let _ = tls.flush();
+                }
+            }
+        });
+        port
+    }
+
+    /// Serve HTTP/2 requests on `sock` until the client closes it.
+    fn serve_h2(config: Arc<rustls::ServerConfig>, sock: std::net::TcpStream) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            sock.set_nonblocking(true).unwrap();
+            let sock = tokio::net::TcpStream::from_std(sock).unwrap();
+            let tls = tokio_rustls::TlsAcceptor::from(config)
+                .accept(sock)
+                .await
+                .unwrap();
+            let mut conn = h2::server::handshake(tls).await.unwrap();
+            while let Some(Ok((request, mut respond))) = conn.accept().await {
+                let response = if request.uri().path() == "/redirect" {
+                    http::Response::builder()
+                        .status(302)
+                        .header("location", "/h2")
+                        .body(())
+                } else {
+                    http::Response::builder()
+                        .status(200)
+                        .header("server", "h2test")
+                        .header("etag", "\"~{h2}~\"")
+                        .body(())
+                };
+                let mut send = respond.send_response(response.unwrap(), false).unwrap();
+                send.send_data(bytes::Bytes::from_static(b"hello"), true)
+                    .unwrap();
+            }
+        });
+    }
+
+    fn http2_config(port: u16) -> HttpConfig {
+        HttpConfig {
+            url: format!("https://127.0.0.1:{port}/h2"),
+            http2: true,
+            ca_file: Some(
+                std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/test-ca.pem"),
+            ),
+            timeout: Duration::from_secs(5),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_http2_probe_negotiates_h2() {
+        let port = spawn_alpn_server(true, 1);
+        let result = probe(&http2_config(port)).unwrap();
+        assert_eq!(result.protocol.as_deref(), Some("h2"));
+        assert_eq!(result.status, 200);
+        assert_eq!(result.body_size, 5);
+        assert_eq!(result.headers.get("server").unwrap(), "h2test");
+        // Punctuation with long Huffman codes decodes too
+        assert_eq!(result.headers.get("etag").unwrap(), "\"~{h2}~\"");
+        assert!(result.tls);
+        assert!(result.timing.tls_ms.is_some());
+    }
+
+    #[test]
+    fn test_http2_probe_follows_redirects() {
+        let port = spawn_alpn_server(true, 2);
+        let config = HttpConfig {
+            url: format!("https://127.0.0.1:{port}/redirect"),
+            follow_redirects: true,
+            ..http2_config(port)
+        };
+        let result = probe(&config).unwrap();
+        assert_eq!(result.status, 200);
+        assert_eq!(result.redirects.len(), 1);
+        assert_eq!(result.redirects[0].status, 302);
+        assert_eq!(result.final_url, format!("https://127.0.0.1:{port}/h2"));
+        assert_eq!(result.protocol.as_deref(), Some("h2"));
+    }
+
+    #[test]
+    fn test_http2_probe_reports_http11_fallback() {
+        let port = spawn_alpn_server(false, 1);
+        let result = probe(&http2_config(port)).unwrap();
+        assert_eq!(result.protocol.as_deref(), Some("http/1.1"));
+        assert_eq!(result.status, 200);
+        assert_eq!(result.status_text, "OK");
+        assert_eq!(result.body_size, 2);
+    }
+
+    #[test]
+    fn test_tls_info_reports_session_resumption() {
+        // A private copy of the CA keeps this test's cached session store to itself
+        let ca_file =
+            std::env::temp_dir().join(format!("netscout-resume-{}.pem", std::process::id()));
+        std::fs::copy(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/test-ca.pem"),
+            &ca_file,
+        )
+        .unwrap();
+        let port = spawn_alpn_server(false, 2);
+        let config = HttpConfig {
+            tls_info: true,
+            connection: ConnectionMode::KeepAlive,
+            ca_file: Some(ca_file.clone()),
+            ..http2_config(port)
+        };
+        let first = probe(&config).unwrap();
+        let second = probe(&config).unwrap();
+        std::fs::remove_file(&ca_file).ok();
+
+        let first = first.tls_info.unwrap();
+        assert!(!first.resumed);
+        assert_eq!(first.alpn.as_deref(), Some("http/1.1"));
+        assert_eq!(first.version, "TLSv1.3");
+        assert_ne!(first.cipher_suite, "unknown");
+        assert!(second.tls_info.unwrap().resumed);
+    }
+
+    #[test]
+    fn test_https_warmup_reuses_tls_connection() {
+        // The server accepts one connection, so the measured request must reuse it
+        let port = spawn_alpn_server(false, 1);
+        let config = HttpConfig {
+            url: format!("https://127.0.0.1:{port}/"),
+            http2: false,
+            warmup: true,
+            tls_info: true,
+            ..http2_config(port)
+        };
+        let result = probe(&config).unwrap();
+        assert_eq!(result.status, 200);
+        let warmup = result.warmup.expect("warmup timing");
+        assert!(warmup.connection_reused);
+        assert!(warmup.cold.tls_ms.unwrap() > 0.0);
+        assert_eq!(result.timing.connect_ms, 0.0);
+        assert_eq!(result.timing.tls_ms, Some(0.0));
+        assert!(result.tls_info.is_some());
+    }
+
+    #[test]
+    fn test_http2_warmup_reuses_connection() {
+        let port = spawn_alpn_server(true, 1);
+        let config = HttpConfig {
+            warmup: true,
+            ..http2_config(port)
+        };
+        let result = probe(&config).unwrap();
+        assert_eq!(result.status, 200);
+        assert_eq!(result.protocol.as_deref(), Some("h2"));
+        let warmup = result.warmup.expect("warmup timing");
+        assert!(warmup.connection_reused);
+        assert!(warmup.cold.tls_ms.unwrap() > 0.0);
+        assert_eq!(result.timing.tls_ms, Some(0.0));
+    }
+
+    #[test]
+    fn test_http2_requires_https() {
+        let config = HttpConfig {
+            url: "http://127.0.0.1:1/".to_string(),
+            http2: true,
+            ..Default::default()
+        };
+        assert!(probe(&config).unwrap_err().contains("https://"));
+    }
+
+    #[test]
+    fn test_https_probe() {
+        let port = spawn_alpn_server(false, 1);
+        let config = HttpConfig {
+            url: format!("https://127.0.0.1:{port}/"),
+            http2: false,
+            tls_info: true,
+            ..http2_config(port)
+        };
+        let result = probe(&config).unwrap();
+        assert!((200..400).contains(&result.status));
+        assert!(result.tls);
+        assert!(result.timing.tls_ms.is_some());
+        assert_eq!(result.body_size, 2);
+        // Plain HTTPS probes offer only http/1.1
+        assert_eq!(result.tls_info.unwrap().alpn.as_deref(), Some("http/1.1"));
+        assert!(result.protocol.is_none());
     }
 }
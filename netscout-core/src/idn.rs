@@ -0,0 +1,338 @@
+use serde::{Deserialize, Serialize};
+
+// Punycode parameters (RFC 3492).
+const BASE: u32 = 36;
+const TMIN: u32 = 1;
+const TMAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 128;
+
+/// Unicode and ASCII forms of a domain name, with homograph analysis.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdnInfo {
+    /// Display form with `xn--` labels decoded.
+    pub unicode: String,
+    /// Wire form with non-ASCII labels punycode-encoded.
+    pub ascii: String,
+    /// True if any label is internationalized.
+    pub is_idn: bool,
+    /// True if any label mixes letters from different scripts.
+    pub mixed_script: bool,
+    pub warning: Option<String>,
+}
+
+/// Writing system of a character, as far as homograph checks are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+    Hebrew,
+    Arabic,
+    Thai,
+    /// Han, kana and Hangul, which legitimately appear together.
+    Cjk,
+    Other,
+}
+
+impl Script {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Latin => "Latin",
+            Self::Greek => "Greek",
+            Self::Cyrillic => "Cyrillic",
+            Self::Armenian => "Armenian",
+            Self::Hebrew => "Hebrew",
+            Self::Arabic => "Arabic",
+            Self::Thai => "Thai",
+            Self::Cjk => "CJK",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Script of a letter; `None` for digits, hyphens and other script-neutral characters.
+fn script_of(c: char) -> Option<Script> {
+    let cp = c as u32;
+    let script = match cp {
+        _ if c.is_ascii_alphabetic() => Script::Latin,
+        _ if c.is_ascii() => return None,
+        0x00C0..=0x024F | 0x1E00..=0x1EFF => Script::Latin,
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => Script::Greek,
+        0x0400..=0x052F => Script::Cyrillic,
+        0x0530..=0x058F => Script::Armenian,
+        0x0590..=0x05FF => Script::Hebrew,
+        0x0600..=0x06FF | 0x0750..=0x077F => Script::Arabic,
+        0x0E00..=0x0E7F => Script::Thai,
+        0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF => Script::Cjk,
+        _ if c.is_alphabetic() => Script::Other,
+        _ => return None,
+    };
+    Some(script)
+}
+
+/// Distinct scripts used by the letters of a label, in order of appearance.
+fn label_scripts(label: &str) -> Vec<Script> {
+    let mut scripts = Vec::new();
+    for script in label.chars().filter_map(script_of) {
+        if !scripts.contains(&script) {
+            scripts.push(script);
+        }
+    }
+    scripts
+}
+
+fn adapt(mut delta: u32, num_points: u32, first_time: bool) -> u32 {
+    delta /= if first_time { DAMP } else { 2 };
+    delta += delta / num_points;
+    let mut k = 0;
+    while delta > ((BASE - TMIN) * TMAX) / 2 {
+        delta /= BASE - TMIN;
+        k += BASE;
+    }
+    k + (BASE - TMIN + 1) * delta / (delta + SKEW)
+}
+
+fn threshold(k: u32, bias: u32) -> u32 {
+    if k <= bias {
+        TMIN
+    } else if k >= bias + TMAX {
+        TMAX
+    } else {
+        k - bias
+    }
+}
+
+fn encode_digit(d: u32) -> char {
+    let b = if d < 26 {
+        b'a' + d as u8
+    } else {
+        b'0' + (d - 26) as u8
+    };
+    b as char
+}
+
+fn decode_digit(b: u8) -> Option<u32> {
+    match b {
+        b'a'..=b'z' => Some((b - b'a') as u32),
+        b'A'..=b'Z' => Some((b - b'A') as u32),
+        b'0'..=b'9' => Some((b - b'0') as u32 + 26),
+        _ => None,
+    }
+}
+
+/// Encode a single label with punycode (without the `xn--` prefix).
+pub fn punycode_encode(input: &str) -> Option<String> {
+    let code_points: Vec<u32> = input.chars().map(|c| c as u32).collect();
+    let mut out: String = input.chars().filter(|c| c.is_ascii()).collect();
+    let basic = out.len() as u32;
+    let mut handled = basic;
+    if basic > 0 {
+        out.push('-');
+    }
+
+    let mut n = INITIAL_N;
+    let mut delta: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    while (handled as usize) < code_points.len() {
+        let m = code_points.iter().copied().filter(|&c| c >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(handled + 1)?)?;
+        n = m;
+        for &c in &code_points {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = threshold(k, bias);
+                    if q < t {
+                        break;
+                    }
+                    out.push(encode_digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                out.push(encode_digit(q));
+                bias = adapt(delta, handled + 1, handled == basic);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta += 1;
+        n += 1;
+    }
+    Some(out)
+}
+
+/// Decode a single punycode label (without the `xn--` prefix).
+pub fn punycode_decode(input: &str) -> Option<String> {
+    let (basic, extended) = match input.rfind('-') {
+        Some(i) => (&input[..i], &input[i + 1..]),
+        None => ("", input),
+    };
+    if !basic.is_ascii() {
+        return None;
+    }
+    let mut out: Vec<u32> = basic.chars().map(|c| c as u32).collect();
+
+    let mut n = INITIAL_N;
+    let mut i: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut bytes = extended.bytes().peekable();
+    while bytes.peek().is_some() {
+        let old_i = i;
+        let mut w: u32 = 1;
+        let mut k = BASE;
+        loop {
+            let digit = decode_digit(bytes.next()?)?;
+            i = i.checked_add(digit.checked_mul(w)?)?;
+            let t = threshold(k, bias);
+            if digit < t {
+                break;
+            }
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+        let len = out.len() as u32 + 1;
+        bias = adapt(i - old_i, len, old_i == 0);
+        n = n.checked_add(i / len)?;
+        i %= len;
+        out.insert(i as usize, n);
+        i += 1;
+    }
+    out.into_iter().map(char::from_u32).collect()
+}
+
+/// Convert a domain to its ASCII (`xn--`) form.
+pub fn to_ascii(domain: &str) -> String {
+    domain
+        .split('.')
+        .map(|label| {
+            if label.is_ascii() {
+                label.to_string()
+            } else {
+                let lower = label.to_lowercase();
+                punycode_encode(&lower)
+                    .map(|p| format!("xn--{p}"))
+                    .unwrap_or(lower)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Convert a domain to its Unicode form, decoding any `xn--` labels.
+pub fn to_unicode(domain: &str) -> String {
+    domain
+        .split('.')
+        .map(|label| {
+            label
+                .get(..4)
+                .filter(|p| p.eq_ignore_ascii_case("xn--"))
+                .and_then(|_| punycode_decode(&label[4..]))
+                .unwrap_or_else(|| label.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Analyze a domain for internationalized labels and mixed-script homographs.
+pub fn analyze_idn(domain: &str) -> IdnInfo {
+    let unicode = to_unicode(domain);
+    let ascii = to_ascii(&unicode);
+    let is_idn = !unicode.is_ascii();
+
+    let mut warning = None;
+    for label in unicode.split('.') {
+        let scripts = label_scripts(label);
+        if scripts.len() > 1 {
+            let names: Vec<&str> = scripts.iter().map(|s| s.name()).collect();
+            warning = Some(format!(
+                "label '{label}' mixes {} scripts (possible homograph)",
+                names.join(" and ")
+            ));
+            break;
+        }
+    }
+
+    IdnInfo {
+        unicode,
+        ascii,
+        is_idn,
+        mixed_script: warning.is_some(),
+        warning,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_punycode_encode_rfc_samples() {
+        assert_eq!(punycode_encode("bücher").unwrap(), "bcher-kva");
+        assert_eq!(punycode_encode("münchen").unwrap(), "mnchen-3ya");
+        assert_eq!(punycode_encode("例え").unwrap(), "r8jz45g");
+        assert_eq!(punycode_encode("abc").unwrap(), "abc-");
+    }
+
+    #[test]
+    fn test_punycode_decode_roundtrip() {
+        for s in ["bücher", "münchen", "例え", "пример", "ελληνικά"] {
+            let encoded = punycode_encode(s).unwrap();
+            assert_eq!(punycode_decode(&encoded).unwrap(), s);
+        }
+        assert!(punycode_decode("!!!").is_none());
+    }
+
+    #[test]
+    fn test_to_ascii_and_unicode() {
+        assert_eq!(to_ascii("bücher.example"), "xn--bcher-kva.example");
+        assert_eq!(to_ascii("Bücher.example"), "xn--bcher-kva.example");
+        assert_eq!(to_unicode("xn--bcher-kva.example"), "bücher.example");
+        assert_eq!(to_unicode("XN--bcher-kva.example"), "bücher.example");
+        assert_eq!(to_unicode("example.com"), "example.com");
+    }
+
+    #[test]
+    fn test_analyze_idn_ascii_no_warning() {
+        let info = analyze_idn("example.com");
+        assert!(!info.is_idn);
+        assert!(!info.mixed_script);
+        assert!(info.warning.is_none());
+        assert_eq!(info.unicode, "example.com");
+        assert_eq!(info.ascii, "example.com");
+    }
+
+    #[test]
+    fn test_analyze_idn_legitimate_idn() {
+        let info = analyze_idn("münchen.de");
+        assert!(info.is_idn);
+        assert_eq!(info.unicode, "münchen.de");
+        assert_eq!(info.ascii, "xn--mnchen-3ya.de");
+        assert!(info.warning.is_none());
+
+        // Starting from the ASCII form gives the same result
+        assert_eq!(analyze_idn("xn--mnchen-3ya.de"), info);
+
+        // Japanese mixes Han and kana legitimately
+        assert!(analyze_idn("例え.jp").warning.is_none());
+    }
+
+    #[test]
+    fn test_analyze_idn_mixed_script_warning() {
+        // "pаypal" with a Cyrillic 'а' (U+0430)
+        let info = analyze_idn("p\u{0430}ypal.com");
+        assert!(info.is_idn);
+        assert!(info.mixed_script);
+        let warning = info.warning.unwrap();
+        assert!(warning.contains("Latin"));
+        assert!(warning.contains("Cyrillic"));
+        assert!(info.ascii.starts_with("xn--"));
+    }
+}
@@ -1,16 +1,34 @@
 pub mod error;
+pub mod addr;
+pub mod batch;
 pub mod cert;
 pub mod config;
+pub mod diff;
 pub mod dns;
+pub mod gzip;
+pub mod health;
 pub mod http;
+pub mod idn;
+pub mod locale;
 pub mod netif;
 pub mod output;
 pub mod ping;
 pub mod port;
+pub mod progress;
+pub mod prometheus;
+pub mod pubip;
+pub mod reach;
+pub mod report;
+pub mod runtime;
 pub mod scan;
 pub mod speed;
+pub mod stats;
+#[cfg(any(test, feature = "testutil"))]
+pub mod testutil;
+pub mod timestamp;
 pub mod trace;
 pub mod whois;
+pub mod x509;
 
 /// Output format for all commands.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
@@ -20,6 +38,10 @@ pub enum OutputFormat {
     Json,
     Table,
     Csv,
+    /// JSON Lines: one compact JSON object per record.
+    Ndjson,
+    /// Prometheus text exposition format (ping, HTTP and certificate results).
+    Prometheus,
 }
 
 impl OutputFormat {
@@ -30,6 +52,8 @@ impl OutputFormat {
             OutputFormat::Json,
             OutputFormat::Table,
             OutputFormat::Csv,
+            OutputFormat::Ndjson,
+            OutputFormat::Prometheus,
         ]
     }
 
@@ -40,6 +64,8 @@ impl OutputFormat {
             OutputFormat::Json => "json",
             OutputFormat::Table => "table",
             OutputFormat::Csv => "csv",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Prometheus => "prometheus",
         }
     }
 
@@ -50,13 +76,21 @@ impl OutputFormat {
             "json" | "j" => Some(OutputFormat::Json),
             "table" | "t" => Some(OutputFormat::Table),
             "csv" | "c" => Some(OutputFormat::Csv),
+            "ndjson" | "jsonl" => Some(OutputFormat::Ndjson),
+            "prometheus" | "prom" => Some(OutputFormat::Prometheus),
             _ => None,
         }
     }
 
     /// Check if the format produces structured data.
     pub fn is_structured(&self) -> bool {
-        matches!(self, OutputFormat::Json | OutputFormat::Csv)
+        matches!(
+            self,
+            OutputFormat::Json
+                | OutputFormat::Csv
+                | OutputFormat::Ndjson
+                | OutputFormat::Prometheus
+        )
     }
 
     /// Check if the format produces human-readable output.
@@ -89,6 +123,8 @@ mod tests {
         let _ = OutputFormat::Json;
         let _ = OutputFormat::Table;
         let _ = OutputFormat::Csv;
+        let _ = OutputFormat::Ndjson;
+        let _ = OutputFormat::Prometheus;
     }
 
     #[test]
@@ -129,6 +165,8 @@ mod tests {
         assert_eq!(format!("{:?}", OutputFormat::Json), "Json");
         assert_eq!(format!("{:?}", OutputFormat::Table), "Table");
         assert_eq!(format!("{:?}", OutputFormat::Csv), "Csv");
+        assert_eq!(format!("{:?}", OutputFormat::Ndjson), "Ndjson");
+        assert_eq!(format!("{:?}", OutputFormat::Prometheus), "Prometheus");
     }
 
     #[test]
@@ -137,6 +175,8 @@ mod tests {
         assert_eq!(format!("{}", OutputFormat::Json), "json");
         assert_eq!(format!("{}", OutputFormat::Table), "table");
         assert_eq!(format!("{}", OutputFormat::Csv), "csv");
+        assert_eq!(format!("{}", OutputFormat::Ndjson), "ndjson");
+        assert_eq!(format!("{}", OutputFormat::Prometheus), "prometheus");
     }
 
     #[test]
@@ -145,6 +185,8 @@ mod tests {
         assert_eq!(OutputFormat::Json.as_str(), "json");
         assert_eq!(OutputFormat::Table.as_str(), "table");
         assert_eq!(OutputFormat::Csv.as_str(), "csv");
+        assert_eq!(OutputFormat::Ndjson.as_str(), "ndjson");
+        assert_eq!(OutputFormat::Prometheus.as_str(), "prometheus");
     }
 
     #[test]
@@ -153,6 +195,13 @@ mod tests {
         assert_eq!(OutputFormat::parse("json"), Some(OutputFormat::Json));
         assert_eq!(OutputFormat::parse("table"), Some(OutputFormat::Table));
         assert_eq!(OutputFormat::parse("csv"), Some(OutputFormat::Csv));
+        assert_eq!(OutputFormat::parse("ndjson"), Some(OutputFormat::Ndjson));
+        assert_eq!(OutputFormat::parse("jsonl"), Some(OutputFormat::Ndjson));
+        assert_eq!(
+            OutputFormat::parse("prometheus"),
+            Some(OutputFormat::Prometheus)
+        );
+        assert_eq!(OutputFormat::parse("prom"), Some(OutputFormat::Prometheus));
     }
 
     #[test]
@@ -182,11 +231,13 @@ mod tests {
     #[test]
     fn test_output_format_all() {
         let all = OutputFormat::all();
-        assert_eq!(all.len(), 4);
+        assert_eq!(all.len(), 6);
         assert!(all.contains(&OutputFormat::Human));
         assert!(all.contains(&OutputFormat::Json));
         assert!(all.contains(&OutputFormat::Table));
         assert!(all.contains(&OutputFormat::Csv));
+        assert!(all.contains(&OutputFormat::Ndjson));
+        assert!(all.contains(&OutputFormat::Prometheus));
     }
 
     #[test]
@@ -204,6 +255,8 @@ mod tests {
         assert!(OutputFormat::Json.is_structured());
         assert!(!OutputFormat::Table.is_structured());
         assert!(OutputFormat::Csv.is_structured());
+        assert!(OutputFormat::Ndjson.is_structured());
+        assert!(OutputFormat::Prometheus.is_structured());
     }
 
     #[test]
@@ -212,6 +265,8 @@ mod tests {
         assert!(!OutputFormat::Json.is_human_readable());
         assert!(OutputFormat::Table.is_human_readable());
         assert!(!OutputFormat::Csv.is_human_readable());
+        assert!(!OutputFormat::Ndjson.is_human_readable());
+        assert!(!OutputFormat::Prometheus.is_human_readable());
     }
 
     #[test]
@@ -227,6 +282,8 @@ mod tests {
             OutputFormat::Json => {} // Expected
             OutputFormat::Table => panic!("Should not match Table"),
             OutputFormat::Csv => panic!("Should not match Csv"),
+            OutputFormat::Ndjson => panic!("Should not match Ndjson"),
+            OutputFormat::Prometheus => panic!("Should not match Prometheus"),
         }
     }
 
@@ -248,11 +305,13 @@ mod tests {
         set.insert(OutputFormat::Json);
         set.insert(OutputFormat::Table);
         set.insert(OutputFormat::Csv);
-        assert_eq!(set.len(), 4);
+        set.insert(OutputFormat::Ndjson);
+        set.insert(OutputFormat::Prometheus);
+        assert_eq!(set.len(), 6);
 
         // Test that inserting the same format doesn't increase size
         set.insert(OutputFormat::Human);
-        assert_eq!(set.len(), 4);
+        assert_eq!(set.len(), 6);
     }
 
     #[test]
@@ -274,6 +333,11 @@ mod tests {
             ("c", Some(OutputFormat::Csv)),
             ("CSV", Some(OutputFormat::Csv)),
             ("C", Some(OutputFormat::Csv)),
+            ("ndjson", Some(OutputFormat::Ndjson)),
+            ("jsonl", Some(OutputFormat::Ndjson)),
+            ("JSONL", Some(OutputFormat::Ndjson)),
+            ("prometheus", Some(OutputFormat::Prometheus)),
+            ("prom", Some(OutputFormat::Prometheus)),
             ("invalid", None),
             ("xml", None),
             ("yaml", None),
@@ -298,7 +362,7 @@ mod tests {
         let structured_count = all.iter().filter(|f| f.is_structured()).count();
         let human_readable_count = all.iter().filter(|f| f.is_human_readable()).count();
 
-        assert_eq!(structured_count, 2); // Json and Csv
+        assert_eq!(structured_count, 4); // Json, Csv, Ndjson and Prometheus
         assert_eq!(human_readable_count, 2); // Human and Table
 
         // Each format should be either structured or human-readable (but not necessarily both)
@@ -324,6 +388,10 @@ mod tests {
         assert_eq!(OutputFormat::from_str("human").unwrap(), OutputFormat::Human);
         assert_eq!(OutputFormat::from_str("table").unwrap(), OutputFormat::Table);
         assert_eq!(OutputFormat::from_str("csv").unwrap(), OutputFormat::Csv);
+        assert_eq!(
+            OutputFormat::from_str("jsonl").unwrap(),
+            OutputFormat::Ndjson
+        );
         assert!(OutputFormat::from_str("xml").is_err());
         assert!(OutputFormat::from_str("").is_err());
     }
@@ -0,0 +1,127 @@
+use num_format::{Locale, ToFormattedString};
+use std::cell::Cell;
+
+/// Digit grouping, decimal separator and minus sign used for human-readable
+/// numbers, taken from the CLDR data in `num-format`.
+///
+/// Only human and table output are localized; JSON and CSV always use the
+/// plain machine format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NumberLocale(Option<Locale>);
+
+impl NumberLocale {
+    /// Plain machine format: no grouping, `.` decimal point.
+    pub const C: Self = Self(None);
+
+    /// Parse a locale name such as `en_US`, `de-DE` or `fr_FR.UTF-8`.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let base = name.split(['.', '@']).next().unwrap_or("");
+        if base.eq_ignore_ascii_case("c") || base.eq_ignore_ascii_case("posix") {
+            return Ok(Self::C);
+        }
+        let mut parts = base.split(['_', '-']);
+        let lang = parts.next().unwrap_or("").to_lowercase();
+        let region = parts.next().unwrap_or("").to_uppercase();
+        // CLDR names a language's main region by the language alone, e.g. `de` for `de_DE`
+        Locale::from_name(format!("{lang}-{region}"))
+            .or_else(|_| Locale::from_name(&lang))
+            .map(|locale| Self(Some(locale)))
+            .map_err(|_| format!("Unsupported locale: {name}"))
+    }
+
+    /// Locale from `LC_ALL`, `LC_NUMERIC` or `LANG`, falling back to plain format.
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|v| !v.is_empty())
+            .and_then(|v| Self::parse(&v).ok())
+            .unwrap_or(Self::C)
+    }
+
+    /// Apply this locale's separators to a plain formatted number like `-1234.50`.
+    pub fn localize(&self, plain: &str) -> String {
+        let Some(locale) = self.0 else {
+            return plain.to_string();
+        };
+        let (minus, digits) = match plain.strip_prefix('-') {
+            Some(rest) => (locale.minus_sign(), rest),
+            None => ("", plain),
+        };
+        let (int, frac) = match digits.split_once('.') {
+            Some((i, f)) => (i, Some(f)),
+            None => (digits, None),
+        };
+        // Leave `inf`, `NaN` and anything else that isn't plain digits alone
+        let Ok(int) = int.parse::<u128>() else {
+            return plain.to_string();
+        };
+        let mut out = format!("{minus}{}", int.to_formatted_string(&locale));
+        if let Some(f) = frac {
+            out.push_str(locale.decimal());
+            out.push_str(f);
+        }
+        out
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<NumberLocale> = const { Cell::new(NumberLocale::C) };
+}
+
+/// Set the number locale for human output rendered on this thread.
+pub fn set_number_locale(locale: NumberLocale) {
+    CURRENT.with(|c| c.set(locale));
+}
+
+/// The number locale for this thread.
+pub fn number_locale() -> NumberLocale {
+    CURRENT.with(|c| c.get())
+}
+
+/// Format a number with `decimals` places using the current locale.
+pub fn format_number(value: f64, decimals: usize) -> String {
+    number_locale().localize(&format!("{value:.decimals$}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locales() {
+        assert_eq!(NumberLocale::parse("C").unwrap(), NumberLocale::C);
+        assert_eq!(NumberLocale::parse("POSIX").unwrap(), NumberLocale::C);
+        let us = NumberLocale::parse("en_US.UTF-8").unwrap();
+        assert_eq!(us.localize("1234.5"), "1,234.5");
+        let de = NumberLocale::parse("de-DE").unwrap();
+        assert_eq!(de.localize("1234.5"), "1.234,5");
+        let ch = NumberLocale::parse("de_CH").unwrap();
+        assert_eq!(ch.localize("1234.5"), "1\u{2019}234.5");
+        assert!(NumberLocale::parse("xx_YY").is_err());
+        assert!(NumberLocale::parse("").is_err());
+    }
+
+    #[test]
+    fn test_localize() {
+        let us = NumberLocale::parse("en_US").unwrap();
+        let de = NumberLocale::parse("de_DE").unwrap();
+        assert_eq!(us.localize("1234567"), "1,234,567");
+        assert_eq!(de.localize("1234567"), "1.234.567");
+        assert_eq!(de.localize("-1234.50"), "-1.234,50");
+        assert_eq!(us.localize("123"), "123");
+        assert_eq!(us.localize("inf"), "inf");
+        assert_eq!(NumberLocale::C.localize("1234567.5"), "1234567.5");
+        // Indian grouping: the first group has three digits, the rest two
+        let hi = NumberLocale::parse("hi_IN").unwrap();
+        assert_eq!(hi.localize("12345678"), "1,23,45,678");
+    }
+
+    #[test]
+    fn test_format_number_uses_thread_locale() {
+        assert_eq!(format_number(1234.5, 1), "1234.5");
+        set_number_locale(NumberLocale::parse("fr_FR").unwrap());
+        assert_eq!(format_number(1234.5, 1), "1\u{202f}234,5");
+        set_number_locale(NumberLocale::C);
+    }
+}
@@ -7,7 +7,7 @@ use serde::Serialize;
 use std::collections::BTreeMap;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
-use crate::output::HumanReadable;
+use crate::output::{csv_row, HumanReadable};
 use colored::Colorize;
 
 /// Information about a single network interface.
@@ -19,6 +19,10 @@ pub struct NetworkInterface {
     pub is_loopback: bool,
     pub addresses: Vec<InterfaceAddress>,
     pub mtu: Option<u32>,
+    /// Hardware address, e.g. `00:1a:11:22:33:44`; `None` for loopback and tunnels.
+    pub mac: Option<String>,
+    /// Manufacturer registered for the MAC's OUI prefix, if it is a known one.
+    pub vendor: Option<String>,
 }
 
 /// An address bound to an interface.
@@ -35,10 +39,13 @@ pub struct NetifResult {
     pub interfaces: Vec<NetworkInterface>,
     pub total: usize,
     pub up_count: usize,
+    /// When the listing started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
 }
 
 /// Enumerate all network interfaces on the system.
 pub fn list_interfaces() -> Result<NetifResult, String> {
+    let timestamp = crate::timestamp::now();
     let raw = gather_interfaces().map_err(|e| format!("Failed to enumerate interfaces: {e}"))?;
 
     let up_count = raw.iter().filter(|i| i.is_up).count();
@@ -48,9 +55,249 @@ pub fn list_interfaces() -> Result<NetifResult, String> {
         interfaces: raw,
         total,
         up_count,
+        timestamp,
     })
 }
 
+/// A default route: the next-hop router and the interface it is reached through.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GatewayInfo {
+    pub interface: String,
+    pub gateway: IpAddr,
+}
+
+/// Result of listing default routes.
+#[derive(Debug, Clone, Serialize)]
+pub struct GatewayResult {
+    /// IPv4 routes first, then IPv6, each in routing table order.
+    pub gateways: Vec<GatewayInfo>,
+    /// When the lookup started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
+}
+
+/// The preferred default gateway: the first IPv4 one, else the first IPv6 one.
+pub fn default_gateway() -> Result<GatewayInfo, String> {
+    default_gateways()?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "No default route".to_string())
+}
+
+/// List the default routes, timestamped for output.
+pub fn list_gateways() -> Result<GatewayResult, String> {
+    let timestamp = crate::timestamp::now();
+    Ok(GatewayResult {
+        gateways: default_gateways()?,
+        timestamp,
+    })
+}
+
+/// All IPv4 and IPv6 default routes, from `/proc/net/route` and `/proc/net/ipv6_route`.
+#[cfg(target_os = "linux")]
+pub fn default_gateways() -> Result<Vec<GatewayInfo>, String> {
+    let table = std::fs::read_to_string("/proc/net/route")
+        .map_err(|e| format!("Cannot read routing table: {e}"))?;
+    let mut gateways = parse_route_table(&table);
+    // Missing when IPv6 is disabled
+    if let Ok(table) = std::fs::read_to_string("/proc/net/ipv6_route") {
+        gateways.extend(parse_ipv6_route_table(&table));
+    }
+    Ok(gateways)
+}
+
+/// All IPv4 and IPv6 default routes, from a `PF_ROUTE` routing table dump.
+#[cfg(target_os = "macos")]
+pub fn default_gateways() -> Result<Vec<GatewayInfo>, String> {
+    let mut mib = [
+        libc::CTL_NET,
+        libc::PF_ROUTE,
+        0,
+        0,
+        libc::NET_RT_FLAGS,
+        libc::RTF_GATEWAY,
+    ];
+    let mib = mib.as_mut_ptr();
+    let mut len = 0usize;
+    let sysctl = |buf: *mut libc::c_void, len: &mut usize| unsafe {
+        libc::sysctl(mib, 6, buf, len, std::ptr::null_mut(), 0)
+    };
+    if sysctl(std::ptr::null_mut(), &mut len) != 0 {
+        return Err(format!(
+            "Cannot read routing table: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    let mut buf = vec![0u8; len];
+    if sysctl(buf.as_mut_ptr().cast(), &mut len) != 0 {
+        return Err(format!(
+            "Cannot read routing table: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    buf.truncate(len);
+
+    let mut v4 = Vec::new();
+    let mut v6 = Vec::new();
+    let mut offset = 0;
+    while offset + std::mem::size_of::<libc::rt_msghdr>() <= buf.len() {
+        let hdr: libc::rt_msghdr =
+            unsafe { std::ptr::read_unaligned(buf[offset..].as_ptr().cast()) };
+        let msg_len = hdr.rtm_msglen as usize;
+        if msg_len == 0 || offset + msg_len > buf.len() {
+            break;
+        }
+        let msg = &buf[offset..offset + msg_len];
+        offset += msg_len;
+
+        // Socket addresses follow the header, one per bit set in rtm_addrs,
+        // each padded to a multiple of 4 bytes
+        let mut addrs: [Option<&[u8]>; libc::RTAX_MAX as usize] = Default::default();
+        let mut pos = std::mem::size_of::<libc::rt_msghdr>();
+        for (i, slot) in addrs.iter_mut().enumerate() {
+            if hdr.rtm_addrs & (1 << i) == 0 || pos >= msg.len() {
+                continue;
+            }
+            let sa_len = msg[pos] as usize;
+            *slot = msg.get(pos..pos + sa_len);
+            pos += if sa_len == 0 {
+                4
+            } else {
+                sa_len.div_ceil(4) * 4
+            };
+        }
+        let dst = addrs[libc::RTAX_DST as usize].and_then(sockaddr_ip);
+        let gateway = addrs[libc::RTAX_GATEWAY as usize].and_then(sockaddr_ip);
+        // A default route has an unspecified destination and an empty or zero netmask
+        let mask_is_zero =
+            addrs[libc::RTAX_NETMASK as usize].is_none_or(|m| m.iter().skip(2).all(|&b| b == 0));
+        let (Some(dst), Some(gateway)) = (dst, gateway) else {
+            continue;
+        };
+        if !dst.is_unspecified() || !mask_is_zero {
+            continue;
+        }
+        let mut name = [0 as libc::c_char; libc::IF_NAMESIZE];
+        let interface = unsafe {
+            if libc::if_indextoname(hdr.rtm_index as u32, name.as_mut_ptr()).is_null() {
+                continue;
+            }
+            std::ffi::CStr::from_ptr(name.as_ptr())
+                .to_string_lossy()
+                .to_string()
+        };
+        let info = GatewayInfo { interface, gateway };
+        if gateway.is_ipv4() {
+            v4.push(info);
+        } else {
+            v6.push(info);
+        }
+    }
+    v4.extend(v6);
+    Ok(v4)
+}
+
+/// The IP in a raw `sockaddr_in` / `sockaddr_in6` from a routing message.
+#[cfg(target_os = "macos")]
+fn sockaddr_ip(sa: &[u8]) -> Option<IpAddr> {
+    match sa.get(1).map(|&f| f as i32) {
+        Some(libc::AF_INET) => {
+            let octets: [u8; 4] = sa.get(4..8)?.try_into().ok()?;
+            Some(IpAddr::from(octets))
+        }
+        Some(libc::AF_INET6) => {
+            let octets: [u8; 16] = sa.get(8..24)?.try_into().ok()?;
+            Some(IpAddr::from(octets))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn default_gateways() -> Result<Vec<GatewayInfo>, String> {
+    Err("Default gateway lookup is not supported on this platform".to_string())
+}
+
+/// Find the default routes in the text of `/proc/net/route`.
+///
+/// Addresses there are hex in host (little-endian) byte order, e.g. `0101A8C0` is 192.168.1.1.
+#[cfg(any(target_os = "linux", test))]
+fn parse_route_table(table: &str) -> Vec<GatewayInfo> {
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 3 || fields[1] != "00000000" {
+                return None;
+            }
+            let gateway = u32::from_str_radix(fields[2], 16)
+                .ok()
+                .filter(|&g| g != 0)?;
+            Some(GatewayInfo {
+                interface: fields[0].to_string(),
+                gateway: IpAddr::V4(Ipv4Addr::from(gateway.to_le_bytes())),
+            })
+        })
+        .collect()
+}
+
+/// Find the default routes in the text of `/proc/net/ipv6_route`.
+///
+/// Each line is `dest prefix src src_prefix next_hop metric refcnt use flags iface`,
+/// addresses as 32 hex digits in network order; `::/0` with a next hop is a default route.
+#[cfg(any(target_os = "linux", test))]
+fn parse_ipv6_route_table(table: &str) -> Vec<GatewayInfo> {
+    table
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10
+                || fields[1] != "00"
+                || u128::from_str_radix(fields[0], 16) != Ok(0)
+            {
+                return None;
+            }
+            let next_hop = u128::from_str_radix(fields[4], 16)
+                .ok()
+                .filter(|&g| g != 0)?;
+            Some(GatewayInfo {
+                interface: fields[9].to_string(),
+                gateway: IpAddr::V6(Ipv6Addr::from(next_hop)),
+            })
+        })
+        .collect()
+}
+
+/// Nameservers listed in `/etc/resolv.conf`.
+pub fn system_resolvers() -> Result<Vec<String>, String> {
+    let conf = std::fs::read_to_string("/etc/resolv.conf")
+        .map_err(|e| format!("Cannot read /etc/resolv.conf: {e}"))?;
+    Ok(parse_resolv_conf(&conf))
+}
+
+/// The `nameserver` entries of a resolv.conf file, in order.
+fn parse_resolv_conf(conf: &str) -> Vec<String> {
+    conf.lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            (words.next() == Some("nameserver"))
+                .then(|| words.next())
+                .flatten()
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+/// Index of the interface called `name`, as used for IPv6 scope ids.
+#[cfg(unix)]
+pub fn interface_index(name: &str) -> Option<u32> {
+    let name = std::ffi::CString::new(name).ok()?;
+    match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+        0 => None,
+        index => Some(index),
+    }
+}
+
 #[cfg(unix)]
 fn gather_interfaces() -> Result<Vec<NetworkInterface>, std::io::Error> {
     use std::ffi::CStr;
@@ -83,6 +330,8 @@ fn gather_interfaces() -> Result<Vec<NetworkInterface>, std::io::Error> {
                 is_loopback,
                 addresses: Vec::new(),
                 mtu: None,
+                mac: None,
+                vendor: None,
             }
         });
 
@@ -118,6 +367,9 @@ fn gather_interfaces() -> Result<Vec<NetworkInterface>, std::io::Error> {
                     prefix_len,
                     scope: scope_for_v6(ip),
                 });
+            } else if let Some(mac) = unsafe { link_layer_addr(entry.ifa_addr) } {
+                iface.vendor = mac_vendor(&mac).map(str::to_string);
+                iface.mac = Some(mac);
             }
         }
 
@@ -147,6 +399,95 @@ fn gather_interfaces() -> Result<Vec<NetworkInterface>, std::io::Error> {
     Ok(map.into_values().collect())
 }
 
+/// The MAC address in an `AF_PACKET` (`sockaddr_ll`) entry from `getifaddrs`.
+///
+/// # Safety
+/// `addr` must point to a valid socket address.
+#[cfg(target_os = "linux")]
+unsafe fn link_layer_addr(addr: *const libc::sockaddr) -> Option<String> {
+    if (*addr).sa_family as i32 != libc::AF_PACKET {
+        return None;
+    }
+    let ll = &*(addr as *const libc::sockaddr_ll);
+    let len = (ll.sll_halen as usize).min(ll.sll_addr.len());
+    format_mac(&ll.sll_addr[..len])
+}
+
+/// The MAC address in an `AF_LINK` (`sockaddr_dl`) entry from `getifaddrs`.
+///
+/// # Safety
+/// `addr` must point to a valid socket address.
+#[cfg(target_os = "macos")]
+unsafe fn link_layer_addr(addr: *const libc::sockaddr) -> Option<String> {
+    if (*addr).sa_family as i32 != libc::AF_LINK {
+        return None;
+    }
+    let dl = &*(addr as *const libc::sockaddr_dl);
+    // LLADDR(): the address follows the interface name in sdl_data
+    let data = dl.sdl_data.as_ptr() as *const u8;
+    let bytes = std::slice::from_raw_parts(data.add(dl.sdl_nlen as usize), dl.sdl_alen as usize);
+    format_mac(bytes)
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+unsafe fn link_layer_addr(_addr: *const libc::sockaddr) -> Option<String> {
+    None
+}
+
+/// Format a 6-byte hardware address as `aa:bb:cc:dd:ee:ff`.
+///
+/// Anything else, including the all-zero address loopback reports, isn't usable.
+fn format_mac(bytes: &[u8]) -> Option<String> {
+    if bytes.len() != 6 || bytes.iter().all(|&b| b == 0) {
+        return None;
+    }
+    let octets: Vec<String> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    Some(octets.join(":"))
+}
+
+/// A few well-known OUI prefixes, mostly virtualisation platforms and common
+/// network or embedded hardware.
+const OUI_VENDORS: &[([u8; 3], &str)] = &[
+    ([0x00, 0x00, 0x0c], "Cisco"),
+    ([0x00, 0x03, 0x93], "Apple"),
+    ([0x00, 0x04, 0x4b], "NVIDIA"),
+    ([0x00, 0x05, 0x69], "VMware"),
+    ([0x00, 0x0c, 0x29], "VMware"),
+    ([0x00, 0x0d, 0xb9], "PC Engines"),
+    ([0x00, 0x14, 0x22], "Dell"),
+    ([0x00, 0x15, 0x5d], "Microsoft Hyper-V"),
+    ([0x00, 0x16, 0x3e], "Xen"),
+    ([0x00, 0x17, 0x88], "Philips Lighting"),
+    ([0x00, 0x1a, 0x11], "Google"),
+    ([0x00, 0x1b, 0x21], "Intel"),
+    ([0x00, 0x1c, 0x42], "Parallels"),
+    ([0x00, 0x25, 0x90], "Super Micro"),
+    ([0x00, 0x50, 0x56], "VMware"),
+    ([0x00, 0xe0, 0x4c], "Realtek"),
+    ([0x02, 0x42, 0xac], "Docker"),
+    ([0x08, 0x00, 0x27], "VirtualBox"),
+    ([0x18, 0xb4, 0x30], "Nest Labs"),
+    ([0x24, 0xa4, 0x3c], "Ubiquiti"),
+    ([0x44, 0x65, 0x0d], "Amazon"),
+    ([0x52, 0x54, 0x00], "QEMU/KVM"),
+    ([0xb8, 0x27, 0xeb], "Raspberry Pi"),
+    ([0xdc, 0xa6, 0x32], "Raspberry Pi"),
+    ([0xe4, 0x5f, 0x01], "Raspberry Pi"),
+    ([0xf0, 0x9f, 0xc2], "Ubiquiti"),
+];
+
+/// Vendor for a MAC address (`aa:bb:cc:...` or `aa-bb-cc-...`) from its OUI prefix.
+pub fn mac_vendor(mac: &str) -> Option<&'static str> {
+    let mut octets = mac
+        .split([':', '-'])
+        .map(|o| u8::from_str_radix(o, 16).ok());
+    let prefix = [octets.next()??, octets.next()??, octets.next()??];
+    OUI_VENDORS
+        .iter()
+        .find(|(oui, _)| *oui == prefix)
+        .map(|(_, vendor)| *vendor)
+}
+
 #[cfg(unix)]
 fn get_mtu_ioctl(name: &str) -> Result<u32, std::io::Error> {
     use std::os::unix::io::AsRawFd;
@@ -211,7 +552,15 @@ fn count_v6_prefix(mask: &[u8; 16]) -> u8 {
 
 impl HumanReadable for NetifResult {
     fn to_csv(&self) -> String {
-        let mut out = String::from("name,state,type,mtu,addresses\n");
+        let mut out = csv_row(&[
+            &"name",
+            &"state",
+            &"type",
+            &"mtu",
+            &"mac",
+            &"vendor",
+            &"addresses",
+        ]);
         for iface in &self.interfaces {
             let state = if iface.is_up { "UP" } else { "DOWN" };
             let kind = if iface.is_loopback { "lo" } else { "eth" };
@@ -224,14 +573,15 @@ impl HumanReadable for NetifResult {
                     format!("{}{}", a.ip, prefix)
                 })
                 .collect();
-            out.push_str(&format!(
-                "{},{},{},{},\"{}\"\n",
-                iface.name,
-                state,
-                kind,
-                mtu,
-                addrs.join(";")
-            ));
+            out.push_str(&csv_row(&[
+                &iface.name,
+                &state,
+                &kind,
+                &mtu,
+                &iface.mac.as_deref().unwrap_or_default(),
+                &iface.vendor.as_deref().unwrap_or_default(),
+                &addrs.join(";"),
+            ]));
         }
         out
     }
@@ -239,10 +589,10 @@ impl HumanReadable for NetifResult {
     fn to_table(&self) -> String {
         let mut out = format!("Interfaces: {} total, {} up\n\n", self.total, self.up_count,);
         out.push_str(&format!(
-            "{:<12} {:<6} {:<8} {:<6} {}\n",
-            "NAME", "STATE", "TYPE", "MTU", "ADDRESSES"
+            "{:<12} {:<6} {:<8} {:<6} {:<18} {}\n",
+            "NAME", "STATE", "TYPE", "MTU", "MAC", "ADDRESSES"
         ));
-        out.push_str(&format!("{}\n", "-".repeat(65)));
+        out.push_str(&format!("{}\n", "-".repeat(84)));
         for iface in &self.interfaces {
             let state = if iface.is_up { "UP" } else { "DOWN" };
             let kind = if iface.is_loopback { "lo" } else { "eth" };
@@ -264,8 +614,13 @@ impl HumanReadable for NetifResult {
                 addrs.join(", ")
             };
             out.push_str(&format!(
-                "{:<12} {:<6} {:<8} {:<6} {}\n",
-                iface.name, state, kind, mtu, addr_str,
+                "{:<12} {:<6} {:<8} {:<6} {:<18} {}\n",
+                iface.name,
+                state,
+                kind,
+                mtu,
+                iface.mac.as_deref().unwrap_or("-"),
+                addr_str,
             ));
         }
         out
@@ -296,6 +651,14 @@ impl HumanReadable for NetifResult {
                 status,
                 mtu_str,
             ));
+            if let Some(mac) = &iface.mac {
+                let vendor = iface
+                    .vendor
+                    .as_ref()
+                    .map(|v| format!(" ({v})"))
+                    .unwrap_or_default();
+                out.push_str(&format!("    ether {mac}{}\n", vendor.dimmed()));
+            }
             for addr in &iface.addresses {
                 let prefix = addr.prefix_len.map(|p| format!("/{p}")).unwrap_or_default();
                 out.push_str(&format!("    {}{} ({})\n", addr.ip, prefix, addr.scope,));
@@ -305,10 +668,127 @@ impl HumanReadable for NetifResult {
     }
 }
 
+impl HumanReadable for GatewayResult {
+    fn to_csv(&self) -> String {
+        let mut out = csv_row(&[&"family", &"gateway", &"interface"]);
+        for g in &self.gateways {
+            out.push_str(&csv_row(&[&family(g.gateway), &g.gateway, &g.interface]));
+        }
+        out
+    }
+
+    fn to_table(&self) -> String {
+        let mut out = format!("{:<8} {:<40} {}\n", "FAMILY", "GATEWAY", "INTERFACE");
+        out.push_str(&format!("{}\n", "-".repeat(60)));
+        for g in &self.gateways {
+            out.push_str(&format!(
+                "{:<8} {:<40} {}\n",
+                family(g.gateway),
+                g.gateway.to_string(),
+                g.interface
+            ));
+        }
+        out
+    }
+
+    fn to_human(&self) -> String {
+        let mut out = format!("{}\n", "DEFAULT GATEWAYS".blue().bold());
+        if self.gateways.is_empty() {
+            out.push_str(&format!("  {}\n", "no default route".dimmed()));
+        }
+        for g in &self.gateways {
+            out.push_str(&format!(
+                "  {:<5} {} via {}\n",
+                family(g.gateway),
+                g.gateway.to_string().bold(),
+                g.interface
+            ));
+        }
+        out
+    }
+}
+
+fn family(ip: IpAddr) -> &'static str {
+    if ip.is_ipv4() {
+        "ipv4"
+    } else {
+        "ipv6"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_route_table_default_gateway() {
+        let table = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\n\
+                     eth0\t0001A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\n\
+                     eth0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\n\
+                     wlan0\t00000000\t0100000A\t0003\t0\t0\t600\t00000000\n";
+        assert_eq!(
+            parse_route_table(table),
+            vec![
+                GatewayInfo {
+                    interface: "eth0".to_string(),
+                    gateway: IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)),
+                },
+                GatewayInfo {
+                    interface: "wlan0".to_string(),
+                    gateway: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+                },
+            ]
+        );
+        assert!(parse_route_table("Iface\tDestination\tGateway\n").is_empty());
+    }
+
+    #[test]
+    fn test_parse_ipv6_route_table_default_gateway() {
+        let table = "\
+fd000000000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001     eth0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000400 00000001 00000000 00000003     eth0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200       lo
+";
+        assert_eq!(
+            parse_ipv6_route_table(table),
+            vec![GatewayInfo {
+                interface: "eth0".to_string(),
+                gateway: "fe80::1".parse().unwrap(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_gateway_result_output() {
+        let result = GatewayResult {
+            gateways: vec![
+                GatewayInfo {
+                    interface: "eth0".to_string(),
+                    gateway: "192.168.1.1".parse().unwrap(),
+                },
+                GatewayInfo {
+                    interface: "eth0".to_string(),
+                    gateway: "fe80::1".parse().unwrap(),
+                },
+            ],
+            timestamp: String::new(),
+        };
+        assert_eq!(
+            result.to_csv(),
+            "family,gateway,interface\nipv4,192.168.1.1,eth0\nipv6,fe80::1,eth0\n"
+        );
+        let table = result.to_table();
+        assert!(table.contains("ipv4     192.168.1.1"));
+        assert!(table.contains("ipv6     fe80::1"));
+    }
+
+    #[test]
+    fn test_parse_resolv_conf() {
+        let conf =
+            "# generated\nsearch lan\nnameserver 1.1.1.1\nnameserver  fd00::53 \noptions edns0\n";
+        assert_eq!(parse_resolv_conf(conf), vec!["1.1.1.1", "fd00::53"]);
+    }
+
     #[test]
     fn test_scope_for_v4() {
         assert_eq!(scope_for_v4(Ipv4Addr::LOCALHOST), "loopback");
@@ -463,11 +943,14 @@ mod tests {
             is_loopback: false,
             addresses: vec![],
             mtu: Some(1500),
+            mac: Some("00:50:56:01:02:03".to_string()),
+            vendor: Some("VMware".to_string()),
         };
         let json = serde_json::to_string(&interface).unwrap();
         assert!(json.contains("eth0"));
         assert!(json.contains("true"));
         assert!(json.contains("1500"));
+        assert!(json.contains(r#""mac":"00:50:56:01:02:03""#));
     }
 
     #[test]
@@ -501,6 +984,7 @@ mod tests {
             interfaces: vec![],
             total: 5,
             up_count: 3,
+            timestamp: String::new(),
         };
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("5"));
@@ -524,6 +1008,56 @@ mod tests {
         assert_eq!(result.total, result.interfaces.len());
     }
 
+    #[test]
+    fn test_format_mac() {
+        assert_eq!(
+            format_mac(&[0x00, 0x1a, 0x11, 0xab, 0xcd, 0xef]).as_deref(),
+            Some("00:1a:11:ab:cd:ef")
+        );
+        assert_eq!(format_mac(&[0; 6]), None);
+        assert_eq!(format_mac(&[1, 2, 3, 4]), None);
+    }
+
+    #[test]
+    fn test_mac_vendor() {
+        assert_eq!(mac_vendor("08:00:27:12:34:56"), Some("VirtualBox"));
+        assert_eq!(mac_vendor("B8-27-EB-00-00-01"), Some("Raspberry Pi"));
+        assert_eq!(mac_vendor("12:34:56:78:9a:bc"), None);
+        assert_eq!(mac_vendor("garbage"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_list_interfaces_reports_macs() {
+        let result = list_interfaces().unwrap();
+        for iface in &result.interfaces {
+            if iface.is_loopback {
+                assert_eq!(iface.mac, None, "{} should have no usable MAC", iface.name);
+                continue;
+            }
+            // Real interfaces expose the same address through sysfs
+            let Ok(sysfs) =
+                std::fs::read_to_string(format!("/sys/class/net/{}/address", iface.name))
+            else {
+                continue;
+            };
+            let sysfs = sysfs.trim();
+            if sysfs.len() == 17 && sysfs != "00:00:00:00:00:00" {
+                assert_eq!(iface.mac.as_deref(), Some(sysfs), "{}", iface.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fresh_result_is_timestamped_now() {
+        let result = list_interfaces().unwrap();
+        let started = crate::timestamp::parse(&result.timestamp).expect("RFC 3339 timestamp");
+        let age = std::time::SystemTime::now()
+            .duration_since(started)
+            .unwrap_or_else(|e| e.duration());
+        assert!(age < std::time::Duration::from_secs(1));
+    }
+
     #[test]
     fn test_network_interface_properties() {
         let interface = NetworkInterface {
@@ -544,6 +1078,8 @@ mod tests {
                 },
             ],
             mtu: Some(65536),
+            mac: None,
+            vendor: None,
         };
 
         assert_eq!(interface.name, "lo");
@@ -562,6 +1098,8 @@ mod tests {
             is_loopback: false,
             addresses: vec![],
             mtu: None,
+            mac: None,
+            vendor: None,
         };
 
         assert!(interface.mtu.is_none());
@@ -1,6 +1,10 @@
+use crate::health::{self, HealthCheck, HealthStatus};
+use crate::locale::format_number;
+use crate::prometheus::{self, Sample};
 use crate::OutputFormat;
 use colored::Colorize;
 use serde::Serialize;
+use std::cell::Cell;
 
 /// Format a value as JSON, table, CSV, or human-readable.
 pub fn format_output<T: Serialize + HumanReadable>(value: &T, format: OutputFormat) -> String {
@@ -10,10 +14,72 @@ pub fn format_output<T: Serialize + HumanReadable>(value: &T, format: OutputForm
         }
         OutputFormat::Table => value.to_table(),
         OutputFormat::Csv => value.to_csv(),
+        OutputFormat::Ndjson => value.to_ndjson().unwrap_or_else(|| json_line(value)),
+        OutputFormat::Prometheus => format_prometheus(std::slice::from_ref(value)),
         OutputFormat::Human => value.to_human(),
     }
 }
 
+/// A value as compact JSON followed by a newline.
+fn json_line<T: Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string(value).map_or_else(|e| format!("JSON error: {e}\n"), |j| j + "\n")
+}
+
+/// Prometheus exposition of the samples from all `values`.
+fn format_prometheus<T: HumanReadable>(values: &[T]) -> String {
+    let samples: Vec<Sample> = values.iter().flat_map(|v| v.prometheus_samples()).collect();
+    if samples.is_empty() {
+        // A comment keeps the file valid for a textfile collector
+        return String::from("# Prometheus output not supported for this command\n");
+    }
+    prometheus::render(&samples)
+}
+
+/// JSON Lines for a result with child records: one line per element of its
+/// `field` array, tagged `"record": kind` and carrying the result's `key` field
+/// (e.g. the target), then a `"record": "summary"` line with the remaining fields.
+pub fn ndjson_records<T: Serialize>(value: &T, key: &str, field: &str, kind: &str) -> String {
+    let Ok(serde_json::Value::Object(mut summary)) = serde_json::to_value(value) else {
+        return json_line(value);
+    };
+    let parent = summary.get(key).cloned().unwrap_or_default();
+    let records = match summary.remove(field) {
+        Some(serde_json::Value::Array(records)) => records,
+        _ => Vec::new(),
+    };
+    let mut out = String::new();
+    for record in records {
+        let mut line = serde_json::Map::new();
+        line.insert("record".to_string(), kind.into());
+        line.insert(key.to_string(), parent.clone());
+        if let serde_json::Value::Object(fields) = record {
+            line.extend(fields);
+        }
+        out.push_str(&json_line(&line));
+    }
+    summary.insert("record".to_string(), "summary".into());
+    out.push_str(&json_line(&summary));
+    out
+}
+
+/// Format several results of the same kind; JSON output becomes a single array,
+/// NDJSON records run on without blank lines between results and Prometheus
+/// metrics from all results share one set of HELP/TYPE lines.
+pub fn format_many<T: Serialize + HumanReadable>(values: &[T], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_string_pretty(values).unwrap_or_else(|e| format!("JSON error: {e}"))
+        }
+        OutputFormat::Ndjson => values.iter().map(|v| format_output(v, format)).collect(),
+        OutputFormat::Prometheus => format_prometheus(values),
+        _ => values
+            .iter()
+            .map(|v| format_output(v, format))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
 /// Trait for human-readable output formatting.
 pub trait HumanReadable {
     fn to_human(&self) -> String;
@@ -24,29 +90,141 @@ pub trait HumanReadable {
         // Default: no CSV; subcommands override
         String::from("CSV output not supported for this command\n")
     }
+    /// NDJSON output, one line per record (probe, host, DNS record, ...) plus a
+    /// summary line; `None` emits the whole result as a single line.
+    fn to_ndjson(&self) -> Option<String> {
+        None
+    }
+    /// Metrics for Prometheus output; empty for results without any.
+    fn prometheus_samples(&self) -> Vec<Sample> {
+        Vec::new()
+    }
+    /// A single plain-text line for status bars; by default the first line of `to_human`.
+    fn to_oneline(&self) -> String {
+        let human = strip_ansi(&self.to_human());
+        human
+            .lines()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("")
+            .trim()
+            .to_string()
+    }
+    /// Summarise as a health check; by default a successful result is passing.
+    fn health(&self) -> HealthCheck {
+        let human = self.to_human();
+        let summary = human.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+        HealthCheck::new(HealthStatus::Passing, summary.trim(), "")
+    }
+}
+
+/// Format a value as health-check JSON.
+pub fn format_health<T: HumanReadable>(value: &T) -> String {
+    value.health().to_json()
+}
+
+/// Format a value as its one-line summary.
+pub fn format_oneline<T: HumanReadable>(value: &T) -> String {
+    format!("{}\n", value.to_oneline())
+}
+
+thread_local! {
+    static CSV_DELIMITER: Cell<char> = const { Cell::new(',') };
+}
+
+/// Set the field delimiter for CSV rendered on this thread (`\t` for TSV).
+pub fn set_csv_delimiter(delimiter: char) {
+    CSV_DELIMITER.with(|d| d.set(delimiter));
+}
+
+/// The CSV field delimiter for this thread.
+pub fn csv_delimiter() -> char {
+    CSV_DELIMITER.with(|d| d.get())
+}
+
+/// Parse a `--delimiter` value: a single character, or `\t`/`tab` for a tab.
+pub fn parse_csv_delimiter(value: &str) -> Result<char, String> {
+    match value {
+        "\\t" | "tab" => return Ok('\t'),
+        _ => {}
+    }
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c != '"' && c != '\n' && c != '\r' => Ok(c),
+        _ => Err(format!(
+            "Invalid delimiter {value:?}: expected a single character other than a quote or newline"
+        )),
+    }
+}
+
+/// Quote a CSV field if it contains the delimiter, a quote or a line break.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([csv_delimiter(), '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One CSV line of `fields`, quoted as needed and joined by the current delimiter.
+pub fn csv_row(fields: &[&dyn std::fmt::Display]) -> String {
+    let fields: Vec<String> = fields.iter().map(|f| csv_field(&f.to_string())).collect();
+    format!("{}\n", fields.join(&csv_delimiter().to_string()))
+}
+
+/// Remove ANSI colour escape sequences from `s`.
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip up to and including the final byte of the CSI sequence
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
 }
 
 /// Format a duration in milliseconds nicely.
 pub fn format_ms(ms: f64) -> String {
     if ms < 1.0 {
-        format!("{:.2} µs", ms * 1000.0)
+        format!("{} µs", format_number(ms * 1000.0, 2))
     } else if ms < 1000.0 {
-        format!("{ms:.2} ms")
+        format!("{} ms", format_number(ms, 2))
     } else {
-        format!("{:.2} s", ms / 1000.0)
+        format!("{} s", format_number(ms / 1000.0, 2))
+    }
+}
+
+/// Format a rate in Mbps with the unit that suits its size (Kbps, Mbps or Gbps).
+pub fn format_rate(mbps: f64) -> String {
+    if mbps < 1.0 {
+        format!("{} Kbps", format_number(mbps * 1000.0, 1))
+    } else if mbps < 1000.0 {
+        format!("{} Mbps", format_number(mbps, 2))
+    } else {
+        format!("{} Gbps", format_number(mbps / 1000.0, 2))
     }
 }
 
 /// Format bytes into human-readable size.
 pub fn format_bytes(bytes: u64) -> String {
     if bytes < 1024 {
-        format!("{bytes} B")
+        format!("{} B", format_number(bytes as f64, 0))
     } else if bytes < 1024 * 1024 {
-        format!("{:.1} KB", bytes as f64 / 1024.0)
+        format!("{} KB", format_number(bytes as f64 / 1024.0, 1))
     } else if bytes < 1024 * 1024 * 1024 {
-        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+        format!("{} MB", format_number(bytes as f64 / (1024.0 * 1024.0), 1))
     } else {
-        format!("{:.2} GB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
+        format!(
+            "{} GB",
+            format_number(bytes as f64 / (1024.0 * 1024.0 * 1024.0), 2)
+        )
     }
 }
 
@@ -60,6 +238,15 @@ pub fn kv(key: &str, value: &str) -> String {
     format!("  {}: {}", key.dimmed(), value)
 }
 
+/// Describe an internationalized domain, with a homograph warning if any.
+pub fn idn_lines(idn: &crate::idn::IdnInfo, indent: &str) -> String {
+    let mut out = format!("{indent}IDN: {} ({})\n", idn.unicode, idn.ascii);
+    if let Some(ref warning) = idn.warning {
+        out.push_str(&format!("{indent}{} {}\n", "⚠".yellow(), warning.yellow()));
+    }
+    out
+}
+
 /// Print a success/fail indicator.
 pub fn status_icon(success: bool) -> &'static str {
     if success {
@@ -80,12 +267,102 @@ pub fn pad_right(s: &str, width: usize) -> String {
 
 // Implement HumanReadable for core types
 impl HumanReadable for crate::ping::PingStats {
+    fn health(&self) -> HealthCheck {
+        let status = if self.received == 0 {
+            HealthStatus::Critical
+        } else if self.lost > 0 {
+            HealthStatus::Warning
+        } else {
+            HealthStatus::Passing
+        };
+        let avg = self
+            .avg_ms
+            .map(|a| format!(", avg {a:.1}ms"))
+            .unwrap_or_default();
+        HealthCheck::new(
+            status,
+            format!(
+                "{}: {}/{} received{avg}",
+                self.target, self.received, self.sent
+            ),
+            "critical if no replies, warning on any loss",
+        )
+    }
+
+    fn to_ndjson(&self) -> Option<String> {
+        Some(ndjson_records(self, "target", "probes", "probe"))
+    }
+
+    fn prometheus_samples(&self) -> Vec<Sample> {
+        let target = [("target", self.target.as_str())];
+        let mut samples = vec![
+            Sample::new(
+                "netscout_ping_packets_sent",
+                "Echo requests sent.",
+                &target,
+                self.sent as f64,
+            ),
+            Sample::new(
+                "netscout_ping_packets_received",
+                "Echo replies received.",
+                &target,
+                self.received as f64,
+            ),
+            Sample::new(
+                "netscout_ping_loss_percent",
+                "Percentage of probes without a reply.",
+                &target,
+                self.loss_percent,
+            ),
+        ];
+        let rtts = [
+            (
+                "netscout_ping_rtt_ms",
+                "Average round-trip time in milliseconds.",
+                self.avg_ms,
+            ),
+            (
+                "netscout_ping_rtt_min_ms",
+                "Minimum round-trip time in milliseconds.",
+                self.min_ms,
+            ),
+            (
+                "netscout_ping_rtt_max_ms",
+                "Maximum round-trip time in milliseconds.",
+                self.max_ms,
+            ),
+            (
+                "netscout_ping_jitter_ms",
+                "Mean change between consecutive RTTs in milliseconds.",
+                self.jitter_ms,
+            ),
+        ];
+        for (name, help, value) in rtts {
+            if let Some(v) = value {
+                samples.push(Sample::new(name, help, &target, v));
+            }
+        }
+        samples
+    }
+
     fn to_csv(&self) -> String {
-        let mut out = String::from("seq,status,rtt_ms,addr\n");
+        let mut out = csv_row(&[
+            &"seq", &"status", &"rtt_ms", &"addr", &"p50_ms", &"p95_ms", &"p99_ms",
+        ]);
+        let ms = |v: Option<f64>| v.map(|r| format!("{r:.2}")).unwrap_or_default();
+        // Session percentiles repeat on every row so each stands alone
+        let (p50, p95, p99) = (ms(self.p50_ms), ms(self.p95_ms), ms(self.p99_ms));
         for p in &self.probes {
             let status = if p.success { "ok" } else { "timeout" };
-            let rtt = p.rtt_ms.map(|r| format!("{r:.2}")).unwrap_or_default();
-            out.push_str(&format!("{},{},{},{}\n", p.seq, status, rtt, p.addr));
+            out.push_str(&csv_row(&[
+                &p.seq,
+                &status,
+                &ms(p.rtt_ms),
+                &p.addr,
+                &p50,
+                &p95,
+                &p99,
+            ]));
         }
         out
     }
@@ -112,9 +389,23 @@ impl HumanReadable for crate::ping::PingStats {
             }
             out.push('\n');
         }
+        if let (Some(p50), Some(p95), Some(p99)) = (self.p50_ms, self.p95_ms, self.p99_ms) {
+            out.push_str(&format!(
+                "p50={:.2} ms  p95={:.2} ms  p99={:.2} ms\n",
+                p50, p95, p99,
+            ));
+        }
         out
     }
 
+    fn to_oneline(&self) -> String {
+        let avg = self
+            .avg_ms
+            .map(|a| format!(", {a:.0}ms avg"))
+            .unwrap_or_default();
+        format!("PING {}: {:.0}% loss{avg}", self.target, self.loss_percent)
+    }
+
     fn to_human(&self) -> String {
         let mut out = String::new();
         out.push_str(&format!(
@@ -124,114 +415,762 @@ impl HumanReadable for crate::ping::PingStats {
             self.resolved_addr,
             self.sent,
         ));
+        if let Some(ref owner) = self.owner {
+            out.push_str(&format!("{}\n", kv("Owner", owner)));
+        }
         for p in &self.probes {
-            if p.success {
+            out.push_str(&ping_probe_line(p));
+        }
+        out.push_str(&ping_summary(self));
+        out
+    }
+}
+
+/// One human-readable line for a ping probe, as printed while streaming.
+pub fn ping_probe_line(p: &crate::ping::PingProbe) -> String {
+    if p.success {
+        format!(
+            "  {} seq={} time={}\n",
+            "✓".green(),
+            p.seq,
+            format_ms(p.rtt_ms.unwrap_or(0.0)),
+        )
+    } else {
+        format!("  {} seq={} timeout\n", "✗".red(), p.seq)
+    }
+}
+
+/// The statistics block printed after the probes of a ping session.
+pub fn ping_summary(stats: &crate::ping::PingStats) -> String {
+    let mut out = format!("\n  --- {} ping statistics ---\n", stats.target);
+    out.push_str(&format!(
+        "  {} sent, {} received, {:.1}% loss\n",
+        stats.sent, stats.received, stats.loss_percent,
+    ));
+    if let (Some(min), Some(avg), Some(max)) = (stats.min_ms, stats.avg_ms, stats.max_ms) {
+        out.push_str(&format!(
+            "  rtt min/avg/max = {:.2}/{:.2}/{:.2} ms",
+            min, avg, max,
+        ));
+        if let Some(stddev) = stats.stddev_ms {
+            out.push_str(&format!(", stddev = {:.2} ms", stddev));
+        }
+        if let Some(jitter) = stats.jitter_ms {
+            out.push_str(&format!(", jitter = {:.2} ms", jitter));
+        }
+        out.push('\n');
+    }
+    if let (Some(p50), Some(p95), Some(p99)) = (stats.p50_ms, stats.p95_ms, stats.p99_ms) {
+        out.push_str(&format!(
+            "  rtt p50/p95/p99 = {:.2}/{:.2}/{:.2} ms\n",
+            p50, p95, p99,
+        ));
+    }
+    out
+}
+
+/// A probe line in the style of classic ping(8).
+pub fn ping_probe_line_classic(p: &crate::ping::PingProbe) -> String {
+    match p.rtt_ms {
+        Some(rtt) if p.success => {
+            format!(
+                "connected to {}: seq={} time={:.3} ms\n",
+                p.addr, p.seq, rtt
+            )
+        }
+        _ => format!("request timeout for seq={}\n", p.seq),
+    }
+}
+
+/// The statistics block of classic ping(8), ending in `rtt min/avg/max/mdev`.
+pub fn ping_summary_classic(stats: &crate::ping::PingStats) -> String {
+    let loss = (stats.loss_percent * 1000.0).round() / 1000.0;
+    let mut out = format!(
+        "\n--- {} ping statistics ---\n{} packets transmitted, {} received, {loss}% packet loss\n",
+        stats.target, stats.sent, stats.received,
+    );
+    if let (Some(min), Some(avg), Some(max), Some(mdev)) =
+        (stats.min_ms, stats.avg_ms, stats.max_ms, stats.mdev_ms)
+    {
+        out.push_str(&format!(
+            "rtt min/avg/max/mdev = {min:.3}/{avg:.3}/{max:.3}/{mdev:.3} ms\n"
+        ));
+    }
+    out
+}
+
+/// Full ping(8)-style output for parsers that expect the classic format.
+pub fn format_ping_classic(stats: &crate::ping::PingStats, mode: crate::ping::PingMode) -> String {
+    let mut out = format!(
+        "PING {} ({}) via {}\n",
+        stats.target,
+        stats.resolved_addr,
+        mode.label()
+    );
+    for p in &stats.probes {
+        out.push_str(&ping_probe_line_classic(p));
+    }
+    out.push_str(&ping_summary_classic(stats));
+    out
+}
+
+impl HumanReadable for crate::dns::DnsResult {
+    fn health(&self) -> HealthCheck {
+        let status = if self.response_code != "NOERROR" {
+            HealthStatus::Critical
+        } else if self.records.is_empty() {
+            HealthStatus::Warning
+        } else {
+            HealthStatus::Passing
+        };
+        HealthCheck::new(
+            status,
+            format!(
+                "{} {}: {} ({} records)",
+                self.domain,
+                self.record_type,
+                self.response_code,
+                self.records.len()
+            ),
+            "critical on error response, warning if no records",
+        )
+    }
+
+    fn to_ndjson(&self) -> Option<String> {
+        Some(ndjson_records(self, "domain", "records", "record"))
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = csv_row(&[&"type", &"name", &"ttl", &"value"]);
+        for r in &self.records {
+            out.push_str(&csv_row(&[&r.record_type, &r.name, &r.ttl, &r.value]));
+        }
+        out
+    }
+
+    fn to_oneline(&self) -> String {
+        let values: Vec<&str> = self.records.iter().map(|r| r.value.as_str()).collect();
+        if values.is_empty() {
+            format!(
+                "DNS {} {}: {}, no records",
+                self.domain, self.record_type, self.response_code
+            )
+        } else {
+            format!(
+                "DNS {} {}: {}",
+                self.domain,
+                self.record_type,
+                values.join(", ")
+            )
+        }
+    }
+
+    fn to_human(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!(
+            "{} {} @{} — {} — {}\n",
+            "DNS".cyan().bold(),
+            self.domain,
+            self.resolver,
+            self.record_type,
+            self.response_code,
+        ));
+        out.push_str(&format!(
+            "  Query time: {}\n",
+            format_ms(self.query_time_ms)
+        ));
+        if let Some(ref idn) = self.idn {
+            out.push_str(&idn_lines(idn, "  "));
+        }
+        if let Some(ref subnet) = self.client_subnet {
+            let scope = self
+                .client_subnet_scope
+                .map(|s| format!("/{s}"))
+                .unwrap_or_else(|| "none".to_string());
+            out.push_str(&format!("  Client subnet: {subnet} (scope {scope})\n"));
+        }
+        if self.authenticated_data {
+            out.push_str(&format!(
+                "  DNSSEC: {}\n",
+                "validated by resolver (AD)".green()
+            ));
+        }
+        if self.records.is_empty() {
+            out.push_str("  No records found.\n");
+        }
+        // Behind an alias, list the chain's own records first and group the
+        // final answers under the canonical name.
+        let finals = if self.cname_chain.is_empty() {
+            Vec::new()
+        } else {
+            self.final_records()
+        };
+        for r in &self.records {
+            if finals.iter().any(|f| std::ptr::eq(*f, r)) {
+                continue;
+            }
+            out.push_str(&format!(
+                "  {} {} TTL={} {}\n",
+                r.record_type.yellow(),
+                r.name,
+                r.ttl,
+                r.value.green(),
+            ));
+        }
+        if !self.cname_chain.is_empty() {
+            out.push_str(&format!(
+                "  CNAME chain: {} → {}\n",
+                self.domain,
+                self.cname_chain.join(" → ")
+            ));
+            out.push_str(&format!("  {}:\n", self.canonical_name().bold()));
+            for r in &finals {
+                out.push_str(&format!(
+                    "    {} TTL={} {}\n",
+                    r.record_type.yellow(),
+                    r.ttl,
+                    r.value.green(),
+                ));
+            }
+        }
+        out
+    }
+
+    fn to_table(&self) -> String {
+        let mut out = format!(
+            "DNS: {} @{} — {} ({})\n\n",
+            self.domain, self.resolver, self.record_type, self.response_code,
+        );
+        out.push_str(&format!(
+            "{:<8} {:<30} {:<8} {}\n",
+            "TYPE", "NAME", "TTL", "VALUE"
+        ));
+        out.push_str(&format!("{}\n", "-".repeat(70)));
+        for r in &self.records {
+            out.push_str(&format!(
+                "{:<8} {:<30} {:<8} {}\n",
+                r.record_type, r.name, r.ttl, r.value,
+            ));
+        }
+        out
+    }
+}
+
+impl HumanReadable for crate::dns::MultiDnsResult {
+    fn to_human(&self) -> String {
+        let mut out = format!(
+            "{} {} — {} — {} resolvers\n",
+            "DNS".cyan().bold(),
+            self.domain,
+            self.record_type,
+            self.answers.len(),
+        );
+        for a in &self.answers {
+            match (&a.result, a.values()) {
+                (Some(r), Some(values)) if a.answered() => {
+                    let shown = if values.is_empty() {
+                        r.response_code.clone()
+                    } else {
+                        values.join(", ")
+                    };
+                    out.push_str(&format!(
+                        "  {} {} {} ({})\n",
+                        "✓".green(),
+                        pad_right(&a.resolver, 22),
+                        shown.green(),
+                        format_ms(r.query_time_ms)
+                    ));
+                }
+                (Some(r), _) => out.push_str(&format!(
+                    "  {} {} {} ({})\n",
+                    "✗".red(),
+                    pad_right(&a.resolver, 22),
+                    r.response_code.red(),
+                    format_ms(r.query_time_ms)
+                )),
+                _ => out.push_str(&format!(
+                    "  {} {} {}\n",
+                    "✗".red(),
+                    pad_right(&a.resolver, 22),
+                    a.error.as_deref().unwrap_or("no answer").red()
+                )),
+            }
+        }
+        let answer = self
+            .consensus
+            .as_ref()
+            .map(|v| v.join(", "))
+            .unwrap_or_else(|| "-".to_string());
+        if let Some(ref by) = self.answered_by {
+            out.push_str(&format!("  First answer: {answer} from {by}\n"));
+        } else {
+            let quorum = self
+                .quorum
+                .map(|q| format!(", quorum {q}"))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "  Consensus: {answer} ({}/{} agree{quorum})\n",
+                self.agreeing,
+                self.answers.len()
+            ));
+        }
+        out
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = csv_row(&[&"resolver", &"status", &"values", &"query_time_ms"]);
+        for a in &self.answers {
+            match (&a.result, a.values()) {
+                (Some(r), Some(values)) => out.push_str(&csv_row(&[
+                    &a.resolver,
+                    &r.response_code,
+                    &values.join(" "),
+                    &format!("{:.1}", r.query_time_ms),
+                ])),
+                _ => out.push_str(&csv_row(&[
+                    &a.resolver,
+                    &"error",
+                    &a.error.as_deref().unwrap_or(""),
+                    &"",
+                ])),
+            }
+        }
+        out
+    }
+}
+
+impl HumanReadable for crate::dns::FastestResolverResult {
+    fn to_human(&self) -> String {
+        let mut out = format!(
+            "{} fastest resolver — {} × {} {}\n",
+            "DNS".cyan().bold(),
+            self.ranking.len(),
+            self.domains.len(),
+            if self.domains.len() == 1 {
+                "name"
+            } else {
+                "names"
+            },
+        );
+        for (i, r) in self.ranking.iter().enumerate() {
+            let latency = match (r.avg_ms, r.p95_ms) {
+                (Some(avg), Some(p95)) => {
+                    format!("avg {}  p95 {}", format_ms(avg), format_ms(p95))
+                }
+                _ => "-".to_string(),
+            };
+            let line = format!(
+                "{:>2}. {} {} ({}/{} answered)",
+                i + 1,
+                pad_right(&r.resolver, 22),
+                latency,
+                r.answered,
+                r.queries
+            );
+            if self.fastest.as_deref() == Some(r.resolver.as_str()) {
                 out.push_str(&format!(
-                    "  {} seq={} time={}\n",
-                    "✓".green(),
-                    p.seq,
-                    format_ms(p.rtt_ms.unwrap_or(0.0)),
+                    "  {} {}\n",
+                    line.green().bold(),
+                    "★ fastest".green()
                 ));
+            } else if let Some(ref e) = r.error {
+                out.push_str(&format!("  {}\n    {} {}\n", line, "✗".red(), e.red()));
             } else {
-                out.push_str(&format!("  {} seq={} timeout\n", "✗".red(), p.seq));
+                out.push_str(&format!("  {line}\n"));
             }
         }
-        out.push_str(&format!("\n  --- {} ping statistics ---\n", self.target));
+        match self.fastest {
+            Some(ref best) => out.push_str(&format!("  Recommended: {}\n", best.green().bold())),
+            None => out.push_str(&format!("  {}\n", "No resolver answered every query".red())),
+        }
+        out
+    }
+
+    fn to_table(&self) -> String {
+        let mut out = format!(
+            "Fastest resolver: {} ({} names, {})\n\n",
+            self.fastest.as_deref().unwrap_or("none"),
+            self.domains.len(),
+            self.record_type
+        );
         out.push_str(&format!(
-            "  {} sent, {} received, {:.1}% loss\n",
-            self.sent, self.received, self.loss_percent,
+            "{:<5} {:<22} {:<12} {:<12} {}\n",
+            "RANK", "RESOLVER", "AVG", "P95", "ANSWERED"
         ));
-        if let (Some(min), Some(avg), Some(max)) = (self.min_ms, self.avg_ms, self.max_ms) {
+        out.push_str(&format!("{}\n", "-".repeat(62)));
+        for (i, r) in self.ranking.iter().enumerate() {
+            let ms = |v: Option<f64>| v.map(format_ms).unwrap_or_else(|| "-".to_string());
             out.push_str(&format!(
-                "  rtt min/avg/max = {:.2}/{:.2}/{:.2} ms",
-                min, avg, max,
+                "{:<5} {:<22} {:<12} {:<12} {}/{}\n",
+                i + 1,
+                r.resolver,
+                ms(r.avg_ms),
+                ms(r.p95_ms),
+                r.answered,
+                r.queries
             ));
-            if let Some(stddev) = self.stddev_ms {
-                out.push_str(&format!(", stddev = {:.2} ms", stddev));
+        }
+        out
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = csv_row(&[
+            &"rank",
+            &"resolver",
+            &"avg_ms",
+            &"p95_ms",
+            &"answered",
+            &"queries",
+            &"error",
+        ]);
+        for (i, r) in self.ranking.iter().enumerate() {
+            let ms = |v: Option<f64>| v.map(|v| format!("{v:.1}")).unwrap_or_default();
+            out.push_str(&csv_row(&[
+                &(i + 1),
+                &r.resolver,
+                &ms(r.avg_ms),
+                &ms(r.p95_ms),
+                &r.answered,
+                &r.queries,
+                &r.error.as_deref().unwrap_or(""),
+            ]));
+        }
+        out
+    }
+
+    fn to_oneline(&self) -> String {
+        match self.ranking.first().filter(|_| self.fastest.is_some()) {
+            Some(r) => format!(
+                "DNS fastest: {} ({:.0}ms avg)",
+                r.resolver,
+                r.avg_ms.unwrap_or_default()
+            ),
+            None => "DNS fastest: no resolver answered every query".to_string(),
+        }
+    }
+
+    fn health(&self) -> HealthCheck {
+        let status = if self.fastest.is_some() {
+            HealthStatus::Passing
+        } else {
+            HealthStatus::Critical
+        };
+        HealthCheck::new(
+            status,
+            self.to_oneline(),
+            "critical when no resolver answers every query",
+        )
+    }
+}
+
+impl HumanReadable for crate::dns::DnssecResult {
+    fn health(&self) -> HealthCheck {
+        use crate::dns::DnssecStatus;
+        let status = match self.status {
+            _ if self.answer.response_code != "NOERROR" => HealthStatus::Critical,
+            DnssecStatus::Validated => HealthStatus::Passing,
+            DnssecStatus::SignedNotValidated | DnssecStatus::Unsigned => HealthStatus::Warning,
+        };
+        HealthCheck::new(
+            status,
+            self.to_oneline(),
+            "warning unless the resolver validated the answer (AD bit)",
+        )
+    }
+
+    fn to_oneline(&self) -> String {
+        format!("DNSSEC {}: {}", self.domain, self.status)
+    }
+
+    fn to_human(&self) -> String {
+        use crate::dns::DnssecStatus;
+        let mut out = format!(
+            "{} {} @{} — {} — {}\n",
+            "DNSSEC".cyan().bold(),
+            self.domain,
+            self.resolver,
+            self.record_type,
+            self.answer.response_code,
+        );
+        let status = match self.status {
+            DnssecStatus::Validated => "validated — the resolver set the AD bit".green(),
+            DnssecStatus::SignedNotValidated => {
+                "signed, but the resolver did not validate it (AD bit clear)".yellow()
             }
-            if let Some(jitter) = self.jitter_ms {
-                out.push_str(&format!(", jitter = {:.2} ms", jitter));
+            DnssecStatus::Unsigned => "unsigned — no RRSIG, DNSKEY or DS records".yellow(),
+        };
+        out.push_str(&format!("{}\n", kv("Status", &status.to_string())));
+        let values: Vec<&str> = self
+            .answer
+            .records_of_type(&self.record_type)
+            .into_iter()
+            .map(|r| r.value.as_str())
+            .collect();
+        if !values.is_empty() {
+            out.push_str(&format!("{}\n", kv("Answer", &values.join(", "))));
+        }
+        for (label, records) in [
+            ("RRSIG", &self.rrsig),
+            ("DNSKEY", &self.dnskey),
+            ("DS", &self.ds),
+        ] {
+            for r in records {
+                out.push_str(&format!("  {} {}\n", label.yellow(), r.value));
             }
-            out.push('\n');
         }
         out
     }
-}
+}
+
+impl HumanReadable for crate::dns::SplitHorizonResult {
+    fn health(&self) -> HealthCheck {
+        let status = if self.split_detected {
+            HealthStatus::Warning
+        } else if self.internal.result.is_none() || self.external.result.is_none() {
+            HealthStatus::Critical
+        } else {
+            HealthStatus::Passing
+        };
+        HealthCheck::new(
+            status,
+            self.to_oneline(),
+            "warning when internal and external answers differ, critical if a resolver fails",
+        )
+    }
+
+    fn to_oneline(&self) -> String {
+        let verdict = if self.split_detected {
+            "split-horizon detected"
+        } else if self.internal.result.is_none() || self.external.result.is_none() {
+            "inconclusive"
+        } else {
+            "same answer"
+        };
+        format!("DNS {} {}: {verdict}", self.domain, self.record_type)
+    }
+
+    fn to_human(&self) -> String {
+        let mut out = format!(
+            "{} {} — {} — split-horizon check\n",
+            "DNS".cyan().bold(),
+            self.domain,
+            self.record_type,
+        );
+        for (label, a) in [("Internal", &self.internal), ("External", &self.external)] {
+            let shown = match (&a.result, a.values()) {
+                (Some(r), Some(values)) if values.is_empty() => r.response_code.clone(),
+                (Some(_), Some(values)) => values.join(", "),
+                _ => a.error.clone().unwrap_or_else(|| "no answer".to_string()),
+            };
+            out.push_str(&format!(
+                "{}\n",
+                kv(&format!("{label} ({})", a.resolver), &shown)
+            ));
+        }
+        if self.split_detected {
+            out.push_str(&format!(
+                "  {}\n",
+                "Split-horizon detected: internal and external answers differ".yellow()
+            ));
+        } else if self.internal.result.is_none() || self.external.result.is_none() {
+            out.push_str(&format!(
+                "  {}\n",
+                "Inconclusive: one resolver did not answer".red()
+            ));
+        } else {
+            out.push_str(&format!(
+                "  {}\n",
+                "No split: both resolvers give the same answer".green()
+            ));
+        }
+        out
+    }
+}
+
+impl HumanReadable for crate::dns::DnsMonitorSet {
+    fn to_oneline(&self) -> String {
+        format!(
+            "DNS {} {}: {} records",
+            self.domain,
+            self.types.join(","),
+            self.records.len()
+        )
+    }
+
+    fn to_human(&self) -> String {
+        let mut out = format!(
+            "{} {} @{} — {}\n",
+            "DNS".cyan().bold(),
+            self.domain,
+            self.resolver,
+            self.types.join(", ")
+        );
+        for (rtype, status) in self.status.iter().filter(|(_, s)| *s != "NOERROR") {
+            out.push_str(&format!("  {} {rtype}: {status}\n", "⚠".yellow()));
+        }
+        for r in &self.records {
+            let owner = if r.name == self.domain {
+                String::new()
+            } else {
+                format!(" ({})", r.name)
+            };
+            out.push_str(&format!(
+                "  {:<6} TTL={:<6} {}{owner}\n",
+                r.record_type, r.ttl, r.value
+            ));
+        }
+        out
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = csv_row(&[&"type", &"name", &"ttl", &"value"]);
+        for r in &self.records {
+            out.push_str(&csv_row(&[&r.record_type, &r.name, &r.ttl, &r.value]));
+        }
+        out
+    }
+}
+
+impl HumanReadable for crate::dns::ReverseVerifyResult {
+    fn health(&self) -> HealthCheck {
+        let verified = self.addresses.iter().filter(|a| a.verified).count();
+        let status = if verified == self.addresses.len() {
+            HealthStatus::Passing
+        } else if verified > 0 {
+            HealthStatus::Warning
+        } else {
+            HealthStatus::Critical
+        };
+        HealthCheck::new(
+            status,
+            self.to_oneline(),
+            "warning if some addresses' PTR doesn't point back to the name, critical if none do",
+        )
+    }
+
+    fn to_oneline(&self) -> String {
+        let verified = self.addresses.iter().filter(|a| a.verified).count();
+        format!(
+            "FCRDNS {}: {verified}/{} addresses verified",
+            self.domain,
+            self.addresses.len()
+        )
+    }
+
+    fn to_human(&self) -> String {
+        let mut out = format!(
+            "{} {} @{} — forward-confirmed reverse DNS\n",
+            "DNS".cyan().bold(),
+            self.domain,
+            self.resolver,
+        );
+        for a in &self.addresses {
+            let mark = if a.verified {
+                "✓".green()
+            } else {
+                "✗".red()
+            };
+            let ptr = match (&a.error, a.ptr.is_empty()) {
+                (Some(e), _) => e.clone(),
+                (None, true) => "no PTR record".to_string(),
+                (None, false) => a.ptr.join(", "),
+            };
+            out.push_str(&format!("  {mark} {:<39} → {ptr}\n", a.ip.to_string()));
+        }
+        out
+    }
 
-impl HumanReadable for crate::dns::DnsResult {
     fn to_csv(&self) -> String {
-        let mut out = String::from("type,name,ttl,value\n");
-        for r in &self.records {
-            // Escape values that might contain commas
-            let val = if r.value.contains(',') {
-                format!("\"{}\"", r.value)
-            } else {
-                r.value.clone()
-            };
-            out.push_str(&format!("{},{},{},{}\n", r.record_type, r.name, r.ttl, val));
+        let mut out = csv_row(&[&"ip", &"ptr", &"verified", &"error"]);
+        for a in &self.addresses {
+            out.push_str(&csv_row(&[
+                &a.ip,
+                &a.ptr.join(" "),
+                &a.verified,
+                &a.error.as_deref().unwrap_or(""),
+            ]));
         }
         out
     }
+}
 
+impl HumanReadable for crate::dns::SystemDnsResult {
     fn to_human(&self) -> String {
-        let mut out = String::new();
-        out.push_str(&format!(
-            "{} {} @{} — {} — {}\n",
+        let mut out = format!(
+            "{} {} @system — {} — {}\n",
             "DNS".cyan().bold(),
             self.domain,
-            self.resolver,
             self.record_type,
-            self.response_code,
-        ));
+            "system-resolved".yellow(),
+        );
         out.push_str(&format!(
-            "  Query time: {}\n",
+            "  Lookup time: {}\n",
             format_ms(self.query_time_ms)
         ));
-        if self.records.is_empty() {
-            out.push_str("  No records found.\n");
-        }
-        for r in &self.records {
+        for ip in &self.addresses {
+            let rtype = if ip.is_ipv4() { "A" } else { "AAAA" };
             out.push_str(&format!(
-                "  {} {} TTL={} {}\n",
-                r.record_type.yellow(),
-                r.name,
-                r.ttl,
-                r.value.green(),
+                "  {} {}\n",
+                rtype.yellow(),
+                ip.to_string().green()
             ));
         }
         out
     }
 
-    fn to_table(&self) -> String {
-        let mut out = format!(
-            "DNS: {} @{} — {} ({})\n\n",
-            self.domain, self.resolver, self.record_type, self.response_code,
-        );
-        out.push_str(&format!(
-            "{:<8} {:<30} {:<8} {}\n",
-            "TYPE", "NAME", "TTL", "VALUE"
-        ));
-        out.push_str(&format!("{}\n", "-".repeat(70)));
-        for r in &self.records {
-            out.push_str(&format!(
-                "{:<8} {:<30} {:<8} {}\n",
-                r.record_type, r.name, r.ttl, r.value,
-            ));
+    fn to_csv(&self) -> String {
+        let mut out = csv_row(&[&"type", &"address", &"source"]);
+        for ip in &self.addresses {
+            let rtype = if ip.is_ipv4() { "A" } else { "AAAA" };
+            out.push_str(&csv_row(&[&rtype, ip, &"system"]));
         }
         out
     }
 }
 
 impl HumanReadable for crate::port::ScanResult {
+    fn health(&self) -> HealthCheck {
+        let status = if self.open_count == 0 {
+            HealthStatus::Critical
+        } else {
+            HealthStatus::Passing
+        };
+        HealthCheck::new(status, self.to_oneline(), "critical if no port is open")
+    }
+
+    fn to_ndjson(&self) -> Option<String> {
+        Some(ndjson_records(self, "target", "ports", "port"))
+    }
+
     fn to_csv(&self) -> String {
-        let mut out = String::from("port,proto,service\n");
+        let mut out = csv_row(&[&"port", &"proto", &"service", &"rtt_ms", &"banner"]);
         for p in &self.ports {
             let svc = p.service.as_deref().unwrap_or("unknown");
-            out.push_str(&format!("{},tcp,{}\n", p.port, svc));
+            let rtt = p.rtt_ms.map(|r| format!("{r:.2}")).unwrap_or_default();
+            let banner = p.banner.as_deref().unwrap_or_default();
+            out.push_str(&csv_row(&[&p.port, &"tcp", &svc, &rtt, &banner]));
         }
         out
     }
 
+    fn to_oneline(&self) -> String {
+        let ports: Vec<String> = self
+            .ports
+            .iter()
+            .filter(|p| p.open)
+            .map(|p| p.port.to_string())
+            .collect();
+        if ports.is_empty() {
+            return format!("PORT {}: 0 open", self.target);
+        }
+        format!(
+            "PORT {}: {} open ({})",
+            self.target,
+            ports.len(),
+            ports.join(",")
+        )
+    }
+
     fn to_human(&self) -> String {
         let mut out = String::new();
         out.push_str(&format!(
@@ -245,13 +1184,25 @@ impl HumanReadable for crate::port::ScanResult {
         ));
         for p in &self.ports {
             let svc = p.service.as_deref().unwrap_or("unknown");
+            let rtt = p
+                .rtt_ms
+                .map(|r| format!("  {}", format!("{r:.2} ms").dimmed()))
+                .unwrap_or_default();
+            let banner = p
+                .banner
+                .as_deref()
+                .map(|b| format!(" {}", b.cyan()))
+                .unwrap_or_default();
             out.push_str(&format!(
-                "  {} {}/{} ({})\n",
+                "  {} {}/{} ({}){banner}{rtt}\n",
                 "OPEN".green().bold(),
                 p.port,
                 "tcp",
                 svc,
             ));
+            if let Some(ref note) = p.security_note {
+                out.push_str(&format!("       {} {}\n", "⚠".yellow(), note.yellow()));
+            }
         }
         out
     }
@@ -261,29 +1212,55 @@ impl HumanReadable for crate::port::ScanResult {
             "Port Scan: {} ({}) — {} open, {} closed\n\n",
             self.target, self.resolved_addr, self.open_count, self.closed_count,
         );
-        out.push_str(&format!("{:<8} {:<8} {:<20}\n", "PORT", "PROTO", "SERVICE"));
-        out.push_str(&format!("{}\n", "-".repeat(36)));
+        out.push_str(&format!(
+            "{:<8} {:<8} {:<20} {}\n",
+            "PORT", "PROTO", "SERVICE", "RTT"
+        ));
+        out.push_str(&format!("{}\n", "-".repeat(48)));
         for p in &self.ports {
             let svc = p.service.as_deref().unwrap_or("unknown");
-            out.push_str(&format!("{:<8} {:<8} {:<20}\n", p.port, "tcp", svc));
+            let rtt = p
+                .rtt_ms
+                .map(|r| format!("{r:.2} ms"))
+                .unwrap_or_else(|| "-".into());
+            out.push_str(&format!("{:<8} {:<8} {:<20} {}\n", p.port, "tcp", svc, rtt));
         }
         out
     }
 }
 
 impl HumanReadable for crate::scan::LanScanResult {
+    fn health(&self) -> HealthCheck {
+        let status = if self.hosts_found == 0 {
+            HealthStatus::Critical
+        } else if self.ping_sweep_error.is_some() {
+            HealthStatus::Warning
+        } else {
+            HealthStatus::Passing
+        };
+        HealthCheck::new(
+            status,
+            self.to_oneline(),
+            "critical if no host answered, warning if the ping sweep couldn't run",
+        )
+    }
+
+    fn to_ndjson(&self) -> Option<String> {
+        Some(ndjson_records(self, "subnet", "hosts", "host"))
+    }
+
     fn to_csv(&self) -> String {
-        let mut out = String::from("ip,hostname,open_ports,rtt_ms\n");
+        let mut out = csv_row(&[&"ip", &"hostname", &"open_ports", &"rtt_ms", &"ping_reply"]);
         for h in &self.hosts {
             let name = h.hostname.as_deref().unwrap_or("");
             let ports: Vec<String> = h.open_ports.iter().map(|p| p.to_string()).collect();
-            out.push_str(&format!(
-                "{},{},\"{}\",{:.2}\n",
-                h.ip,
-                name,
-                ports.join(";"),
-                h.rtt_ms
-            ));
+            out.push_str(&csv_row(&[
+                &h.ip,
+                &name,
+                &ports.join(";"),
+                &format!("{:.2}", h.rtt_ms),
+                &h.ping_reply,
+            ]));
         }
         out
     }
@@ -301,17 +1278,29 @@ impl HumanReadable for crate::scan::LanScanResult {
         for h in &self.hosts {
             let name = h.hostname.as_deref().unwrap_or("-");
             let ports: Vec<String> = h.open_ports.iter().map(|p| p.to_string()).collect();
+            let ports = if ports.is_empty() && h.ping_reply {
+                "none (ping reply)".to_string()
+            } else {
+                ports.join(",")
+            };
             out.push_str(&format!(
                 "{:<16} {:<20} {:<20} {}\n",
                 h.ip,
                 name,
-                ports.join(","),
+                ports,
                 format_ms(h.rtt_ms),
             ));
         }
         out
     }
 
+    fn to_oneline(&self) -> String {
+        format!(
+            "SCAN {}: {}/{} hosts up",
+            self.subnet, self.hosts_found, self.total_scanned
+        )
+    }
+
     fn to_human(&self) -> String {
         let mut out = String::new();
         out.push_str(&format!(
@@ -325,35 +1314,109 @@ impl HumanReadable for crate::scan::LanScanResult {
         for h in &self.hosts {
             let name = h.hostname.as_deref().unwrap_or("");
             let ports: Vec<String> = h.open_ports.iter().map(|p| p.to_string()).collect();
+            let ping = if h.ping_reply {
+                " ping".dimmed().to_string()
+            } else {
+                String::new()
+            };
             out.push_str(&format!(
-                "  {} {} ports=[{}] {}\n",
+                "  {} {} ports=[{}] {}{}\n",
                 h.ip.green(),
                 name,
                 ports.join(","),
                 format_ms(h.rtt_ms),
+                ping,
+            ));
+        }
+        if let Some(ref e) = self.ping_sweep_error {
+            out.push_str(&format!(
+                "  {} {}\n",
+                "⚠".yellow(),
+                format!("Ping sweep skipped, TCP only: {e}").dimmed()
+            ));
+        }
+        if let Some(ms) = self.adaptive_timeout_ms {
+            out.push_str(&format!(
+                "  {}\n",
+                format!("Adaptive timeout: {}", format_ms(ms)).dimmed()
             ));
         }
         out
     }
 }
 
+/// Each probe's round trip, e.g. `1.20 ms  1.30 ms  *`; results saved before
+/// per-probe timings only have the single `rtt_ms`.
+fn hop_rtts(hop: &crate::trace::TraceHop) -> String {
+    if hop.rtts_ms.is_empty() {
+        return hop.rtt_ms.map(|r| format!("{r:.2} ms")).unwrap_or_default();
+    }
+    hop.rtts_ms
+        .iter()
+        .map(|rtt| rtt.map_or_else(|| "*".to_string(), |r| format!("{r:.2} ms")))
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
 impl HumanReadable for crate::trace::TraceResult {
+    fn health(&self) -> HealthCheck {
+        let status = if !self.reached {
+            HealthStatus::Critical
+        } else if self.hops.iter().any(|h| h.timed_out) {
+            HealthStatus::Warning
+        } else {
+            HealthStatus::Passing
+        };
+        HealthCheck::new(
+            status,
+            self.to_oneline(),
+            "critical if the target wasn't reached, warning if any hop timed out",
+        )
+    }
+
+    fn to_ndjson(&self) -> Option<String> {
+        Some(ndjson_records(self, "target", "hops", "hop"))
+    }
+
     fn to_csv(&self) -> String {
-        let mut out = String::from("hop,addr,hostname,rtt_ms,timed_out\n");
+        let mut out = csv_row(&[
+            &"hop",
+            &"addr",
+            &"hostname",
+            &"rtt_ms",
+            &"timed_out",
+            &"rtts_ms",
+        ]);
         for hop in &self.hops {
             let addr = hop.addr.as_deref().unwrap_or("");
             let name = hop.hostname.as_deref().unwrap_or("");
             let rtt = hop.rtt_ms.map(|r| format!("{r:.2}")).unwrap_or_default();
-            out.push_str(&format!(
-                "{},{},{},{},{}\n",
-                hop.hop, addr, name, rtt, hop.timed_out
-            ));
+            // Space-separated, `*` for a lost probe
+            let rtts = hop
+                .rtts_ms
+                .iter()
+                .map(|rtt| rtt.map_or_else(|| "*".to_string(), |r| format!("{r:.2}")))
+                .collect::<Vec<_>>()
+                .join(" ");
+            out.push_str(&csv_row(&[
+                &hop.hop,
+                &addr,
+                &name,
+                &rtt,
+                &hop.timed_out,
+                &rtts,
+            ]));
         }
         out
     }
 
     fn to_table(&self) -> String {
-        let mut out = format!("Traceroute: {} ({})\n\n", self.target, self.resolved_addr);
+        let mut out = format!(
+            "Traceroute: {} ({}) via {}\n\n",
+            self.target,
+            self.resolved_addr,
+            self.method.label()
+        );
         out.push_str(&format!(
             "{:<5} {:<16} {:<30} {}\n",
             "HOP", "ADDRESS", "HOSTNAME", "RTT"
@@ -365,9 +1428,8 @@ impl HumanReadable for crate::trace::TraceResult {
             } else {
                 let addr = hop.addr.as_deref().unwrap_or("???");
                 let name = hop.hostname.as_deref().unwrap_or("-");
-                let rtt = hop
-                    .rtt_ms
-                    .map(|r| format!("{r:.2} ms"))
+                let rtt = Some(hop_rtts(hop))
+                    .filter(|r| !r.is_empty())
                     .unwrap_or_else(|| "-".into());
                 out.push_str(&format!(
                     "{:<5} {:<16} {:<30} {}\n",
@@ -383,12 +1445,22 @@ impl HumanReadable for crate::trace::TraceResult {
         out
     }
 
+    fn to_oneline(&self) -> String {
+        let verdict = if self.reached {
+            "reached"
+        } else {
+            "not reached"
+        };
+        format!("TRACE {}: {} hops, {verdict}", self.target, self.hops.len())
+    }
+
     fn to_human(&self) -> String {
         let mut out = format!(
-            "{} {} ({})\n",
+            "{} {} ({}) via {}\n",
             "TRACEROUTE".yellow().bold(),
             self.target,
             self.resolved_addr,
+            self.method.label(),
         );
         for hop in &self.hops {
             if hop.timed_out {
@@ -400,8 +1472,13 @@ impl HumanReadable for crate::trace::TraceResult {
                     .as_deref()
                     .map(|h| format!(" ({h})"))
                     .unwrap_or_default();
-                let rtt = hop.rtt_ms.map(|r| format!("{r:.2} ms")).unwrap_or_default();
-                out.push_str(&format!("  {:>2}  {addr}{name}  {rtt}\n", hop.hop));
+                let rtt = hop_rtts(hop);
+                let owner = hop
+                    .owner
+                    .as_deref()
+                    .map(|o| format!("  [{o}]"))
+                    .unwrap_or_default();
+                out.push_str(&format!("  {:>2}  {addr}{name}  {rtt}{owner}\n", hop.hop));
             }
         }
         if self.reached {
@@ -413,26 +1490,244 @@ impl HumanReadable for crate::trace::TraceResult {
     }
 }
 
+/// `DNS=..ms Connect=..ms [TLS=..ms] TTFB=..ms Total=..ms` for the cold/warm comparison.
+fn warmup_timing(t: &crate::http::HttpTiming) -> String {
+    let tls = t
+        .tls_ms
+        .map(|ms| format!(" TLS={ms:.1}ms"))
+        .unwrap_or_default();
+    format!(
+        "DNS={:.1}ms Connect={:.1}ms{tls} TTFB={:.1}ms Total={:.1}ms",
+        t.dns_ms, t.connect_ms, t.ttfb_ms, t.total_ms
+    )
+}
+
+/// e.g. `gzip (3.2x, 120.0 KB→37.5 KB)`, or `none` for an uncompressed body.
+fn compression_summary(c: &crate::http::CompressionInfo) -> String {
+    let Some(ref encoding) = c.encoding else {
+        return "none".to_string();
+    };
+    let compressed = format_bytes(c.compressed_bytes as u64);
+    match (c.decompressed_bytes, c.ratio, &c.error) {
+        (Some(full), Some(ratio), _) => format!(
+            "{encoding} ({}x, {}→{compressed})",
+            format_number(ratio, 1),
+            format_bytes(full as u64)
+        ),
+        (_, _, Some(e)) => format!("{encoding} ({compressed}, could not decode: {e})"),
+        _ => format!("{encoding} ({compressed} on the wire, not decoded)"),
+    }
+}
+
+/// Response headers sorted by name, for a stable display order.
+fn sorted_headers(headers: &std::collections::HashMap<String, String>) -> Vec<(&String, &String)> {
+    let mut sorted: Vec<_> = headers.iter().collect();
+    sorted.sort();
+    sorted
+}
+
 impl HumanReadable for crate::http::HttpResult {
+    fn health(&self) -> HealthCheck {
+        let status = if self.status >= 500 || self.status == 0 || self.ttfb_exceeded() {
+            HealthStatus::Critical
+        } else if self.status >= 400 || self.content_length_mismatch.is_some() {
+            HealthStatus::Warning
+        } else {
+            HealthStatus::Passing
+        };
+        HealthCheck::new(
+            status,
+            format!(
+                "{} {}: {} {}",
+                self.method, self.url, self.status, self.status_text
+            ),
+            "critical on 5xx or TTFB over --max-ttfb, warning on 4xx or Content-Length mismatch",
+        )
+    }
+
+    fn prometheus_samples(&self) -> Vec<Sample> {
+        let url = [("url", self.url.as_str())];
+        let mut samples = vec![
+            Sample::new(
+                "netscout_http_status_code",
+                "HTTP status code of the final response.",
+                &url,
+                self.status as f64,
+            ),
+            Sample::new(
+                "netscout_http_body_bytes",
+                "Size of the response body in bytes.",
+                &url,
+                self.body_size as f64,
+            ),
+        ];
+        let t = &self.timing;
+        let phases = [
+            ("dns", Some(t.dns_ms)),
+            ("connect", Some(t.connect_ms)),
+            ("tls", t.tls_ms),
+            ("ttfb", Some(t.ttfb_ms)),
+            ("transfer", Some(t.transfer_ms)),
+            ("total", Some(t.total_ms)),
+        ];
+        for (phase, ms) in phases {
+            if let Some(ms) = ms {
+                samples.push(
+                    Sample::new(
+                        "netscout_http_duration_ms",
+                        "Time spent in each phase of the request in milliseconds.",
+                        &url,
+                        ms,
+                    )
+                    .with_label("phase", phase),
+                );
+            }
+        }
+        samples
+    }
+
     fn to_csv(&self) -> String {
-        let mut out = String::from("field,value\n");
-        out.push_str(&format!("url,{}\n", self.url));
-        out.push_str(&format!("method,{}\n", self.method));
-        out.push_str(&format!("status,{}\n", self.status));
-        out.push_str(&format!("body_size,{}\n", self.body_size));
-        out.push_str(&format!("dns_ms,{:.1}\n", self.timing.dns_ms));
-        out.push_str(&format!("connect_ms,{:.1}\n", self.timing.connect_ms));
-        out.push_str(&format!("ttfb_ms,{:.1}\n", self.timing.ttfb_ms));
-        out.push_str(&format!("total_ms,{:.1}\n", self.timing.total_ms));
+        let mut out = csv_row(&[&"field", &"value"]);
+        out.push_str(&csv_row(&[&"url", &self.url]));
+        out.push_str(&csv_row(&[&"method", &self.method]));
+        out.push_str(&csv_row(&[&"status", &self.status]));
+        if self.final_url != self.url {
+            out.push_str(&csv_row(&[&"final_url", &self.final_url]));
+        }
+        if let Some(ref protocol) = self.protocol {
+            out.push_str(&csv_row(&[&"protocol", protocol]));
+        }
+        if let Some(ref tls) = self.tls_info {
+            out.push_str(&csv_row(&[&"tls_version", &tls.version]));
+            out.push_str(&csv_row(&[&"tls_cipher", &tls.cipher_suite]));
+            out.push_str(&csv_row(&[&"tls_alpn", &tls.alpn.as_deref().unwrap_or("")]));
+            out.push_str(&csv_row(&[&"tls_resumed", &tls.resumed]));
+        }
+        if let Some(ref c) = self.compression {
+            out.push_str(&csv_row(&[
+                &"content_encoding",
+                &c.encoding.as_deref().unwrap_or(""),
+            ]));
+            out.push_str(&csv_row(&[&"compressed_bytes", &c.compressed_bytes]));
+            if let Some(n) = c.decompressed_bytes {
+                out.push_str(&csv_row(&[&"decompressed_bytes", &n]));
+            }
+            if let Some(ratio) = c.ratio {
+                out.push_str(&csv_row(&[&"compression_ratio", &format!("{ratio:.2}")]));
+            }
+        }
+        out.push_str(&csv_row(&[&"body_size", &self.body_size]));
+        if let Some(mbps) = self.body_throughput_mbps {
+            out.push_str(&csv_row(&[&"body_throughput_mbps", &format!("{mbps:.2}")]));
+        }
+        out.push_str(&csv_row(&[
+            &"dns_ms",
+            &format!("{:.1}", self.timing.dns_ms),
+        ]));
+        out.push_str(&csv_row(&[
+            &"connect_ms",
+            &format!("{:.1}", self.timing.connect_ms),
+        ]));
+        out.push_str(&csv_row(&[
+            &"ttfb_ms",
+            &format!("{:.1}", self.timing.ttfb_ms),
+        ]));
+        out.push_str(&csv_row(&[
+            &"total_ms",
+            &format!("{:.1}", self.timing.total_ms),
+        ]));
+        if let Some(ref w) = self.warmup {
+            out.push_str(&csv_row(&[
+                &"cold_dns_ms",
+                &format!("{:.1}", w.cold.dns_ms),
+            ]));
+            out.push_str(&csv_row(&[
+                &"cold_connect_ms",
+                &format!("{:.1}", w.cold.connect_ms),
+            ]));
+            if let Some(tls_ms) = w.cold.tls_ms {
+                out.push_str(&csv_row(&[&"cold_tls_ms", &format!("{tls_ms:.1}")]));
+            }
+            out.push_str(&csv_row(&[
+                &"cold_ttfb_ms",
+                &format!("{:.1}", w.cold.ttfb_ms),
+            ]));
+            out.push_str(&csv_row(&[
+                &"cold_total_ms",
+                &format!("{:.1}", w.cold.total_ms),
+            ]));
+            out.push_str(&csv_row(&[&"connection_reused", &w.connection_reused]));
+        }
         out
     }
 
     fn to_table(&self) -> String {
+        self.table(false)
+    }
+
+    fn to_oneline(&self) -> String {
+        let status = format!("{} {}", self.status, self.status_text);
+        let protocol = self
+            .protocol
+            .as_deref()
+            .map(|p| format!(" via {p}"))
+            .unwrap_or_default();
+        format!(
+            "HTTP {}: {}{protocol}, {:.0}ms",
+            self.url,
+            status.trim_end(),
+            self.timing.total_ms
+        )
+    }
+
+    fn to_human(&self) -> String {
+        self.human(false)
+    }
+}
+
+impl crate::http::HttpResult {
+    /// Human output that also lists the response headers (`http --show-headers`).
+    pub fn to_human_with_headers(&self) -> String {
+        self.human(true)
+    }
+
+    /// Table output that also lists the response headers.
+    pub fn to_table_with_headers(&self) -> String {
+        self.table(true)
+    }
+
+    fn table(&self, show_headers: bool) -> String {
         let mut out = format!(
             "HTTP {} {} — {} {}\n\n",
             self.method, self.url, self.status, self.status_text
         );
-        out.push_str(&format!("{:<15} {} bytes\n", "Body Size", self.body_size));
+        if let Some(ref protocol) = self.protocol {
+            out.push_str(&format!("{:<15} {protocol}\n", "Protocol"));
+        }
+        if let Some(ref tls) = self.tls_info {
+            out.push_str(&format!(
+                "{:<15} {} {}{}\n",
+                "TLS",
+                tls.version,
+                tls.cipher_suite,
+                if tls.resumed { " (resumed)" } else { "" }
+            ));
+        }
+        if let Some(ref c) = self.compression {
+            out.push_str(&format!(
+                "{:<15} {}\n",
+                "Compression",
+                compression_summary(c)
+            ));
+        }
+        out.push_str(&format!(
+            "{:<15} {} bytes\n",
+            "Body Size",
+            format_number(self.body_size as f64, 0)
+        ));
+        if let Some(mbps) = self.body_throughput_mbps {
+            out.push_str(&format!("{:<15} {}\n", "Throughput", format_rate(mbps)));
+        }
         out.push_str(&format!(
             "{:<15} {}\n",
             "DNS",
@@ -458,16 +1753,50 @@ impl HumanReadable for crate::http::HttpResult {
             "Total",
             format_ms(self.timing.total_ms)
         ));
+        if let Some(ref w) = self.warmup {
+            out.push_str(&format!(
+                "{:<15} {}{}\n",
+                "Cold total",
+                format_ms(w.cold.total_ms),
+                if w.connection_reused {
+                    " (warm request reused the connection)"
+                } else {
+                    ""
+                }
+            ));
+        }
+        if show_headers {
+            out.push_str("\nHeaders:\n");
+            for (name, value) in sorted_headers(&self.headers) {
+                out.push_str(&format!("  {name}: {value}\n"));
+            }
+        }
         if !self.redirects.is_empty() {
             out.push_str("\nRedirects:\n");
+            let mut cumulative = 0.0;
             for r in &self.redirects {
-                out.push_str(&format!("  {} → {}\n", r.status, r.url));
+                cumulative += r.timing.total_ms;
+                out.push_str(&format!(
+                    "  {} → {}  {} (cumulative {})\n",
+                    r.status,
+                    r.url,
+                    format_ms(r.timing.total_ms),
+                    format_ms(cumulative)
+                ));
             }
+            out.push_str(&format!(
+                "{:<15} {}\n",
+                "Chain total",
+                format_ms(self.redirect_chain_ms)
+            ));
+        }
+        if self.final_url != self.url {
+            out.push_str(&format!("{:<15} {}\n", "Final URL", self.final_url));
         }
         out
     }
 
-    fn to_human(&self) -> String {
+    fn human(&self, show_headers: bool) -> String {
         let mut out = format!("{} {} {}\n", "HTTP".blue().bold(), self.method, self.url);
         let status_color = if self.status < 300 {
             format!("{} {}", self.status, self.status_text)
@@ -482,38 +1811,225 @@ impl HumanReadable for crate::http::HttpResult {
                 .red()
                 .to_string()
         };
+        if let Some(ref idn) = self.idn {
+            out.push_str(&idn_lines(idn, "  "));
+        }
         out.push_str(&format!("  Status: {status_color}\n"));
-        out.push_str(&format!("  Body: {} bytes\n", self.body_size));
+        if let Some(ref protocol) = self.protocol {
+            let label = if protocol == "h2" {
+                "h2 (negotiated via ALPN)".green().to_string()
+            } else {
+                format!("{protocol} (server did not negotiate h2)")
+                    .yellow()
+                    .to_string()
+            };
+            out.push_str(&format!("  Protocol: {label}\n"));
+        }
+        if let Some(ref tls) = self.tls_info {
+            out.push_str("  TLS:\n");
+            out.push_str(&format!("    Version: {}\n", tls.version));
+            out.push_str(&format!("    Cipher: {}\n", tls.cipher_suite));
+            out.push_str(&format!(
+                "    ALPN: {}\n",
+                tls.alpn.as_deref().unwrap_or("(none)")
+            ));
+            let resumed = if tls.resumed {
+                "yes (session ticket reused)".green().to_string()
+            } else {
+                "no (full handshake)".to_string()
+            };
+            out.push_str(&format!("    Resumed: {resumed}\n"));
+        }
+        if show_headers {
+            out.push_str("  Headers:\n");
+            for (name, value) in sorted_headers(&self.headers) {
+                out.push_str(&format!("    {}: {value}\n", name.cyan()));
+            }
+        }
+        out.push_str(&format!(
+            "  Body: {} bytes\n",
+            format_number(self.body_size as f64, 0)
+        ));
+        if let Some(mbps) = self.body_throughput_mbps {
+            out.push_str(&format!("  Throughput: {}\n", format_rate(mbps).cyan()));
+        }
+        if let Some(ref c) = self.compression {
+            let summary = compression_summary(c);
+            let summary = match (&c.encoding, &c.error) {
+                (None, _) => format!("{summary} (server ignored Accept-Encoding)")
+                    .yellow()
+                    .to_string(),
+                (_, Some(_)) => summary.red().to_string(),
+                _ => summary.green().to_string(),
+            };
+            out.push_str(&format!("  Compression: {summary}\n"));
+        }
+        if let Some((declared, actual)) = self.content_length_mismatch {
+            out.push_str(&format!(
+                "  {} {}\n",
+                "⚠".yellow(),
+                format!("Content-Length declared {declared} bytes but {actual} were received")
+                    .yellow()
+            ));
+        }
+        if let Some(ref options) = self.options {
+            let allow = if options.allow.is_empty() {
+                "(no Allow header)".dimmed().to_string()
+            } else {
+                options.allow.join(", ").cyan().bold().to_string()
+            };
+            out.push_str(&format!("  Allow: {allow}\n"));
+            if options.cors.is_empty() {
+                out.push_str(&format!("  CORS: {}\n", "none".dimmed()));
+            } else {
+                out.push_str("  CORS:\n");
+                for (k, v) in &options.cors {
+                    out.push_str(&format!("    {}: {v}\n", k.cyan()));
+                }
+            }
+        }
         out.push_str(&format!(
             "  Timing: DNS={:.1}ms Connect={:.1}ms TTFB={:.1}ms Total={:.1}ms\n",
             self.timing.dns_ms, self.timing.connect_ms, self.timing.ttfb_ms, self.timing.total_ms,
         ));
+        if let Some(ref w) = self.warmup {
+            let reuse = if w.connection_reused {
+                "connection reused".green().to_string()
+            } else {
+                "new connection".yellow().to_string()
+            };
+            out.push_str(&format!("  Cold vs warm ({reuse}):\n"));
+            out.push_str(&format!("    Cold: {}\n", warmup_timing(&w.cold)));
+            out.push_str(&format!("    Warm: {}\n", warmup_timing(&self.timing)));
+            out.push_str(&format!(
+                "    Setup cost: {:.1}ms\n",
+                (w.cold.total_ms - self.timing.total_ms).max(0.0)
+            ));
+        }
+        if let Some(max) = self.max_ttfb_ms.filter(|_| self.ttfb_exceeded()) {
+            out.push_str(&format!(
+                "  {} {}\n",
+                "✗".red(),
+                format!(
+                    "TTFB {:.1}ms exceeds limit of {max:.0}ms",
+                    self.timing.ttfb_ms
+                )
+                .red()
+            ));
+        }
         if !self.redirects.is_empty() {
             out.push_str("  Redirects:\n");
+            let mut cumulative = 0.0;
             for r in &self.redirects {
-                out.push_str(&format!("    {} → {}\n", r.status, r.url));
+                cumulative += r.timing.total_ms;
+                out.push_str(&format!(
+                    "    {} → {}  {} {}\n",
+                    r.status,
+                    r.url,
+                    format_ms(r.timing.total_ms),
+                    format!("(cumulative {})", format_ms(cumulative)).dimmed()
+                ));
+                if let Some(ref idn) = r.idn {
+                    out.push_str(&idn_lines(idn, "      "));
+                }
             }
+            out.push_str(&format!(
+                "  Chain total: {}\n",
+                format_ms(self.redirect_chain_ms)
+            ));
+        }
+        if self.final_url != self.url {
+            out.push_str(&format!("  Final URL: {}\n", self.final_url.cyan()));
         }
         out
     }
 }
 
 impl HumanReadable for crate::cert::CertResult {
+    fn health(&self) -> HealthCheck {
+        let Some(leaf) = self.certificate_chain.first() else {
+            return HealthCheck::from_error(&format!(
+                "{}:{}: no certificate presented",
+                self.host, self.port
+            ));
+        };
+        let status = if !self.chain_valid {
+            HealthStatus::Critical
+        } else if !self.hostname_matches {
+            HealthStatus::Warning.max(health::cert_expiry_status(leaf.days_until_expiry))
+        } else {
+            health::cert_expiry_status(leaf.days_until_expiry)
+        };
+        HealthCheck::new(
+            status,
+            format!(
+                "{}:{}: certificate expires in {} days ({})",
+                self.host, self.port, leaf.days_until_expiry, leaf.not_after
+            ),
+            format!(
+                "warning < {} days or a hostname mismatch, critical < {} days or an untrusted chain",
+                health::CERT_WARNING_DAYS,
+                health::CERT_CRITICAL_DAYS
+            ),
+        )
+    }
+
+    fn prometheus_samples(&self) -> Vec<Sample> {
+        let port = self.port.to_string();
+        let labels = [("host", self.host.as_str()), ("port", port.as_str())];
+        let flag = |b: bool| if b { 1.0 } else { 0.0 };
+        let mut samples = Vec::new();
+        if let Some(leaf) = self.certificate_chain.first() {
+            samples.push(Sample::new(
+                "netscout_cert_days_until_expiry",
+                "Days until the leaf certificate expires.",
+                &labels,
+                leaf.days_until_expiry as f64,
+            ));
+        }
+        samples.extend([
+            Sample::new(
+                "netscout_cert_chain_valid",
+                "Whether the chain validates against the trusted roots (1) or not (0).",
+                &labels,
+                flag(self.chain_valid),
+            ),
+            Sample::new(
+                "netscout_cert_hostname_match",
+                "Whether the leaf certificate covers the host name (1) or not (0).",
+                &labels,
+                flag(self.hostname_matches),
+            ),
+            Sample::new(
+                "netscout_cert_connect_ms",
+                "Time to connect and complete the TLS handshake in milliseconds.",
+                &labels,
+                self.connection_time_ms,
+            ),
+        ]);
+        samples
+    }
+
     fn to_csv(&self) -> String {
-        let mut out =
-            String::from("index,subject,issuer,not_before,not_after,days_until_expiry,serial\n");
+        let mut out = csv_row(&[
+            &"index",
+            &"subject",
+            &"issuer",
+            &"not_before",
+            &"not_after",
+            &"days_until_expiry",
+            &"serial",
+        ]);
         for (i, cert) in self.certificate_chain.iter().enumerate() {
-            // Quote fields that may contain commas
-            out.push_str(&format!(
-                "{},\"{}\",\"{}\",{},{},{},{}\n",
-                i,
-                cert.subject,
-                cert.issuer,
-                cert.not_before,
-                cert.not_after,
-                cert.days_until_expiry,
-                cert.serial,
-            ));
+            out.push_str(&csv_row(&[
+                &i,
+                &cert.subject,
+                &cert.issuer,
+                &cert.not_before,
+                &cert.not_after,
+                &cert.days_until_expiry,
+                &cert.serial,
+            ]));
         }
         out
     }
@@ -547,12 +2063,32 @@ impl HumanReadable for crate::cert::CertResult {
                 i, subj, iss, cert.not_after, cert.days_until_expiry,
             ));
         }
+        if self.certificate_chain.iter().any(|c| !c.sha256.is_empty()) {
+            out.push_str(&format!("\n{:<4} {:<8} {}\n", "#", "DIGEST", "FINGERPRINT"));
+            for (i, cert) in self.certificate_chain.iter().enumerate() {
+                out.push_str(&format!("{:<4} {:<8} {}\n", i, "SHA-256", cert.sha256));
+                out.push_str(&format!("{:<4} {:<8} {}\n", "", "SHA-1", cert.sha1));
+            }
+        }
+        if let Some(problem) = self.hostname_problem() {
+            out.push_str(&format!("\nWarning: {problem}\n"));
+        }
         if let Some(w) = &self.warning {
             out.push_str(&format!("\nWarning: {w}\n"));
         }
         out
     }
 
+    fn to_oneline(&self) -> String {
+        match self.certificate_chain.first() {
+            Some(leaf) => format!(
+                "CERT {}:{}: expires in {} days",
+                self.host, self.port, leaf.days_until_expiry
+            ),
+            None => format!("CERT {}:{}: no certificate", self.host, self.port),
+        }
+    }
+
     fn to_human(&self) -> String {
         let mut out = format!(
             "{} {}:{}\n",
@@ -563,27 +2099,173 @@ impl HumanReadable for crate::cert::CertResult {
         out.push_str(&format!("  TLS Version: {}\n", self.tls_version));
         out.push_str(&format!("  Cipher: {}\n", self.cipher_suite));
         out.push_str(&format!("  Connect: {:.1} ms\n", self.connection_time_ms));
+        if let Some(ref h) = self.handshakes {
+            out.push_str(&format!(
+                "  Handshakes: {} — min/avg/p95/max = {:.2}/{:.2}/{:.2}/{:.2} ms\n",
+                h.count, h.min_ms, h.avg_ms, h.p95_ms, h.max_ms,
+            ));
+        }
         for (i, cert) in self.certificate_chain.iter().enumerate() {
             out.push_str(&format!("  Certificate #{i}:\n"));
             out.push_str(&format!("    Subject: {}\n", cert.subject));
             out.push_str(&format!("    Issuer: {}\n", cert.issuer));
             out.push_str(&format!("    Serial: {}\n", cert.serial));
+            if !cert.sha256.is_empty() {
+                out.push_str(&format!("    SHA-256: {}\n", cert.sha256));
+                out.push_str(&format!("    SHA-1: {}\n", cert.sha1));
+            }
+        }
+        if !self.chain_valid {
+            out.push_str(&format!(
+                "  {} {}\n",
+                "✗".red(),
+                "Chain does not validate against the trusted roots".red()
+            ));
+        }
+        if let Some(problem) = self.hostname_problem() {
+            out.push_str(&format!("  {} {}\n", "✗".yellow(), problem.yellow()));
+        }
+        if let Some(w) = &self.warning {
+            out.push_str(&format!("  {}\n", w.yellow()));
+        }
+        out
+    }
+}
+
+impl HumanReadable for crate::cert::ExpiryReport {
+    fn health(&self) -> HealthCheck {
+        let status = if self.critical_count() > 0 {
+            HealthStatus::Critical
+        } else if !self.expiring.is_empty() {
+            HealthStatus::Warning
+        } else {
+            HealthStatus::Passing
+        };
+        HealthCheck::new(
+            status,
+            self.to_oneline(),
+            format!(
+                "warning if any expire within {} days, critical within {} days",
+                self.window_days, self.critical_days
+            ),
+        )
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = csv_row(&[
+            &"host",
+            &"port",
+            &"subject",
+            &"not_after",
+            &"days_until_expiry",
+            &"critical",
+        ]);
+        for c in &self.expiring {
+            out.push_str(&csv_row(&[
+                &c.host,
+                &c.port,
+                &c.subject,
+                &c.not_after,
+                &c.days_until_expiry,
+                &c.critical,
+            ]));
+        }
+        out
+    }
+
+    fn to_table(&self) -> String {
+        let mut out = format!(
+            "Expiring within {} days: {} of {} checked\n\n",
+            self.window_days,
+            self.expiring.len(),
+            self.checked
+        );
+        out.push_str(&format!(
+            "{:<30} {:<12} {:<10} {}\n",
+            "HOST", "EXPIRES", "DAYS LEFT", "SUBJECT"
+        ));
+        out.push_str(&format!("{}\n", "-".repeat(80)));
+        for c in &self.expiring {
+            out.push_str(&format!(
+                "{:<30} {:<12} {:<10} {}\n",
+                format!("{}:{}", c.host, c.port),
+                c.not_after,
+                c.days_until_expiry,
+                c.subject
+            ));
+        }
+        out
+    }
+
+    fn to_oneline(&self) -> String {
+        format!(
+            "CERT {} of {} expiring within {} days ({} critical)",
+            self.expiring.len(),
+            self.checked,
+            self.window_days,
+            self.critical_count()
+        )
+    }
+
+    fn to_human(&self) -> String {
+        let mut out = format!(
+            "{} within {} days: {} of {} checked\n",
+            "CERT EXPIRY".green().bold(),
+            self.window_days,
+            self.expiring.len(),
+            self.checked
+        );
+        if self.expiring.is_empty() {
+            out.push_str(&format!("  {} Nothing to renew\n", "✓".green()));
         }
-        if let Some(w) = &self.warning {
-            out.push_str(&format!("  {}\n", w.yellow()));
+        for c in &self.expiring {
+            let days = if c.days_until_expiry < 0 {
+                format!("expired {} days ago", -c.days_until_expiry)
+            } else {
+                format!("{} days", c.days_until_expiry)
+            };
+            let days = if c.critical {
+                days.red().bold().to_string()
+            } else {
+                days.yellow().to_string()
+            };
+            out.push_str(&format!(
+                "  {:<30} {days} ({})\n",
+                format!("{}:{}", c.host, c.port),
+                c.not_after
+            ));
         }
         out
     }
 }
 
 impl HumanReadable for crate::speed::SpeedResult {
+    fn health(&self) -> HealthCheck {
+        let status = if self.download_mbps.is_none() && self.upload_mbps.is_none() {
+            HealthStatus::Critical
+        } else if !self.is_complete() {
+            HealthStatus::Warning
+        } else {
+            HealthStatus::Passing
+        };
+        HealthCheck::new(
+            status,
+            format!(
+                "download {}, upload {}",
+                self.download_display(),
+                self.upload_display()
+            ),
+            "critical if nothing was measured, warning if only one direction was",
+        )
+    }
+
     fn to_human(&self) -> String {
         let mut out = format!("{}\n", "SPEED TEST".cyan().bold());
         if let Some(dl) = self.download_mbps {
-            out.push_str(&format!("  Download: {:.2} Mbps\n", dl));
+            out.push_str(&format!("  Download: {}\n", format_rate(dl)));
         }
         if let Some(ul) = self.upload_mbps {
-            out.push_str(&format!("  Upload: {:.2} Mbps\n", ul));
+            out.push_str(&format!("  Upload: {}\n", format_rate(ul)));
         }
         if let Some(bytes) = self.download_bytes {
             out.push_str(&format!("  Downloaded: {}\n", format_bytes(bytes)));
@@ -591,6 +2273,9 @@ impl HumanReadable for crate::speed::SpeedResult {
         if let Some(ms) = self.download_time_ms {
             out.push_str(&format!("  Duration: {}\n", format_ms(ms)));
         }
+        if let Some(w) = &self.warning {
+            out.push_str(&format!("  {} {}\n", "⚠".yellow(), w.yellow()));
+        }
         out
     }
 
@@ -613,7 +2298,7 @@ impl HumanReadable for crate::speed::SpeedResult {
             out.push_str(&format!(
                 "{:<15} {:<15} {:<15} {}\n",
                 "Download",
-                format!("{:.2} Mbps", dl),
+                format_rate(dl),
                 bytes,
                 time
             ));
@@ -630,55 +2315,73 @@ impl HumanReadable for crate::speed::SpeedResult {
             out.push_str(&format!(
                 "{:<15} {:<15} {:<15} {}\n",
                 "Upload",
-                format!("{:.2} Mbps", ul),
+                format_rate(ul),
                 bytes,
                 time
             ));
         }
+        if let Some(w) = &self.warning {
+            out.push_str(&format!("\nWarning: {w}\n"));
+        }
         out
     }
 
     fn to_csv(&self) -> String {
-        let mut out = String::from("direction,speed_mbps,bytes,time_ms\n");
+        let mut out = csv_row(&[&"direction", &"speed_mbps", &"bytes", &"time_ms"]);
         if let Some(dl) = self.download_mbps {
-            out.push_str(&format!(
-                "download,{:.2},{},{:.1}\n",
-                dl,
-                self.download_bytes.unwrap_or(0),
-                self.download_time_ms.unwrap_or(0.0),
-            ));
+            out.push_str(&csv_row(&[
+                &"download",
+                &format!("{dl:.2}"),
+                &self.download_bytes.unwrap_or(0),
+                &format!("{:.1}", self.download_time_ms.unwrap_or(0.0)),
+            ]));
         }
         if let Some(ul) = self.upload_mbps {
-            out.push_str(&format!(
-                "upload,{:.2},{},{:.1}\n",
-                ul,
-                self.upload_bytes.unwrap_or(0),
-                self.upload_time_ms.unwrap_or(0.0),
-            ));
+            out.push_str(&csv_row(&[
+                &"upload",
+                &format!("{ul:.2}"),
+                &self.upload_bytes.unwrap_or(0),
+                &format!("{:.1}", self.upload_time_ms.unwrap_or(0.0)),
+            ]));
         }
         out
     }
 }
 
 impl HumanReadable for crate::whois::WhoisResult {
+    fn health(&self) -> HealthCheck {
+        let status = if self.raw.trim().is_empty() {
+            HealthStatus::Critical
+        } else if self.partial {
+            HealthStatus::Warning
+        } else {
+            HealthStatus::Passing
+        };
+        HealthCheck::new(
+            status,
+            self.to_oneline(),
+            "critical on an empty response, warning if the response was cut off",
+        )
+    }
+
     fn to_csv(&self) -> String {
-        let mut out = String::from("field,value\n");
-        out.push_str(&format!("target,{}\n", self.target));
-        out.push_str(&format!("server,{}\n", self.server));
+        let mut out = csv_row(&[&"field", &"value"]);
+        out.push_str(&csv_row(&[&"target", &self.target]));
+        out.push_str(&csv_row(&[&"server", &self.server]));
         if let Some(r) = &self.registrar {
-            out.push_str(&format!("registrar,{r}\n"));
+            out.push_str(&csv_row(&[&"registrar", r]));
         }
         if let Some(d) = &self.creation_date {
-            out.push_str(&format!("created,{d}\n"));
+            out.push_str(&csv_row(&[&"created", d]));
         }
         if let Some(d) = &self.expiry_date {
-            out.push_str(&format!("expires,{d}\n"));
+            out.push_str(&csv_row(&[&"expires", d]));
         }
         if let Some(d) = &self.updated_date {
-            out.push_str(&format!("updated,{d}\n"));
+            out.push_str(&csv_row(&[&"updated", d]));
         }
         for ns in &self.nameservers {
-            out.push_str(&format!("nameserver,{ns}\n"));
+            out.push_str(&csv_row(&[&"nameserver", ns]));
         }
         out
     }
@@ -737,6 +2440,14 @@ impl HumanReadable for crate::whois::WhoisResult {
             }
         }
         out.push_str(&format!("  Query time: {:.1} ms\n", self.query_time_ms));
+        if self.partial {
+            out.push_str(&format!(
+                "  {} {}\n",
+                "⚠".yellow(),
+                "Partial response: the server stopped sending without closing the connection"
+                    .yellow()
+            ));
+        }
         out
     }
 }
@@ -773,6 +2484,18 @@ mod tests {
         assert!(format_bytes(5_000_000).contains("MB"));
     }
 
+    #[test]
+    fn test_format_rate_units() {
+        assert_eq!(format_rate(0.0), "0.0 Kbps");
+        assert_eq!(format_rate(0.0123), "12.3 Kbps");
+        assert_eq!(format_rate(0.999), "999.0 Kbps");
+        assert_eq!(format_rate(1.0), "1.00 Mbps");
+        assert_eq!(format_rate(123.456), "123.46 Mbps");
+        assert_eq!(format_rate(999.99), "999.99 Mbps");
+        assert_eq!(format_rate(1000.0), "1.00 Gbps");
+        assert_eq!(format_rate(9400.0), "9.40 Gbps");
+    }
+
     #[test]
     fn test_format_bytes_edge_cases() {
         assert_eq!(format_bytes(0), "0 B");
@@ -811,6 +2534,16 @@ mod tests {
         assert_eq!(status_icon(false), "✗");
     }
 
+    #[test]
+    fn test_idn_lines() {
+        let out = idn_lines(&crate::idn::analyze_idn("münchen.de"), "  ");
+        assert!(out.contains("IDN: münchen.de (xn--mnchen-3ya.de)"));
+        assert!(!out.contains("⚠"));
+        let out = idn_lines(&crate::idn::analyze_idn("p\u{0430}ypal.com"), "  ");
+        assert!(out.contains("⚠"));
+        assert!(out.contains("Cyrillic"));
+    }
+
     #[test]
     fn test_kv_format() {
         let result = kv("Key", "Value");
@@ -866,6 +2599,11 @@ mod tests {
             truncated: false,
             recursion_available: true,
             authenticated_data: false,
+            client_subnet: None,
+            client_subnet_scope: None,
+            idn: None,
+            cname_chain: vec![],
+            timestamp: String::new(),
         };
 
         let output = format_output(&result, OutputFormat::Json);
@@ -895,7 +2633,13 @@ mod tests {
             avg_ms: Some(25.0),
             max_ms: Some(25.0),
             stddev_ms: Some(0.0),
+            mdev_ms: Some(0.0),
             jitter_ms: None,
+            p50_ms: None,
+            p95_ms: None,
+            p99_ms: None,
+            owner: None,
+            timestamp: String::new(),
         };
 
         let output = format_output(&stats, OutputFormat::Human);
@@ -916,10 +2660,13 @@ mod tests {
                 open: true,
                 service: Some("http".to_string()),
                 rtt_ms: Some(10.0),
+                security_note: None,
+                banner: None,
             }],
             open_count: 1,
             closed_count: 0,
             scan_time_ms: 100.0,
+            timestamp: String::new(),
         };
 
         let output = format_output(&result, OutputFormat::Table);
@@ -949,10 +2696,760 @@ mod tests {
             truncated: false,
             recursion_available: true,
             authenticated_data: false,
+            client_subnet: None,
+            client_subnet_scope: None,
+            idn: None,
+            cname_chain: vec![],
+            timestamp: String::new(),
         };
 
         let output = format_output(&result, OutputFormat::Csv);
         assert!(output.contains("type,name,ttl,value"));
         assert!(output.contains("A,example.com,300,93.184.216.34"));
     }
+
+    #[test]
+    fn test_format_output_prometheus() {
+        use crate::ping::{PingProbe, PingStats};
+
+        let stats = |target: &str| PingStats {
+            target: target.to_string(),
+            resolved_addr: "192.0.2.1".to_string(),
+            probes: vec![PingProbe {
+                seq: 0,
+                success: true,
+                rtt_ms: Some(12.3),
+                addr: "192.0.2.1".to_string(),
+            }],
+            sent: 2,
+            received: 1,
+            lost: 1,
+            loss_percent: 50.0,
+            min_ms: Some(12.3),
+            avg_ms: Some(12.3),
+            max_ms: Some(12.3),
+            stddev_ms: Some(0.0),
+            mdev_ms: Some(0.0),
+            jitter_ms: None,
+            p50_ms: None,
+            p95_ms: None,
+            p99_ms: None,
+            owner: None,
+            timestamp: String::new(),
+        };
+
+        let output = format_output(&stats("example.com"), OutputFormat::Prometheus);
+        assert!(output.starts_with(
+            "# HELP netscout_ping_packets_sent Echo requests sent.\n\
+             # TYPE netscout_ping_packets_sent gauge\n\
+             netscout_ping_packets_sent{target=\"example.com\"} 2\n"
+        ));
+        assert!(output.contains("\nnetscout_ping_loss_percent{target=\"example.com\"} 50\n"));
+        assert!(output.contains("\n# TYPE netscout_ping_rtt_ms gauge\n"));
+        assert!(output.contains("\nnetscout_ping_rtt_ms{target=\"example.com\"} 12.3\n"));
+        // No sample for a statistic that wasn't measured
+        assert!(!output.contains("netscout_ping_jitter_ms"));
+
+        // Label values are escaped, and several targets share one HELP/TYPE per family
+        let many = format_many(&[stats("a"), stats("b\"c")], OutputFormat::Prometheus);
+        assert_eq!(many.matches("# TYPE netscout_ping_rtt_ms gauge").count(), 1);
+        assert!(many.contains(
+            "netscout_ping_rtt_ms{target=\"a\"} 12.3\nnetscout_ping_rtt_ms{target=\"b\\\"c\"} 12.3\n"
+        ));
+
+        let unsupported = crate::netif::GatewayResult {
+            gateways: vec![],
+            timestamp: String::new(),
+        };
+        assert_eq!(
+            format_output(&unsupported, OutputFormat::Prometheus),
+            "# Prometheus output not supported for this command\n"
+        );
+    }
+
+    #[test]
+    fn test_format_output_ndjson() {
+        use crate::dns::{DnsRecord, DnsResult};
+        use crate::whois::WhoisResult;
+
+        let record = |value: &str| DnsRecord {
+            name: "example.com".to_string(),
+            record_type: "A".to_string(),
+            ttl: 300,
+            value: value.to_string(),
+        };
+        let result = DnsResult {
+            domain: "example.com".to_string(),
+            resolver: "8.8.8.8".to_string(),
+            record_type: "A".to_string(),
+            records: vec![record("192.0.2.1"), record("192.0.2.2")],
+            query_time_ms: 25.0,
+            response_code: "NOERROR".to_string(),
+            truncated: false,
+            recursion_available: true,
+            authenticated_data: false,
+            client_subnet: None,
+            client_subnet_scope: None,
+            idn: None,
+            cname_chain: vec![],
+            timestamp: String::new(),
+        };
+
+        // One compact object per DNS record, then the summary
+        let output = format_output(&result, OutputFormat::Ndjson);
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["record"], "record");
+        assert_eq!(lines[0]["domain"], "example.com");
+        assert_eq!(lines[0]["value"], "192.0.2.1");
+        assert_eq!(lines[2]["record"], "summary");
+        assert_eq!(lines[2]["response_code"], "NOERROR");
+        assert!(lines[2].get("records").is_none());
+        assert!(output.ends_with("\n"));
+
+        // Several results run on without blank lines
+        let many = format_many(&[result.clone(), result], OutputFormat::Ndjson);
+        assert_eq!(many.lines().count(), 6);
+        assert!(many.lines().all(|l| l.starts_with('{')));
+
+        // Results without records are a single line
+        let whois = WhoisResult {
+            target: "example.com".to_string(),
+            server: "whois.example".to_string(),
+            registrar: None,
+            creation_date: None,
+            expiry_date: None,
+            updated_date: None,
+            nameservers: vec![],
+            status: vec![],
+            raw: String::new(),
+            query_time_ms: 0.0,
+            partial: false,
+            timestamp: String::new(),
+        };
+        let output = format_output(&whois, OutputFormat::Ndjson);
+        assert_eq!(output.lines().count(), 1);
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["target"], "example.com");
+
+        // A scan without any open ports still reports its summary
+        let scan = crate::port::ScanResult {
+            target: "192.0.2.7".to_string(),
+            resolved_addr: "192.0.2.7".to_string(),
+            ports: vec![],
+            open_count: 0,
+            closed_count: 3,
+            scan_time_ms: 12.0,
+            timestamp: String::new(),
+        };
+        let output = format_output(&scan, OutputFormat::Ndjson);
+        assert_eq!(output.lines().count(), 1);
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["record"], "summary");
+        assert_eq!(value["target"], "192.0.2.7");
+        assert_eq!(value["closed_count"], 3);
+    }
+
+    #[test]
+    fn test_tsv_uses_tabs_and_quotes_tab_fields() {
+        use crate::whois::WhoisResult;
+
+        let result = WhoisResult {
+            target: "example.com".to_string(),
+            server: "whois.example".to_string(),
+            registrar: Some("Example\tRegistrar, Inc.".to_string()),
+            creation_date: None,
+            expiry_date: None,
+            updated_date: None,
+            nameservers: vec![],
+            status: vec![],
+            raw: String::new(),
+            query_time_ms: 0.0,
+            partial: false,
+            timestamp: String::new(),
+        };
+        set_csv_delimiter('\t');
+        let tsv = format_output(&result, OutputFormat::Csv);
+        set_csv_delimiter(',');
+        let csv = format_output(&result, OutputFormat::Csv);
+
+        assert!(tsv.starts_with("field\tvalue\n"));
+        assert!(tsv.contains("target\texample.com\n"));
+        assert!(tsv.contains("registrar\t\"Example\tRegistrar, Inc.\"\n"));
+        assert!(csv.contains("registrar,\"Example\tRegistrar, Inc.\"\n"));
+        assert!(csv.contains("target,example.com\n"));
+    }
+
+    #[test]
+    fn test_csv_field_escapes_quotes() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+    }
+
+    #[test]
+    fn test_parse_csv_delimiter() {
+        assert_eq!(parse_csv_delimiter(";"), Ok(';'));
+        assert_eq!(parse_csv_delimiter("\\t"), Ok('\t'));
+        assert_eq!(parse_csv_delimiter("tab"), Ok('\t'));
+        assert_eq!(parse_csv_delimiter("\t"), Ok('\t'));
+        assert!(parse_csv_delimiter("ab").is_err());
+        assert!(parse_csv_delimiter("\"").is_err());
+        assert!(parse_csv_delimiter("").is_err());
+    }
+
+    #[test]
+    fn test_dns_human_groups_cname_chain() {
+        use crate::dns::{DnsRecord, DnsResult};
+
+        let record = |name: &str, rtype: &str, value: &str| DnsRecord {
+            name: name.to_string(),
+            record_type: rtype.to_string(),
+            ttl: 300,
+            value: value.to_string(),
+        };
+        let result = DnsResult {
+            domain: "www.example.com".to_string(),
+            resolver: "8.8.8.8".to_string(),
+            record_type: "A".to_string(),
+            records: vec![
+                record("www.example.com", "CNAME", "cdn.example.net"),
+                record("cdn.example.net", "A", "192.0.2.1"),
+                record("cdn.example.net", "A", "192.0.2.2"),
+            ],
+            query_time_ms: 5.0,
+            response_code: "NOERROR".to_string(),
+            truncated: false,
+            recursion_available: true,
+            authenticated_data: false,
+            client_subnet: None,
+            client_subnet_scope: None,
+            idn: None,
+            cname_chain: vec!["cdn.example.net".to_string()],
+            timestamp: String::new(),
+        };
+
+        let output = result.to_human();
+        assert!(output.contains("CNAME chain: www.example.com → cdn.example.net"));
+        assert!(output
+            .contains("  cdn.example.net:\n    A TTL=300 192.0.2.1\n    A TTL=300 192.0.2.2\n"));
+        assert_eq!(output.matches("192.0.2.1").count(), 1);
+    }
+
+    #[test]
+    fn test_port_scan_rtt_column() {
+        use crate::port::{PortResult, ScanResult};
+        let port = |port: u16, service: &str, rtt_ms: Option<f64>| PortResult {
+            port,
+            open: true,
+            service: Some(service.to_string()),
+            rtt_ms,
+            security_note: None,
+            banner: None,
+        };
+        let result = ScanResult {
+            target: "example.com".to_string(),
+            resolved_addr: "192.0.2.9".to_string(),
+            ports: vec![port(22, "ssh", Some(1.234)), port(443, "https", None)],
+            open_count: 2,
+            closed_count: 98,
+            scan_time_ms: 120.0,
+            timestamp: String::new(),
+        };
+        let csv = result.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("port,proto,service,rtt_ms,banner"));
+        assert_eq!(lines.next(), Some("22,tcp,ssh,1.23,"));
+        assert_eq!(lines.next(), Some("443,tcp,https,,"));
+        let table = result.to_table();
+        assert!(table.contains("RTT"));
+        assert!(table.contains("1.23 ms"));
+        assert!(result.to_human().contains("1.23 ms"));
+    }
+
+    #[test]
+    fn test_port_scan_banner_column() {
+        use crate::port::{PortResult, ScanResult};
+        let result = ScanResult {
+            target: "example.com".to_string(),
+            resolved_addr: "192.0.2.9".to_string(),
+            ports: vec![PortResult {
+                port: 22,
+                open: true,
+                service: Some("ssh".to_string()),
+                rtt_ms: None,
+                security_note: None,
+                banner: Some("SSH-2.0-OpenSSH_9.6".to_string()),
+            }],
+            open_count: 1,
+            closed_count: 0,
+            scan_time_ms: 10.0,
+            timestamp: String::new(),
+        };
+        assert!(result
+            .to_csv()
+            .contains("\n22,tcp,ssh,,SSH-2.0-OpenSSH_9.6\n"));
+        let human = result.to_human();
+        let line = human.lines().find(|l| l.contains("22/tcp")).unwrap();
+        assert!(line.find("(ssh)") < line.find("SSH-2.0-OpenSSH_9.6"));
+    }
+
+    #[test]
+    fn test_trace_renders_each_probe() {
+        use crate::trace::{TraceHop, TraceMethod, TraceResult};
+        let result = TraceResult {
+            target: "example.com".to_string(),
+            resolved_addr: "192.0.2.9".to_string(),
+            hops: vec![
+                TraceHop::from_probes(
+                    1,
+                    Some("10.0.0.1".into()),
+                    None,
+                    vec![Some(1.2), Some(1.3), None],
+                ),
+                TraceHop::from_probes(2, None, None, vec![None, None, None]),
+            ],
+            reached: false,
+            method: TraceMethod::Icmp,
+            timestamp: String::new(),
+        };
+        assert!(result.to_human().contains("10.0.0.1  1.20 ms  1.30 ms  *"));
+        assert!(result.to_table().contains("1.20 ms  1.30 ms  *"));
+        let csv = result.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("hop,addr,hostname,rtt_ms,timed_out,rtts_ms")
+        );
+        assert_eq!(lines.next(), Some("1,10.0.0.1,,1.20,false,1.20 1.30 *"));
+        assert_eq!(lines.next(), Some("2,,,,true,* * *"));
+    }
+
+    #[test]
+    fn test_ping_percentiles_rendered() {
+        use crate::ping::{PingProbe, PingStats};
+        let probes = [1.0, 2.0, 3.0, 4.0, 5.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &rtt)| PingProbe {
+                seq: i as u32,
+                success: true,
+                rtt_ms: Some(rtt),
+                addr: "192.0.2.1".to_string(),
+            })
+            .collect();
+        let stats = PingStats::from_probes("example.com", "192.0.2.1", probes);
+
+        assert!(stats
+            .to_human()
+            .contains("rtt p50/p95/p99 = 3.00/4.80/4.96 ms"));
+        assert!(stats
+            .to_table()
+            .contains("p50=3.00 ms  p95=4.80 ms  p99=4.96 ms"));
+        let csv = stats.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("seq,status,rtt_ms,addr,p50_ms,p95_ms,p99_ms")
+        );
+        assert_eq!(lines.next(), Some("0,ok,1.00,192.0.2.1,3.00,4.80,4.96"));
+
+        // No replies: no percentile lines, empty CSV cells
+        let dead = PingStats::from_probes("example.com", "192.0.2.1", Vec::new());
+        assert!(!dead.to_human().contains("p50"));
+        assert!(!dead.to_table().contains("p50"));
+    }
+
+    #[test]
+    fn test_format_ping_classic() {
+        use crate::ping::{PingProbe, PingStats};
+
+        let probe = |seq, rtt_ms: Option<f64>| PingProbe {
+            seq,
+            success: rtt_ms.is_some(),
+            rtt_ms,
+            addr: "192.0.2.1".to_string(),
+        };
+        let stats = PingStats {
+            target: "example.com".to_string(),
+            resolved_addr: "192.0.2.1".to_string(),
+            probes: vec![probe(0, Some(10.0)), probe(1, None), probe(2, Some(20.0))],
+            sent: 3,
+            received: 2,
+            lost: 1,
+            loss_percent: 100.0 / 3.0,
+            min_ms: Some(10.0),
+            avg_ms: Some(15.0),
+            max_ms: Some(20.0),
+            stddev_ms: Some(5.0),
+            mdev_ms: Some(5.0),
+            jitter_ms: Some(10.0),
+            p50_ms: None,
+            p95_ms: None,
+            p99_ms: None,
+            owner: None,
+            timestamp: String::new(),
+        };
+
+        let output = format_ping_classic(&stats, crate::ping::PingMode::Tcp);
+        assert!(output.starts_with("PING example.com (192.0.2.1) via TCP\n"));
+        assert!(output.contains("connected to 192.0.2.1: seq=0 time=10.000 ms\n"));
+        assert!(output.contains("request timeout for seq=1\n"));
+        assert!(output.contains("3 packets transmitted, 2 received, 33.333% packet loss\n"));
+        assert!(output.ends_with("rtt min/avg/max/mdev = 10.000/15.000/20.000/5.000 ms\n"));
+    }
+
+    #[test]
+    fn test_health_healthy_ping_is_passing() {
+        use crate::ping::PingStats;
+
+        let stats = PingStats {
+            target: "example.com".to_string(),
+            resolved_addr: "93.184.216.34".to_string(),
+            probes: vec![],
+            sent: 4,
+            received: 4,
+            lost: 0,
+            loss_percent: 0.0,
+            min_ms: Some(20.0),
+            avg_ms: Some(25.0),
+            max_ms: Some(30.0),
+            stddev_ms: Some(3.0),
+            mdev_ms: Some(2.5),
+            jitter_ms: None,
+            p50_ms: None,
+            p95_ms: None,
+            p99_ms: None,
+            owner: None,
+            timestamp: String::new(),
+        };
+        let check = stats.health();
+        assert_eq!(check.status, HealthStatus::Passing);
+        assert_eq!(check.output, "example.com: 4/4 received, avg 25.0ms");
+
+        let lossy = PingStats {
+            received: 3,
+            lost: 1,
+            ..stats.clone()
+        };
+        assert_eq!(lossy.health().status, HealthStatus::Warning);
+        let dead = PingStats {
+            received: 0,
+            lost: 4,
+            ..stats
+        };
+        assert_eq!(dead.health().status, HealthStatus::Critical);
+    }
+
+    #[test]
+    fn test_health_cert_expiring_soon_is_critical() {
+        use crate::cert::{CertInfo, CertResult};
+
+        let cert = |days| CertResult {
+            host: "example.com".to_string(),
+            port: 443,
+            tls_version: "TLSv1.3".to_string(),
+            cipher_suite: "TLS13_AES_128_GCM_SHA256".to_string(),
+            certificate_chain: vec![CertInfo {
+                subject: "CN=example.com".to_string(),
+                issuer: "CN=Test CA".to_string(),
+                not_before: "2026-01-01".to_string(),
+                not_after: "2026-10-19".to_string(),
+                days_until_expiry: days,
+                serial: "01".to_string(),
+                is_ca: false,
+                sha256: String::new(),
+                sha1: String::new(),
+            }],
+            connection_time_ms: 40.0,
+            warning: None,
+            handshakes: None,
+            chain_valid: true,
+            hostname_matches: true,
+            presented_names: Vec::new(),
+            timestamp: String::new(),
+        };
+        let check = cert(3).health();
+        assert_eq!(check.status, HealthStatus::Critical);
+        assert!(check.output.contains("expires in 3 days"));
+        let json: serde_json::Value = serde_json::from_str(&format_health(&cert(3))).unwrap();
+        assert_eq!(json["status"], "critical");
+        assert_eq!(cert(20).health().status, HealthStatus::Warning);
+        assert_eq!(cert(90).health().status, HealthStatus::Passing);
+    }
+
+    #[test]
+    fn test_health_default_uses_summary_line() {
+        let result = crate::dns::SystemDnsResult {
+            domain: "example.com".to_string(),
+            resolver: "system".to_string(),
+            record_type: "A".to_string(),
+            addresses: vec!["93.184.216.34".parse().unwrap()],
+            query_time_ms: 1.0,
+            timestamp: String::new(),
+        };
+        let check = result.health();
+        assert_eq!(check.status, HealthStatus::Passing);
+        assert!(check.output.contains("example.com"));
+        assert!(!check.output.contains('\n'));
+    }
+
+    #[test]
+    fn test_health_port_scan_without_open_ports_is_critical() {
+        let result = crate::port::ScanResult {
+            target: "example.com".to_string(),
+            resolved_addr: "93.184.216.34".to_string(),
+            ports: vec![],
+            open_count: 0,
+            closed_count: 10,
+            scan_time_ms: 100.0,
+            timestamp: String::new(),
+        };
+        let check = result.health();
+        assert_eq!(check.status, HealthStatus::Critical);
+        assert!(check.output.contains("example.com"));
+        let open = crate::port::ScanResult {
+            open_count: 1,
+            ..result
+        };
+        assert_eq!(open.health().status, HealthStatus::Passing);
+    }
+
+    #[test]
+    fn test_health_lan_scan_without_hosts_is_critical() {
+        let result = crate::scan::LanScanResult {
+            subnet: "192.0.2.0/24".to_string(),
+            hosts: vec![],
+            total_scanned: 254,
+            hosts_found: 0,
+            scan_time_ms: 100.0,
+            adaptive_timeout_ms: None,
+            ping_sweep_error: None,
+            timestamp: String::new(),
+        };
+        assert_eq!(result.health().status, HealthStatus::Critical);
+        let no_sweep = crate::scan::LanScanResult {
+            hosts_found: 1,
+            ping_sweep_error: Some("needs a raw socket".to_string()),
+            ..result
+        };
+        assert_eq!(no_sweep.health().status, HealthStatus::Warning);
+    }
+
+    #[test]
+    fn test_health_unreached_trace_is_critical() {
+        use crate::trace::{TraceHop, TraceResult};
+
+        let trace = TraceResult {
+            target: "example.com".to_string(),
+            resolved_addr: "93.184.216.34".to_string(),
+            hops: vec![
+                TraceHop::from_probes(1, Some("192.0.2.1".to_string()), None, vec![Some(1.0)]),
+                TraceHop::from_probes(2, None, None, vec![None]),
+            ],
+            reached: false,
+            method: Default::default(),
+            timestamp: String::new(),
+        };
+        assert_eq!(trace.health().status, HealthStatus::Critical);
+        let reached = TraceResult {
+            reached: true,
+            ..trace
+        };
+        assert_eq!(reached.health().status, HealthStatus::Warning);
+    }
+
+    #[test]
+    fn test_health_speed_without_measurements_is_critical() {
+        let result = crate::speed::SpeedResult {
+            download_mbps: None,
+            upload_mbps: None,
+            download_bytes: None,
+            upload_bytes: None,
+            download_time_ms: None,
+            upload_time_ms: None,
+            warning: Some("sample too short".to_string()),
+            timestamp: String::new(),
+        };
+        let check = result.health();
+        assert_eq!(check.status, HealthStatus::Critical);
+        assert_eq!(check.output, "download N/A, upload N/A");
+        let download_only = crate::speed::SpeedResult {
+            download_mbps: Some(100.0),
+            ..result
+        };
+        assert_eq!(download_only.health().status, HealthStatus::Warning);
+    }
+
+    #[test]
+    fn test_health_empty_whois_is_critical() {
+        let result = crate::whois::WhoisResult {
+            target: "example.com".to_string(),
+            server: "whois.example".to_string(),
+            registrar: None,
+            creation_date: None,
+            expiry_date: None,
+            updated_date: None,
+            nameservers: vec![],
+            status: vec![],
+            raw: String::new(),
+            query_time_ms: 10.0,
+            partial: false,
+            timestamp: String::new(),
+        };
+        assert_eq!(result.health().status, HealthStatus::Critical);
+        let partial = crate::whois::WhoisResult {
+            raw: "Domain Name: EXAMPLE.COM\n".to_string(),
+            partial: true,
+            ..result
+        };
+        assert_eq!(partial.health().status, HealthStatus::Warning);
+    }
+
+    #[test]
+    fn test_locale_affects_human_output_only() {
+        use crate::http::{HttpResult, HttpTiming};
+        use crate::locale::{set_number_locale, NumberLocale};
+
+        let result = HttpResult {
+            url: "http://example.com/".to_string(),
+            method: "GET".to_string(),
+            status: 200,
+            status_text: "OK".to_string(),
+            headers: std::collections::HashMap::new(),
+            body_size: 1234567,
+            timing: HttpTiming {
+                dns_ms: 1.0,
+                connect_ms: 2.0,
+                tls_ms: None,
+                ttfb_ms: 1500.5,
+                transfer_ms: 3.0,
+                total_ms: 1506.5,
+            },
+            redirects: vec![],
+            redirect_chain_ms: 1506.5,
+            final_url: "http://example.com/".to_string(),
+            tls: false,
+            idn: None,
+            content_length_mismatch: None,
+            options: None,
+            max_ttfb_ms: None,
+            protocol: None,
+            tls_info: None,
+            compression: None,
+            warmup: None,
+            body_throughput_mbps: None,
+            timestamp: String::new(),
+            body: None,
+        };
+
+        set_number_locale(NumberLocale::parse("en_US").unwrap());
+        let us = format_output(&result, OutputFormat::Human);
+        let us_json = format_output(&result, OutputFormat::Json);
+        set_number_locale(NumberLocale::parse("de_DE").unwrap());
+        let de = format_output(&result, OutputFormat::Human);
+        let de_json = format_output(&result, OutputFormat::Json);
+        let de_table = format_output(&result, OutputFormat::Table);
+        set_number_locale(NumberLocale::C);
+
+        assert!(us.contains("Body: 1,234,567 bytes"));
+        assert!(de.contains("Body: 1.234.567 bytes"));
+        assert!(de_table.contains("1,50 s"));
+        assert_eq!(us_json, de_json);
+        assert!(de_json.contains("1234567"));
+        assert!(format_output(&result, OutputFormat::Human).contains("Body: 1234567 bytes"));
+    }
+
+    #[test]
+    fn test_oneline_ping() {
+        use crate::ping::PingStats;
+
+        let stats = PingStats {
+            target: "example.com".to_string(),
+            resolved_addr: "93.184.216.34".to_string(),
+            probes: vec![],
+            sent: 4,
+            received: 4,
+            lost: 0,
+            loss_percent: 0.0,
+            min_ms: Some(20.0),
+            avg_ms: Some(23.4),
+            max_ms: Some(30.0),
+            stddev_ms: Some(3.0),
+            mdev_ms: Some(2.5),
+            jitter_ms: None,
+            p50_ms: None,
+            p95_ms: None,
+            p99_ms: None,
+            owner: None,
+            timestamp: String::new(),
+        };
+        assert_eq!(stats.to_oneline(), "PING example.com: 0% loss, 23ms avg");
+        assert_eq!(
+            format_oneline(&stats),
+            "PING example.com: 0% loss, 23ms avg\n"
+        );
+
+        let dead = PingStats {
+            received: 0,
+            lost: 4,
+            loss_percent: 100.0,
+            min_ms: None,
+            avg_ms: None,
+            max_ms: None,
+            ..stats
+        };
+        assert_eq!(dead.to_oneline(), "PING example.com: 100% loss");
+    }
+
+    #[test]
+    fn test_oneline_port() {
+        use crate::port::{PortResult, ScanResult};
+
+        let open = |port| PortResult {
+            port,
+            open: true,
+            service: None,
+            rtt_ms: Some(1.0),
+            security_note: None,
+            banner: None,
+        };
+        let result = ScanResult {
+            target: "host".to_string(),
+            resolved_addr: "10.0.0.1".to_string(),
+            ports: vec![open(22), open(80), open(443)],
+            open_count: 3,
+            closed_count: 97,
+            scan_time_ms: 100.0,
+            timestamp: String::new(),
+        };
+        assert_eq!(result.to_oneline(), "PORT host: 3 open (22,80,443)");
+
+        let none = ScanResult {
+            ports: vec![],
+            open_count: 0,
+            closed_count: 100,
+            ..result
+        };
+        assert_eq!(none.to_oneline(), "PORT host: 0 open");
+    }
+
+    #[test]
+    fn test_oneline_default_uses_plain_summary_line() {
+        let result = crate::dns::SystemDnsResult {
+            domain: "localhost".to_string(),
+            resolver: "system".to_string(),
+            record_type: "A/AAAA".to_string(),
+            addresses: vec!["127.0.0.1".parse().unwrap()],
+            query_time_ms: 0.1,
+            timestamp: String::new(),
+        };
+        let line = result.to_oneline();
+        assert!(line.contains("localhost"));
+        assert!(!line.contains('\n'));
+        assert_eq!(strip_ansi("\x1b[1;32mPING\x1b[0m x"), "PING x");
+    }
 }
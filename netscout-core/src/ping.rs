@@ -1,17 +1,55 @@
-use serde::Serialize;
-use std::net::{SocketAddr, ToSocketAddrs};
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::future::Future;
+use std::io::Read;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::net::TcpStream;
-use tokio::time::timeout;
+use tokio::time::{timeout, MissedTickBehavior};
+
+pub(crate) const ICMP_ECHO_REPLY: u8 = 0;
+const ICMP_ECHO_REQUEST: u8 = 8;
+const ICMPV6_ECHO_REQUEST: u8 = 128;
+pub(crate) const ICMPV6_ECHO_REPLY: u8 = 129;
+/// Echo payload; replies carry it back unchanged.
+const ICMP_PAYLOAD: &[u8; 32] = b"netscout icmp echo payload 01234";
+
+/// Distinguishes concurrent ICMP sessions' echo identifiers.
+static ICMP_SESSION: AtomicU16 = AtomicU16::new(0);
+
+/// How a ping probe reaches the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PingMode {
+    /// Time a TCP connect to `port`.
+    Tcp,
+    /// Send ICMP echo requests over a raw socket (needs root or CAP_NET_RAW).
+    Icmp,
+}
+
+impl PingMode {
+    /// Protocol name for headers such as `PING host (addr) via TCP`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            PingMode::Tcp => "TCP",
+            PingMode::Icmp => "ICMP",
+        }
+    }
+}
 
 /// Configuration for a ping operation.
 #[derive(Debug, Clone)]
 pub struct PingConfig {
     pub target: String,
+    /// Number of probes to send; 0 keeps going until `duration` runs out (or forever).
     pub count: u32,
     pub interval: Duration,
     pub timeout: Duration,
     pub port: u16,
+    /// Stop sending probes once this much time has passed, even if `count` isn't reached.
+    pub duration: Option<Duration>,
+    pub mode: PingMode,
 }
 
 impl Default for PingConfig {
@@ -22,12 +60,14 @@ impl Default for PingConfig {
             interval: Duration::from_secs(1),
             timeout: Duration::from_secs(2),
             port: 80,
+            duration: None,
+            mode: PingMode::Tcp,
         }
     }
 }
 
 /// Result of a single ping probe.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PingProbe {
     pub seq: u32,
     pub success: bool,
@@ -36,7 +76,7 @@ pub struct PingProbe {
 }
 
 /// Aggregated ping statistics.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PingStats {
     pub target: String,
     pub resolved_addr: String,
@@ -49,10 +89,86 @@ pub struct PingStats {
     pub avg_ms: Option<f64>,
     pub max_ms: Option<f64>,
     pub stddev_ms: Option<f64>,
+    /// Mean absolute deviation from the average RTT (`mdev` in classic output).
+    #[serde(default)]
+    pub mdev_ms: Option<f64>,
     pub jitter_ms: Option<f64>,
+    /// RTT percentiles, interpolated between the closest successful probes.
+    #[serde(default)]
+    pub p50_ms: Option<f64>,
+    #[serde(default)]
+    pub p95_ms: Option<f64>,
+    #[serde(default)]
+    pub p99_ms: Option<f64>,
+    /// Organisation owning the resolved address (only filled in on request).
+    pub owner: Option<String>,
+    /// When the ping started (RFC 3339, UTC, milliseconds).
+    #[serde(default)]
+    pub timestamp: String,
 }
 
 impl PingStats {
+    /// Aggregate the probes sent so far, e.g. when a session is interrupted.
+    pub fn from_probes(target: &str, resolved_addr: &str, probes: Vec<PingProbe>) -> Self {
+        let rtts: Vec<f64> = probes.iter().filter_map(|p| p.rtt_ms).collect();
+        let sent = probes.len() as u32;
+        let received = rtts.len() as u32;
+        let lost = sent - received;
+        let loss_percent = if sent > 0 {
+            (lost as f64 / sent as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        let (min_ms, avg_ms, max_ms, stddev_ms, jitter_ms) = if rtts.is_empty() {
+            (None, None, None, None, None)
+        } else {
+            let min = rtts.iter().cloned().reduce(f64::min).unwrap();
+            let max = rtts.iter().cloned().reduce(f64::max).unwrap();
+            let avg = rtts.iter().sum::<f64>() / rtts.len() as f64;
+            let variance = rtts.iter().map(|r| (r - avg).powi(2)).sum::<f64>() / rtts.len() as f64;
+            let stddev = variance.sqrt();
+            let jitter = if rtts.len() > 1 {
+                let diffs: Vec<f64> = rtts.windows(2).map(|w| (w[1] - w[0]).abs()).collect();
+                Some(diffs.iter().sum::<f64>() / diffs.len() as f64)
+            } else {
+                None
+            };
+            (Some(min), Some(avg), Some(max), Some(stddev), jitter)
+        };
+
+        Self {
+            target: target.to_string(),
+            resolved_addr: resolved_addr.to_string(),
+            probes,
+            sent,
+            received,
+            lost,
+            loss_percent,
+            min_ms,
+            avg_ms,
+            max_ms,
+            stddev_ms,
+            mdev_ms: mean_abs_deviation(&rtts),
+            jitter_ms,
+            p50_ms: crate::stats::percentile(&rtts, 50.0),
+            p95_ms: crate::stats::percentile(&rtts, 95.0),
+            p99_ms: crate::stats::percentile(&rtts, 99.0),
+            owner: None,
+            timestamp: String::new(),
+        }
+    }
+
+    /// Fill in `owner` using an IP-to-organisation lookup such as `whois::ip_owner`.
+    pub fn enrich_owner(&mut self, lookup: impl Fn(&str) -> Option<String>) {
+        self.owner = lookup(&self.resolved_addr);
+    }
+
+    /// Keep only the lost probes (`--only-errors`); counts and RTT stats still cover every probe.
+    pub fn retain_failures(&mut self) {
+        self.probes.retain(|p| !p.success);
+    }
+
     /// Returns true if all probes were successful.
     pub fn is_all_success(&self) -> bool {
         self.lost == 0 && self.sent > 0
@@ -138,11 +254,20 @@ impl std::fmt::Display for PingProbe {
     }
 }
 
+/// Mean absolute deviation of RTTs from their average, or `None` if empty.
+pub fn mean_abs_deviation(rtts: &[f64]) -> Option<f64> {
+    if rtts.is_empty() {
+        return None;
+    }
+    let avg = rtts.iter().sum::<f64>() / rtts.len() as f64;
+    Some(rtts.iter().map(|r| (r - avg).abs()).sum::<f64>() / rtts.len() as f64)
+}
+
 /// Resolve hostname to a socket address.
 fn resolve(target: &str, port: u16) -> Result<SocketAddr, String> {
-    let host = format!("{target}:{port}");
-    host.to_socket_addrs()
+    crate::addr::resolve(target, port)
         .map_err(|e| format!("DNS resolution failed for {target}: {e}"))?
+        .into_iter()
         .next()
         .ok_or_else(|| format!("No addresses found for {target}"))
 }
@@ -159,66 +284,219 @@ async fn tcp_ping(addr: SocketAddr, to: Duration) -> (bool, Option<f64>) {
     }
 }
 
-/// Run a full ping session.
-pub async fn ping(config: &PingConfig) -> Result<PingStats, String> {
-    // Try port 80, then 443 as fallback
-    let addr = resolve(&config.target, config.port).or_else(|_| resolve(&config.target, 443))?;
+/// Open an unconnected raw ICMP socket for `ip`'s address family.
+pub(crate) fn raw_icmp_socket(ip: IpAddr) -> std::io::Result<Socket> {
+    let (domain, protocol) = match ip {
+        IpAddr::V4(_) => (Domain::IPV4, Protocol::ICMPV4),
+        IpAddr::V6(_) => (Domain::IPV6, Protocol::ICMPV6),
+    };
+    Socket::new(domain, Type::RAW, Some(protocol))
+}
 
-    let mut probes = Vec::with_capacity(config.count as usize);
+/// A fresh echo identifier, distinct from other sessions in this process.
+pub(crate) fn icmp_ident() -> u16 {
+    ICMP_SESSION.fetch_add(1, Ordering::Relaxed) ^ std::process::id() as u16
+}
 
-    for seq in 0..config.count {
-        if seq > 0 {
-            tokio::time::sleep(config.interval).await;
+/// Open a raw ICMP socket connected to `ip`, so it only receives that host's packets.
+fn icmp_socket(ip: IpAddr) -> Result<Socket, String> {
+    let socket = raw_icmp_socket(ip).map_err(|e| {
+        if e.kind() == std::io::ErrorKind::PermissionDenied {
+            "ICMP ping needs a raw socket: run as root or grant CAP_NET_RAW, \
+             or drop --icmp to ping over TCP"
+                .to_string()
+        } else {
+            format!("Failed to open ICMP socket: {e}")
         }
-        let (success, rtt_ms) = tcp_ping(addr, config.timeout).await;
-        probes.push(PingProbe {
-            seq,
-            success,
-            rtt_ms,
-            addr: addr.ip().to_string(),
-        });
-    }
+    })?;
+    socket
+        .connect(&SocketAddr::new(ip, 0).into())
+        .map_err(|e| format!("ICMP connect to {ip} failed: {e}"))?;
+    Ok(socket)
+}
 
-    let rtts: Vec<f64> = probes.iter().filter_map(|p| p.rtt_ms).collect();
-    let received = rtts.len() as u32;
-    let lost = config.count - received;
-    let loss_percent = if config.count > 0 {
-        (lost as f64 / config.count as f64) * 100.0
+/// Build an echo request. The kernel fills in the ICMPv6 checksum itself.
+pub(crate) fn echo_request(v6: bool, ident: u16, seq: u16) -> Vec<u8> {
+    let kind = if v6 {
+        ICMPV6_ECHO_REQUEST
     } else {
-        0.0
+        ICMP_ECHO_REQUEST
     };
+    let mut packet = vec![kind, 0, 0, 0];
+    packet.extend_from_slice(&ident.to_be_bytes());
+    packet.extend_from_slice(&seq.to_be_bytes());
+    packet.extend_from_slice(ICMP_PAYLOAD);
+    if !v6 {
+        let sum = icmp_checksum(&packet);
+        packet[2..4].copy_from_slice(&sum.to_be_bytes());
+    }
+    packet
+}
 
-    let (min_ms, avg_ms, max_ms, stddev_ms, jitter_ms) = if rtts.is_empty() {
-        (None, None, None, None, None)
+/// The Internet checksum (RFC 1071).
+fn icmp_checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|c| u32::from(u16::from_be_bytes([c[0], c.get(1).copied().unwrap_or(0)])))
+        .sum();
+    while sum >> 16 != 0 {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// The `(ident, seq)` of an echo reply, or `None` for any other packet.
+/// IPv4 raw sockets deliver the IP header too; IPv6 ones don't.
+pub(crate) fn parse_echo_reply(packet: &[u8], v6: bool) -> Option<(u16, u16)> {
+    let (icmp, reply) = if v6 {
+        (packet, ICMPV6_ECHO_REPLY)
     } else {
-        let min = rtts.iter().cloned().reduce(f64::min).unwrap();
-        let max = rtts.iter().cloned().reduce(f64::max).unwrap();
-        let avg = rtts.iter().sum::<f64>() / rtts.len() as f64;
-        let variance = rtts.iter().map(|r| (r - avg).powi(2)).sum::<f64>() / rtts.len() as f64;
-        let stddev = variance.sqrt();
-        let jitter = if rtts.len() > 1 {
-            let diffs: Vec<f64> = rtts.windows(2).map(|w| (w[1] - w[0]).abs()).collect();
-            Some(diffs.iter().sum::<f64>() / diffs.len() as f64)
-        } else {
-            None
-        };
-        (Some(min), Some(avg), Some(max), Some(stddev), jitter)
+        let header_len = usize::from(packet.first()? & 0x0F) * 4;
+        (packet.get(header_len..)?, ICMP_ECHO_REPLY)
     };
+    if icmp.len() < 8 || icmp[0] != reply {
+        return None;
+    }
+    Some((
+        u16::from_be_bytes([icmp[4], icmp[5]]),
+        u16::from_be_bytes([icmp[6], icmp[7]]),
+    ))
+}
+
+/// Send one echo request and wait for its reply.
+fn icmp_ping(socket: &Socket, v6: bool, ident: u16, seq: u16, to: Duration) -> (bool, Option<f64>) {
+    let start = Instant::now();
+    if socket.send(&echo_request(v6, ident, seq)).is_err() {
+        return (false, None);
+    }
+    let mut reader = socket;
+    let mut buf = [0u8; 1500];
+    loop {
+        let left = to.saturating_sub(start.elapsed());
+        if left.is_zero() || socket.set_read_timeout(Some(left)).is_err() {
+            return (false, None);
+        }
+        match reader.read(&mut buf) {
+            Ok(n) if parse_echo_reply(&buf[..n], v6) == Some((ident, seq)) => {
+                return (true, Some(start.elapsed().as_secs_f64() * 1000.0));
+            }
+            // Another session's reply, or our own request looped back
+            Ok(_) => continue,
+            Err(_) => return (false, None),
+        }
+    }
+}
+
+/// Run a full ping session.
+pub async fn ping(config: &PingConfig) -> Result<PingStats, String> {
+    ping_streaming(config, |_| {}).await
+}
+
+/// Run a ping session, calling `on_probe` as soon as each probe completes.
+pub async fn ping_streaming(
+    config: &PingConfig,
+    on_probe: impl FnMut(&PingProbe),
+) -> Result<PingStats, String> {
+    ping_until(config, on_probe, std::future::pending()).await
+}
+
+/// Like [`ping_streaming`], but ends the session early when `stop` resolves.
+///
+/// Stats cover the probes completed before the stop; a probe still in flight is dropped.
+pub async fn ping_until(
+    config: &PingConfig,
+    on_probe: impl FnMut(&PingProbe),
+    stop: impl Future<Output = ()>,
+) -> Result<PingStats, String> {
+    let timestamp = crate::timestamp::now();
+    let (addr, icmp) = match config.mode {
+        // Try port 80, then 443 as fallback
+        PingMode::Tcp => (
+            resolve(&config.target, config.port).or_else(|_| resolve(&config.target, 443))?,
+            None,
+        ),
+        PingMode::Icmp => {
+            let addr = resolve(&config.target, 0)?;
+            (addr, Some(Arc::new(icmp_socket(addr.ip())?)))
+        }
+    };
+    let ident = icmp_ident();
+
+    let probes = run_probes(
+        config,
+        |seq| {
+            let icmp = icmp.clone();
+            async move {
+                let (success, rtt_ms) = match icmp {
+                    Some(socket) => {
+                        let to = config.timeout;
+                        crate::runtime::blocking(move || {
+                            Ok(icmp_ping(&socket, addr.is_ipv6(), ident, seq as u16, to))
+                        })
+                        .await
+                        .unwrap_or((false, None))
+                    }
+                    None => tcp_ping(addr, config.timeout).await,
+                };
+                PingProbe {
+                    seq,
+                    success,
+                    rtt_ms,
+                    addr: addr.ip().to_string(),
+                }
+            }
+        },
+        on_probe,
+        stop,
+    )
+    .await;
 
-    Ok(PingStats {
-        target: config.target.clone(),
-        resolved_addr: addr.ip().to_string(),
-        probes,
-        sent: config.count,
-        received,
-        lost,
-        loss_percent,
-        min_ms,
-        avg_ms,
-        max_ms,
-        stddev_ms,
-        jitter_ms,
-    })
+    let mut stats = PingStats::from_probes(&config.target, &addr.ip().to_string(), probes);
+    stats.timestamp = timestamp;
+    Ok(stats)
+}
+
+/// Send probes on a fixed cadence anchored to the session start, so a slow probe
+/// doesn't push every later send back by its RTT.
+async fn run_probes<F, Fut>(
+    config: &PingConfig,
+    mut probe: F,
+    mut on_probe: impl FnMut(&PingProbe),
+    stop: impl Future<Output = ()>,
+) -> Vec<PingProbe>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = PingProbe>,
+{
+    // tokio::time::interval panics on a zero period
+    let mut ticker = tokio::time::interval(config.interval.max(Duration::from_millis(1)));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    tokio::pin!(stop);
+
+    let mut probes = Vec::with_capacity(config.count as usize);
+    let start = Instant::now();
+
+    for seq in (0..).take_while(|&seq| config.count == 0 || seq < config.count) {
+        // Don't start a probe the session duration would cut off anyway
+        if seq > 0
+            && config
+                .duration
+                .is_some_and(|d| (config.interval * seq).max(start.elapsed()) >= d)
+        {
+            break;
+        }
+        let next = tokio::select! {
+            _ = &mut stop => None,
+            p = async {
+                ticker.tick().await;
+                probe(seq).await
+            } => Some(p),
+        };
+        let Some(p) = next else { break };
+        on_probe(&p);
+        probes.push(p);
+    }
+    probes
 }
 
 #[cfg(test)]
@@ -243,6 +521,8 @@ mod tests {
             interval: Duration::from_millis(500),
             timeout: Duration::from_secs(5),
             port: 443,
+            duration: None,
+            mode: PingMode::Tcp,
         };
         assert_eq!(cfg.target, "example.com");
         assert_eq!(cfg.count, 10);
@@ -312,6 +592,69 @@ mod tests {
         assert!(elapsed < Duration::from_millis(200));
     }
 
+    #[test]
+    fn test_echo_request_checksum() {
+        let packet = echo_request(false, 0x1234, 7);
+        assert_eq!(packet[0], ICMP_ECHO_REQUEST);
+        assert_eq!(&packet[4..8], &[0x12, 0x34, 0, 7]);
+        // A packet with a correct checksum sums to zero
+        assert_eq!(icmp_checksum(&packet), 0);
+        // RFC 1071 worked example
+        assert_eq!(
+            icmp_checksum(&[0x00, 0x01, 0xf2, 0x03, 0xf4, 0xf5, 0xf6, 0xf7]),
+            !0xddf2
+        );
+        // ICMPv6 checksums are left to the kernel
+        let v6 = echo_request(true, 1, 2);
+        assert_eq!(v6[0], ICMPV6_ECHO_REQUEST);
+        assert_eq!(&v6[2..4], &[0, 0]);
+    }
+
+    #[test]
+    fn test_parse_echo_reply() {
+        let mut reply = echo_request(false, 0xBEEF, 3);
+        reply[0] = ICMP_ECHO_REPLY;
+        // IPv4 raw sockets deliver a 20-byte IP header first
+        let mut v4 = vec![0x45];
+        v4.extend_from_slice(&[0; 19]);
+        v4.extend_from_slice(&reply);
+        assert_eq!(parse_echo_reply(&v4, false), Some((0xBEEF, 3)));
+        // Our own request, looped back, isn't a reply
+        let mut request = vec![0x45];
+        request.extend_from_slice(&[0; 19]);
+        request.extend_from_slice(&echo_request(false, 0xBEEF, 3));
+        assert_eq!(parse_echo_reply(&request, false), None);
+        assert_eq!(parse_echo_reply(&v4[..24], false), None);
+
+        let mut v6 = echo_request(true, 9, 10);
+        v6[0] = ICMPV6_ECHO_REPLY;
+        assert_eq!(parse_echo_reply(&v6, true), Some((9, 10)));
+        assert_eq!(parse_echo_reply(&[], true), None);
+    }
+
+    #[tokio::test]
+    async fn test_icmp_ping_localhost() {
+        let config = PingConfig {
+            target: "127.0.0.1".to_string(),
+            count: 2,
+            interval: Duration::from_millis(10),
+            mode: PingMode::Icmp,
+            ..Default::default()
+        };
+        let stats = match ping(&config).await {
+            Ok(stats) => stats,
+            // Unprivileged: the error says how to get a raw socket
+            Err(e) => {
+                assert!(e.contains("CAP_NET_RAW"), "{e}");
+                return;
+            }
+        };
+        assert_eq!(stats.sent, 2);
+        assert_eq!(stats.received, 2);
+        assert_eq!(stats.resolved_addr, "127.0.0.1");
+        assert!(stats.probes.iter().all(|p| p.rtt_ms.is_some()));
+    }
+
     #[test]
     fn test_stats_calculation() {
         let probes = [
@@ -390,7 +733,13 @@ mod tests {
             avg_ms: Some(20.0),
             max_ms: Some(30.0),
             stddev_ms: Some(5.0),
+            mdev_ms: Some(4.0),
             jitter_ms: Some(2.5),
+            p50_ms: None,
+            p95_ms: None,
+            p99_ms: None,
+            owner: None,
+            timestamp: String::new(),
         };
         let json = serde_json::to_string(&stats).unwrap();
         assert!(json.contains("example.com"));
@@ -412,7 +761,13 @@ mod tests {
             avg_ms: None,
             max_ms: None,
             stddev_ms: None,
+            mdev_ms: None,
             jitter_ms: None,
+            p50_ms: None,
+            p95_ms: None,
+            p99_ms: None,
+            owner: None,
+            timestamp: String::new(),
         };
         assert_eq!(stats.loss_percent, 100.0);
         assert!(stats.min_ms.is_none());
@@ -444,6 +799,165 @@ mod tests {
         assert!((stddev - (200.0 / 3.0_f64).sqrt()).abs() < 0.01);
     }
 
+    #[test]
+    fn test_mean_abs_deviation() {
+        // avg = 25; |dev| = 15, 5, 5, 15 -> mean 10
+        let rtts = [10.0, 20.0, 30.0, 40.0];
+        let mdev = mean_abs_deviation(&rtts).unwrap();
+        let avg = rtts.iter().sum::<f64>() / rtts.len() as f64;
+        let expected = rtts.iter().map(|r| (r - avg).abs()).sum::<f64>() / rtts.len() as f64;
+        assert!((mdev - expected).abs() < 1e-9);
+        assert!((mdev - 10.0).abs() < 1e-9);
+        assert_eq!(mean_abs_deviation(&[5.0]), Some(0.0));
+        assert_eq!(mean_abs_deviation(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn test_ping_duration_limits_probes() {
+        let config = PingConfig {
+            target: "127.0.0.1".to_string(),
+            count: 5,
+            interval: Duration::from_secs(1),
+            timeout: Duration::from_millis(200),
+            duration: Some(Duration::from_millis(500)),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let stats = ping(&config).await.unwrap();
+        assert_eq!(stats.sent, 1);
+        assert_eq!(stats.probes.len(), 1);
+        assert_eq!(stats.lost, stats.sent - stats.received);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_ping_streaming_unlimited_count_stops_at_duration() {
+        let config = PingConfig {
+            target: "127.0.0.1".to_string(),
+            count: 0,
+            interval: Duration::from_millis(100),
+            timeout: Duration::from_millis(200),
+            duration: Some(Duration::from_millis(450)),
+            ..Default::default()
+        };
+        let mut streamed = Vec::new();
+        let stats = ping_streaming(&config, |p| streamed.push(p.seq))
+            .await
+            .unwrap();
+        assert!(stats.sent >= 2);
+        assert_eq!(streamed, (0..stats.sent).collect::<Vec<_>>());
+    }
+
+    fn fake_probe(seq: u32) -> PingProbe {
+        PingProbe {
+            seq,
+            success: true,
+            rtt_ms: Some(60.0),
+            addr: "127.0.0.1".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_spacing_tracks_interval_not_rtt() {
+        let config = PingConfig {
+            count: 5,
+            interval: Duration::from_millis(100),
+            ..Default::default()
+        };
+        let mut sent = Vec::new();
+        let probes = run_probes(
+            &config,
+            |seq| {
+                sent.push(Instant::now());
+                async move {
+                    tokio::time::sleep(Duration::from_millis(60)).await;
+                    fake_probe(seq)
+                }
+            },
+            |_| {},
+            std::future::pending(),
+        )
+        .await;
+        assert_eq!(probes.len(), 5);
+        // Four gaps at 100ms each; a sleep-after-probe loop would take 4 * 160ms
+        let span = sent[4] - sent[0];
+        assert!(span >= Duration::from_millis(390), "span {span:?}");
+        assert!(span < Duration::from_millis(560), "span {span:?}");
+    }
+
+    #[tokio::test]
+    async fn test_stop_interrupts_interval_wait() {
+        let config = PingConfig {
+            count: 0,
+            interval: Duration::from_secs(10),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let probes = run_probes(
+            &config,
+            |seq| async move { fake_probe(seq) },
+            |_| {},
+            tokio::time::sleep(Duration::from_millis(100)),
+        )
+        .await;
+        assert_eq!(probes.len(), 1);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_ping_until_returns_stats_so_far() {
+        let config = PingConfig {
+            target: "127.0.0.1".to_string(),
+            count: 0,
+            interval: Duration::from_secs(10),
+            timeout: Duration::from_millis(200),
+            ..Default::default()
+        };
+        let stats = ping_until(
+            &config,
+            |_| {},
+            tokio::time::sleep(Duration::from_millis(300)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.sent, 1);
+        assert_eq!(stats.probes.len(), 1);
+    }
+
+    #[test]
+    fn test_stats_from_partial_probes() {
+        let probe = |seq, rtt_ms: Option<f64>| PingProbe {
+            seq,
+            success: rtt_ms.is_some(),
+            rtt_ms,
+            addr: "192.0.2.1".to_string(),
+        };
+        // Interrupted after three probes, one of them lost
+        let stats = PingStats::from_probes(
+            "example.com",
+            "192.0.2.1",
+            vec![probe(0, Some(10.0)), probe(1, None), probe(2, Some(20.0))],
+        );
+        assert_eq!((stats.sent, stats.received, stats.lost), (3, 2, 1));
+        assert!((stats.loss_percent - 100.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.min_ms, Some(10.0));
+        assert_eq!(stats.avg_ms, Some(15.0));
+        assert_eq!(stats.max_ms, Some(20.0));
+        assert_eq!(stats.jitter_ms, Some(10.0));
+        // Interpolated between the two successful probes
+        assert_eq!(stats.p50_ms, Some(15.0));
+        assert!((stats.p95_ms.unwrap() - 19.5).abs() < 1e-9);
+        assert!((stats.p99_ms.unwrap() - 19.9).abs() < 1e-9);
+
+        let empty = PingStats::from_probes("example.com", "192.0.2.1", Vec::new());
+        assert_eq!((empty.sent, empty.loss_percent), (0, 0.0));
+        assert_eq!(empty.avg_ms, None);
+        assert_eq!(
+            (empty.p50_ms, empty.p95_ms, empty.p99_ms),
+            (None, None, None)
+        );
+    }
+
     #[tokio::test]
     async fn test_ping_invalid_target() {
         let config = PingConfig {
@@ -506,10 +1020,41 @@ mod display_tests {
             avg_ms: avg,
             max_ms: max,
             stddev_ms: Some(0.5),
+            mdev_ms: Some(0.4),
             jitter_ms: Some(0.3),
+            p50_ms: None,
+            p95_ms: None,
+            p99_ms: None,
+            owner: None,
+            timestamp: String::new(),
         }
     }
 
+    #[test]
+    fn test_retain_failures_keeps_only_lost_probe() {
+        let mut stats = make_stats(3, 1, vec![10.0, 11.0, 12.0]);
+        stats.retain_failures();
+        assert_eq!(stats.probes.len(), 1);
+        assert_eq!(stats.probes[0].seq, 3);
+        assert!(!stats.probes[0].success);
+        assert_eq!((stats.sent, stats.lost), (4, 1));
+        let csv = crate::output::HumanReadable::to_csv(&stats);
+        assert_eq!(csv.lines().count(), 2);
+        assert!(csv.contains("3,timeout"));
+    }
+
+    #[test]
+    fn test_ping_stats_enrich_owner() {
+        let mut stats = make_stats(1, 0, vec![10.0]);
+        assert!(stats.owner.is_none());
+        let whois_raw = "NetName: EDGECAST\nOrgName: Edgecast Inc.\n";
+        stats.enrich_owner(|ip| {
+            assert_eq!(ip, "93.184.216.34");
+            crate::whois::parse_ip_owner(whois_raw)
+        });
+        assert_eq!(stats.owner.as_deref(), Some("Edgecast Inc."));
+    }
+
     #[test]
     fn test_ping_stats_display_success() {
         let stats = make_stats(4, 0, vec![10.0, 12.0, 11.0, 13.0]);
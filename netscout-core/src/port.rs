@@ -1,11 +1,23 @@
-use serde::Serialize;
-use std::collections::HashMap;
-use std::net::{SocketAddr, ToSocketAddrs};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 use tokio::time::timeout;
 
+/// How often a running scan rewrites its checkpoint file.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Most bytes read from an open port when grabbing its banner.
+pub const BANNER_MAX_BYTES: usize = 512;
+
+/// How long to wait for a banner, so silent services don't stall the scan.
+const BANNER_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// Configuration for a port scan.
 #[derive(Debug, Clone)]
 pub struct PortConfig {
@@ -13,6 +25,15 @@ pub struct PortConfig {
     pub ports: Vec<u16>,
     pub timeout: Duration,
     pub parallel: usize,
+    /// Cross-reference detected service versions against [`security_note`];
+    /// banners are read for this even without `banner`.
+    pub security_notes: bool,
+    /// Progress file: ports it lists as scanned are skipped and its open ports merged.
+    pub checkpoint: Option<PathBuf>,
+    /// Read the first line each open port sends (sending `HEAD /` to HTTP ports).
+    pub banner: bool,
+    /// Called with `(scanned, total)` ports as probes finish.
+    pub progress: Option<crate::progress::Progress>,
 }
 
 impl Default for PortConfig {
@@ -22,21 +43,31 @@ impl Default for PortConfig {
             ports: top_ports(),
             timeout: Duration::from_secs(2),
             parallel: 100,
+            security_notes: false,
+            checkpoint: None,
+            banner: false,
+            progress: None,
         }
     }
 }
 
 /// Result of scanning a single port.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortResult {
     pub port: u16,
     pub open: bool,
     pub service: Option<String>,
     pub rtt_ms: Option<f64>,
+    /// Heuristic note when the detected version falls in a known-vulnerable range.
+    #[serde(default)]
+    pub security_note: Option<String>,
+    /// First line the service sent after connecting, when banners were requested.
+    #[serde(default)]
+    pub banner: Option<String>,
 }
 
 /// Aggregated scan result.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanResult {
     pub target: String,
     pub resolved_addr: String,
@@ -44,14 +75,95 @@ pub struct ScanResult {
     pub open_count: usize,
     pub closed_count: usize,
     pub scan_time_ms: f64,
+    /// When the scan started (RFC 3339, UTC, milliseconds).
+    #[serde(default)]
+    pub timestamp: String,
+}
+
+/// Progress of an interrupted port scan, saved so it can be resumed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub target: String,
+    /// Ports already scanned, in `parse_ports` syntax (e.g. "1-32767").
+    pub scanned: String,
+    /// Open ports found so far.
+    pub open: Vec<PortResult>,
+}
+
+impl Checkpoint {
+    /// Read a checkpoint for `target`; a missing file means a fresh scan.
+    pub fn load(path: &Path, target: &str) -> Result<Option<Self>, String> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+        };
+        let checkpoint: Self = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid checkpoint {}: {e}", path.display()))?;
+        if checkpoint.target != target {
+            return Err(format!(
+                "Checkpoint {} is for {}, not {target}",
+                path.display(),
+                checkpoint.target
+            ));
+        }
+        Ok(Some(checkpoint))
+    }
+
+    /// Write the checkpoint, replacing the file only once it is complete.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string(self).map_err(|e| format!("JSON error: {e}"))?;
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, json)
+            .and_then(|()| std::fs::rename(&tmp, path))
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+
+    /// The ports this checkpoint has already scanned.
+    pub fn scanned_ports(&self) -> Result<BTreeSet<u16>, String> {
+        if self.scanned.is_empty() {
+            return Ok(BTreeSet::new());
+        }
+        Ok(parse_ports(&self.scanned)?.into_iter().collect())
+    }
 }
 
-/// Parse a port range string like "80,443,8000-9000".
+/// Format ports as compact ranges, e.g. `[1, 2, 3, 80]` as "1-3,80".
+pub fn format_port_ranges(ports: &BTreeSet<u16>) -> String {
+    let mut ranges: Vec<(u16, u16)> = Vec::new();
+    for &port in ports {
+        match ranges.last_mut() {
+            Some((_, end)) if u32::from(*end) + 1 == u32::from(port) => *end = port,
+            _ => ranges.push((port, port)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(a, b)| {
+            if a == b {
+                a.to_string()
+            } else {
+                format!("{a}-{b}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Parse a port list like "80,443,8000-9000" or "ssh,https,8000-9000".
+///
+/// Service names from [`service_map`] may stand in for single ports.
 pub fn parse_ports(s: &str) -> Result<Vec<u16>, String> {
     let mut ports = Vec::new();
     for part in s.split(',') {
         let part = part.trim();
-        if let Some((start, end)) = part.split_once('-') {
+        // Checked before ranges since names like "http-alt" contain a dash
+        if let Some(port) = service_port(part) {
+            ports.push(port);
+        } else if part.chars().any(|c| c.is_ascii_alphabetic()) {
+            return Err(format!("Unknown service name: {part}"));
+        } else if let Some((start, end)) = part.split_once('-') {
             let start: u16 = start
                 .trim()
                 .parse()
@@ -72,6 +184,15 @@ pub fn parse_ports(s: &str) -> Result<Vec<u16>, String> {
     Ok(ports)
 }
 
+/// Remove every port in `exclude` from `ports`, keeping the original order.
+pub fn exclude_ports(ports: &[u16], exclude: &[u16]) -> Vec<u16> {
+    ports
+        .iter()
+        .copied()
+        .filter(|p| !exclude.contains(p))
+        .collect()
+}
+
 /// Return the top 100 most common ports.
 pub fn top_ports() -> Vec<u16> {
     vec![
@@ -80,13 +201,20 @@ pub fn top_ports() -> Vec<u16> {
         547, 554, 563, 587, 593, 631, 636, 691, 860, 873, 902, 989, 990, 993, 995, 1025, 1026,
         1027, 1028, 1029, 1080, 1194, 1214, 1241, 1311, 1337, 1433, 1434, 1512, 1589, 1701, 1723,
         1725, 1741, 1755, 1812, 1813, 1863, 1900, 1985, 2000, 2049, 2082, 2083, 2100, 2222, 2483,
-        2484, 2745, 3000, 3128, 3268, 3306, 3389, 5900, 8080, 8443, 8888,
+        2484, 2745, 3000, 3128, 3268, 3306, 3389, 5432, 5900, 6379, 8080, 8443, 8888, 27017,
+    ]
+}
+
+/// Return a small high-signal port set for a quick "what kind of box is this" check.
+pub fn quick_ports() -> Vec<u16> {
+    vec![
+        21, 22, 23, 25, 53, 80, 110, 143, 443, 445, 3306, 3389, 5432, 6379, 8080, 8443,
     ]
 }
 
 /// Well-known service names for common ports.
-fn service_map() -> HashMap<u16, &'static str> {
-    let mut m = HashMap::new();
+pub fn service_map() -> BTreeMap<u16, &'static str> {
+    let mut m = BTreeMap::new();
     let entries: &[(u16, &str)] = &[
         (20, "ftp-data"),
         (21, "ftp"),
@@ -156,20 +284,190 @@ fn service_map() -> HashMap<u16, &'static str> {
     m
 }
 
-/// Scan a single port.
-async fn scan_port(addr: SocketAddr, to: Duration) -> (bool, Option<f64>) {
+/// Port for a service name from [`service_map`] (case-insensitive); the
+/// lowest port wins for names listed more than once, e.g. `vnc` is 5900.
+pub fn service_port(name: &str) -> Option<u16> {
+    service_map()
+        .into_iter()
+        .find(|(_, service)| service.eq_ignore_ascii_case(name))
+        .map(|(port, _)| port)
+}
+
+/// Built-in port tables, exported for other tools.
+#[derive(Debug, Clone, Serialize)]
+pub struct PortData {
+    pub services: BTreeMap<u16, &'static str>,
+    pub top_ports: Vec<u16>,
+    pub quick_ports: Vec<u16>,
+}
+
+/// Collect the built-in service map and port lists.
+pub fn port_data() -> PortData {
+    PortData {
+        services: service_map(),
+        top_ports: top_ports(),
+        quick_ports: quick_ports(),
+    }
+}
+
+/// A version range of a well-known product with publicly known vulnerabilities.
+struct VulnerableRange {
+    /// Product name as it appears in service banners (matched case-insensitively).
+    product: &'static str,
+    /// First affected version (inclusive).
+    from: &'static str,
+    /// First fixed version (exclusive upper bound).
+    below: &'static str,
+    note: &'static str,
+}
+
+/// Small embedded table of notable vulnerable version ranges. This is a
+/// posture heuristic, not a vulnerability database: backported distro fixes
+/// and patched builds will still match.
+const VULNERABLE_RANGES: &[VulnerableRange] = &[
+    VulnerableRange {
+        product: "OpenSSH",
+        from: "0",
+        below: "7.4",
+        note: "OpenSSH < 7.4 has several known CVEs (e.g. CVE-2016-10009)",
+    },
+    VulnerableRange {
+        product: "OpenSSH",
+        from: "8.5",
+        below: "9.8",
+        note: "OpenSSH 8.5-9.7 is affected by regreSSHion (CVE-2024-6387)",
+    },
+    VulnerableRange {
+        product: "nginx",
+        from: "0.6.18",
+        below: "1.20.1",
+        note: "nginx < 1.20.1 has a resolver off-by-one (CVE-2021-23017)",
+    },
+    VulnerableRange {
+        product: "Apache",
+        from: "2.4.49",
+        below: "2.4.51",
+        note: "Apache httpd 2.4.49-2.4.50 has a path traversal (CVE-2021-41773, CVE-2021-42013)",
+    },
+    VulnerableRange {
+        product: "vsFTPd",
+        from: "2.3.4",
+        below: "2.3.5",
+        note: "vsftpd 2.3.4 shipped with a backdoor (CVE-2011-2523)",
+    },
+    VulnerableRange {
+        product: "ProFTPD",
+        from: "1.3.5",
+        below: "1.3.6",
+        note: "ProFTPD 1.3.5 mod_copy allows unauthenticated file copy (CVE-2015-3306)",
+    },
+    VulnerableRange {
+        product: "Exim",
+        from: "4.87",
+        below: "4.92",
+        note: "Exim 4.87-4.91 allows remote command execution (CVE-2019-10149)",
+    },
+];
+
+/// Parse the leading dotted numeric part of a version, e.g. `7.2p2` -> `[7, 2]`.
+fn version_parts(v: &str) -> Vec<u32> {
+    v.split('.')
+        .map_while(|part| {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().ok()
+        })
+        .collect()
+}
+
+/// Compare two versions component-wise, treating missing components as zero.
+fn compare_versions(a: &[u32], b: &[u32]) -> std::cmp::Ordering {
+    (0..a.len().max(b.len()))
+        .map(|i| a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)))
+        .find(|o| o.is_ne())
+        .unwrap_or(std::cmp::Ordering::Equal)
+}
+
+/// Find the version following `product` in a service banner such as
+/// `SSH-2.0-OpenSSH_7.2p2` or `Apache/2.4.49 (Unix)`.
+fn banner_version(banner: &str, product: &str) -> Option<Vec<u32>> {
+    // ASCII lowercasing keeps byte offsets valid in `banner`
+    let lower = banner.to_ascii_lowercase();
+    let start = lower.find(&product.to_ascii_lowercase())? + product.len();
+    let rest = banner[start..].trim_start_matches(['/', '_', ' ', '-']);
+    let parts = version_parts(rest);
+    (!parts.is_empty()).then_some(parts)
+}
+
+/// Heuristic security note for a detected service version string.
+///
+/// Returns a note when the version falls in one of a few embedded
+/// known-vulnerable ranges. Matches should be verified; this is not a
+/// vulnerability scanner.
+pub fn security_note(detected: &str) -> Option<String> {
+    VULNERABLE_RANGES.iter().find_map(|r| {
+        let version = banner_version(detected, r.product)?;
+        let in_range = compare_versions(&version, &version_parts(r.from)).is_ge()
+            && compare_versions(&version, &version_parts(r.below)).is_lt();
+        in_range.then(|| format!("{} (heuristic version match)", r.note))
+    })
+}
+
+/// Whether a port speaks plain HTTP and needs a request before it answers.
+fn is_http_port(port: u16) -> bool {
+    matches!(port, 80 | 8000 | 8008 | 8080 | 8888)
+}
+
+/// First non-empty line of a banner, trimmed and with control characters dropped.
+pub fn banner_line(bytes: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(bytes);
+    text.lines()
+        .map(|l| l.chars().filter(|c| !c.is_control()).collect::<String>())
+        .map(|l| l.trim().to_string())
+        .find(|l| !l.is_empty())
+}
+
+/// Read the service banner from a freshly connected stream.
+async fn grab_banner(mut stream: TcpStream, port: u16) -> Option<String> {
+    if is_http_port(port) {
+        timeout(BANNER_TIMEOUT, stream.write_all(b"HEAD / HTTP/1.0\r\n\r\n"))
+            .await
+            .ok()?
+            .ok()?;
+    }
+    let mut buf = vec![0u8; BANNER_MAX_BYTES];
+    let n = timeout(BANNER_TIMEOUT, stream.read(&mut buf))
+        .await
+        .ok()?
+        .ok()?;
+    banner_line(&buf[..n])
+}
+
+/// Scan a single port, optionally grabbing its banner.
+async fn scan_port(
+    addr: SocketAddr,
+    to: Duration,
+    banner: bool,
+) -> (bool, Option<f64>, Option<String>) {
     let start = Instant::now();
     match timeout(to, TcpStream::connect(addr)).await {
-        Ok(Ok(_)) => (true, Some(start.elapsed().as_secs_f64() * 1000.0)),
-        _ => (false, None),
+        Ok(Ok(stream)) => {
+            let rtt = start.elapsed().as_secs_f64() * 1000.0;
+            let banner = match banner {
+                true => grab_banner(stream, addr.port()).await,
+                false => None,
+            };
+            (true, Some(rtt), banner)
+        }
+        _ => (false, None, None),
     }
 }
 
 /// Run a port scan.
 pub async fn scan(config: &PortConfig) -> Result<ScanResult, String> {
-    let base_addr: SocketAddr = format!("{}:0", config.target)
-        .to_socket_addrs()
+    let timestamp = crate::timestamp::now();
+    let base_addr: SocketAddr = crate::addr::resolve(&config.target, 0)
         .map_err(|e| format!("Failed to resolve {}: {e}", config.target))?
+        .into_iter()
         .next()
         .ok_or_else(|| format!("No address for {}", config.target))?;
 
@@ -177,32 +475,82 @@ pub async fn scan(config: &PortConfig) -> Result<ScanResult, String> {
     let sem = std::sync::Arc::new(Semaphore::new(config.parallel));
     let start = Instant::now();
 
-    let mut handles = Vec::new();
-    for &port in &config.ports {
-        let permit = sem.clone().acquire_owned().await.unwrap();
-        let addr = SocketAddr::new(base_addr.ip(), port);
-        let to = config.timeout;
-        handles.push(tokio::spawn(async move {
-            let result = scan_port(addr, to).await;
-            drop(permit);
-            (port, result)
-        }));
-    }
-
-    let mut ports = Vec::new();
-    for handle in handles {
-        let (port, (open, rtt_ms)) = handle.await.map_err(|e| format!("Task failed: {e}"))?;
+    let wanted: BTreeSet<u16> = config.ports.iter().copied().collect();
+    let mut progress = match config.checkpoint {
+        Some(ref path) => Checkpoint::load(path, &config.target)?.unwrap_or_default(),
+        None => Checkpoint::default(),
+    };
+    progress.target = config.target.clone();
+    let mut scanned = progress.scanned_ports()?;
+    let pending: Vec<u16> = wanted.difference(&scanned).copied().collect();
+    // Ports resumed from a checkpoint count as already done
+    let mut completed = wanted.len() - pending.len();
+    let mut last_save = Instant::now();
+    type Probe = (bool, Option<f64>, Option<String>);
+    let mut record = |joined: Result<(u16, Probe), tokio::task::JoinError>,
+                      progress: &mut Checkpoint,
+                      force_save: bool|
+     -> Result<(), String> {
+        let (port, (open, rtt_ms, banner)) = joined.map_err(|e| format!("Task failed: {e}"))?;
+        scanned.insert(port);
+        completed += 1;
+        if let Some(ref progress) = config.progress {
+            progress.report(completed, wanted.len());
+        }
         if open {
-            ports.push(PortResult {
+            progress.open.push(PortResult {
                 port,
                 open,
                 service: services.get(&port).map(|s| s.to_string()),
                 rtt_ms,
+                // Needs a detected service version, which only banners provide
+                security_note: banner
+                    .as_deref()
+                    .filter(|_| config.security_notes)
+                    .and_then(security_note),
+                banner,
             });
         }
+        match config.checkpoint {
+            Some(ref path) if force_save || last_save.elapsed() >= CHECKPOINT_INTERVAL => {
+                progress.scanned = format_port_ranges(&scanned);
+                last_save = Instant::now();
+                progress.save(path)
+            }
+            _ => Ok(()),
+        }
+    };
+
+    let mut set = JoinSet::new();
+    for port in pending {
+        let permit = sem.clone().acquire_owned().await.unwrap();
+        // Record ports that finished while we waited for a free slot
+        while let Some(joined) = set.try_join_next() {
+            record(joined, &mut progress, false)?;
+        }
+        // Keeps the scope id of a link-local IPv6 target
+        let mut addr = base_addr;
+        addr.set_port(port);
+        let (to, banner) = (config.timeout, config.banner || config.security_notes);
+        set.spawn(async move {
+            let result = scan_port(addr, to, banner).await;
+            drop(permit);
+            (port, result)
+        });
+    }
+    while let Some(joined) = set.join_next().await {
+        let last = set.is_empty();
+        record(joined, &mut progress, last)?;
     }
 
+    // Merge open ports from earlier runs, limited to the ports asked for this time
+    let mut ports: Vec<PortResult> = progress
+        .open
+        .into_iter()
+        .filter(|p| wanted.contains(&p.port))
+        .collect();
     ports.sort_by_key(|p| p.port);
+    ports.dedup_by_key(|p| p.port);
     let open_count = ports.len();
     let closed_count = config.ports.len() - open_count;
     let scan_time_ms = start.elapsed().as_secs_f64() * 1000.0;
@@ -214,6 +562,7 @@ pub async fn scan(config: &PortConfig) -> Result<ScanResult, String> {
         open_count,
         closed_count,
         scan_time_ms,
+        timestamp,
     })
 }
 
@@ -305,6 +654,24 @@ mod tests {
         assert!(parse_ports("80--443").is_err());
     }
 
+    #[test]
+    fn test_exclude_ports_from_top_ports() {
+        let top = top_ports();
+        let exclude = parse_ports("22,80").unwrap();
+        let remaining = exclude_ports(&top, &exclude);
+        assert_eq!(remaining.len(), top.len() - 2);
+        assert!(!remaining.contains(&22));
+        assert!(!remaining.contains(&80));
+        let expected: Vec<u16> = top.into_iter().filter(|p| *p != 22 && *p != 80).collect();
+        assert_eq!(remaining, expected);
+    }
+
+    #[test]
+    fn test_exclude_ports_ignores_absent() {
+        assert_eq!(exclude_ports(&[80, 443], &[22, 8080]), vec![80, 443]);
+        assert!(exclude_ports(&[80], &[80]).is_empty());
+    }
+
     #[test]
     fn test_top_ports_nonempty() {
         let ports = top_ports();
@@ -316,6 +683,17 @@ mod tests {
         assert!(ports.contains(&25));
     }
 
+    #[test]
+    fn test_quick_ports_subset_of_top_ports() {
+        let quick = quick_ports();
+        let top = top_ports();
+        for essential in [22, 80, 443] {
+            assert!(quick.contains(&essential));
+        }
+        assert!(quick.iter().all(|p| top.contains(p)));
+        assert!(quick.len() < top.len());
+    }
+
     #[test]
     fn test_top_ports_unique() {
         let ports = top_ports();
@@ -349,6 +727,31 @@ mod tests {
         assert_eq!(m.get(&6379), Some(&"redis"));
     }
 
+    #[test]
+    fn test_port_data_json() {
+        let json: serde_json::Value = serde_json::to_value(port_data()).unwrap();
+        assert_eq!(json["services"]["80"], "http");
+        assert!(!json["top_ports"].as_array().unwrap().is_empty());
+        assert!(!json["quick_ports"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_ports_service_names() {
+        assert_eq!(parse_ports("ssh,443,mysql").unwrap(), vec![22, 443, 3306]);
+        assert_eq!(
+            parse_ports("HTTPS, http-alt,8000-8001").unwrap(),
+            vec![443, 8080, 8000, 8001]
+        );
+        assert_eq!(service_port("vnc"), Some(5900));
+    }
+
+    #[test]
+    fn test_parse_ports_unknown_service_name() {
+        let err = parse_ports("ssh,frobnicate").unwrap_err();
+        assert!(err.contains("frobnicate"), "{err}");
+        assert_eq!(service_port("frobnicate"), None);
+    }
+
     #[test]
     fn test_service_map_unknown_port() {
         let m = service_map();
@@ -372,6 +775,10 @@ mod tests {
             ports: vec![80, 443],
             timeout: Duration::from_secs(5),
             parallel: 50,
+            security_notes: true,
+            checkpoint: None,
+            banner: true,
+            progress: None,
         };
         assert_eq!(cfg.target, "example.com");
         assert_eq!(cfg.ports, vec![80, 443]);
@@ -379,6 +786,56 @@ mod tests {
         assert_eq!(cfg.parallel, 50);
     }
 
+    #[test]
+    fn test_security_note_flags_version_in_range() {
+        let note = security_note("SSH-2.0-OpenSSH_7.2p2 Ubuntu-4ubuntu2.8").unwrap();
+        assert!(note.contains("CVE-2016-10009"));
+        assert!(note.contains("heuristic"));
+        assert!(security_note("Apache/2.4.49 (Unix)")
+            .unwrap()
+            .contains("CVE-2021-41773"));
+        assert!(security_note("220 (vsFTPd 2.3.4)").is_some());
+        assert!(security_note("SSH-2.0-OpenSSH_9.3").is_some());
+    }
+
+    #[test]
+    fn test_security_note_ignores_version_outside_range() {
+        assert!(security_note("SSH-2.0-OpenSSH_7.4").is_none());
+        assert!(security_note("SSH-2.0-OpenSSH_9.8p1").is_none());
+        assert!(security_note("Apache/2.4.51 (Unix)").is_none());
+        assert!(security_note("nginx/1.20.1").is_none());
+        assert!(security_note("220 (vsFTPd 3.0.3)").is_none());
+        assert!(security_note("Microsoft-IIS/10.0").is_none());
+        assert!(security_note("OpenSSH").is_none());
+    }
+
+    #[test]
+    fn test_security_note_non_ascii_banner() {
+        // "İ" lowercases to three bytes; offsets from a full lowercase land inside "é"
+        assert!(security_note("İİİ SSH-2.0-OpenSSH_7.2p2").is_some());
+        assert!(security_note("İ OpenSSHé").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_security_notes_read_banners() {
+        use tokio::io::AsyncWriteExt;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut s, _) = listener.accept().await.unwrap();
+            s.write_all(b"SSH-2.0-OpenSSH_7.2p2\r\n").await.unwrap();
+        });
+        let config = PortConfig {
+            target: "127.0.0.1".to_string(),
+            ports: vec![port],
+            timeout: Duration::from_millis(500),
+            security_notes: true,
+            ..Default::default()
+        };
+        let result = scan(&config).await.unwrap();
+        assert!(result.ports[0].security_note.is_some());
+    }
+
     #[test]
     fn test_port_result_serialization() {
         let result = PortResult {
@@ -386,6 +843,8 @@ mod tests {
             open: true,
             service: Some("http".to_string()),
             rtt_ms: Some(15.5),
+            security_note: None,
+            banner: None,
         };
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("80"));
@@ -401,6 +860,8 @@ mod tests {
             open: false,
             service: None,
             rtt_ms: None,
+            security_note: None,
+            banner: None,
         };
         assert!(!result.open);
         assert!(result.service.is_none());
@@ -416,6 +877,7 @@ mod tests {
             open_count: 2,
             closed_count: 8,
             scan_time_ms: 1234.5,
+            timestamp: String::new(),
         };
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("example.com"));
@@ -431,12 +893,16 @@ mod tests {
                 open: true,
                 service: Some("http".to_string()),
                 rtt_ms: Some(10.0),
+                security_note: None,
+                banner: None,
             },
             PortResult {
                 port: 443,
                 open: true,
                 service: Some("https".to_string()),
                 rtt_ms: Some(12.5),
+                security_note: None,
+                banner: None,
             },
         ];
         let result = ScanResult {
@@ -446,6 +912,7 @@ mod tests {
             open_count: 2,
             closed_count: 8,
             scan_time_ms: 500.0,
+            timestamp: String::new(),
         };
 
         assert_eq!(result.ports.len(), 2);
@@ -453,4 +920,119 @@ mod tests {
         assert_eq!(result.closed_count, 8);
         assert!(result.ports.iter().all(|p| p.open));
     }
+
+    #[test]
+    fn test_format_port_ranges_round_trips() {
+        let ports: BTreeSet<u16> = [1, 2, 3, 80, 443, 444, 65535].into_iter().collect();
+        let ranges = format_port_ranges(&ports);
+        assert_eq!(ranges, "1-3,80,443-444,65535");
+        let parsed: BTreeSet<u16> = parse_ports(&ranges).unwrap().into_iter().collect();
+        assert_eq!(parsed, ports);
+        assert_eq!(format_port_ranges(&BTreeSet::new()), "");
+    }
+
+    #[tokio::test]
+    async fn test_scan_resumes_from_checkpoint() {
+        let path =
+            std::env::temp_dir().join(format!("netscout-checkpoint-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let first = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let second = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let (a, b) = (
+            first.local_addr().unwrap().port(),
+            second.local_addr().unwrap().port(),
+        );
+        let config = |ports: Vec<u16>| PortConfig {
+            target: "127.0.0.1".to_string(),
+            ports,
+            timeout: Duration::from_millis(500),
+            checkpoint: Some(path.clone()),
+            ..Default::default()
+        };
+
+        // Scan the first half, then take its open port away
+        scan(&config(vec![a])).await.unwrap();
+        let saved = Checkpoint::load(&path, "127.0.0.1").unwrap().unwrap();
+        assert_eq!(saved.scanned, a.to_string());
+        drop(first);
+
+        // The restart skips the saved port and keeps its earlier finding
+        let result = scan(&config(vec![a, b])).await.unwrap();
+        let open: Vec<u16> = result.ports.iter().map(|p| p.port).collect();
+        let mut expected = vec![a, b];
+        expected.sort_unstable();
+        assert_eq!(open, expected);
+        assert_eq!(result.open_count, 2);
+        let saved = Checkpoint::load(&path, "127.0.0.1").unwrap().unwrap();
+        assert_eq!(saved.scanned_ports().unwrap().len(), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_banner_line_takes_first_trimmed_line() {
+        assert_eq!(
+            banner_line(b"SSH-2.0-OpenSSH_8.9p1 Ubuntu-3\r\n").as_deref(),
+            Some("SSH-2.0-OpenSSH_8.9p1 Ubuntu-3")
+        );
+        assert_eq!(
+            banner_line(b"\r\n  220 mail ESMTP Postfix \r\n250 ok").as_deref(),
+            Some("220 mail ESMTP Postfix")
+        );
+        assert_eq!(banner_line(b"\x07\r\n"), None);
+        assert_eq!(banner_line(b""), None);
+    }
+
+    #[tokio::test]
+    async fn test_scan_grabs_banner_and_tolerates_silent_ports() {
+        let talker = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let silent = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let (a, b) = (
+            talker.local_addr().unwrap().port(),
+            silent.local_addr().unwrap().port(),
+        );
+        tokio::spawn(async move {
+            let (mut s, _) = talker.accept().await.unwrap();
+            s.write_all(b"SSH-2.0-OpenSSH_7.2p2\r\n").await.unwrap();
+        });
+        tokio::spawn(async move {
+            // Accept and hold the connection without saying anything
+            let (_s, _) = silent.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+        let config = PortConfig {
+            target: "127.0.0.1".to_string(),
+            ports: vec![a, b],
+            timeout: Duration::from_millis(500),
+            banner: true,
+            security_notes: true,
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let result = scan(&config).await.unwrap();
+        assert!(start.elapsed() < Duration::from_secs(3));
+        let by_port = |p| result.ports.iter().find(|r| r.port == p).unwrap();
+        assert_eq!(by_port(a).banner.as_deref(), Some("SSH-2.0-OpenSSH_7.2p2"));
+        assert!(by_port(a).security_note.is_some());
+        assert!(by_port(b).open);
+        assert_eq!(by_port(b).banner, None);
+    }
+
+    #[test]
+    fn test_checkpoint_for_other_target_is_rejected() {
+        let path = std::env::temp_dir().join(format!(
+            "netscout-checkpoint-other-{}.json",
+            std::process::id()
+        ));
+        let checkpoint = Checkpoint {
+            target: "example.com".to_string(),
+            scanned: "1-1024".to_string(),
+            open: Vec::new(),
+        };
+        checkpoint.save(&path).unwrap();
+        let err = Checkpoint::load(&path, "example.org").unwrap_err();
+        assert!(err.contains("is for example.com"));
+        assert!(Checkpoint::load(&path, "example.com").unwrap().is_some());
+        std::fs::remove_file(&path).unwrap();
+        assert!(Checkpoint::load(&path, "example.com").unwrap().is_none());
+    }
 }
@@ -0,0 +1,66 @@
+//! Completion callbacks for long-running scans.
+//!
+//! [`port::scan`](crate::port::scan) and [`scan::scan`](crate::scan::scan)
+//! call a [`Progress`] with `(completed, total)` each time a probe finishes.
+
+use std::sync::Arc;
+
+/// A shareable `(completed, total)` callback.
+#[derive(Clone)]
+pub struct Progress(Arc<dyn Fn(usize, usize) + Send + Sync>);
+
+impl Progress {
+    pub fn new(f: impl Fn(usize, usize) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    /// Report that `completed` of `total` items are done.
+    pub fn report(&self, completed: usize, total: usize) {
+        (self.0)(completed, total)
+    }
+}
+
+impl std::fmt::Debug for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Progress(..)")
+    }
+}
+
+/// Render a progress bar, e.g. `[#####---------------]  25% (256/1024)`.
+pub fn format_bar(completed: usize, total: usize, width: usize) -> String {
+    let completed = completed.min(total);
+    let (filled, percent) = match total {
+        0 => (width, 100),
+        _ => (width * completed / total, 100 * completed / total),
+    };
+    format!(
+        "[{}{}] {percent:>3}% ({completed}/{total})",
+        "#".repeat(filled),
+        "-".repeat(width - filled)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_format_bar() {
+        assert_eq!(format_bar(0, 4, 8), "[--------]   0% (0/4)");
+        assert_eq!(format_bar(1, 4, 8), "[##------]  25% (1/4)");
+        assert_eq!(format_bar(4, 4, 8), "[########] 100% (4/4)");
+        assert_eq!(format_bar(9, 4, 8), "[########] 100% (4/4)");
+        assert_eq!(format_bar(0, 0, 4), "[####] 100% (0/0)");
+    }
+
+    #[test]
+    fn test_progress_reports_to_callback() {
+        let last = Arc::new(AtomicUsize::new(0));
+        let seen = last.clone();
+        let progress = Progress::new(move |done, _| seen.store(done, Ordering::SeqCst));
+        progress.clone().report(3, 10);
+        assert_eq!(last.load(Ordering::SeqCst), 3);
+        assert_eq!(format!("{progress:?}"), "Progress(..)");
+    }
+}
@@ -0,0 +1,140 @@
+//! Prometheus text exposition format, e.g. for a node_exporter textfile collector.
+//!
+//! Results that support it expose [`Sample`]s through
+//! [`HumanReadable::prometheus_samples`](crate::output::HumanReadable::prometheus_samples);
+//! [`render`] groups them into metric families with `# HELP` and `# TYPE` lines.
+
+/// One sample of a gauge metric family.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub labels: Vec<(&'static str, String)>,
+    pub value: f64,
+}
+
+impl Sample {
+    pub fn new(
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&'static str, &str)],
+        value: f64,
+    ) -> Self {
+        Self {
+            name,
+            help,
+            labels: labels.iter().map(|(k, v)| (*k, v.to_string())).collect(),
+            value,
+        }
+    }
+
+    /// Add a label, e.g. to split one family by phase.
+    pub fn with_label(mut self, key: &'static str, value: &str) -> Self {
+        self.labels.push((key, value.to_string()));
+        self
+    }
+}
+
+/// Escape a label value: backslash, double quote and newline.
+pub fn escape_label(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// A sample value as Prometheus spells it (`NaN`, `+Inf`, `-Inf` for non-finite values).
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// A single sample line, e.g. `netscout_ping_rtt_ms{target="example.com"} 12.3`.
+pub fn sample_line(sample: &Sample) -> String {
+    let labels = sample
+        .labels
+        .iter()
+        .map(|(k, v)| format!("{k}=\"{}\"", escape_label(v)))
+        .collect::<Vec<_>>()
+        .join(",");
+    let labels = if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{labels}}}")
+    };
+    format!("{}{labels} {}\n", sample.name, format_value(sample.value))
+}
+
+/// Render samples grouped by metric family, in first-seen order, each family
+/// preceded by its `# HELP` and `# TYPE` lines. All netscout metrics are gauges.
+pub fn render(samples: &[Sample]) -> String {
+    let mut names: Vec<&'static str> = Vec::new();
+    for s in samples {
+        if !names.contains(&s.name) {
+            names.push(s.name);
+        }
+    }
+    let mut out = String::new();
+    for name in names {
+        let mut family = samples.iter().filter(|s| s.name == name).peekable();
+        if let Some(first) = family.peek() {
+            out.push_str(&format!("# HELP {name} {}\n", first.help));
+            out.push_str(&format!("# TYPE {name} gauge\n"));
+        }
+        for s in family {
+            out.push_str(&sample_line(s));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("plain"), "plain");
+        assert_eq!(escape_label("a\"b"), "a\\\"b");
+        assert_eq!(escape_label("C:\\path"), "C:\\\\path");
+        assert_eq!(escape_label("two\nlines"), "two\\nlines");
+    }
+
+    #[test]
+    fn test_sample_line() {
+        let s = Sample::new("netscout_x", "X.", &[("target", "a\"b")], 1.5);
+        assert_eq!(sample_line(&s), "netscout_x{target=\"a\\\"b\"} 1.5\n");
+        let bare = Sample::new("netscout_x", "X.", &[], 2.0);
+        assert_eq!(sample_line(&bare), "netscout_x 2\n");
+        let nan = Sample::new("netscout_x", "X.", &[], f64::NAN);
+        assert_eq!(sample_line(&nan), "netscout_x NaN\n");
+    }
+
+    #[test]
+    fn test_render_groups_families() {
+        let samples = vec![
+            Sample::new("netscout_a", "A.", &[("target", "one")], 1.0),
+            Sample::new("netscout_b", "B.", &[("target", "one")], 2.0),
+            Sample::new("netscout_a", "A.", &[("target", "two")], 3.0),
+        ];
+        assert_eq!(
+            render(&samples),
+            "# HELP netscout_a A.\n# TYPE netscout_a gauge\n\
+             netscout_a{target=\"one\"} 1\nnetscout_a{target=\"two\"} 3\n\
+             # HELP netscout_b B.\n# TYPE netscout_b gauge\n\
+             netscout_b{target=\"one\"} 2\n"
+        );
+        assert_eq!(render(&[]), "");
+    }
+}
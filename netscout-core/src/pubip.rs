@@ -0,0 +1,284 @@
+use crate::health::{HealthCheck, HealthStatus};
+use crate::output::{csv_row, HumanReadable};
+use colored::Colorize;
+use serde::Serialize;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+/// Configuration for public IP discovery.
+#[derive(Debug, Clone)]
+pub struct PubipConfig {
+    /// Echo service that answers with the caller's address as plain text.
+    pub endpoint: String,
+    /// IPv6-only echo service; `None` skips the IPv6 lookup.
+    pub endpoint_v6: Option<String>,
+    /// Reverse-resolve each address.
+    pub ptr: bool,
+    /// Look up the owning organisation of each address via WHOIS.
+    pub owner: bool,
+    pub timeout: Duration,
+}
+
+impl Default for PubipConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://api.ipify.org/".to_string(),
+            endpoint_v6: Some("https://api6.ipify.org/".to_string()),
+            ptr: false,
+            owner: false,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// A public address and what is known about it.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicAddress {
+    pub ip: IpAddr,
+    /// Endpoint that reported the address.
+    pub endpoint: String,
+    pub ptr: Option<String>,
+    pub owner: Option<String>,
+}
+
+/// Public IPv4 and IPv6 addresses of this host.
+#[derive(Debug, Clone, Serialize)]
+pub struct PubipResult {
+    pub ipv4: Option<PublicAddress>,
+    pub ipv6: Option<PublicAddress>,
+    /// Lookups that failed while at least one address was found.
+    pub errors: Vec<String>,
+    /// When the lookup started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
+}
+
+/// Extract the address from an echo service's plain-text answer.
+pub fn parse_ip(body: &[u8]) -> Result<IpAddr, String> {
+    let text = String::from_utf8_lossy(body);
+    let text = text.trim();
+    text.parse().map_err(|_| {
+        let shown: String = text.chars().take(40).collect();
+        format!("Endpoint did not answer with an IP address: '{shown}'")
+    })
+}
+
+/// Ask one echo endpoint for the caller's address.
+pub fn fetch_ip(endpoint: &str, timeout: Duration) -> Result<IpAddr, String> {
+    let config = crate::http::HttpConfig {
+        url: endpoint.to_string(),
+        timeout,
+        ..Default::default()
+    };
+    let (status, body) = crate::http::fetch(&config)?;
+    if status != 200 {
+        return Err(format!("{endpoint} returned HTTP {status}"));
+    }
+    parse_ip(&body)
+}
+
+/// Query an endpoint on the blocking pool, then optionally add PTR and owner.
+async fn discover(
+    endpoint: String,
+    config: &PubipConfig,
+    errors: &mut Vec<String>,
+) -> Result<PublicAddress, String> {
+    let timeout = config.timeout;
+    let url = endpoint.clone();
+    let ip = crate::runtime::blocking(move || fetch_ip(&url, timeout)).await?;
+    let ptr = if config.ptr {
+        crate::runtime::blocking(move || {
+            Ok(crate::trace::dns_lookup_reverse(SocketAddr::new(ip, 0)))
+        })
+        .await?
+    } else {
+        None
+    };
+    let owner = if config.owner {
+        crate::runtime::blocking(move || crate::whois::ip_owner(&ip.to_string(), timeout))
+            .await
+            .unwrap_or_else(|e| {
+                errors.push(format!("owner of {ip}: {e}"));
+                None
+            })
+    } else {
+        None
+    };
+    Ok(PublicAddress {
+        ip,
+        endpoint,
+        ptr,
+        owner,
+    })
+}
+
+/// Discover the public IPv4 and (if configured) IPv6 address.
+///
+/// Fails only when no address could be found at all.
+pub async fn lookup(config: &PubipConfig) -> Result<PubipResult, String> {
+    let timestamp = crate::timestamp::now();
+    let mut errors = Vec::new();
+    let mut v6_errors = Vec::new();
+    let v4 = discover(config.endpoint.clone(), config, &mut errors);
+    let v6 = async {
+        match config.endpoint_v6 {
+            Some(ref e) => Some(discover(e.clone(), config, &mut v6_errors).await),
+            None => None,
+        }
+    };
+    let (v4, v6) = tokio::join!(v4, v6);
+    errors.append(&mut v6_errors);
+
+    let (mut ipv4, mut ipv6) = (None, None);
+    // Place each answer by its family; a dual-stack endpoint may answer either
+    for answer in [Some(v4), v6].into_iter().flatten() {
+        match answer {
+            Ok(addr) => {
+                let slot = if addr.ip.is_ipv4() {
+                    &mut ipv4
+                } else {
+                    &mut ipv6
+                };
+                if slot.is_none() {
+                    *slot = Some(addr);
+                }
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    if ipv4.is_none() && ipv6.is_none() {
+        return Err(format!("No public IP found: {}", errors.join("; ")));
+    }
+    Ok(PubipResult {
+        ipv4,
+        ipv6,
+        errors,
+        timestamp,
+    })
+}
+
+impl PubipResult {
+    fn addresses(&self) -> impl Iterator<Item = &PublicAddress> {
+        self.ipv4.iter().chain(self.ipv6.iter())
+    }
+}
+
+impl HumanReadable for PubipResult {
+    fn to_human(&self) -> String {
+        let mut out = format!("{}\n", "PUBLIC IP".cyan().bold());
+        for (label, addr) in [("IPv4", &self.ipv4), ("IPv6", &self.ipv6)] {
+            let Some(addr) = addr else {
+                out.push_str(&format!("  {label}: {}\n", "none".dimmed()));
+                continue;
+            };
+            out.push_str(&format!("  {label}: {}\n", addr.ip.to_string().bold()));
+            if let Some(ref ptr) = addr.ptr {
+                out.push_str(&format!("    PTR:   {ptr}\n"));
+            }
+            if let Some(ref owner) = addr.owner {
+                out.push_str(&format!("    Owner: {owner}\n"));
+            }
+        }
+        for e in &self.errors {
+            out.push_str(&format!("  {} {e}\n", "⚠".yellow()));
+        }
+        out
+    }
+
+    fn to_oneline(&self) -> String {
+        let ips: Vec<String> = self.addresses().map(|a| a.ip.to_string()).collect();
+        format!("PUBIP {}", ips.join(" "))
+    }
+
+    fn health(&self) -> HealthCheck {
+        let status = if self.errors.is_empty() {
+            HealthStatus::Passing
+        } else {
+            HealthStatus::Warning
+        };
+        HealthCheck::new(
+            status,
+            self.to_oneline(),
+            "warning if any lookup failed after an address was found",
+        )
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = csv_row(&[&"family", &"ip", &"ptr", &"owner", &"endpoint"]);
+        for a in self.addresses() {
+            let family = if a.ip.is_ipv4() { "ipv4" } else { "ipv6" };
+            out.push_str(&csv_row(&[
+                &family,
+                &a.ip,
+                &a.ptr.as_deref().unwrap_or(""),
+                &a.owner.as_deref().unwrap_or(""),
+                &a.endpoint,
+            ]));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{response, spawn_test_http_server};
+
+    #[test]
+    fn test_parse_ip_trims_and_validates() {
+        assert_eq!(
+            parse_ip(b"203.0.113.7\n").unwrap(),
+            "203.0.113.7".parse::<IpAddr>().unwrap()
+        );
+        assert!(parse_ip(b" 2001:db8::1 ").unwrap().is_ipv6());
+        let err = parse_ip(b"<html>rate limited</html>").unwrap_err();
+        assert!(err.contains("rate limited"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_lookup_extracts_address_from_endpoint() {
+        let addr = spawn_test_http_server(|_| response(200, "OK", &[], b"198.51.100.23\n")).await;
+        let config = PubipConfig {
+            endpoint: format!("http://{addr}/"),
+            endpoint_v6: None,
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let result = lookup(&config).await.unwrap();
+        let v4 = result.ipv4.expect("IPv4 address");
+        assert_eq!(v4.ip, "198.51.100.23".parse::<IpAddr>().unwrap());
+        assert_eq!(v4.endpoint, config.endpoint);
+        assert!(result.ipv6.is_none());
+        assert!(result.errors.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_lookup_fails_without_any_address() {
+        let addr = spawn_test_http_server(|_| response(503, "Busy", &[], b"")).await;
+        let config = PubipConfig {
+            endpoint: format!("http://{addr}/"),
+            endpoint_v6: None,
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let err = lookup(&config).await.unwrap_err();
+        assert!(err.contains("HTTP 503"));
+    }
+
+    #[test]
+    fn test_pubip_oneline_lists_both_families() {
+        let addr = |ip: &str| PublicAddress {
+            ip: ip.parse().unwrap(),
+            endpoint: "http://echo/".to_string(),
+            ptr: None,
+            owner: None,
+        };
+        let result = PubipResult {
+            ipv4: Some(addr("198.51.100.23")),
+            ipv6: Some(addr("2001:db8::23")),
+            errors: Vec::new(),
+            timestamp: String::new(),
+        };
+        assert_eq!(result.to_oneline(), "PUBIP 198.51.100.23 2001:db8::23");
+        assert_eq!(result.to_csv().lines().count(), 3);
+    }
+}
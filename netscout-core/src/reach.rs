@@ -0,0 +1,352 @@
+use crate::health::{HealthCheck, HealthStatus};
+use crate::output::{csv_row, HumanReadable};
+use colored::Colorize;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Configuration for a reachability check.
+#[derive(Debug, Clone)]
+pub struct ReachConfig {
+    pub target: String,
+    /// Ports to try; the port check passes if any of them accepts a connection.
+    pub ports: Vec<u16>,
+    /// URL for the optional HTTP status check.
+    pub http_url: Option<String>,
+    pub ping_count: u32,
+    pub timeout: Duration,
+}
+
+impl Default for ReachConfig {
+    fn default() -> Self {
+        Self {
+            target: String::new(),
+            ports: vec![443, 80],
+            http_url: None,
+            ping_count: 2,
+            timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Outcome of the ping part of a reachability check.
+#[derive(Debug, Clone, Serialize)]
+pub struct PingCheck {
+    pub success: bool,
+    pub avg_ms: Option<f64>,
+    pub loss_percent: f64,
+}
+
+/// Outcome of the port part of a reachability check.
+#[derive(Debug, Clone, Serialize)]
+pub struct PortCheck {
+    pub ports: Vec<u16>,
+    pub open_port: Option<u16>,
+}
+
+/// Outcome of the HTTP part of a reachability check.
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpCheck {
+    pub url: String,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+impl HttpCheck {
+    /// Returns true if the server answered with a non-error status.
+    pub fn is_ok(&self) -> bool {
+        self.status.is_some_and(|s| s < 400)
+    }
+}
+
+/// Combined "is this service up" verdict.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReachResult {
+    pub target: String,
+    pub reachable: bool,
+    pub reason: String,
+    pub ping: Option<PingCheck>,
+    pub port: PortCheck,
+    pub http: Option<HttpCheck>,
+    /// When the check started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
+}
+
+impl ReachResult {
+    /// Combine sub-check outcomes into a verdict.
+    ///
+    /// The host is reachable if a port connects or ping succeeds. When an HTTP
+    /// check was requested it must also return a status below 400.
+    pub fn from_checks(
+        target: &str,
+        ping: Option<PingCheck>,
+        port: PortCheck,
+        http: Option<HttpCheck>,
+    ) -> Self {
+        let ping_ok = ping.as_ref().is_some_and(|p| p.success);
+        let port_ok = port.open_port.is_some();
+        let (reachable, reason) = if !ping_ok && !port_ok {
+            (false, "no response to ping or port checks".to_string())
+        } else if let Some(h) = http.as_ref().filter(|h| !h.is_ok()) {
+            let why = match (h.status, &h.error) {
+                (Some(s), _) => format!("HTTP status {s}"),
+                (None, Some(e)) => format!("HTTP failed: {e}"),
+                (None, None) => "HTTP failed".to_string(),
+            };
+            (false, why)
+        } else if let Some(p) = port.open_port {
+            (true, format!("port {p} open"))
+        } else {
+            (true, "ping succeeded".to_string())
+        };
+        Self {
+            target: target.to_string(),
+            reachable,
+            reason,
+            ping,
+            port,
+            http,
+            timestamp: crate::timestamp::now(),
+        }
+    }
+}
+
+/// Check whether a target is reachable via ping, port and optional HTTP checks.
+pub async fn reach(config: &ReachConfig) -> Result<ReachResult, String> {
+    let timestamp = crate::timestamp::now();
+    let ping_config = crate::ping::PingConfig {
+        target: config.target.clone(),
+        count: config.ping_count,
+        interval: Duration::from_millis(200),
+        timeout: config.timeout,
+        ..Default::default()
+    };
+    // A failed ping (e.g. DNS error) counts as "no ping"; the port check reports the cause
+    let ping = crate::ping::ping(&ping_config)
+        .await
+        .ok()
+        .map(|s| PingCheck {
+            success: s.received > 0,
+            avg_ms: s.avg_ms,
+            loss_percent: s.loss_percent,
+        });
+
+    let port_config = crate::port::PortConfig {
+        target: config.target.clone(),
+        ports: config.ports.clone(),
+        timeout: config.timeout,
+        parallel: config.ports.len().max(1),
+        ..Default::default()
+    };
+    let scan = crate::port::scan(&port_config).await?;
+    let open_port = config
+        .ports
+        .iter()
+        .copied()
+        .find(|p| scan.ports.iter().any(|r| r.port == *p && r.open));
+    let port = PortCheck {
+        ports: config.ports.clone(),
+        open_port,
+    };
+
+    let http = match config.http_url {
+        Some(ref url) => {
+            let http_config = crate::http::HttpConfig {
+                url: url.clone(),
+                timeout: config.timeout,
+                ..Default::default()
+            };
+            let url = url.clone();
+            Some(match crate::http::probe_async(&http_config).await {
+                Ok(r) => HttpCheck {
+                    url,
+                    status: Some(r.status),
+                    error: None,
+                },
+                Err(e) => HttpCheck {
+                    url,
+                    status: None,
+                    error: Some(e),
+                },
+            })
+        }
+        None => None,
+    };
+
+    let mut result = ReachResult::from_checks(&config.target, ping, port, http);
+    result.timestamp = timestamp;
+    Ok(result)
+}
+
+impl HumanReadable for ReachResult {
+    fn to_human(&self) -> String {
+        let verdict = if self.reachable {
+            "UP".green().bold()
+        } else {
+            "DOWN".red().bold()
+        };
+        let mut out = format!(
+            "{} {} — {verdict} ({})\n",
+            "REACH".cyan().bold(),
+            self.target,
+            self.reason
+        );
+        match self.ping {
+            Some(ref p) if p.success => out.push_str(&format!(
+                "  {} ping {}\n",
+                "✓".green(),
+                p.avg_ms.map(crate::output::format_ms).unwrap_or_default()
+            )),
+            _ => out.push_str(&format!("  {} ping\n", "✗".red())),
+        }
+        let ports = self
+            .port
+            .ports
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        match self.port.open_port {
+            Some(p) => out.push_str(&format!("  {} port {p} open\n", "✓".green())),
+            None => out.push_str(&format!("  {} port {ports} closed\n", "✗".red())),
+        }
+        if let Some(ref h) = self.http {
+            let icon = if h.is_ok() {
+                "✓".green()
+            } else {
+                "✗".red()
+            };
+            let detail = match (h.status, &h.error) {
+                (Some(s), _) => s.to_string(),
+                (None, Some(e)) => e.clone(),
+                (None, None) => "-".to_string(),
+            };
+            out.push_str(&format!("  {icon} http {} → {detail}\n", h.url));
+        }
+        out
+    }
+
+    fn health(&self) -> HealthCheck {
+        let status = if self.reachable {
+            HealthStatus::Passing
+        } else {
+            HealthStatus::Critical
+        };
+        HealthCheck::new(status, format!("{}: {}", self.target, self.reason), "")
+    }
+
+    fn to_csv(&self) -> String {
+        csv_row(&[&"target", &"reachable", &"reason"])
+            + &csv_row(&[&self.target, &self.reachable, &self.reason])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ping_check(success: bool) -> Option<PingCheck> {
+        Some(PingCheck {
+            success,
+            avg_ms: success.then_some(12.0),
+            loss_percent: if success { 0.0 } else { 100.0 },
+        })
+    }
+
+    fn port_check(open_port: Option<u16>) -> PortCheck {
+        PortCheck {
+            ports: vec![443, 80],
+            open_port,
+        }
+    }
+
+    fn http_check(status: Option<u16>) -> Option<HttpCheck> {
+        Some(HttpCheck {
+            url: "http://example.com/".to_string(),
+            status,
+            error: status.is_none().then(|| "Connect failed".to_string()),
+        })
+    }
+
+    #[test]
+    fn test_reach_config_default() {
+        let cfg = ReachConfig::default();
+        assert_eq!(cfg.ports, vec![443, 80]);
+        assert!(cfg.http_url.is_none());
+    }
+
+    #[test]
+    fn test_verdict_up_via_port() {
+        let r = ReachResult::from_checks(
+            "example.com",
+            ping_check(false),
+            port_check(Some(443)),
+            None,
+        );
+        assert!(r.reachable);
+        assert_eq!(r.reason, "port 443 open");
+    }
+
+    #[test]
+    fn test_verdict_up_via_ping() {
+        let r = ReachResult::from_checks("example.com", ping_check(true), port_check(None), None);
+        assert!(r.reachable);
+        assert_eq!(r.reason, "ping succeeded");
+    }
+
+    #[test]
+    fn test_verdict_down() {
+        let r = ReachResult::from_checks("example.com", ping_check(false), port_check(None), None);
+        assert!(!r.reachable);
+        let r = ReachResult::from_checks("example.com", None, port_check(None), None);
+        assert!(!r.reachable);
+        assert!(r.to_human().contains("DOWN"));
+    }
+
+    #[test]
+    fn test_verdict_http_must_succeed_when_requested() {
+        let r = ReachResult::from_checks(
+            "example.com",
+            ping_check(true),
+            port_check(Some(80)),
+            http_check(Some(200)),
+        );
+        assert!(r.reachable);
+        let r = ReachResult::from_checks(
+            "example.com",
+            ping_check(true),
+            port_check(Some(80)),
+            http_check(Some(503)),
+        );
+        assert!(!r.reachable);
+        assert_eq!(r.reason, "HTTP status 503");
+        let r = ReachResult::from_checks(
+            "example.com",
+            ping_check(true),
+            port_check(Some(80)),
+            http_check(None),
+        );
+        assert!(!r.reachable);
+        assert!(r.reason.contains("Connect failed"));
+    }
+
+    #[tokio::test]
+    async fn test_reach_localhost_open_port() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let _ = listener.accept().await;
+            }
+        });
+        let config = ReachConfig {
+            target: "127.0.0.1".to_string(),
+            ports: vec![port],
+            ping_count: 1,
+            timeout: Duration::from_millis(500),
+            ..Default::default()
+        };
+        let r = reach(&config).await.unwrap();
+        assert!(r.reachable);
+        assert_eq!(r.port.open_port, Some(port));
+    }
+}
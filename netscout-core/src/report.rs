@@ -0,0 +1,384 @@
+use crate::health::{HealthCheck, HealthStatus};
+use crate::netif::GatewayInfo;
+use crate::output::{csv_row, format_ms, HumanReadable};
+use colored::Colorize;
+use serde::Serialize;
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Configuration for a network report.
+#[derive(Debug, Clone)]
+pub struct ReportConfig {
+    /// Well-known host used for the DNS and ping checks.
+    pub probe_host: String,
+    /// Service that answers with the caller's public IP as plain text.
+    pub public_ip_url: String,
+    pub timeout: Duration,
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            probe_host: "one.one.one.one".to_string(),
+            public_ip_url: "https://api.ipify.org/".to_string(),
+            timeout: Duration::from_secs(3),
+        }
+    }
+}
+
+/// An interface that is up, with its addresses as `ip/prefix`.
+#[derive(Debug, Clone, Serialize)]
+pub struct InterfaceSummary {
+    pub name: String,
+    pub addresses: Vec<String>,
+}
+
+/// Outcome of resolving the probe host through the system resolver.
+#[derive(Debug, Clone, Serialize)]
+pub struct DnsCheck {
+    pub host: String,
+    pub addresses: Vec<IpAddr>,
+    pub time_ms: f64,
+}
+
+/// Outcome of pinging the probe host.
+#[derive(Debug, Clone, Serialize)]
+pub struct PingCheck {
+    pub target: String,
+    pub avg_ms: Option<f64>,
+    pub loss_percent: f64,
+}
+
+/// Snapshot of the local network: interfaces, gateway, resolvers, public IP
+/// and a quick DNS + ping check.
+///
+/// Every section is best-effort; a section that could not be gathered is left
+/// empty and the reason recorded in `errors`.
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkReport {
+    pub interfaces: Vec<InterfaceSummary>,
+    pub gateway: Option<GatewayInfo>,
+    pub resolvers: Vec<String>,
+    pub public_ip: Option<String>,
+    pub dns: Option<DnsCheck>,
+    pub ping: Option<PingCheck>,
+    /// `section: reason` for each section that failed.
+    pub errors: Vec<String>,
+    /// When the report started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
+}
+
+/// Up, non-loopback interfaces that have at least one address.
+fn summarize_interfaces(netif: &crate::netif::NetifResult) -> Vec<InterfaceSummary> {
+    netif
+        .interfaces
+        .iter()
+        .filter(|i| i.is_up && !i.is_loopback && !i.addresses.is_empty())
+        .map(|i| InterfaceSummary {
+            name: i.name.clone(),
+            addresses: i
+                .addresses
+                .iter()
+                .map(|a| match a.prefix_len {
+                    Some(p) => format!("{}/{p}", a.ip),
+                    None => a.ip.to_string(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Resolve the probe host, then ping the first address it resolved to.
+async fn dns_and_ping(config: &ReportConfig) -> (Result<DnsCheck, String>, Option<PingCheck>) {
+    let dns = match crate::dns::system_resolve_async(&config.probe_host, None).await {
+        Ok(r) => DnsCheck {
+            host: r.domain,
+            addresses: r.addresses,
+            time_ms: r.query_time_ms,
+        },
+        Err(e) => return (Err(e), None),
+    };
+    let Some(target) = dns.addresses.first().map(|a| a.to_string()) else {
+        return (Ok(dns), None);
+    };
+    let ping_config = crate::ping::PingConfig {
+        target: target.clone(),
+        count: 3,
+        interval: Duration::from_millis(200),
+        timeout: config.timeout,
+        port: 443,
+        ..Default::default()
+    };
+    let ping = crate::ping::ping(&ping_config)
+        .await
+        .ok()
+        .map(|s| PingCheck {
+            target,
+            avg_ms: s.avg_ms,
+            loss_percent: s.loss_percent,
+        });
+    (Ok(dns), ping)
+}
+
+/// Gather a network report. Never fails; failed sections are listed in `errors`.
+pub async fn report(config: &ReportConfig) -> NetworkReport {
+    let timestamp = crate::timestamp::now();
+    let mut errors = Vec::new();
+    let interfaces = match crate::netif::list_interfaces() {
+        Ok(r) => summarize_interfaces(&r),
+        Err(e) => {
+            errors.push(format!("interfaces: {e}"));
+            Vec::new()
+        }
+    };
+    let gateway = crate::netif::default_gateway()
+        .map_err(|e| errors.push(format!("gateway: {e}")))
+        .ok();
+    let resolvers = crate::netif::system_resolvers()
+        .map_err(|e| errors.push(format!("resolvers: {e}")))
+        .unwrap_or_default();
+
+    let (url, timeout) = (config.public_ip_url.clone(), config.timeout);
+    let public_ip = crate::runtime::blocking(move || crate::pubip::fetch_ip(&url, timeout));
+    let (public_ip, (dns, ping)) = tokio::join!(public_ip, dns_and_ping(config));
+    let public_ip = public_ip
+        .map(|ip| ip.to_string())
+        .map_err(|e| errors.push(format!("public IP: {e}")))
+        .ok();
+    let dns = dns.map_err(|e| errors.push(format!("dns: {e}"))).ok();
+    if dns.is_some() && ping.as_ref().is_none_or(|p| p.avg_ms.is_none()) {
+        errors.push(format!("ping: no reply from {}", config.probe_host));
+    }
+
+    NetworkReport {
+        interfaces,
+        gateway,
+        resolvers,
+        public_ip,
+        dns,
+        ping,
+        errors,
+        timestamp,
+    }
+}
+
+impl NetworkReport {
+    /// Whether the probe host answered a ping.
+    fn ping_ok(&self) -> bool {
+        self.ping.as_ref().is_some_and(|p| p.avg_ms.is_some())
+    }
+}
+
+impl HumanReadable for NetworkReport {
+    fn to_human(&self) -> String {
+        let unknown = || "unknown".dimmed().to_string();
+        let mut out = format!("{}\n", "NETWORK REPORT".cyan().bold());
+        out.push_str("  Interfaces:\n");
+        if self.interfaces.is_empty() {
+            out.push_str(&format!("    {}\n", "none up".dimmed()));
+        }
+        for i in &self.interfaces {
+            out.push_str(&format!("    {:<10} {}\n", i.name, i.addresses.join(", ")));
+        }
+        let gateway = self
+            .gateway
+            .as_ref()
+            .map(|g| format!("{} via {}", g.gateway, g.interface))
+            .unwrap_or_else(unknown);
+        out.push_str(&format!("  Gateway: {gateway}\n"));
+        let resolvers = if self.resolvers.is_empty() {
+            unknown()
+        } else {
+            self.resolvers.join(", ")
+        };
+        out.push_str(&format!("  DNS servers: {resolvers}\n"));
+        let public_ip = self.public_ip.clone().unwrap_or_else(unknown);
+        out.push_str(&format!("  Public IP: {public_ip}\n"));
+        match self.dns {
+            Some(ref d) => out.push_str(&format!(
+                "  {} DNS {} → {} ({})\n",
+                "✓".green(),
+                d.host,
+                d.addresses
+                    .iter()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                format_ms(d.time_ms)
+            )),
+            None => out.push_str(&format!("  {} DNS\n", "✗".red())),
+        }
+        match self.ping {
+            Some(PingCheck {
+                ref target,
+                avg_ms: Some(avg),
+                loss_percent,
+            }) => out.push_str(&format!(
+                "  {} ping {target} {} avg, {loss_percent:.0}% loss\n",
+                "✓".green(),
+                format_ms(avg)
+            )),
+            _ => out.push_str(&format!("  {} ping\n", "✗".red())),
+        }
+        if !self.errors.is_empty() {
+            out.push_str("  Problems:\n");
+            for e in &self.errors {
+                out.push_str(&format!("    {} {e}\n", "⚠".yellow()));
+            }
+        }
+        out
+    }
+
+    fn to_oneline(&self) -> String {
+        let gateway = self
+            .gateway
+            .as_ref()
+            .map_or("?".to_string(), |g| g.gateway.to_string());
+        let ping = match self.ping.as_ref().and_then(|p| p.avg_ms) {
+            Some(avg) => format!("{avg:.0}ms"),
+            None => "down".to_string(),
+        };
+        format!(
+            "NET gw {gateway}, public {}, dns {}, ping {ping}",
+            self.public_ip.as_deref().unwrap_or("?"),
+            if self.dns.is_some() { "ok" } else { "failed" },
+        )
+    }
+
+    fn health(&self) -> HealthCheck {
+        let status = if self.dns.is_none() || !self.ping_ok() {
+            HealthStatus::Critical
+        } else if !self.errors.is_empty() {
+            HealthStatus::Warning
+        } else {
+            HealthStatus::Passing
+        };
+        HealthCheck::new(
+            status,
+            self.to_oneline(),
+            "critical if DNS or ping fails, warning if any other section is missing",
+        )
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = csv_row(&[&"section", &"value"]);
+        for i in &self.interfaces {
+            out.push_str(&csv_row(&[
+                &format!("interface {}", i.name),
+                &i.addresses.join(" "),
+            ]));
+        }
+        if let Some(ref g) = self.gateway {
+            out.push_str(&csv_row(&[&"gateway", &g.gateway]));
+        }
+        out.push_str(&csv_row(&[&"resolvers", &self.resolvers.join(" ")]));
+        out.push_str(&csv_row(&[
+            &"public_ip",
+            &self.public_ip.as_deref().unwrap_or(""),
+        ]));
+        if let Some(ref d) = self.dns {
+            out.push_str(&csv_row(&[&"dns_ms", &format!("{:.1}", d.time_ms)]));
+        }
+        if let Some(avg) = self.ping.as_ref().and_then(|p| p.avg_ms) {
+            out.push_str(&csv_row(&[&"ping_avg_ms", &format!("{avg:.1}")]));
+        }
+        for e in &self.errors {
+            out.push_str(&csv_row(&[&"error", e]));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn partial_report() -> NetworkReport {
+        NetworkReport {
+            interfaces: vec![InterfaceSummary {
+                name: "eth0".to_string(),
+                addresses: vec!["192.168.1.20/24".to_string()],
+            }],
+            gateway: Some(GatewayInfo {
+                interface: "eth0".to_string(),
+                gateway: IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)),
+            }),
+            resolvers: vec!["192.168.1.1".to_string()],
+            public_ip: None,
+            dns: Some(DnsCheck {
+                host: "one.one.one.one".to_string(),
+                addresses: vec![IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1))],
+                time_ms: 12.5,
+            }),
+            ping: Some(PingCheck {
+                target: "1.1.1.1".to_string(),
+                avg_ms: Some(14.0),
+                loss_percent: 0.0,
+            }),
+            errors: vec!["public IP: Connect failed: timed out".to_string()],
+            timestamp: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_report_renders_present_and_missing_sections() {
+        colored::control::set_override(false);
+        let human = partial_report().to_human();
+        assert!(human.contains("eth0       192.168.1.20/24"));
+        assert!(human.contains("Gateway: 192.168.1.1 via eth0"));
+        assert!(human.contains("DNS servers: 192.168.1.1"));
+        assert!(human.contains("Public IP: unknown"));
+        assert!(human.contains("DNS one.one.one.one → 1.1.1.1"));
+        assert!(human.contains("ping 1.1.1.1"));
+        assert!(human.contains("public IP: Connect failed: timed out"));
+    }
+
+    #[test]
+    fn test_report_oneline_and_health() {
+        let report = partial_report();
+        assert_eq!(
+            report.to_oneline(),
+            "NET gw 192.168.1.1, public ?, dns ok, ping 14ms"
+        );
+        assert_eq!(report.health().status, HealthStatus::Warning);
+
+        let mut down = report;
+        down.ping = None;
+        assert_eq!(down.health().status, HealthStatus::Critical);
+        assert!(down.to_oneline().ends_with("ping down"));
+    }
+
+    #[test]
+    fn test_summarize_interfaces_skips_loopback_and_down() {
+        use crate::netif::{InterfaceAddress, NetifResult, NetworkInterface};
+        let iface = |name: &str, up: bool, lo: bool| NetworkInterface {
+            name: name.to_string(),
+            index: 1,
+            is_up: up,
+            is_loopback: lo,
+            addresses: vec![InterfaceAddress {
+                ip: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)),
+                prefix_len: Some(8),
+                scope: "private".to_string(),
+            }],
+            mtu: None,
+            mac: None,
+            vendor: None,
+        };
+        let netif = NetifResult {
+            interfaces: vec![
+                iface("lo", true, true),
+                iface("eth0", true, false),
+                iface("eth1", false, false),
+            ],
+            total: 3,
+            up_count: 2,
+            timestamp: String::new(),
+        };
+        let summary = summarize_interfaces(&netif);
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].name, "eth0");
+        assert_eq!(summary[0].addresses, vec!["10.0.0.2/8"]);
+    }
+}
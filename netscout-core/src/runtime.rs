@@ -0,0 +1,33 @@
+//! Running blocking probes on the caller's tokio runtime.
+//!
+//! Commands whose I/O is still synchronous expose `*_async` wrappers built on
+//! [`blocking`], so async callers share one runtime without stalling its workers.
+
+/// Run blocking probe code on the runtime's blocking pool.
+pub async fn blocking<T, F>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Blocking task failed: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_blocking_returns_result() {
+        assert_eq!(blocking(|| Ok(42)).await, Ok(42));
+        let err: Result<(), String> = blocking(|| Err("boom".to_string())).await;
+        assert_eq!(err.unwrap_err(), "boom");
+    }
+
+    #[tokio::test]
+    async fn test_blocking_reports_panics() {
+        let err: Result<(), String> = blocking(|| panic!("probe panicked")).await;
+        assert!(err.unwrap_err().starts_with("Blocking task failed"));
+    }
+}
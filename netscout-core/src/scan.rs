@@ -1,9 +1,11 @@
-use serde::Serialize;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::net::TcpStream;
-use tokio::sync::Semaphore;
-use tokio::time::timeout;
+use tokio::sync::{watch, Semaphore};
+use tokio::task::JoinSet;
 
 /// Configuration for LAN scan.
 #[derive(Debug, Clone)]
@@ -12,6 +14,23 @@ pub struct LanScanConfig {
     pub ports: Vec<u16>,
     pub timeout: Duration,
     pub parallel: usize,
+    /// Explicit hosts (IPs or hostnames) to scan instead of `subnet`.
+    pub hosts: Vec<String>,
+    /// Minimum pause between dispatching consecutive host probes.
+    pub inter_host_delay: Option<Duration>,
+    /// Random extra pause of up to this much added to each gap.
+    pub jitter: Option<Duration>,
+    /// Replace `timeout` with one derived from the first responsive hosts' RTTs.
+    pub adaptive_timeout: Option<AdaptiveTimeout>,
+    /// Called with `(probed, total)` hosts as probes finish.
+    pub progress: Option<crate::progress::Progress>,
+    /// Time allowed for reverse DNS (PTR) lookups of found hosts; `None` skips them.
+    pub rdns_timeout: Option<Duration>,
+    /// Resolver for those lookups; the system's first nameserver when `None`.
+    pub rdns_resolver: Option<String>,
+    /// ICMP-echo every address first and report hosts that answer even with no
+    /// open ports. Needs a raw socket; without one only the TCP probes run.
+    pub ping_sweep: bool,
 }
 
 impl Default for LanScanConfig {
@@ -21,40 +40,180 @@ impl Default for LanScanConfig {
             ports: vec![22, 80, 443, 8080],
             timeout: Duration::from_millis(500),
             parallel: 256,
+            hosts: Vec::new(),
+            inter_host_delay: None,
+            jitter: None,
+            adaptive_timeout: None,
+            progress: None,
+            rdns_timeout: Some(Duration::from_secs(1)),
+            rdns_resolver: None,
+            ping_sweep: false,
         }
     }
 }
 
+/// How to derive the per-connection timeout from observed RTTs.
+#[derive(Debug, Clone)]
+pub struct AdaptiveTimeout {
+    /// Connections to the first responsive hosts to measure before adapting.
+    pub samples: usize,
+    /// Timeout as a multiple of the median sampled RTT.
+    pub multiplier: f64,
+    pub floor: Duration,
+    pub ceiling: Duration,
+}
+
+impl Default for AdaptiveTimeout {
+    fn default() -> Self {
+        Self {
+            samples: 5,
+            multiplier: 4.0,
+            floor: Duration::from_millis(20),
+            ceiling: Duration::from_secs(2),
+        }
+    }
+}
+
+impl AdaptiveTimeout {
+    /// The timeout for the sampled RTTs, or `None` until `samples` have been taken.
+    pub fn derive(&self, rtts_ms: &[f64]) -> Option<Duration> {
+        let sampled = rtts_ms.get(..self.samples.max(1))?;
+        let median = crate::stats::percentile(sampled, 50.0)?;
+        let timeout = Duration::from_secs_f64((median * self.multiplier / 1000.0).max(0.0));
+        Some(timeout.clamp(self.floor, self.ceiling.max(self.floor)))
+    }
+}
+
+/// The per-connection timeout shared by all probes, narrowed once RTTs are sampled.
+struct TimeoutState {
+    adaptive: Option<AdaptiveTimeout>,
+    rtts: Mutex<Vec<f64>>,
+    limit: watch::Sender<Duration>,
+}
+
+impl TimeoutState {
+    fn new(config: &LanScanConfig) -> Self {
+        Self {
+            adaptive: config.adaptive_timeout.clone(),
+            rtts: Mutex::new(Vec::new()),
+            limit: watch::Sender::new(config.timeout),
+        }
+    }
+
+    /// Record a connection's RTT, adapting the limit once enough are sampled.
+    fn observe(&self, rtt_ms: f64) {
+        let Some(ref adaptive) = self.adaptive else {
+            return;
+        };
+        let mut rtts = self.rtts.lock().unwrap();
+        if rtts.len() >= adaptive.samples.max(1) {
+            return;
+        }
+        rtts.push(rtt_ms);
+        if let Some(timeout) = adaptive.derive(&rtts) {
+            self.limit.send_replace(timeout);
+        }
+    }
+
+    /// The adapted timeout, if sampling has finished.
+    fn adapted(&self) -> Option<Duration> {
+        let adaptive = self.adaptive.as_ref()?;
+        adaptive.derive(&self.rtts.lock().unwrap())
+    }
+}
+
 /// A discovered host.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HostResult {
     pub ip: String,
     pub hostname: Option<String>,
+    /// Empty for hosts found only by the ping sweep.
     pub open_ports: Vec<u16>,
     pub rtt_ms: f64,
+    /// Whether the host answered the ICMP ping sweep.
+    #[serde(default)]
+    pub ping_reply: bool,
 }
 
 /// LAN scan result.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LanScanResult {
     pub subnet: String,
     pub hosts: Vec<HostResult>,
     pub total_scanned: u32,
     pub hosts_found: usize,
     pub scan_time_ms: f64,
+    /// Per-connection timeout derived by adaptive mode, once enough hosts answered.
+    #[serde(default)]
+    pub adaptive_timeout_ms: Option<f64>,
+    /// Why a requested ping sweep didn't run (e.g. no raw-socket privileges);
+    /// hosts were then found by TCP alone.
+    #[serde(default)]
+    pub ping_sweep_error: Option<String>,
+    /// When the scan started (RFC 3339, UTC, milliseconds).
+    #[serde(default)]
+    pub timestamp: String,
 }
 
-/// Parse a CIDR subnet into a list of IP addresses.
-pub fn parse_subnet(cidr: &str) -> Result<Vec<Ipv4Addr>, String> {
+/// One line of a streamed scan: a host as soon as it is found, then a final summary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ScanEvent {
+    Host(HostResult),
+    Summary {
+        subnet: String,
+        total_scanned: u32,
+        hosts_found: usize,
+        scan_time_ms: f64,
+    },
+}
+
+impl ScanEvent {
+    /// The closing summary event for a finished scan.
+    pub fn summary(result: &LanScanResult) -> Self {
+        Self::Summary {
+            subnet: result.subnet.clone(),
+            total_scanned: result.total_scanned,
+            hosts_found: result.hosts_found,
+            scan_time_ms: result.scan_time_ms,
+        }
+    }
+}
+
+/// Parse an IPv4 or IPv6 CIDR subnet into a list of IP addresses.
+///
+/// An IPv6 zone (`fe80::/120%eth0`) is accepted and ignored here; see [`subnet_scope_id`].
+pub fn parse_subnet(cidr: &str) -> Result<Vec<IpAddr>, String> {
+    let (cidr, _) = crate::addr::split_zone(cidr);
     let (ip_str, prefix_str) = cidr
         .split_once('/')
         .ok_or_else(|| format!("Invalid CIDR: {cidr}. Expected format: x.x.x.x/N"))?;
 
-    let ip: Ipv4Addr = ip_str.parse().map_err(|e| format!("Invalid IP: {e}"))?;
+    let ip: IpAddr = ip_str.parse().map_err(|e| format!("Invalid IP: {e}"))?;
     let prefix: u32 = prefix_str
         .parse()
         .map_err(|e| format!("Invalid prefix: {e}"))?;
 
+    match ip {
+        IpAddr::V4(ip) => Ok(parse_subnet_v4(ip, prefix)?
+            .into_iter()
+            .map(IpAddr::V4)
+            .collect()),
+        IpAddr::V6(ip) => Ok(parse_subnet_v6(ip, prefix)?
+            .into_iter()
+            .map(IpAddr::V6)
+            .collect()),
+    }
+}
+
+/// Scope id for the zone of an IPv6 subnet like `fe80::/120%eth0`, or 0 without one.
+pub fn subnet_scope_id(cidr: &str) -> Result<u32, String> {
+    crate::addr::split_zone(cidr)
+        .1
+        .map_or(Ok(0), crate::addr::scope_id)
+}
+
+fn parse_subnet_v4(ip: Ipv4Addr, prefix: u32) -> Result<Vec<Ipv4Addr>, String> {
     if prefix > 32 {
         return Err(format!("Invalid prefix length: {prefix}"));
     }
@@ -87,18 +246,101 @@ pub fn parse_subnet(cidr: &str) -> Result<Vec<Ipv4Addr>, String> {
     Ok(addrs)
 }
 
-/// Check if a host has any open ports.
+/// IPv6 has no broadcast address, but the all-zero host (the subnet-router
+/// anycast address) is skipped like the IPv4 network address.
+fn parse_subnet_v6(ip: Ipv6Addr, prefix: u32) -> Result<Vec<Ipv6Addr>, String> {
+    if prefix > 128 {
+        return Err(format!("Invalid prefix length: {prefix}"));
+    }
+    if prefix < 112 {
+        return Err("Prefix too large (< /112). Maximum 65536 hosts.".to_string());
+    }
+
+    let network = u128::from(ip) & (u128::MAX << (128 - prefix));
+    let host_count = 1u128 << (128 - prefix);
+    let start = if prefix < 127 { 1 } else { 0 };
+    Ok((start..host_count)
+        .map(|i| Ipv6Addr::from(network + i))
+        .collect())
+}
+
+/// Split a host list on commas and newlines, skipping blanks and `#` comments.
+pub fn parse_host_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or(""))
+        .flat_map(|l| l.split(','))
+        .map(str::trim)
+        .filter(|h| !h.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Resolve hosts to addresses, keeping the name for hosts given as hostnames.
+///
+/// Addresses listed more than once are scanned once.
+pub fn resolve_hosts(hosts: &[String]) -> Result<Vec<(IpAddr, Option<String>)>, String> {
+    let mut resolved: Vec<(IpAddr, Option<String>)> = Vec::new();
+    for host in hosts {
+        let entry = match host.parse::<IpAddr>() {
+            Ok(ip) => (ip, None),
+            Err(_) => {
+                let ip = (host.as_str(), 0)
+                    .to_socket_addrs()
+                    .map_err(|e| format!("Failed to resolve {host}: {e}"))?
+                    .next()
+                    .ok_or_else(|| format!("No address for {host}"))?
+                    .ip();
+                (ip, Some(host.clone()))
+            }
+        };
+        if !resolved.iter().any(|(ip, _)| *ip == entry.0) {
+            resolved.push(entry);
+        }
+    }
+    Ok(resolved)
+}
+
+/// Connect within the current limit, returning the RTT if the host answered.
+///
+/// The deadline follows `limit`, so a pending connect is cut short when the scan adapts.
+async fn connect_within(addr: SocketAddr, mut limit: watch::Receiver<Duration>) -> Option<f64> {
+    let start = tokio::time::Instant::now();
+    let connect = TcpStream::connect(addr);
+    tokio::pin!(connect);
+    loop {
+        let deadline = start + *limit.borrow_and_update();
+        tokio::select! {
+            // A refused connection still shows the host is up
+            _ = &mut connect => return Some(start.elapsed().as_secs_f64() * 1000.0),
+            _ = tokio::time::sleep_until(deadline) => return None,
+            changed = limit.changed() => {
+                if changed.is_err() {
+                    let answered = tokio::time::timeout_at(deadline, &mut connect).await;
+                    return answered.ok().map(|_| start.elapsed().as_secs_f64() * 1000.0);
+                }
+            }
+        }
+    }
+}
+
+/// Check if a host has any open ports; `scope_id` applies to IPv6 link-local hosts.
 async fn probe_host(
-    ip: Ipv4Addr,
+    ip: IpAddr,
+    scope_id: u32,
     ports: &[u16],
-    to: Duration,
-) -> Option<(Ipv4Addr, Vec<u16>, f64)> {
+    timeouts: &TimeoutState,
+) -> Option<(IpAddr, Vec<u16>, f64)> {
     let start = Instant::now();
     let mut open_ports = Vec::new();
 
     for &port in ports {
-        let addr = SocketAddr::new(IpAddr::V4(ip), port);
-        if timeout(to, TcpStream::connect(addr)).await.is_ok() {
+        let mut addr = SocketAddr::new(ip, port);
+        if let SocketAddr::V6(ref mut v6) = addr {
+            v6.set_scope_id(scope_id);
+        }
+        if let Some(rtt_ms) = connect_within(addr, timeouts.limit.subscribe()).await {
+            timeouts.observe(rtt_ms);
             open_ports.push(port);
         }
     }
@@ -111,35 +353,251 @@ async fn probe_host(
     }
 }
 
+/// Echo every address over ICMP and return those that answered, with their RTTs.
+///
+/// Requests go out on one raw socket per address family. Replies that are
+/// already waiting are read after every send, so a large range can't overflow
+/// the receive buffer and RTTs aren't inflated by the rest of the send loop.
+/// Replies carrying our identifier are matched by source address until
+/// `timeout` after the last request.
+fn icmp_sweep(
+    addrs: &[IpAddr],
+    scope_id: u32,
+    timeout: Duration,
+) -> Result<HashMap<IpAddr, f64>, String> {
+    use socket2::SockAddr;
+
+    let mut answered = HashMap::new();
+    for v6 in [false, true] {
+        let targets: Vec<IpAddr> = addrs
+            .iter()
+            .copied()
+            .filter(|ip| ip.is_ipv6() == v6)
+            .collect();
+        let Some(&first) = targets.first() else {
+            continue;
+        };
+        let socket = crate::ping::raw_icmp_socket(first).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::PermissionDenied {
+                "ICMP ping sweep needs a raw socket (run as root or grant CAP_NET_RAW)".to_string()
+            } else {
+                format!("Failed to open ICMP socket: {e}")
+            }
+        })?;
+        let ident = crate::ping::icmp_ident();
+        let mut sent = HashMap::new();
+        socket
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure ICMP socket: {e}"))?;
+        for (seq, &ip) in targets.iter().enumerate() {
+            let mut dest = SocketAddr::new(ip, 0);
+            if let SocketAddr::V6(ref mut d) = dest {
+                d.set_scope_id(scope_id);
+            }
+            let request = crate::ping::echo_request(v6, ident, seq as u16);
+            if socket.send_to(&request, &SockAddr::from(dest)).is_ok() {
+                sent.insert(ip, Instant::now());
+            }
+            while recv_echo_reply(&socket, v6, ident, &mut sent, &mut answered) {}
+        }
+
+        let deadline = Instant::now() + timeout;
+        if socket.set_nonblocking(false).is_err() {
+            continue;
+        }
+        // Answered hosts leave `sent`, so stop early once everyone replied
+        while !sent.is_empty() {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() || socket.set_read_timeout(Some(left)).is_err() {
+                break;
+            }
+            if !recv_echo_reply(&socket, v6, ident, &mut sent, &mut answered) {
+                break;
+            }
+        }
+    }
+    Ok(answered)
+}
+
+/// Read one packet for [`icmp_sweep`], moving a matching sender from `sent` to
+/// `answered`. Returns false when nothing could be read (no packet waiting on a
+/// non-blocking socket, or the read timeout expired).
+fn recv_echo_reply(
+    socket: &socket2::Socket,
+    v6: bool,
+    ident: u16,
+    sent: &mut HashMap<IpAddr, Instant>,
+    answered: &mut HashMap<IpAddr, f64>,
+) -> bool {
+    let mut buf = [std::mem::MaybeUninit::<u8>::uninit(); 1500];
+    let Ok((n, from)) = socket.recv_from(&mut buf) else {
+        return false;
+    };
+    // SAFETY: recv_from initialised the first `n` bytes
+    let packet = unsafe { std::slice::from_raw_parts(buf.as_ptr().cast::<u8>(), n) };
+    let Some(from) = from.as_socket().map(|a| a.ip()) else {
+        return true;
+    };
+    if crate::ping::parse_echo_reply(packet, v6).is_some_and(|(id, _)| id == ident) {
+        if let Some(start) = sent.remove(&from) {
+            answered.insert(from, start.elapsed().as_secs_f64() * 1000.0);
+        }
+    }
+    true
+}
+
+/// Pause before the next host dispatch: the configured delay plus random jitter.
+fn dispatch_gap(config: &LanScanConfig, rng: &mut u64) -> Duration {
+    let delay = config.inter_host_delay.unwrap_or_default();
+    let jitter = config.jitter.unwrap_or_default();
+    if jitter.is_zero() {
+        return delay;
+    }
+    // xorshift64; scan spacing only needs to look irregular, not be unpredictable
+    *rng ^= *rng << 13;
+    *rng ^= *rng >> 7;
+    *rng ^= *rng << 17;
+    delay + Duration::from_nanos(*rng % (jitter.as_nanos() as u64 + 1))
+}
+
+/// Reverse DNS lookups in flight at once while naming found hosts.
+const RDNS_PARALLEL: usize = 64;
+
+/// Name hosts that have no hostname yet from their PTR records.
+///
+/// Lookups run concurrently and whatever hasn't answered after `timeout` is
+/// left unnamed, so slow or missing reverse zones cost at most `timeout`.
+async fn resolve_hostnames(hosts: &mut [HostResult], resolver: &str, timeout: Duration) {
+    let sem = Arc::new(Semaphore::new(RDNS_PARALLEL));
+    let mut set = JoinSet::new();
+    for (i, host) in hosts.iter().enumerate() {
+        let Ok(ip) = host.ip.parse::<IpAddr>() else {
+            continue;
+        };
+        if host.hostname.is_some() {
+            continue;
+        }
+        let config = crate::dns::DnsConfig {
+            resolver: resolver.to_string(),
+            timeout,
+            ..Default::default()
+        };
+        let sem = sem.clone();
+        set.spawn(async move {
+            let _permit = sem.acquire_owned().await.unwrap();
+            (i, crate::dns::reverse_lookup_async(&config, ip).await)
+        });
+    }
+    let collect = async {
+        while let Some(joined) = set.join_next().await {
+            if let Ok((i, Ok(names))) = joined {
+                hosts[i].hostname = names.into_iter().next();
+            }
+        }
+    };
+    let _ = tokio::time::timeout(timeout, collect).await;
+}
+
 /// Run a LAN scan.
 pub async fn scan(config: &LanScanConfig) -> Result<LanScanResult, String> {
-    let addrs = parse_subnet(&config.subnet)?;
+    scan_streaming(config, |_| {}).await
+}
+
+/// Run a LAN scan, calling `on_host` for each live host as soon as it is found.
+///
+/// Scans `config.hosts` when given, otherwise every address in `config.subnet`.
+pub async fn scan_streaming(
+    config: &LanScanConfig,
+    mut on_host: impl FnMut(&HostResult),
+) -> Result<LanScanResult, String> {
+    let timestamp = crate::timestamp::now();
+    let (addrs, subnet, scope_id) = if config.hosts.is_empty() {
+        let addrs = parse_subnet(&config.subnet)?
+            .into_iter()
+            .map(|ip| (ip, None))
+            .collect();
+        let scope_id = subnet_scope_id(&config.subnet)?;
+        (addrs, config.subnet.clone(), scope_id)
+    } else {
+        (resolve_hosts(&config.hosts)?, config.hosts.join(","), 0)
+    };
     let total_scanned = addrs.len() as u32;
-    let sem = std::sync::Arc::new(Semaphore::new(config.parallel));
+    let sem = Arc::new(Semaphore::new(config.parallel));
+    let timeouts = Arc::new(TimeoutState::new(config));
     let start = Instant::now();
 
-    let mut handles = Vec::new();
-    for ip in addrs {
+    let (pinged, ping_sweep_error) = if config.ping_sweep {
+        let ips: Vec<IpAddr> = addrs.iter().map(|(ip, _)| *ip).collect();
+        let timeout = config.timeout;
+        match crate::runtime::blocking(move || icmp_sweep(&ips, scope_id, timeout)).await {
+            Ok(pinged) => (pinged, None),
+            Err(e) => (HashMap::new(), Some(e)),
+        }
+    } else {
+        (HashMap::new(), None)
+    };
+    let pinged = Arc::new(pinged);
+
+    let mut hosts = Vec::new();
+    let total = addrs.len();
+    let mut completed = 0;
+    let mut found = |joined: Result<Option<HostResult>, _>| {
+        completed += 1;
+        if let Some(ref progress) = config.progress {
+            progress.report(completed, total);
+        }
+        if let Ok(Some(host)) = joined {
+            on_host(&host);
+            hosts.push(host);
+        }
+    };
+
+    let mut rng = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64 | 1)
+        .unwrap_or(0x2545_F491_4F6C_DD1D);
+    let mut next_dispatch = tokio::time::Instant::now();
+
+    let mut set = JoinSet::new();
+    for (ip, hostname) in addrs {
+        // Space dispatches out when a delay or jitter is configured
+        tokio::time::sleep_until(next_dispatch).await;
+        next_dispatch = tokio::time::Instant::now() + dispatch_gap(config, &mut rng);
         let permit = sem.clone().acquire_owned().await.unwrap();
+        // Report hosts that finished while we waited for a free slot
+        while let Some(joined) = set.try_join_next() {
+            found(joined);
+        }
         let ports = config.ports.clone();
-        let to = config.timeout;
-        handles.push(tokio::spawn(async move {
-            let result = probe_host(ip, &ports, to).await;
+        let timeouts = timeouts.clone();
+        let ping_rtt = pinged.get(&ip).copied();
+        set.spawn(async move {
+            let result = probe_host(ip, scope_id, &ports, &timeouts).await;
             drop(permit);
-            result
-        }));
-    }
-
-    let mut hosts = Vec::new();
-    for handle in handles {
-        if let Ok(Some((ip, open_ports, rtt_ms))) = handle.await {
-            hosts.push(HostResult {
+            // A ping reply alone is enough to report the host
+            let (open_ports, rtt_ms) = match (result, ping_rtt) {
+                (Some((_, open_ports, rtt_ms)), _) => (open_ports, rtt_ms),
+                (None, Some(rtt_ms)) => (Vec::new(), rtt_ms),
+                (None, None) => return None,
+            };
+            Some(HostResult {
                 ip: ip.to_string(),
-                hostname: None, // Reverse DNS could be added
+                hostname, // Subnet hosts get theirs from reverse DNS below
                 open_ports,
                 rtt_ms,
-            });
-        }
+                ping_reply: ping_rtt.is_some(),
+            })
+        });
+    }
+    while let Some(joined) = set.join_next().await {
+        found(joined);
+    }
+    if let Some(timeout) = config.rdns_timeout {
+        let resolver = config
+            .rdns_resolver
+            .clone()
+            .unwrap_or_else(crate::dns::ptr_resolver);
+        resolve_hostnames(&mut hosts, &resolver, timeout).await;
     }
 
     hosts.sort_by(|a, b| a.ip.cmp(&b.ip));
@@ -147,11 +605,14 @@ pub async fn scan(config: &LanScanConfig) -> Result<LanScanResult, String> {
     let scan_time_ms = start.elapsed().as_secs_f64() * 1000.0;
 
     Ok(LanScanResult {
-        subnet: config.subnet.clone(),
+        subnet,
         hosts,
         total_scanned,
         hosts_found,
         scan_time_ms,
+        adaptive_timeout_ms: timeouts.adapted().map(|t| t.as_secs_f64() * 1000.0),
+        ping_sweep_error,
+        timestamp,
     })
 }
 
@@ -239,6 +700,7 @@ mod tests {
             hostname: Some("router.local".into()),
             open_ports: vec![22, 80],
             rtt_ms: 1.5,
+            ping_reply: false,
         };
         let json = serde_json::to_string(&host).unwrap();
         assert!(json.contains("192.168.1.1"));
@@ -255,6 +717,7 @@ mod tests {
             hostname: None,
             open_ports: vec![443, 8080],
             rtt_ms: 25.3,
+            ping_reply: false,
         };
         assert_eq!(host.ip, "10.0.0.5");
         assert!(host.hostname.is_none());
@@ -268,6 +731,7 @@ mod tests {
             hostname: Some("device.local".into()),
             open_ports: vec![],
             rtt_ms: 0.5,
+            ping_reply: false,
         };
         assert!(host.open_ports.is_empty());
         assert_eq!(host.rtt_ms, 0.5);
@@ -289,6 +753,14 @@ mod tests {
             ports: vec![22, 443],
             timeout: Duration::from_millis(1000),
             parallel: 100,
+            hosts: Vec::new(),
+            inter_host_delay: None,
+            jitter: None,
+            adaptive_timeout: None,
+            progress: None,
+            rdns_timeout: None,
+            rdns_resolver: None,
+            ping_sweep: false,
         };
         assert_eq!(config.subnet, "10.0.0.0/24");
         assert_eq!(config.ports, vec![22, 443]);
@@ -304,6 +776,9 @@ mod tests {
             total_scanned: 254,
             hosts_found: 0,
             scan_time_ms: 5000.0,
+            adaptive_timeout_ms: None,
+            ping_sweep_error: None,
+            timestamp: String::new(),
         };
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("192.168.1.0/24"));
@@ -319,12 +794,14 @@ mod tests {
                 hostname: Some("gateway".into()),
                 open_ports: vec![22, 80],
                 rtt_ms: 1.0,
+                ping_reply: false,
             },
             HostResult {
                 ip: "192.168.1.10".into(),
                 hostname: None,
                 open_ports: vec![443],
                 rtt_ms: 5.5,
+                ping_reply: false,
             },
         ];
         let result = LanScanResult {
@@ -333,6 +810,9 @@ mod tests {
             total_scanned: 254,
             hosts_found: 2,
             scan_time_ms: 3500.0,
+            adaptive_timeout_ms: None,
+            ping_sweep_error: None,
+            timestamp: String::new(),
         };
 
         assert_eq!(result.hosts.len(), 2);
@@ -341,6 +821,50 @@ mod tests {
         assert_eq!(result.hosts[1].open_ports, vec![443]);
     }
 
+    #[test]
+    fn test_parse_subnet_v6_124() {
+        let addrs = parse_subnet("2001:db8::10/124").unwrap();
+        // 16 addresses minus the subnet-router anycast address
+        assert_eq!(addrs.len(), 15);
+        assert_eq!(addrs[0], "2001:db8::11".parse::<IpAddr>().unwrap());
+        assert_eq!(addrs[14], "2001:db8::1f".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_parse_subnet_v6_limits() {
+        assert_eq!(parse_subnet("fe80::/112").unwrap().len(), 65535);
+        assert_eq!(parse_subnet("2001:db8::1/128").unwrap().len(), 1);
+        assert_eq!(parse_subnet("2001:db8::/127").unwrap().len(), 2);
+        assert!(parse_subnet("2001:db8::/64").is_err()); // Too large
+        assert!(parse_subnet("2001:db8::/129").is_err());
+    }
+
+    #[test]
+    fn test_parse_subnet_v6_zone() {
+        assert_eq!(parse_subnet("fe80::/126%lo").unwrap().len(), 3);
+        assert_eq!(subnet_scope_id("fe80::/126%7").unwrap(), 7);
+        assert_eq!(subnet_scope_id("192.168.1.0/24").unwrap(), 0);
+        assert!(subnet_scope_id("fe80::/126%nosuchif0").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scan_ipv6_subnet() {
+        let Ok(listener) = std::net::TcpListener::bind("[::1]:0") else {
+            return; // no IPv6 loopback here
+        };
+        let config = LanScanConfig {
+            subnet: "::/126".to_string(),
+            ports: vec![listener.local_addr().unwrap().port()],
+            timeout: Duration::from_millis(500),
+            rdns_timeout: None,
+            ..Default::default()
+        };
+        let result = scan(&config).await.unwrap();
+        assert_eq!(result.total_scanned, 3);
+        assert_eq!(result.hosts_found, 1);
+        assert_eq!(result.hosts[0].ip, "::1");
+    }
+
     #[test]
     fn test_subnet_calculation_edge_cases() {
         // Test very small subnets
@@ -359,4 +883,315 @@ mod tests {
         assert_eq!(addrs[5], Ipv4Addr::new(10, 1, 2, 6));
         // Should not include 10.1.2.0 (network) or 10.1.2.7 (broadcast)
     }
+
+    #[tokio::test]
+    async fn test_scan_streaming_reports_each_host() {
+        // 127.0.0.0/30 covers 127.0.0.1 and 127.0.0.2, both served by a wildcard listener
+        let listener = std::net::TcpListener::bind("0.0.0.0:0").unwrap();
+        let config = LanScanConfig {
+            subnet: "127.0.0.0/30".to_string(),
+            ports: vec![listener.local_addr().unwrap().port()],
+            timeout: Duration::from_millis(500),
+            rdns_timeout: None,
+            ..Default::default()
+        };
+        let mut streamed = Vec::new();
+        let result = scan_streaming(&config, |h| streamed.push(h.ip.clone()))
+            .await
+            .unwrap();
+        streamed.sort();
+        assert_eq!(streamed, ["127.0.0.1", "127.0.0.2"]);
+        assert_eq!(result.hosts_found, 2);
+        assert_eq!(result.total_scanned, 2);
+    }
+
+    #[tokio::test]
+    async fn test_scan_reports_progress_for_every_host() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let config = LanScanConfig {
+            subnet: "127.0.0.0/29".to_string(),
+            // Nothing listens on port 1, so no host is found but all are probed
+            ports: vec![1],
+            timeout: Duration::from_millis(200),
+            progress: Some(crate::progress::Progress::new(move |done, total| {
+                sink.lock().unwrap().push((done, total))
+            })),
+            ..Default::default()
+        };
+        let result = scan(&config).await.unwrap();
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), result.total_scanned as usize);
+        assert_eq!(seen.last(), Some(&(6, 6)));
+    }
+
+    #[tokio::test]
+    async fn test_inter_host_delay_spaces_dispatch() {
+        let listener = std::net::TcpListener::bind("0.0.0.0:0").unwrap();
+        let delay = Duration::from_millis(30);
+        let config = LanScanConfig {
+            subnet: "127.0.0.0/29".to_string(),
+            ports: vec![listener.local_addr().unwrap().port()],
+            timeout: Duration::from_millis(500),
+            inter_host_delay: Some(delay),
+            rdns_timeout: None,
+            ..Default::default()
+        };
+        let result = scan(&config).await.unwrap();
+        assert_eq!(result.total_scanned, 6);
+        // Five gaps between six dispatches
+        assert!(result.scan_time_ms >= 5.0 * delay.as_secs_f64() * 1000.0);
+    }
+
+    #[test]
+    fn test_dispatch_gap_stays_within_jitter() {
+        let config = LanScanConfig {
+            inter_host_delay: Some(Duration::from_millis(10)),
+            jitter: Some(Duration::from_millis(5)),
+            ..Default::default()
+        };
+        let mut rng = 0x1234_5678;
+        let gaps: Vec<_> = (0..50).map(|_| dispatch_gap(&config, &mut rng)).collect();
+        assert!(gaps
+            .iter()
+            .all(|g| (Duration::from_millis(10)..=Duration::from_millis(15)).contains(g)));
+        assert!(gaps.iter().any(|g| *g != gaps[0]));
+        assert_eq!(
+            dispatch_gap(&LanScanConfig::default(), &mut rng),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_parse_host_list() {
+        let hosts = parse_host_list("db1.lan, 10.0.0.5\n# spare\n\nweb.lan # frontend\n");
+        assert_eq!(hosts, ["db1.lan", "10.0.0.5", "web.lan"]);
+    }
+
+    #[test]
+    fn test_resolve_hosts_dedupes_addresses() {
+        let hosts = ["127.0.0.1".to_string(), "127.0.0.1".to_string()];
+        assert_eq!(resolve_hosts(&hosts).unwrap().len(), 1);
+        assert!(resolve_hosts(&["nonexistent.invalid".to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scan_host_list_mixes_names_and_ips() {
+        let listener = std::net::TcpListener::bind("0.0.0.0:0").unwrap();
+        let config = LanScanConfig {
+            hosts: vec!["localhost".to_string(), "127.0.0.2".to_string()],
+            ports: vec![listener.local_addr().unwrap().port()],
+            timeout: Duration::from_millis(500),
+            rdns_timeout: None,
+            ..Default::default()
+        };
+        let localhost = resolve_hosts(&config.hosts[..1]).unwrap()[0].0.to_string();
+        let result = scan(&config).await.unwrap();
+        assert_eq!(result.total_scanned, 2);
+        assert_eq!(result.subnet, "localhost,127.0.0.2");
+        let found = |ip: &str| result.hosts.iter().find(|h| h.ip == ip).unwrap();
+        assert_eq!(found(&localhost).hostname.as_deref(), Some("localhost"));
+        assert_eq!(found("127.0.0.2").hostname, None);
+    }
+
+    /// Answer one PTR query for 127.0.0.1 with `name`.
+    fn mock_ptr_resolver(name: &'static str) -> String {
+        use crate::testutil::DnsResponseBuilder;
+        let sock = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let resolver = sock.local_addr().unwrap().to_string();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 512];
+            let (_, src) = sock.recv_from(&mut buf).unwrap();
+            let id = u16::from_be_bytes([buf[0], buf[1]]);
+            let qname = "1.0.0.127.in-addr.arpa";
+            let resp = DnsResponseBuilder::new(id)
+                .question(qname, 12)
+                .answer_name(qname, 12, 60, name);
+            sock.send_to(&resp.build(), src).unwrap();
+        });
+        resolver
+    }
+
+    #[tokio::test]
+    async fn test_scan_names_hosts_by_reverse_dns() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = LanScanConfig {
+            hosts: vec!["127.0.0.1".to_string()],
+            ports: vec![listener.local_addr().unwrap().port()],
+            rdns_resolver: Some(mock_ptr_resolver("loopback.test")),
+            ..Default::default()
+        };
+        let result = scan(&config).await.unwrap();
+        assert_eq!(result.hosts[0].hostname.as_deref(), Some("loopback.test"));
+
+        let config = LanScanConfig {
+            rdns_timeout: None,
+            ..config
+        };
+        let result = scan(&config).await.unwrap();
+        assert_eq!(result.hosts[0].hostname, None);
+    }
+
+    #[tokio::test]
+    async fn test_reverse_dns_is_time_boxed() {
+        let silent = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut hosts = vec![HostResult {
+            ip: "127.0.0.1".to_string(),
+            hostname: None,
+            open_ports: vec![],
+            rtt_ms: 0.1,
+            ping_reply: false,
+        }];
+        let start = Instant::now();
+        let resolver = silent.local_addr().unwrap().to_string();
+        resolve_hostnames(&mut hosts, &resolver, Duration::from_millis(200)).await;
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(hosts[0].hostname, None);
+    }
+
+    #[test]
+    fn test_icmp_sweep_loopback() {
+        let loopback = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        match icmp_sweep(&[loopback], 0, Duration::from_secs(1)) {
+            Ok(pinged) => assert!(pinged.contains_key(&loopback)),
+            // Unprivileged test runs can't open a raw socket
+            Err(e) => assert!(e.contains("raw socket"), "{e}"),
+        }
+    }
+
+    #[test]
+    fn test_icmp_sweep_many_loopback_addresses() {
+        // All of 127.0.0.0/8 answers locally; replies must be read while sending
+        let addrs: Vec<IpAddr> = (0..2048u32)
+            .map(|i| IpAddr::V4(Ipv4Addr::from(0x7f00_0001 + i)))
+            .collect();
+        match icmp_sweep(&addrs, 0, Duration::from_secs(2)) {
+            Ok(pinged) => assert_eq!(pinged.len(), addrs.len()),
+            Err(e) => assert!(e.contains("raw socket"), "{e}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ping_sweep_reports_hosts_without_open_ports() {
+        let config = LanScanConfig {
+            hosts: vec!["127.0.0.1".to_string()],
+            ports: vec![],
+            ping_sweep: true,
+            rdns_timeout: None,
+            ..Default::default()
+        };
+        let result = scan(&config).await.unwrap();
+        if result.ping_sweep_error.is_some() {
+            assert_eq!(result.hosts_found, 0);
+            return;
+        }
+        assert_eq!(result.hosts_found, 1);
+        assert!(result.hosts[0].ping_reply);
+        assert!(result.hosts[0].open_ports.is_empty());
+
+        let config = LanScanConfig {
+            ping_sweep: false,
+            ..config
+        };
+        assert_eq!(scan(&config).await.unwrap().hosts_found, 0);
+    }
+
+    #[test]
+    fn test_adaptive_timeout_from_mock_prober_rtts() {
+        let config = LanScanConfig {
+            timeout: Duration::from_millis(500),
+            adaptive_timeout: Some(AdaptiveTimeout {
+                samples: 5,
+                multiplier: 4.0,
+                floor: Duration::from_millis(10),
+                ceiling: Duration::from_millis(200),
+            }),
+            ..Default::default()
+        };
+        let timeouts = TimeoutState::new(&config);
+        // A mock prober reporting known RTTs; only the first five are sampled
+        let prober_rtts = [2.0, 3.0, 4.0, 50.0, 5.0, 900.0];
+        for (i, &rtt) in prober_rtts.iter().enumerate() {
+            if i < 4 {
+                assert_eq!(*timeouts.limit.borrow(), config.timeout);
+                assert_eq!(timeouts.adapted(), None);
+            }
+            timeouts.observe(rtt);
+        }
+        // Median of the first five is 4ms, so 16ms, within the 10–200ms bounds
+        let derived = *timeouts.limit.borrow();
+        assert_eq!(derived, Duration::from_millis(16));
+        assert_eq!(timeouts.adapted(), Some(derived));
+    }
+
+    #[test]
+    fn test_adaptive_timeout_clamps_to_floor_and_ceiling() {
+        let adaptive = AdaptiveTimeout {
+            samples: 3,
+            multiplier: 4.0,
+            floor: Duration::from_millis(20),
+            ceiling: Duration::from_millis(1000),
+        };
+        assert_eq!(adaptive.derive(&[0.1, 0.2]), None);
+        assert_eq!(
+            adaptive.derive(&[0.1, 0.2, 0.3]),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(
+            adaptive.derive(&[800.0, 900.0, 950.0]),
+            Some(Duration::from_millis(1000))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_reports_adaptive_timeout() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let adaptive = AdaptiveTimeout {
+            samples: 1,
+            ..Default::default()
+        };
+        let config = LanScanConfig {
+            hosts: vec!["127.0.0.1".to_string()],
+            ports: vec![listener.local_addr().unwrap().port()],
+            adaptive_timeout: Some(adaptive.clone()),
+            rdns_timeout: None,
+            ..Default::default()
+        };
+        let result = scan(&config).await.unwrap();
+        assert_eq!(result.hosts_found, 1);
+        let derived = result
+            .adaptive_timeout_ms
+            .expect("adapted after one sample");
+        let bounds =
+            adaptive.floor.as_secs_f64() * 1000.0..=adaptive.ceiling.as_secs_f64() * 1000.0;
+        assert!(bounds.contains(&derived));
+    }
+
+    #[test]
+    fn test_scan_event_json_has_type() {
+        let host = ScanEvent::Host(HostResult {
+            ip: "192.168.1.10".to_string(),
+            hostname: None,
+            open_ports: vec![22],
+            rtt_ms: 1.5,
+            ping_reply: false,
+        });
+        let json: serde_json::Value = serde_json::to_value(&host).unwrap();
+        assert_eq!(json["type"], "host");
+        assert_eq!(json["ip"], "192.168.1.10");
+
+        let summary = ScanEvent::summary(&LanScanResult {
+            subnet: "192.168.1.0/24".to_string(),
+            hosts: vec![],
+            total_scanned: 254,
+            hosts_found: 0,
+            scan_time_ms: 10.0,
+            adaptive_timeout_ms: None,
+            ping_sweep_error: None,
+            timestamp: String::new(),
+        });
+        let json: serde_json::Value = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["type"], "summary");
+        assert_eq!(json["total_scanned"], 254);
+    }
 }
@@ -1,8 +1,12 @@
 use serde::Serialize;
 use std::io::Read;
-use std::net::{TcpStream, ToSocketAddrs};
+use std::net::TcpStream;
 use std::time::{Duration, Instant};
 
+/// Shortest transfer a rate is reported for; below this, timer resolution and
+/// connection setup swamp the measurement.
+pub const MIN_SAMPLE_MS: f64 = 50.0;
+
 /// Configuration for a speed test.
 #[derive(Debug, Clone)]
 pub struct SpeedConfig {
@@ -34,6 +38,10 @@ pub struct SpeedResult {
     pub upload_bytes: Option<u64>,
     pub download_time_ms: Option<f64>,
     pub upload_time_ms: Option<f64>,
+    /// Why a rate wasn't reported, e.g. a sample too short to measure.
+    pub warning: Option<String>,
+    /// When the test started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
 }
 
 
@@ -83,22 +91,27 @@ fn parse_http_url(url: &str) -> Result<(String, u16, String), String> {
         Some(i) => (&rest[..i], &rest[i..]),
         None => (rest, "/"),
     };
-    let (host, port) = match host_port.rsplit_once(':') {
-        Some((h, p)) => (h.to_string(), p.parse::<u16>().map_err(|_| "Bad port")?),
-        None => (host_port.to_string(), 80),
-    };
+    let (host, port) = crate::addr::split_host_port(host_port)?;
+    let (host, port) = (host.to_string(), port.unwrap_or(80));
     if host.is_empty() {
         return Err("Empty host".to_string());
     }
     Ok((host, port, path.to_string()))
 }
 
-/// Perform a download speed test via HTTP.
-fn download_test(url: &str, timeout_dur: Duration) -> Result<(f64, u64, f64), String> {
+/// Throughput of `bytes` over `elapsed_ms`, or `None` when the sample is shorter
+/// than [`MIN_SAMPLE_MS`].
+pub fn throughput_mbps(bytes: u64, elapsed_ms: f64) -> Option<f64> {
+    (elapsed_ms >= MIN_SAMPLE_MS)
+        .then(|| (bytes as f64 * 8.0) / (elapsed_ms / 1000.0) / 1_000_000.0)
+}
+
+/// Perform a download speed test via HTTP, returning bytes received and elapsed ms.
+fn download_test(url: &str, timeout_dur: Duration) -> Result<(u64, f64), String> {
     let (host, port, path) = parse_http_url(url)?;
-    let addr = format!("{host}:{port}")
-        .to_socket_addrs()
+    let addr = crate::addr::resolve(&host, port)
         .map_err(|e| format!("DNS: {e}"))?
+        .into_iter()
         .next()
         .ok_or("No address")?;
 
@@ -136,13 +149,7 @@ fn download_test(url: &str, timeout_dur: Duration) -> Result<(f64, u64, f64), St
     }
 
     let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
-    let mbps = if elapsed_ms > 0.0 {
-        (total_bytes as f64 * 8.0) / (elapsed_ms / 1000.0) / 1_000_000.0
-    } else {
-        0.0
-    };
-
-    Ok((mbps, total_bytes, elapsed_ms))
+    Ok((total_bytes, elapsed_ms))
 }
 
 /// Find the end of HTTP headers (\r\n\r\n).
@@ -152,6 +159,7 @@ fn find_header_end(buf: &[u8]) -> Option<usize> {
 
 /// Run a speed test.
 pub fn test_speed(config: &SpeedConfig) -> Result<SpeedResult, String> {
+    let timestamp = crate::timestamp::now();
     let mut result = SpeedResult {
         download_mbps: None,
         upload_mbps: None,
@@ -159,11 +167,18 @@ pub fn test_speed(config: &SpeedConfig) -> Result<SpeedResult, String> {
         upload_bytes: None,
         download_time_ms: None,
         upload_time_ms: None,
+        warning: None,
+        timestamp,
     };
 
     if !config.upload_only {
-        let (mbps, bytes, time_ms) = download_test(&config.download_url, config.timeout)?;
-        result.download_mbps = Some(mbps);
+        let (bytes, time_ms) = download_test(&config.download_url, config.timeout)?;
+        result.download_mbps = throughput_mbps(bytes, time_ms);
+        if result.download_mbps.is_none() {
+            result.warning = Some(format!(
+                "Download sample too short ({time_ms:.1} ms, need {MIN_SAMPLE_MS:.0} ms); use a larger download"
+            ));
+        }
         result.download_bytes = Some(bytes);
         result.download_time_ms = Some(time_ms);
     }
@@ -174,6 +189,12 @@ pub fn test_speed(config: &SpeedConfig) -> Result<SpeedResult, String> {
     Ok(result)
 }
 
+/// Async [`test_speed`]; the transfers run on the blocking pool.
+pub async fn test_speed_async(config: &SpeedConfig) -> Result<SpeedResult, String> {
+    let config = config.clone();
+    crate::runtime::blocking(move || test_speed(&config)).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -341,6 +362,8 @@ mod tests {
             upload_bytes: Some(5_000_000),
             download_time_ms: Some(800.0),
             upload_time_ms: Some(800.0),
+            warning: None,
+            timestamp: String::new(),
         };
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("100.5"));
@@ -357,6 +380,8 @@ mod tests {
             upload_bytes: None,
             download_time_ms: Some(107.0),
             upload_time_ms: None,
+            warning: None,
+            timestamp: String::new(),
         };
         assert!(result.download_mbps.is_some());
         assert!(result.upload_mbps.is_none());
@@ -372,6 +397,8 @@ mod tests {
             upload_bytes: Some(500_000),
             download_time_ms: None,
             upload_time_ms: Some(160.0),
+            warning: None,
+            timestamp: String::new(),
         };
         assert!(result.download_mbps.is_none());
         assert!(result.upload_mbps.is_some());
@@ -387,6 +414,8 @@ mod tests {
             upload_bytes: None,
             download_time_ms: None,
             upload_time_ms: None,
+            warning: None,
+            timestamp: String::new(),
         };
         assert!(result.download_mbps.is_none());
         assert!(result.upload_mbps.is_none());
@@ -401,6 +430,8 @@ mod tests {
             upload_bytes: Some(5_000_000),
             download_time_ms: Some(800.0),
             upload_time_ms: Some(800.0),
+            warning: None,
+            timestamp: String::new(),
         };
         assert!(complete.is_complete());
     }
@@ -414,6 +445,8 @@ mod tests {
             upload_bytes: None,
             download_time_ms: Some(800.0),
             upload_time_ms: None,
+            warning: None,
+            timestamp: String::new(),
         };
         assert!(!download_only.is_complete());
 
@@ -424,6 +457,8 @@ mod tests {
             upload_bytes: Some(5_000_000),
             download_time_ms: None,
             upload_time_ms: Some(800.0),
+            warning: None,
+            timestamp: String::new(),
         };
         assert!(!upload_only.is_complete());
 
@@ -434,6 +469,8 @@ mod tests {
             upload_bytes: None,
             download_time_ms: None,
             upload_time_ms: None,
+            warning: None,
+            timestamp: String::new(),
         };
         assert!(!empty.is_complete());
     }
@@ -447,6 +484,8 @@ mod tests {
             upload_bytes: None,
             download_time_ms: None,
             upload_time_ms: None,
+            warning: None,
+            timestamp: String::new(),
         };
         assert_eq!(result.download_display(), "123.46 Mbps");
     }
@@ -460,6 +499,8 @@ mod tests {
             upload_bytes: None,
             download_time_ms: None,
             upload_time_ms: None,
+            warning: None,
+            timestamp: String::new(),
         };
         assert_eq!(result.upload_display(), "45.60 Mbps");
     }
@@ -473,6 +514,8 @@ mod tests {
             upload_bytes: None,
             download_time_ms: None,
             upload_time_ms: None,
+            warning: None,
+            timestamp: String::new(),
         };
         assert_eq!(result.download_display(), "N/A");
         assert_eq!(result.upload_display(), "N/A");
@@ -487,6 +530,8 @@ mod tests {
             upload_bytes: Some(5_000_000),
             download_time_ms: Some(800.0),
             upload_time_ms: Some(795.0),
+            warning: None,
+            timestamp: String::new(),
         };
         let display = format!("{}", result);
         assert_eq!(display, "Download: 100.50 Mbps | Upload: 50.25 Mbps (dl 800ms, ul 795ms)");
@@ -501,6 +546,8 @@ mod tests {
             upload_bytes: None,
             download_time_ms: Some(107.0),
             upload_time_ms: None,
+            warning: None,
+            timestamp: String::new(),
         };
         let display = format!("{}", result);
         assert_eq!(display, "Download: 75.00 Mbps | Upload: N/A (dl 107ms)");
@@ -515,9 +562,20 @@ mod tests {
             upload_bytes: None,
             download_time_ms: None,
             upload_time_ms: None,
+            warning: None,
+            timestamp: String::new(),
         };
         let display = format!("{}", result);
         assert_eq!(display, "Download: 100.00 Mbps | Upload: 50.00 Mbps");
     }
 
+    #[test]
+    fn test_throughput_requires_min_sample() {
+        // 1 MB in 80 ms is 100 Mbps
+        assert_eq!(throughput_mbps(1_000_000, 80.0), Some(100.0));
+        assert!(throughput_mbps(1_000_000, MIN_SAMPLE_MS).is_some());
+        // A near-zero elapsed time would report an absurd rate
+        assert_eq!(throughput_mbps(1_000_000, 0.001), None);
+        assert_eq!(throughput_mbps(0, 0.0), None);
+    }
 }
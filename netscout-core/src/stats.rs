@@ -0,0 +1,63 @@
+/// Percentile `p` (0–100) of a set of samples, using linear interpolation
+/// between the closest ranks. Returns `None` for an empty set.
+pub fn percentile(samples: &[f64], p: f64) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let rank = (p.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let frac = rank - lower as f64;
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * frac)
+}
+
+/// Arithmetic mean of a set of samples, or `None` if empty.
+pub fn mean(samples: &[f64]) -> Option<f64> {
+    if samples.is_empty() {
+        None
+    } else {
+        Some(samples.iter().sum::<f64>() / samples.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_empty() {
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn test_percentile_single() {
+        assert_eq!(percentile(&[7.0], 0.0), Some(7.0));
+        assert_eq!(percentile(&[7.0], 95.0), Some(7.0));
+    }
+
+    #[test]
+    fn test_percentile_interpolates() {
+        let samples = [4.0, 1.0, 3.0, 2.0, 5.0];
+        assert_eq!(percentile(&samples, 0.0), Some(1.0));
+        assert_eq!(percentile(&samples, 50.0), Some(3.0));
+        assert_eq!(percentile(&samples, 100.0), Some(5.0));
+        assert_eq!(percentile(&samples, 25.0), Some(2.0));
+        let p95 = percentile(&samples, 95.0).unwrap();
+        assert!((p95 - 4.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_percentile_clamps_range() {
+        let samples = [1.0, 2.0];
+        assert_eq!(percentile(&samples, -5.0), Some(1.0));
+        assert_eq!(percentile(&samples, 150.0), Some(2.0));
+    }
+
+    #[test]
+    fn test_mean() {
+        assert_eq!(mean(&[]), None);
+        assert_eq!(mean(&[1.0, 2.0, 3.0]), Some(2.0));
+    }
+}
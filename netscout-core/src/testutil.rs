@@ -0,0 +1,478 @@
+//! Local HTTP and TLS servers and synthetic DNS packets for tests, so HTTP, TLS
+//! and DNS features can be exercised without the network.
+//!
+//! Only built for this crate's own tests or with the `testutil` feature.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A request received by the test server.
+#[derive(Debug, Clone)]
+pub struct TestRequest {
+    pub method: String,
+    pub path: String,
+    /// Header names are lowercased.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl TestRequest {
+    /// Value of a header, matched case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Build a complete response with a `Content-Length`-framed body.
+pub fn response(status: u16, reason: &str, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+    let mut out = format!("HTTP/1.1 {status} {reason}\r\n");
+    for (k, v) in headers {
+        out.push_str(&format!("{k}: {v}\r\n"));
+    }
+    out.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+    let mut out = out.into_bytes();
+    out.extend_from_slice(body);
+    out
+}
+
+/// Serve canned responses on a random localhost port until the runtime shuts down.
+///
+/// `handler` turns each request into raw response bytes. Connections stay open
+/// for further requests unless the client sends `Connection: close`.
+pub async fn spawn_test_http_server<F>(handler: F) -> SocketAddr
+where
+    F: Fn(&TestRequest) -> Vec<u8> + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind test server");
+    let addr = listener.local_addr().expect("test server address");
+    let handler = Arc::new(handler);
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let _ = serve_connection(stream, handler.as_ref()).await;
+            });
+        }
+    });
+    addr
+}
+
+async fn serve_connection<F>(mut stream: TcpStream, handler: &F) -> std::io::Result<()>
+where
+    F: Fn(&TestRequest) -> Vec<u8>,
+{
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let head_end = loop {
+            if let Some(i) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break i + 4;
+            }
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                return Ok(());
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        };
+
+        let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+        let mut lines = head.lines();
+        let mut request_line = lines.next().unwrap_or("").split_whitespace();
+        let method = request_line.next().unwrap_or("").to_string();
+        let path = request_line.next().unwrap_or("/").to_string();
+        let headers: Vec<(String, String)> = lines
+            .filter_map(|l| l.split_once(':'))
+            .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+            .collect();
+        let body_len = headers
+            .iter()
+            .find(|(k, _)| k == "content-length")
+            .and_then(|(_, v)| v.parse::<usize>().ok())
+            .unwrap_or(0);
+        while buf.len() < head_end + body_len {
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                return Ok(());
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+
+        let request = TestRequest {
+            method,
+            path,
+            body: buf[head_end..head_end + body_len].to_vec(),
+            headers,
+        };
+        buf.drain(..head_end + body_len);
+
+        stream.write_all(&handler(&request)).await?;
+        if request
+            .header("connection")
+            .is_some_and(|v| v.eq_ignore_ascii_case("close"))
+        {
+            return stream.shutdown().await;
+        }
+    }
+}
+
+/// Test certificates: a private CA (`test-ca.pem`) and a `127.0.0.1` server
+/// certificate it issued (`test-server.pem` / `test-server.key`).
+pub fn testdata(name: &str) -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("testdata")
+        .join(name)
+}
+
+/// Server config presenting the test certificate (valid for 127.0.0.1).
+fn tls_server_config() -> Arc<rustls::ServerConfig> {
+    use rustls::pki_types::pem::PemObject;
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+    let certs = CertificateDer::pem_file_iter(testdata("test-server.pem"))
+        .expect("read test certificate")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("parse test certificate");
+    let key = PrivateKeyDer::from_pem_file(testdata("test-server.key")).expect("read test key");
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    Arc::new(
+        rustls::ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .expect("TLS versions")
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .expect("server certificate"),
+    )
+}
+
+/// Complete `handshakes` TLS handshakes with the test server certificate on a
+/// random port of `bind` (e.g. `127.0.0.1`), then stop listening.
+pub fn spawn_tls_server(bind: &str, handshakes: usize) -> u16 {
+    let config = tls_server_config();
+    let listener = std::net::TcpListener::bind((bind, 0)).expect("bind TLS test server");
+    let port = listener
+        .local_addr()
+        .expect("TLS test server address")
+        .port();
+    std::thread::spawn(move || {
+        for _ in 0..handshakes {
+            let Ok((mut sock, _)) = listener.accept() else {
+                return;
+            };
+            let mut conn = rustls::ServerConnection::new(config.clone()).unwrap();
+            while conn.is_handshaking() {
+                if conn.complete_io(&mut sock).is_err() {
+                    break;
+                }
+            }
+            conn.send_close_notify();
+            let _ = conn.complete_io(&mut sock);
+        }
+    });
+    port
+}
+
+/// Answer `connections` HTTPS requests on 127.0.0.1 with the same raw
+/// `response`, using the test server certificate.
+pub fn spawn_https_server(connections: usize, response: Vec<u8>) -> u16 {
+    use std::io::{Read, Write};
+    let config = tls_server_config();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind HTTPS test server");
+    let port = listener
+        .local_addr()
+        .expect("HTTPS test server address")
+        .port();
+    std::thread::spawn(move || {
+        for _ in 0..connections {
+            let Ok((sock, _)) = listener.accept() else {
+                return;
+            };
+            let conn = rustls::ServerConnection::new(config.clone()).unwrap();
+            let mut tls = rustls::StreamOwned::new(conn, sock);
+            let mut head = Vec::new();
+            let mut byte = [0u8; 1];
+            while !head.ends_with(b"\r\n\r\n") && matches!(tls.read(&mut byte), Ok(1)) {
+                head.push(byte[0]);
+            }
+            let _ = tls.write_all(&response);
+            tls.conn.send_close_notify();
+            let _ = tls.flush();
+        }
+    });
+    port
+}
+
+/// Record data, either raw bytes or a domain name that may be compressed.
+#[derive(Debug, Clone)]
+enum TestRdata {
+    Raw(Vec<u8>),
+    /// `prefix` is written before the name, e.g. the MX preference.
+    Name {
+        prefix: Vec<u8>,
+        name: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct TestRecord {
+    name: String,
+    rtype: u16,
+    ttl: u32,
+    rdata: TestRdata,
+}
+
+/// Builds DNS response packets so parser tests don't hand-assemble bytes.
+///
+/// Names repeated across the question and records are written as compression
+/// pointers (RFC 1035 §4.1.4) unless [`DnsResponseBuilder::compress`] turns
+/// that off. The default flags are a standard recursive answer (QR, RD, RA).
+#[derive(Debug, Clone)]
+pub struct DnsResponseBuilder {
+    id: u16,
+    flags: u16,
+    question: Option<(String, u16)>,
+    answers: Vec<TestRecord>,
+    authority: Vec<TestRecord>,
+    additional: Vec<TestRecord>,
+    compress: bool,
+}
+
+impl DnsResponseBuilder {
+    pub fn new(id: u16) -> Self {
+        Self {
+            id,
+            flags: 0x8180,
+            question: None,
+            answers: Vec::new(),
+            authority: Vec::new(),
+            additional: Vec::new(),
+            compress: true,
+        }
+    }
+
+    /// Set the raw header flags word (QR, opcode, AA, TC, RD, RA, AD, CD, RCODE).
+    pub fn flags(mut self, flags: u16) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Set the response code, keeping the other flags.
+    pub fn rcode(mut self, rcode: u8) -> Self {
+        self.flags = (self.flags & !0x000F) | u16::from(rcode & 0x0F);
+        self
+    }
+
+    /// Echo a question (class IN).
+    pub fn question(mut self, name: &str, rtype: u16) -> Self {
+        self.question = Some((name.to_string(), rtype));
+        self
+    }
+
+    /// Write names in full instead of using compression pointers.
+    pub fn compress(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Add an answer record with raw record data.
+    pub fn answer(mut self, name: &str, rtype: u16, ttl: u32, rdata: &[u8]) -> Self {
+        self.answers
+            .push(record(name, rtype, ttl, TestRdata::Raw(rdata.to_vec())));
+        self
+    }
+
+    /// Add an answer whose data is a domain name (CNAME, NS, PTR).
+    pub fn answer_name(mut self, name: &str, rtype: u16, ttl: u32, target: &str) -> Self {
+        let rdata = TestRdata::Name {
+            prefix: Vec::new(),
+            name: target.to_string(),
+        };
+        self.answers.push(record(name, rtype, ttl, rdata));
+        self
+    }
+
+    /// Add an MX answer.
+    pub fn answer_mx(mut self, name: &str, ttl: u32, preference: u16, exchange: &str) -> Self {
+        let rdata = TestRdata::Name {
+            prefix: preference.to_be_bytes().to_vec(),
+            name: exchange.to_string(),
+        };
+        self.answers.push(record(name, 15, ttl, rdata));
+        self
+    }
+
+    /// Add an authority-section record with raw record data.
+    pub fn authority(mut self, name: &str, rtype: u16, ttl: u32, rdata: &[u8]) -> Self {
+        self.authority
+            .push(record(name, rtype, ttl, TestRdata::Raw(rdata.to_vec())));
+        self
+    }
+
+    /// Add an additional-section record with raw record data.
+    pub fn additional(mut self, name: &str, rtype: u16, ttl: u32, rdata: &[u8]) -> Self {
+        self.additional
+            .push(record(name, rtype, ttl, TestRdata::Raw(rdata.to_vec())));
+        self
+    }
+
+    /// Assemble the packet.
+    pub fn build(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut names = HashMap::new();
+        buf.extend_from_slice(&self.id.to_be_bytes());
+        buf.extend_from_slice(&self.flags.to_be_bytes());
+        let counts = [
+            usize::from(self.question.is_some()),
+            self.answers.len(),
+            self.authority.len(),
+            self.additional.len(),
+        ];
+        for count in counts {
+            buf.extend_from_slice(&(count as u16).to_be_bytes());
+        }
+        if let Some((ref name, rtype)) = self.question {
+            self.write_name(&mut buf, &mut names, name);
+            buf.extend_from_slice(&rtype.to_be_bytes());
+            buf.extend_from_slice(&1u16.to_be_bytes());
+        }
+        for rr in self
+            .answers
+            .iter()
+            .chain(&self.authority)
+            .chain(&self.additional)
+        {
+            self.write_name(&mut buf, &mut names, &rr.name);
+            buf.extend_from_slice(&rr.rtype.to_be_bytes());
+            buf.extend_from_slice(&1u16.to_be_bytes());
+            buf.extend_from_slice(&rr.ttl.to_be_bytes());
+            let len_at = buf.len();
+            buf.extend_from_slice(&[0, 0]);
+            match rr.rdata {
+                TestRdata::Raw(ref data) => buf.extend_from_slice(data),
+                TestRdata::Name {
+                    ref prefix,
+                    ref name,
+                } => {
+                    buf.extend_from_slice(prefix);
+                    self.write_name(&mut buf, &mut names, name);
+                }
+            }
+            let rdlen = (buf.len() - len_at - 2) as u16;
+            buf[len_at..len_at + 2].copy_from_slice(&rdlen.to_be_bytes());
+        }
+        buf
+    }
+
+    /// Write a name, pointing at an earlier copy of its longest known suffix.
+    fn write_name(&self, buf: &mut Vec<u8>, names: &mut HashMap<String, u16>, name: &str) {
+        let labels: Vec<&str> = name
+            .trim_end_matches('.')
+            .split('.')
+            .filter(|l| !l.is_empty())
+            .collect();
+        for i in 0..labels.len() {
+            let suffix = labels[i..].join(".").to_ascii_lowercase();
+            if self.compress {
+                if let Some(&offset) = names.get(&suffix) {
+                    buf.extend_from_slice(&(0xC000 | offset).to_be_bytes());
+                    return;
+                }
+                if buf.len() < 0x3FFF {
+                    names.insert(suffix, buf.len() as u16);
+                }
+            }
+            buf.push(labels[i].len() as u8);
+            buf.extend_from_slice(labels[i].as_bytes());
+        }
+        buf.push(0);
+    }
+}
+
+fn record(name: &str, rtype: u16, ttl: u32, rdata: TestRdata) -> TestRecord {
+    TestRecord {
+        name: name.to_string(),
+        rtype,
+        ttl,
+        rdata,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{probe, ConnectionMode, HttpConfig};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_server_serves_known_body() {
+        let addr = spawn_test_http_server(|req| {
+            assert_eq!(req.method, "GET");
+            assert_eq!(req.path, "/hello");
+            response(
+                200,
+                "OK",
+                &[("Content-Type", "text/plain")],
+                b"hello, world",
+            )
+        })
+        .await;
+        let config = HttpConfig {
+            url: format!("http://{addr}/hello"),
+            ..Default::default()
+        };
+        let result = tokio::task::spawn_blocking(move || probe(&config))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.status, 200);
+        assert_eq!(result.body_size, 12);
+        assert_eq!(result.headers["content-type"], "text/plain");
+        assert!(result.content_length_mismatch.is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_server_keeps_connection_open() {
+        let addr = spawn_test_http_server(|req| {
+            let body = req.header("x-probe").unwrap_or("none").to_string();
+            response(200, "OK", &[], body.as_bytes())
+        })
+        .await;
+        let config = HttpConfig {
+            url: format!("http://{addr}/"),
+            headers: vec![("X-Probe".to_string(), "abc".to_string())],
+            connection: ConnectionMode::KeepAlive,
+            ..Default::default()
+        };
+        let result = tokio::task::spawn_blocking(move || probe(&config))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.body_size, 3);
+    }
+
+    #[test]
+    fn test_dns_builder_compresses_repeated_names() {
+        let packet = DnsResponseBuilder::new(0x1234)
+            .question("example.com", 1)
+            .answer("example.com", 1, 300, &[192, 0, 2, 1])
+            .build();
+        assert_eq!(&packet[..4], &[0x12, 0x34, 0x81, 0x80]);
+        assert_eq!(&packet[4..12], &[0, 1, 0, 1, 0, 0, 0, 0]);
+        // Question name at offset 12, then the answer points back to it
+        let answer_at = 12 + 13 + 4;
+        assert_eq!(&packet[answer_at..answer_at + 2], &[0xC0, 12]);
+        assert_eq!(&packet[packet.len() - 6..], &[0, 4, 192, 0, 2, 1]);
+
+        let uncompressed = DnsResponseBuilder::new(0x1234)
+            .question("example.com", 1)
+            .answer("example.com", 1, 300, &[192, 0, 2, 1])
+            .compress(false)
+            .build();
+        assert_eq!(uncompressed.len(), packet.len() + 11);
+    }
+}
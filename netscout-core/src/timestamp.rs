@@ -0,0 +1,177 @@
+//! RFC 3339 wall-clock timestamps for results.
+//!
+//! Every result records when its operation started as `2026-01-02T03:04:05.678Z`
+//! (UTC, millisecond resolution), so saved JSON is self-dating.
+
+use serde::Serialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The current time as an RFC 3339 UTC timestamp with milliseconds.
+pub fn now() -> String {
+    format(SystemTime::now())
+}
+
+/// Format a time as an RFC 3339 UTC timestamp with milliseconds.
+pub fn format(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs() as i64;
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Parse an RFC 3339 timestamp (`Z` or `±HH:MM` offset, optional fraction).
+pub fn parse(s: &str) -> Option<SystemTime> {
+    let num = |range: std::ops::Range<usize>| -> Option<i64> {
+        let part = s.get(range)?;
+        part.bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| part.parse().ok())?
+    };
+    let b = s.as_bytes();
+    if b.len() < 20 || b[4] != b'-' || b[7] != b'-' || !matches!(b[10], b'T' | b't' | b' ') {
+        return None;
+    }
+    if b[13] != b':' || b[16] != b':' {
+        return None;
+    }
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (hour, minute, second) = (num(11..13)?, num(14..16)?, num(17..19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // Optional fraction, kept to nanosecond precision
+    let mut rest = &s[19..];
+    let mut nanos = 0u32;
+    if let Some(frac) = rest.strip_prefix('.') {
+        let digits = frac.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        for (i, d) in frac[..digits].bytes().take(9).enumerate() {
+            nanos += u32::from(d - b'0') * 10u32.pow(8 - i as u32);
+        }
+        rest = &frac[digits..];
+    }
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.as_bytes().first()? {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let rb = rest.as_bytes();
+            if rest.len() != 6 || rb[3] != b':' {
+                return None;
+            }
+            let (h, m) = (
+                rest[1..3].parse::<i64>().ok()?,
+                rest[4..6].parse::<i64>().ok()?,
+            );
+            sign * (h * 3600 + m * 60)
+        }
+    };
+
+    let secs =
+        days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second - offset;
+    let secs = u64::try_from(secs).ok()?;
+    Some(UNIX_EPOCH + Duration::new(secs, nanos))
+}
+
+/// The `timestamp` field of a serialized result, if it has one.
+pub fn of<T: Serialize>(value: &T) -> Option<String> {
+    match serde_json::to_value(value).ok()?.get("timestamp")? {
+        serde_json::Value::String(s) if !s.is_empty() => Some(s.clone()),
+        _ => None,
+    }
+}
+
+/// Days since 1970-01-01 to a proleptic Gregorian `(year, month, day)`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Proleptic Gregorian date to days since 1970-01-01.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_known_instants() {
+        assert_eq!(format(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        let t = UNIX_EPOCH + Duration::from_millis(951_782_400_123); // leap day 2000
+        assert_eq!(format(t), "2000-02-29T00:00:00.123Z");
+        let t = UNIX_EPOCH + Duration::from_millis(1_767_225_599_999);
+        assert_eq!(format(t), "2025-12-31T23:59:59.999Z");
+    }
+
+    #[test]
+    fn test_parse_round_trips_and_offsets() {
+        let t = UNIX_EPOCH + Duration::from_millis(1_767_225_599_999);
+        assert_eq!(parse(&format(t)), Some(t));
+        assert_eq!(
+            parse("2026-01-01T01:00:00+01:00"),
+            parse("2026-01-01T00:00:00Z")
+        );
+        assert!(parse("2026-13-01T00:00:00Z").is_none());
+        assert!(parse("2026-01-01 00:00").is_none());
+        assert!(parse("2026-01-01T00:00:00").is_none());
+    }
+
+    #[test]
+    fn test_now_is_valid_rfc3339_close_to_now() {
+        let stamp = now();
+        assert_eq!(stamp.len(), "2026-01-02T03:04:05.678Z".len());
+        let parsed = parse(&stamp).expect("valid RFC 3339");
+        let drift = SystemTime::now()
+            .duration_since(parsed)
+            .unwrap_or_else(|e| e.duration());
+        assert!(drift < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_of_reads_timestamp_field() {
+        #[derive(Serialize)]
+        struct Stamped {
+            timestamp: String,
+        }
+        let stamp = now();
+        assert_eq!(
+            of(&Stamped {
+                timestamp: stamp.clone()
+            }),
+            Some(stamp)
+        );
+        assert_eq!(of(&42), None);
+    }
+}
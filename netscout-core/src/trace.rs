@@ -1,9 +1,17 @@
-use serde::Serialize;
-use std::net::{SocketAddr, ToSocketAddrs};
+use serde::{Deserialize, Serialize};
+use socket2::{SockAddr, Socket};
+use std::collections::{HashMap, HashSet};
+use std::mem::MaybeUninit;
+use std::net::{IpAddr, SocketAddr};
 use std::time::{Duration, Instant};
 use tokio::net::TcpStream;
 use tokio::time::timeout;
 
+const ICMP_DEST_UNREACHABLE: u8 = 3;
+const ICMP_TIME_EXCEEDED: u8 = 11;
+const ICMPV6_DEST_UNREACHABLE: u8 = 1;
+const ICMPV6_TIME_EXCEEDED: u8 = 3;
+
 /// Configuration for traceroute.
 #[derive(Debug, Clone)]
 pub struct TraceConfig {
@@ -11,6 +19,10 @@ pub struct TraceConfig {
     pub max_hops: u8,
     pub timeout: Duration,
     pub port: u16,
+    /// Time limit for each hop's reverse DNS (PTR) lookup.
+    pub rdns_timeout: Duration,
+    /// Probes sent per hop, like traceroute's `-q`.
+    pub probes_per_hop: u8,
 }
 
 impl Default for TraceConfig {
@@ -20,110 +32,327 @@ impl Default for TraceConfig {
             max_hops: 30,
             timeout: Duration::from_secs(2),
             port: 80,
+            rdns_timeout: Duration::from_secs(1),
+            probes_per_hop: 3,
+        }
+    }
+}
+
+/// How a traceroute discovered its hops.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceMethod {
+    /// ICMP echo requests with increasing TTL over a raw socket; finds every router.
+    Icmp,
+    /// TCP connects to the target only, used when raw sockets aren't permitted.
+    #[default]
+    Tcp,
+}
+
+impl TraceMethod {
+    /// Name for headers such as `TRACEROUTE host (addr) via ICMP`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            TraceMethod::Icmp => "ICMP",
+            TraceMethod::Tcp => "TCP connect",
         }
     }
 }
 
 /// A single hop in the traceroute.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TraceHop {
     pub hop: u8,
     pub addr: Option<String>,
     pub hostname: Option<String>,
+    /// Fastest of `rtts_ms`.
     pub rtt_ms: Option<f64>,
+    /// Round trip of each probe sent to this hop; `None` for one that got no answer.
+    #[serde(default)]
+    pub rtts_ms: Vec<Option<f64>>,
     pub timed_out: bool,
+    /// Organisation owning the hop address (only filled in on request).
+    pub owner: Option<String>,
+}
+
+impl TraceHop {
+    /// A hop from the round trips of its probes; it timed out if none was answered.
+    pub fn from_probes(
+        hop: u8,
+        addr: Option<String>,
+        hostname: Option<String>,
+        rtts_ms: Vec<Option<f64>>,
+    ) -> Self {
+        let rtt_ms = rtts_ms.iter().flatten().copied().reduce(f64::min);
+        Self {
+            hop,
+            addr,
+            hostname,
+            rtt_ms,
+            timed_out: rtt_ms.is_none(),
+            rtts_ms,
+            owner: None,
+        }
+    }
 }
 
 /// Traceroute result.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TraceResult {
     pub target: String,
     pub resolved_addr: String,
     pub hops: Vec<TraceHop>,
     pub reached: bool,
+    /// How the hops were probed; results saved before ICMP support are TCP.
+    #[serde(default)]
+    pub method: TraceMethod,
+    /// When the trace started (RFC 3339, UTC, milliseconds).
+    #[serde(default)]
+    pub timestamp: String,
 }
 
-/// Perform traceroute using TCP connections.
+impl TraceResult {
+    /// Fill in each hop's `owner` using an IP-to-organisation lookup.
+    ///
+    /// Each distinct address is looked up only once.
+    pub fn enrich_owner(&mut self, lookup: impl Fn(&str) -> Option<String>) {
+        let mut cache: HashMap<String, Option<String>> = HashMap::new();
+        for hop in &mut self.hops {
+            if let Some(ref addr) = hop.addr {
+                hop.owner = cache
+                    .entry(addr.clone())
+                    .or_insert_with(|| lookup(addr))
+                    .clone();
+            }
+        }
+    }
+
+    /// Returns true if the target was not reached or any hop timed out.
+    pub fn has_failures(&self) -> bool {
+        !self.reached || self.hops.iter().any(|h| h.timed_out)
+    }
+
+    /// Keep only the hops that timed out (`--only-errors`).
+    pub fn retain_failures(&mut self) {
+        self.hops.retain(|h| h.timed_out);
+    }
+}
+
+/// What answered a TTL-limited echo request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HopReply {
+    /// A router on the path dropped the probe when its TTL ran out.
+    TimeExceeded,
+    /// The target answered the echo.
+    EchoReply,
+    /// A router or the target reported the destination unreachable.
+    Unreachable,
+}
+
+/// The payload of an IPv4 packet, skipping its header and options.
+fn ipv4_payload(packet: &[u8]) -> Option<&[u8]> {
+    packet.get(usize::from(packet.first()? & 0x0F) * 4..)
+}
+
+/// Classify an ICMP packet and return the `(ident, seq)` of the echo request it answers.
 ///
-/// Note: True TTL-based traceroute requires raw sockets (root privileges).
-/// This implementation uses TCP connect probes — it shows the destination
-/// reachability but cannot enumerate intermediate hops without raw sockets.
-/// Each "hop" attempts a TCP connect to simulate traceroute output.
+/// IPv4 raw sockets deliver the IP header too; IPv6 ones don't. Error messages quote
+/// the original IP header followed by the first 8 bytes of our echo request.
+fn parse_hop_reply(packet: &[u8], v6: bool) -> Option<(HopReply, u16, u16)> {
+    let icmp = if v6 { packet } else { ipv4_payload(packet)? };
+    let kind = match (v6, *icmp.first()?) {
+        (false, crate::ping::ICMP_ECHO_REPLY) | (true, crate::ping::ICMPV6_ECHO_REPLY) => {
+            HopReply::EchoReply
+        }
+        (false, ICMP_TIME_EXCEEDED) | (true, ICMPV6_TIME_EXCEEDED) => HopReply::TimeExceeded,
+        (false, ICMP_DEST_UNREACHABLE) | (true, ICMPV6_DEST_UNREACHABLE) => HopReply::Unreachable,
+        _ => return None,
+    };
+    let echo = match kind {
+        HopReply::EchoReply => icmp,
+        // The quoted IPv6 header is a fixed 40 bytes
+        _ if v6 => icmp.get(8 + 40..)?,
+        _ => ipv4_payload(icmp.get(8..)?)?,
+    };
+    let id = echo.get(4..8)?;
+    Some((
+        kind,
+        u16::from_be_bytes([id[0], id[1]]),
+        u16::from_be_bytes([id[2], id[3]]),
+    ))
+}
+
+/// Send echo request `seq` with TTL `ttl` and wait for whoever answers it.
+fn probe_hop(
+    socket: &Socket,
+    dest: &SockAddr,
+    ident: u16,
+    ttl: u8,
+    seq: u16,
+    to: Duration,
+) -> Option<(IpAddr, HopReply, f64)> {
+    let v6 = dest.is_ipv6();
+    let set = if v6 {
+        socket.set_unicast_hops_v6(u32::from(ttl))
+    } else {
+        socket.set_ttl_v4(u32::from(ttl))
+    };
+    let start = Instant::now();
+    let request = crate::ping::echo_request(v6, ident, seq);
+    if set.is_err() || socket.send_to(&request, dest).is_err() {
+        return None;
+    }
+    let mut buf = [MaybeUninit::<u8>::uninit(); 1500];
+    loop {
+        let left = to.saturating_sub(start.elapsed());
+        if left.is_zero() || socket.set_read_timeout(Some(left)).is_err() {
+            return None;
+        }
+        let (n, from) = socket.recv_from(&mut buf).ok()?;
+        // SAFETY: recv_from initialised the first `n` bytes
+        let packet = unsafe { std::slice::from_raw_parts(buf.as_ptr().cast::<u8>(), n) };
+        match parse_hop_reply(packet, v6) {
+            Some((kind, id, reply_seq)) if id == ident && reply_seq == seq => {
+                let rtt = start.elapsed().as_secs_f64() * 1000.0;
+                return Some((from.as_socket()?.ip(), kind, rtt));
+            }
+            // Another session's traffic, or a late answer for an earlier probe
+            _ => continue,
+        }
+    }
+}
+
+/// The PTR name of a hop address, or `None` if there is none or `resolver`
+/// doesn't answer within `timeout`.
+fn hop_hostname(ip: IpAddr, resolver: &str, timeout: Duration) -> Option<String> {
+    let config = crate::dns::DnsConfig {
+        resolver: resolver.to_string(),
+        timeout,
+        ..Default::default()
+    };
+    crate::dns::reverse_lookup(&config, ip)
+        .ok()?
+        .into_iter()
+        .next()
+}
+
+/// Traceroute with TTL-limited ICMP echo requests; returns the hops and whether
+/// the target answered.
+fn trace_icmp(socket: &Socket, target: IpAddr, config: &TraceConfig) -> (Vec<TraceHop>, bool) {
+    let resolver = crate::dns::ptr_resolver();
+    let dest = SockAddr::from(SocketAddr::new(target, 0));
+    let ident = crate::ping::icmp_ident();
+    let mut hops = Vec::new();
+    for ttl in 1..=config.max_hops {
+        let mut rtts = Vec::new();
+        // The hop is whoever answered first
+        let mut answer = None;
+        for probe in 0..config.probes_per_hop.max(1) {
+            let seq = u16::from_be_bytes([ttl, probe]);
+            let reply = probe_hop(socket, &dest, ident, ttl, seq, config.timeout);
+            if let Some((addr, kind, _)) = reply {
+                answer.get_or_insert((addr, kind));
+            }
+            rtts.push(reply.map(|(_, _, rtt)| rtt));
+        }
+        let Some((addr, kind)) = answer else {
+            hops.push(TraceHop::from_probes(ttl, None, None, rtts));
+            continue;
+        };
+        let hostname = hop_hostname(addr, &resolver, config.rdns_timeout);
+        hops.push(TraceHop::from_probes(
+            ttl,
+            Some(addr.to_string()),
+            hostname,
+            rtts,
+        ));
+        // An unreachable from a router ends the trace short of the target
+        if addr == target || kind != HopReply::TimeExceeded {
+            return (hops, addr == target || kind == HopReply::EchoReply);
+        }
+    }
+    (hops, false)
+}
+
+/// Perform a traceroute.
+///
+/// With a raw ICMP socket (root or CAP_NET_RAW) this sends echo requests with
+/// increasing TTL and records the router that reports each one expired. Without
+/// one it falls back to TCP connects to the target: that shows whether the target
+/// is reachable, but cannot enumerate intermediate hops.
 pub async fn trace(config: &TraceConfig) -> Result<TraceResult, String> {
-    let addr: SocketAddr = format!("{}:{}", config.target, config.port)
-        .to_socket_addrs()
+    let timestamp = crate::timestamp::now();
+    let addr: SocketAddr = crate::addr::resolve(&config.target, config.port)
         .map_err(|e| format!("Failed to resolve {}: {e}", config.target))?
+        .into_iter()
         .next()
         .ok_or_else(|| format!("No address for {}", config.target))?;
 
     let resolved = addr.ip().to_string();
+    if let Ok(socket) = crate::ping::raw_icmp_socket(addr.ip()) {
+        let cfg = config.clone();
+        let (hops, reached) =
+            crate::runtime::blocking(move || Ok(trace_icmp(&socket, addr.ip(), &cfg))).await?;
+        return Ok(TraceResult {
+            target: config.target.clone(),
+            resolved_addr: resolved,
+            hops,
+            reached,
+            method: TraceMethod::Icmp,
+            timestamp,
+        });
+    }
     let mut hops = Vec::new();
 
-    // Attempt TCP connects with increasing simulated hop numbers.
-    // Without raw sockets we cannot set TTL, so we do a single probe
-    // to the target and report it as a single-hop trace.
-    // For a full experience, the user would need to run with elevated privileges.
-
+    // Without raw sockets we cannot set TTL, so every "hop" is a round of TCP
+    // connects to the target itself; the first answered round ends the trace.
     for hop_num in 1..=config.max_hops {
-        let start = Instant::now();
-        match timeout(config.timeout, TcpStream::connect(addr)).await {
-            Ok(Ok(_stream)) => {
-                let rtt = start.elapsed().as_secs_f64() * 1000.0;
-                // Reverse DNS lookup
-                let hostname = tokio::task::spawn_blocking({
-                    let ip = addr.ip();
-                    move || {
-                        use std::net::SocketAddr;
-                        let sock = SocketAddr::new(ip, 0);
-                        dns_lookup_reverse(sock)
-                    }
-                })
-                .await
-                .ok()
-                .flatten();
-
-                hops.push(TraceHop {
-                    hop: hop_num,
-                    addr: Some(resolved.clone()),
-                    hostname,
-                    rtt_ms: Some(rtt),
-                    timed_out: false,
-                });
-                // Reached destination
-                return Ok(TraceResult {
-                    target: config.target.clone(),
-                    resolved_addr: resolved,
-                    hops,
-                    reached: true,
-                });
-            }
-            Ok(Err(_)) => {
+        let mut rtts = Vec::new();
+        let mut connected = false;
+        for _ in 0..config.probes_per_hop.max(1) {
+            let start = Instant::now();
+            match timeout(config.timeout, TcpStream::connect(addr)).await {
                 // Connection refused = host is there but port closed
-                let rtt = start.elapsed().as_secs_f64() * 1000.0;
-                hops.push(TraceHop {
-                    hop: hop_num,
-                    addr: Some(resolved.clone()),
-                    hostname: None,
-                    rtt_ms: Some(rtt),
-                    timed_out: false,
-                });
-                return Ok(TraceResult {
-                    target: config.target.clone(),
-                    resolved_addr: resolved,
-                    hops,
-                    reached: true,
-                });
-            }
-            Err(_) => {
-                hops.push(TraceHop {
-                    hop: hop_num,
-                    addr: None,
-                    hostname: None,
-                    rtt_ms: None,
-                    timed_out: true,
-                });
+                Ok(result) => {
+                    connected |= result.is_ok();
+                    rtts.push(Some(start.elapsed().as_secs_f64() * 1000.0));
+                }
+                Err(_) => rtts.push(None),
             }
         }
+        if rtts.iter().all(Option::is_none) {
+            hops.push(TraceHop::from_probes(hop_num, None, None, rtts));
+            continue;
+        }
+        let hostname = if connected {
+            let rdns_timeout = config.rdns_timeout;
+            crate::runtime::blocking(move || {
+                Ok(hop_hostname(
+                    addr.ip(),
+                    &crate::dns::ptr_resolver(),
+                    rdns_timeout,
+                ))
+            })
+            .await
+            .ok()
+            .flatten()
+        } else {
+            None
+        };
+        hops.push(TraceHop::from_probes(
+            hop_num,
+            Some(resolved.clone()),
+            hostname,
+            rtts,
+        ));
+        return Ok(TraceResult {
+            target: config.target.clone(),
+            resolved_addr: resolved,
+            hops,
+            reached: true,
+            method: TraceMethod::Tcp,
+            timestamp,
+        });
     }
 
     Ok(TraceResult {
@@ -131,11 +360,80 @@ pub async fn trace(config: &TraceConfig) -> Result<TraceResult, String> {
         resolved_addr: resolved,
         hops,
         reached: false,
+        method: TraceMethod::Tcp,
+        timestamp,
     })
 }
 
+/// Render one or more traces as a Graphviz DOT path map.
+///
+/// Hops become nodes keyed by address, so hops shared between traces are merged;
+/// timed-out hops get their own `*` node per trace. Edges join consecutive hops
+/// and are labelled with the RTT of the hop they lead to.
+pub fn to_dot(results: &[TraceResult]) -> String {
+    let mut nodes: Vec<(String, String)> = Vec::new();
+    let mut edges: Vec<(String, String, Option<f64>)> = Vec::new();
+    let mut seen_nodes = HashSet::new();
+    let mut seen_edges = HashSet::new();
+
+    for result in results {
+        let mut prev: Option<String> = None;
+        for hop in &result.hops {
+            let (id, label) = match hop.addr {
+                Some(ref addr) => {
+                    // Labels are stored escaped; `\n` is a DOT line break.
+                    let label = match hop.hostname {
+                        Some(ref name) if name != addr => {
+                            format!("{}\\n{}", dot_escape(addr), dot_escape(name))
+                        }
+                        _ => dot_escape(addr),
+                    };
+                    (addr.clone(), label)
+                }
+                None => (format!("* {} #{}", result.target, hop.hop), "*".to_string()),
+            };
+            if seen_nodes.insert(id.clone()) {
+                nodes.push((id.clone(), label));
+            }
+            if let Some(from) = prev.take() {
+                if seen_edges.insert((from.clone(), id.clone())) {
+                    edges.push((from, id.clone(), hop.rtt_ms));
+                }
+            }
+            prev = Some(id);
+        }
+    }
+
+    let mut out = String::from("digraph traceroute {\n    rankdir=LR;\n");
+    for (id, label) in &nodes {
+        out.push_str(&format!(
+            "    \"{}\" [label=\"{}\"];\n",
+            dot_escape(id),
+            label
+        ));
+    }
+    for (from, to, rtt) in &edges {
+        let attrs = match rtt {
+            Some(ms) => format!(" [label=\"{ms:.2} ms\"]"),
+            None => String::new(),
+        };
+        out.push_str(&format!(
+            "    \"{}\" -> \"{}\"{attrs};\n",
+            dot_escape(from),
+            dot_escape(to)
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Escape a string for use inside a double-quoted DOT ID.
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
 /// Reverse DNS lookup via PTR query on the system resolver.
-fn dns_lookup_reverse(addr: SocketAddr) -> Option<String> {
+pub(crate) fn dns_lookup_reverse(addr: SocketAddr) -> Option<String> {
     // Attempt reverse lookup by resolving the IP back to a hostname.
     // This uses the system resolver (getaddrinfo is forward-only, so we
     // build a PTR-style query via getnameinfo).
@@ -158,7 +456,18 @@ fn dns_lookup_reverse(addr: SocketAddr) -> Option<String> {
                 let ptr = Box::into_raw(boxed) as *const libc::sockaddr;
                 (ptr, std::mem::size_of::<libc::sockaddr_in>() as libc::socklen_t)
             }
-            _ => return None,
+            SocketAddr::V6(ref v6) => {
+                // SAFETY: sockaddr_in6 is plain data; all-zero is a valid value
+                let mut sin6: libc::sockaddr_in6 = unsafe { std::mem::zeroed() };
+                sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+                sin6.sin6_addr.s6_addr = v6.ip().octets();
+                sin6.sin6_scope_id = v6.scope_id();
+                let ptr = Box::into_raw(Box::new(sin6)) as *const libc::sockaddr;
+                (
+                    ptr,
+                    std::mem::size_of::<libc::sockaddr_in6>() as libc::socklen_t,
+                )
+            }
         };
         let mut host_buf = [0u8; 256];
         let ret = unsafe {
@@ -173,7 +482,12 @@ fn dns_lookup_reverse(addr: SocketAddr) -> Option<String> {
             )
         };
         // Free the allocated sockaddr
-        unsafe { drop(Box::from_raw(sa_ptr as *mut libc::sockaddr_in)); }
+        unsafe {
+            match addr {
+                SocketAddr::V4(_) => drop(Box::from_raw(sa_ptr as *mut libc::sockaddr_in)),
+                SocketAddr::V6(_) => drop(Box::from_raw(sa_ptr as *mut libc::sockaddr_in6)),
+            }
+        }
         if ret == 0 {
             let cstr = unsafe { CStr::from_ptr(host_buf.as_ptr() as *const libc::c_char) };
             let name = cstr.to_string_lossy().to_string();
@@ -197,6 +511,89 @@ fn dns_lookup_reverse(addr: SocketAddr) -> Option<String> {
 mod tests {
     use super::*;
 
+    fn hop(n: u8, addr: Option<&str>, rtt_ms: Option<f64>) -> TraceHop {
+        TraceHop {
+            hop: n,
+            addr: addr.map(String::from),
+            hostname: None,
+            rtt_ms,
+            rtts_ms: Vec::new(),
+            timed_out: addr.is_none(),
+            owner: None,
+        }
+    }
+
+    fn trace_result(target: &str, hops: Vec<TraceHop>) -> TraceResult {
+        TraceResult {
+            target: target.to_string(),
+            resolved_addr: hops.last().and_then(|h| h.addr.clone()).unwrap_or_default(),
+            hops,
+            reached: true,
+            method: TraceMethod::Tcp,
+            timestamp: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_to_dot_single_trace() {
+        let result = trace_result(
+            "example.com",
+            vec![
+                hop(1, Some("192.168.1.1"), Some(1.0)),
+                hop(2, Some("10.0.0.1"), Some(5.5)),
+                hop(3, Some("93.184.216.34"), Some(12.25)),
+            ],
+        );
+        let dot = to_dot(&[result]);
+        assert!(dot.starts_with("digraph traceroute {\n"));
+        assert!(dot.ends_with("}\n"));
+        let nodes = dot
+            .lines()
+            .filter(|l| l.contains("[label=") && !l.contains("->"));
+        assert_eq!(nodes.count(), 3);
+        let edges: Vec<&str> = dot.lines().filter(|l| l.contains("->")).collect();
+        assert_eq!(
+            edges,
+            [
+                r#"    "192.168.1.1" -> "10.0.0.1" [label="5.50 ms"];"#,
+                r#"    "10.0.0.1" -> "93.184.216.34" [label="12.25 ms"];"#,
+            ]
+        );
+        assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+    }
+
+    #[test]
+    fn test_to_dot_merges_shared_hops() {
+        let a = trace_result(
+            "a",
+            vec![
+                hop(1, Some("192.168.1.1"), Some(1.0)),
+                hop(2, Some("1.1.1.1"), Some(9.0)),
+            ],
+        );
+        let b = trace_result(
+            "b",
+            vec![
+                hop(1, Some("192.168.1.1"), Some(1.2)),
+                hop(2, None, None),
+                hop(3, Some("8.8.8.8"), Some(11.0)),
+            ],
+        );
+        let dot = to_dot(&[a, b]);
+        assert_eq!(dot.matches(r#""192.168.1.1" [label"#).count(), 1);
+        assert!(dot.contains(r#""* b #2" [label="*"];"#));
+        assert!(dot.contains(r#""192.168.1.1" -> "* b #2";"#));
+        assert_eq!(dot.lines().filter(|l| l.contains("->")).count(), 3);
+    }
+
+    #[test]
+    fn test_to_dot_escapes_hostnames() {
+        let mut h = hop(1, Some("10.0.0.1"), Some(1.0));
+        h.hostname = Some("odd\"name".to_string());
+        let dot = to_dot(&[trace_result("x", vec![h])]);
+        assert!(dot.contains(r#""10.0.0.1" [label="10.0.0.1\nodd\"name"];"#));
+    }
+
     #[test]
     fn test_trace_config_default() {
         let cfg = TraceConfig::default();
@@ -213,6 +610,8 @@ mod tests {
             max_hops: 15,
             timeout: Duration::from_secs(5),
             port: 443,
+            rdns_timeout: Duration::from_secs(1),
+            probes_per_hop: 3,
         };
         assert_eq!(cfg.target, "example.com");
         assert_eq!(cfg.max_hops, 15);
@@ -227,7 +626,9 @@ mod tests {
             addr: Some("1.2.3.4".into()),
             hostname: Some("router.example.com".into()),
             rtt_ms: Some(5.2),
+            rtts_ms: Vec::new(),
             timed_out: false,
+            owner: None,
         };
         let json = serde_json::to_string(&hop).unwrap();
         assert!(json.contains("router.example.com"));
@@ -243,7 +644,9 @@ mod tests {
             addr: None,
             hostname: None,
             rtt_ms: None,
+            rtts_ms: Vec::new(),
             timed_out: true,
+            owner: None,
         };
         let json = serde_json::to_string(&hop).unwrap();
         assert!(json.contains("true"));
@@ -259,7 +662,9 @@ mod tests {
             addr: Some("10.0.0.1".into()),
             hostname: None,
             rtt_ms: Some(15.7),
+            rtts_ms: Vec::new(),
             timed_out: false,
+            owner: None,
         };
         assert_eq!(hop.addr.as_ref().unwrap(), "10.0.0.1");
         assert!(hop.hostname.is_none());
@@ -273,6 +678,8 @@ mod tests {
             resolved_addr: "93.184.216.34".into(),
             hops: vec![],
             reached: false,
+            method: TraceMethod::Tcp,
+            timestamp: String::new(),
         };
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("example.com"));
@@ -287,25 +694,81 @@ mod tests {
             addr: Some("192.168.1.1".into()),
             hostname: Some("gateway".into()),
             rtt_ms: Some(1.0),
+            rtts_ms: Vec::new(),
             timed_out: false,
+            owner: None,
         };
         let hop2 = TraceHop {
             hop: 2,
             addr: None,
             hostname: None,
             rtt_ms: None,
+            rtts_ms: Vec::new(),
             timed_out: true,
+            owner: None,
         };
         let result = TraceResult {
             target: "example.com".into(),
             resolved_addr: "93.184.216.34".into(),
             hops: vec![hop1, hop2],
             reached: true,
+            method: TraceMethod::Tcp,
+            timestamp: String::new(),
         };
         assert_eq!(result.hops.len(), 2);
         assert!(result.reached);
         assert_eq!(result.hops[0].hop, 1);
         assert_eq!(result.hops[1].hop, 2);
+
+        let mut failures = result.clone();
+        assert!(failures.has_failures());
+        failures.retain_failures();
+        assert_eq!(failures.hops.len(), 1);
+        assert_eq!(failures.hops[0].hop, 2);
+    }
+
+    #[test]
+    fn test_hop_arpa_names() {
+        let ip: IpAddr = "8.8.8.8".parse().unwrap();
+        assert_eq!(crate::dns::reverse_name(ip), "8.8.8.8.in-addr.arpa");
+        let ip: IpAddr = "192.0.2.10".parse().unwrap();
+        assert_eq!(crate::dns::reverse_name(ip), "10.2.0.192.in-addr.arpa");
+        let ip: IpAddr = "2001:4860:4860::8888".parse().unwrap();
+        assert_eq!(
+            crate::dns::reverse_name(ip),
+            "8.8.8.8.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.6.8.4.0.6.8.4.1.0.0.2.ip6.arpa"
+        );
+    }
+
+    #[test]
+    fn test_hop_hostname_from_ptr() {
+        use crate::testutil::DnsResponseBuilder;
+        let sock = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let resolver = sock.local_addr().unwrap().to_string();
+        let server = std::thread::spawn(move || {
+            let mut buf = [0u8; 512];
+            let (_, src) = sock.recv_from(&mut buf).unwrap();
+            let id = u16::from_be_bytes([buf[0], buf[1]]);
+            let name = "8.8.8.8.in-addr.arpa";
+            let resp = DnsResponseBuilder::new(id).question(name, 12);
+            let resp = resp.answer_name(name, 12, 60, "dns.google");
+            sock.send_to(&resp.build(), src).unwrap();
+        });
+        let ip: IpAddr = "8.8.8.8".parse().unwrap();
+        let name = hop_hostname(ip, &resolver, Duration::from_secs(2));
+        server.join().unwrap();
+        assert_eq!(name.as_deref(), Some("dns.google"));
+    }
+
+    #[test]
+    fn test_hop_hostname_gives_up_on_silent_resolver() {
+        let sock = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let resolver = sock.local_addr().unwrap().to_string();
+        let start = Instant::now();
+        let ip: IpAddr = "8.8.8.8".parse().unwrap();
+        assert!(hop_hostname(ip, &resolver, Duration::from_millis(200)).is_none());
+        assert!(start.elapsed() < Duration::from_secs(2));
+        drop(sock);
     }
 
     #[test]
@@ -331,6 +794,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_dns_lookup_reverse_ipv6_localhost() {
+        use std::net::{IpAddr, Ipv6Addr};
+        let addr = std::net::SocketAddr::new(IpAddr::V6(Ipv6Addr::LOCALHOST), 0);
+        // Never the bare address; a name such as "localhost" or "ip6-localhost" is fine
+        if let Some(name) = dns_lookup_reverse(addr) {
+            assert_ne!(name, "::1");
+        }
+    }
+
     #[tokio::test]
     async fn test_trace_invalid_host() {
         let config = TraceConfig {
@@ -350,6 +823,8 @@ mod tests {
             port: 80,
             timeout: Duration::from_millis(100),
             max_hops: 1,
+            rdns_timeout: Duration::from_secs(1),
+            probes_per_hop: 3,
         };
         let result = trace(&config).await;
         // This might succeed or fail depending on whether port 80 is open on localhost
@@ -367,6 +842,8 @@ mod tests {
             port: 80,
             timeout: Duration::from_millis(50),
             max_hops: 2,
+            rdns_timeout: Duration::from_secs(1),
+            probes_per_hop: 3,
         };
         let result = trace(&config).await;
         if let Ok(trace_result) = result {
@@ -377,6 +854,140 @@ mod tests {
         }
     }
 
+    /// An IPv4 header (no options) followed by `payload`.
+    fn ipv4(payload: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0x45];
+        packet.resize(20, 0);
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    /// An ICMP error of `kind` quoting the start of an echo request.
+    fn icmp_error(kind: u8, quoted: &[u8]) -> Vec<u8> {
+        let mut icmp = vec![kind, 0, 0, 0, 0, 0, 0, 0];
+        icmp.extend_from_slice(quoted);
+        icmp
+    }
+
+    #[test]
+    fn test_parse_hop_reply_v4() {
+        let request = crate::ping::echo_request(false, 0x1234, 7);
+        let expired = ipv4(&icmp_error(11, &ipv4(&request[..8])));
+        assert_eq!(
+            parse_hop_reply(&expired, false),
+            Some((HopReply::TimeExceeded, 0x1234, 7))
+        );
+        let unreachable = ipv4(&icmp_error(3, &ipv4(&request[..8])));
+        assert_eq!(
+            parse_hop_reply(&unreachable, false),
+            Some((HopReply::Unreachable, 0x1234, 7))
+        );
+        let mut reply = request.clone();
+        reply[0] = 0;
+        assert_eq!(
+            parse_hop_reply(&ipv4(&reply), false),
+            Some((HopReply::EchoReply, 0x1234, 7))
+        );
+        // Our own request looped back, and truncated packets, are ignored
+        assert_eq!(parse_hop_reply(&ipv4(&request), false), None);
+        assert_eq!(parse_hop_reply(&expired[..40], false), None);
+    }
+
+    #[test]
+    fn test_parse_hop_reply_v6() {
+        let request = crate::ping::echo_request(true, 0xBEEF, 3);
+        let mut quoted = vec![0x60];
+        quoted.resize(40, 0);
+        quoted.extend_from_slice(&request[..8]);
+        assert_eq!(
+            parse_hop_reply(&icmp_error(3, &quoted), true),
+            Some((HopReply::TimeExceeded, 0xBEEF, 3))
+        );
+        assert_eq!(
+            parse_hop_reply(&icmp_error(1, &quoted), true),
+            Some((HopReply::Unreachable, 0xBEEF, 3))
+        );
+        let mut reply = request.clone();
+        reply[0] = 129;
+        assert_eq!(
+            parse_hop_reply(&reply, true),
+            Some((HopReply::EchoReply, 0xBEEF, 3))
+        );
+        assert_eq!(parse_hop_reply(&request, true), None);
+    }
+
+    /// Whether this process may open raw ICMP sockets (root or CAP_NET_RAW).
+    fn raw_sockets_allowed() -> bool {
+        crate::ping::raw_icmp_socket("127.0.0.1".parse().unwrap()).is_ok()
+    }
+
+    #[tokio::test]
+    async fn test_icmp_trace_localhost() {
+        if !raw_sockets_allowed() {
+            return;
+        }
+        let config = TraceConfig {
+            target: "127.0.0.1".into(),
+            timeout: Duration::from_millis(500),
+            max_hops: 5,
+            ..Default::default()
+        };
+        let result = trace(&config).await.unwrap();
+        assert_eq!(result.method, TraceMethod::Icmp);
+        assert!(result.reached);
+        assert_eq!(result.hops.len(), 1);
+        let hop = &result.hops[0];
+        assert_eq!(hop.addr.as_deref(), Some("127.0.0.1"));
+        assert_eq!(hop.rtts_ms.len(), 3);
+        let fastest = hop.rtts_ms.iter().flatten().copied().reduce(f64::min);
+        assert_eq!(hop.rtt_ms, fastest);
+    }
+
+    #[test]
+    fn test_hop_from_probes() {
+        let hop = TraceHop::from_probes(
+            4,
+            Some("10.0.0.1".into()),
+            None,
+            vec![Some(1.3), None, Some(1.2)],
+        );
+        assert_eq!(hop.rtt_ms, Some(1.2));
+        assert!(!hop.timed_out);
+        assert_eq!(hop.rtts_ms.len(), 3);
+
+        let lost = TraceHop::from_probes(5, None, None, vec![None, None, None]);
+        assert!(lost.timed_out);
+        assert_eq!(lost.rtt_ms, None);
+
+        // JSON saved before per-probe timings still loads
+        let old: TraceHop = serde_json::from_str(
+            r#"{"hop":1,"addr":"10.0.0.1","hostname":null,"rtt_ms":1.0,"timed_out":false,"owner":null}"#,
+        )
+        .unwrap();
+        assert!(old.rtts_ms.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_icmp_trace_public_host_finds_routers() {
+        if !raw_sockets_allowed() {
+            return;
+        }
+        let config = TraceConfig {
+            target: "1.1.1.1".into(),
+            timeout: Duration::from_millis(500),
+            max_hops: 8,
+            ..Default::default()
+        };
+        let result = trace(&config).await.unwrap();
+        assert_eq!(result.method, TraceMethod::Icmp);
+        let hops: HashSet<_> = result.hops.iter().filter_map(|h| h.addr.as_ref()).collect();
+        // No route out at all; nothing to enumerate
+        if hops.is_empty() {
+            return;
+        }
+        assert!(hops.len() > 1, "{:?}", result.hops);
+    }
+
     #[test]
     fn test_trace_config_validation() {
         let cfg = TraceConfig {
@@ -384,6 +995,8 @@ mod tests {
             max_hops: 0,
             timeout: Duration::from_secs(0),
             port: 0,
+            rdns_timeout: Duration::from_secs(1),
+            probes_per_hop: 3,
         };
         // These are edge cases that should be handled gracefully
         assert_eq!(cfg.max_hops, 0);
@@ -398,7 +1011,9 @@ mod tests {
             addr: Some("203.0.113.1".into()),
             hostname: Some("backbone.provider.com".into()),
             rtt_ms: Some(45.123),
+            rtts_ms: Vec::new(),
             timed_out: false,
+            owner: None,
         };
 
         // Test various properties
@@ -419,13 +1034,57 @@ mod tests {
                 addr: Some("93.184.216.34".into()),
                 hostname: Some("example.com".into()),
                 rtt_ms: Some(25.0),
+                rtts_ms: Vec::new(),
                 timed_out: false,
+                owner: None,
             }],
             reached: true,
+            method: TraceMethod::Tcp,
+            timestamp: String::new(),
         };
 
         assert!(result.reached);
         assert_eq!(result.hops.len(), 1);
         assert_eq!(result.target, "example.com");
     }
+
+    #[test]
+    fn test_trace_result_enrich_owner() {
+        let hop = |n: u8, addr: Option<&str>| TraceHop {
+            hop: n,
+            addr: addr.map(String::from),
+            hostname: None,
+            rtt_ms: addr.map(|_| 5.0),
+            rtts_ms: Vec::new(),
+            timed_out: addr.is_none(),
+            owner: None,
+        };
+        let mut result = TraceResult {
+            target: "example.com".into(),
+            resolved_addr: "3.5.0.1".into(),
+            hops: vec![
+                hop(1, Some("3.5.0.1")),
+                hop(2, None),
+                hop(3, Some("3.5.0.1")),
+            ],
+            reached: true,
+            method: TraceMethod::Tcp,
+            timestamp: String::new(),
+        };
+        let calls = std::cell::Cell::new(0);
+        result.enrich_owner(|_| {
+            calls.set(calls.get() + 1);
+            crate::whois::parse_ip_owner("OrgName: Amazon Technologies Inc.\n")
+        });
+        assert_eq!(calls.get(), 1);
+        assert_eq!(
+            result.hops[0].owner.as_deref(),
+            Some("Amazon Technologies Inc.")
+        );
+        assert!(result.hops[1].owner.is_none());
+        assert_eq!(
+            result.hops[2].owner.as_deref(),
+            Some("Amazon Technologies Inc.")
+        );
+    }
 }
@@ -1,13 +1,16 @@
 use serde::Serialize;
 use std::io::{Read, Write};
-use std::net::TcpStream;
+use std::net::{IpAddr, TcpStream};
 use std::time::{Duration, Instant};
 
 /// Configuration for a WHOIS query.
 #[derive(Debug, Clone)]
 pub struct WhoisConfig {
     pub target: String,
+    /// WHOIS server host, optionally with a port (`host:port`); defaults to port 43.
     pub server: Option<String>,
+    /// Exact query string to send instead of the target (e.g. `n + 8.8.8.8` for ARIN).
+    pub query_override: Option<String>,
     pub timeout: Duration,
 }
 
@@ -16,11 +19,19 @@ impl Default for WhoisConfig {
         Self {
             target: String::new(),
             server: None,
+            query_override: None,
             timeout: Duration::from_secs(10),
         }
     }
 }
 
+impl WhoisConfig {
+    /// The string sent to the server: `query_override` if set, otherwise the target.
+    pub fn query_string(&self) -> &str {
+        self.query_override.as_deref().unwrap_or(&self.target)
+    }
+}
+
 /// Parsed WHOIS result.
 #[derive(Debug, Clone, Serialize)]
 pub struct WhoisResult {
@@ -34,6 +45,10 @@ pub struct WhoisResult {
     pub status: Vec<String>,
     pub raw: String,
     pub query_time_ms: f64,
+    /// The server stopped sending without closing the connection; `raw` is what arrived.
+    pub partial: bool,
+    /// When the query started (RFC 3339, UTC, milliseconds).
+    pub timestamp: String,
 }
 
 impl WhoisResult {
@@ -134,8 +149,19 @@ fn whois_server_for(target: &str) -> String {
 }
 
 /// Perform raw WHOIS query to a server.
-fn raw_whois(server: &str, query: &str, timeout_dur: Duration) -> Result<(String, f64), String> {
-    let addr = format!("{server}:43");
+///
+/// Returns the response, the query time and whether the read timed out after
+/// some data had arrived (the response may then be incomplete).
+fn raw_whois(
+    server: &str,
+    query: &str,
+    timeout_dur: Duration,
+) -> Result<(String, f64, bool), String> {
+    let addr = if server.parse::<std::net::SocketAddr>().is_ok() {
+        server.to_string()
+    } else {
+        format!("{server}:43")
+    };
     let start = Instant::now();
 
     let mut stream = TcpStream::connect(&addr).map_err(|e| format!("Connect to {server}: {e}"))?;
@@ -147,13 +173,33 @@ fn raw_whois(server: &str, query: &str, timeout_dur: Duration) -> Result<(String
         .write_all(q.as_bytes())
         .map_err(|e| format!("Write: {e}"))?;
 
-    let mut response = String::new();
-    stream
-        .read_to_string(&mut response)
-        .map_err(|e| format!("Read: {e}"))?;
+    // Some servers leave the connection open after answering, so a timeout
+    // with data in hand ends the response instead of failing it
+    let mut response = Vec::new();
+    let mut buf = [0u8; 4096];
+    let partial = loop {
+        match stream.read(&mut buf) {
+            Ok(0) => break false,
+            Ok(n) => response.extend_from_slice(&buf[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) && !response.is_empty() =>
+            {
+                break true
+            }
+            Err(e) => return Err(format!("Read: {e}")),
+        }
+    };
 
     let query_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-    Ok((response, query_time_ms))
+    Ok((
+        String::from_utf8_lossy(&response).into_owned(),
+        query_time_ms,
+        partial,
+    ))
 }
 
 /// Extract a field value from WHOIS response text.
@@ -193,14 +239,87 @@ fn extract_all(text: &str, keys: &[&str]) -> Vec<String> {
     results
 }
 
+/// Organisation fields used by the regional registries, in order of preference.
+const OWNER_KEYS: &[&str] = &["OrgName", "org-name", "owner", "descr", "netname"];
+
+/// Extract the owning organisation from an IP WHOIS response.
+pub fn parse_ip_owner(raw: &str) -> Option<String> {
+    for key in OWNER_KEYS {
+        for line in raw.lines() {
+            if let Some((k, v)) = line.split_once(':') {
+                let v = v.trim();
+                if k.trim().eq_ignore_ascii_case(key) && !v.is_empty() {
+                    return Some(v.to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Extract the referral server from an IANA WHOIS response.
+fn parse_referral(raw: &str) -> Option<String> {
+    extract_field(raw, &["refer:", "whois:"])
+}
+
+/// Returns `true` if the address is routable on the public internet.
+fn is_public_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || v4.is_unspecified()
+                || v4.is_multicast()
+                // 100.64.0.0/10 carrier-grade NAT
+                || (v4.octets()[0] == 100 && v4.octets()[1] & 0xC0 == 64))
+        }
+        IpAddr::V6(v6) => {
+            let seg0 = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || seg0 & 0xFE00 == 0xFC00 // unique local
+                || seg0 & 0xFFC0 == 0xFE80 // link local
+                || seg0 == 0x2001 && v6.segments()[1] == 0x0DB8) // documentation
+        }
+    }
+}
+
+/// Look up the organisation that owns an IP address.
+///
+/// Asks IANA which regional registry is responsible, then queries that registry.
+/// Returns `Ok(None)` for private and other non-public addresses.
+pub fn ip_owner(ip: &str, timeout: Duration) -> Result<Option<String>, String> {
+    let addr: IpAddr = ip
+        .parse()
+        .map_err(|e| format!("Invalid IP address '{ip}': {e}"))?;
+    if !is_public_ip(&addr) {
+        return Ok(None);
+    }
+    let (iana, _, _) = raw_whois("whois.iana.org", ip, timeout)?;
+    let server = parse_referral(&iana).unwrap_or_else(|| "whois.arin.net".to_string());
+    // ARIN needs the "n +" prefix to return the full network record
+    let query = if server == "whois.arin.net" {
+        format!("n + {ip}")
+    } else {
+        ip.to_string()
+    };
+    let (raw, _, _) = raw_whois(&server, &query, timeout)?;
+    Ok(parse_ip_owner(&raw))
+}
+
 /// Query WHOIS for a domain or IP.
 pub fn query(config: &WhoisConfig) -> Result<WhoisResult, String> {
+    let timestamp = crate::timestamp::now();
     let server = config
         .server
         .clone()
         .unwrap_or_else(|| whois_server_for(&config.target));
 
-    let (raw, query_time_ms) = raw_whois(&server, &config.target, config.timeout)?;
+    let (raw, query_time_ms, partial) = raw_whois(&server, config.query_string(), config.timeout)?;
 
     let registrar = extract_field(&raw, &["Registrar:", "registrar"]);
     let creation_date = extract_field(&raw, &["Creation Date", "Created", "created"]);
@@ -228,13 +347,105 @@ pub fn query(config: &WhoisConfig) -> Result<WhoisResult, String> {
         status,
         raw,
         query_time_ms,
+        partial,
+        timestamp,
     })
 }
 
+/// Async [`query`]; the lookup runs on the blocking pool.
+pub async fn query_async(config: &WhoisConfig) -> Result<WhoisResult, String> {
+    let config = config.clone();
+    crate::runtime::blocking(move || query(&config)).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// One-shot WHOIS server that answers with the query line it received.
+    fn echo_whois_server() -> (String, std::thread::JoinHandle<String>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 256];
+            let n = stream.read(&mut buf).unwrap();
+            let line = String::from_utf8_lossy(&buf[..n]).trim_end().to_string();
+            stream
+                .write_all(format!("% query: {line}\r\n").as_bytes())
+                .unwrap();
+            line
+        });
+        (addr, handle)
+    }
+
+    #[test]
+    fn test_query_override_is_sent_instead_of_target() {
+        let (server, handle) = echo_whois_server();
+        let config = WhoisConfig {
+            target: "8.8.8.8".to_string(),
+            server: Some(server),
+            query_override: Some("n + 8.8.8.8".to_string()),
+            timeout: Duration::from_secs(2),
+        };
+        let result = query(&config).unwrap();
+        assert_eq!(handle.join().unwrap(), "n + 8.8.8.8");
+        assert!(result.raw.contains("% query: n + 8.8.8.8"));
+        assert_eq!(result.target, "8.8.8.8");
+    }
+
+    #[test]
+    fn test_stalled_response_is_returned_as_partial() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = listener.local_addr().unwrap().to_string();
+        let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.read(&mut [0u8; 256]).unwrap();
+            stream
+                .write_all(b"Registrar: Slow Registry Inc.\r\n")
+                .unwrap();
+            // Hold the connection open until the client has given up
+            let _ = done_rx.recv();
+        });
+        let config = WhoisConfig {
+            target: "example.com".to_string(),
+            server: Some(server),
+            timeout: Duration::from_millis(300),
+            ..Default::default()
+        };
+        let result = query(&config).unwrap();
+        done_tx.send(()).unwrap();
+        assert!(result.partial);
+        assert_eq!(result.registrar.as_deref(), Some("Slow Registry Inc."));
+    }
+
+    #[test]
+    fn test_complete_response_is_not_partial() {
+        let (server, handle) = echo_whois_server();
+        let config = WhoisConfig {
+            target: "example.com".to_string(),
+            server: Some(server),
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let result = query(&config).unwrap();
+        handle.join().unwrap();
+        assert!(!result.partial);
+    }
+
+    #[test]
+    fn test_query_sends_target_by_default() {
+        let (server, handle) = echo_whois_server();
+        let config = WhoisConfig {
+            target: "example.com".to_string(),
+            server: Some(server),
+            ..Default::default()
+        };
+        query(&config).unwrap();
+        assert_eq!(handle.join().unwrap(), "example.com");
+    }
+
     #[test]
     fn test_whois_server_for_com() {
         assert_eq!(whois_server_for("example.com"), "whois.verisign-grs.com");
@@ -402,6 +613,59 @@ mod tests {
         assert_eq!(ns.len(), 3);
     }
 
+    #[test]
+    fn test_parse_ip_owner_arin() {
+        let raw = "NetRange: 3.0.0.0 - 3.127.255.255\nNetName: AT-88-Z\nOrgName: Amazon Technologies Inc.\nOrgId: AT-88-Z\n";
+        assert_eq!(
+            parse_ip_owner(raw),
+            Some("Amazon Technologies Inc.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_ip_owner_ripe() {
+        let raw = "inetnum: 193.0.0.0 - 193.0.7.255\nnetname: RIPE-NCC\ndescr: RIPE Network Coordination Centre\norg-name: Reseaux IP Europeens\n";
+        // org-name is preferred over descr and netname
+        assert_eq!(
+            parse_ip_owner(raw),
+            Some("Reseaux IP Europeens".to_string())
+        );
+        let raw = "inetnum: 193.0.0.0 - 193.0.7.255\nnetname: RIPE-NCC\n";
+        assert_eq!(parse_ip_owner(raw), Some("RIPE-NCC".to_string()));
+    }
+
+    #[test]
+    fn test_parse_ip_owner_none() {
+        assert_eq!(parse_ip_owner("% No entries found\n"), None);
+        // owner-c is a handle, not the owner
+        assert_eq!(parse_ip_owner("owner-c: ABC123\n"), None);
+    }
+
+    #[test]
+    fn test_parse_referral() {
+        let raw = "% IANA WHOIS server\nrefer:        whois.ripe.net\n\ninetnum: 193.0.0.0 - 193.255.255.255\n";
+        assert_eq!(parse_referral(raw), Some("whois.ripe.net".to_string()));
+        assert_eq!(parse_referral("nothing here"), None);
+    }
+
+    #[test]
+    fn test_is_public_ip() {
+        assert!(is_public_ip(&"8.8.8.8".parse().unwrap()));
+        assert!(is_public_ip(&"2606:4700::1111".parse().unwrap()));
+        assert!(!is_public_ip(&"10.0.0.1".parse().unwrap()));
+        assert!(!is_public_ip(&"192.168.1.1".parse().unwrap()));
+        assert!(!is_public_ip(&"127.0.0.1".parse().unwrap()));
+        assert!(!is_public_ip(&"100.64.0.1".parse().unwrap()));
+        assert!(!is_public_ip(&"fe80::1".parse().unwrap()));
+        assert!(!is_public_ip(&"fd00::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_ip_owner_private_skips_lookup() {
+        assert_eq!(ip_owner("192.168.1.1", Duration::from_millis(10)), Ok(None));
+        assert!(ip_owner("not-an-ip", Duration::from_millis(10)).is_err());
+    }
+
     #[test]
     fn test_whois_config_default() {
         let cfg = WhoisConfig::default();
@@ -415,6 +679,7 @@ mod tests {
         let cfg = WhoisConfig {
             target: "example.com".to_string(),
             server: Some("whois.verisign-grs.com".to_string()),
+            query_override: None,
             timeout: Duration::from_secs(5),
         };
         assert_eq!(cfg.target, "example.com");
@@ -441,6 +706,8 @@ mod tests {
             ],
             raw: "Raw WHOIS data here...".to_string(),
             query_time_ms: 1250.5,
+            partial: false,
+            timestamp: String::new(),
         };
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("example.com"));
@@ -462,6 +729,8 @@ mod tests {
             status: vec![],
             raw: "No match for domain".to_string(),
             query_time_ms: 500.0,
+            partial: false,
+            timestamp: String::new(),
         };
 
         assert!(result.registrar.is_none());
@@ -483,6 +752,8 @@ mod tests {
             status: vec![],
             raw: String::new(),
             query_time_ms: 100.0,
+            partial: false,
+            timestamp: String::new(),
         };
         assert!(result.has_registrar());
 
@@ -506,6 +777,8 @@ mod tests {
             status: vec![],
             raw: String::new(),
             query_time_ms: 100.0,
+            partial: false,
+            timestamp: String::new(),
         };
         assert!(result.has_expiry());
 
@@ -529,6 +802,8 @@ mod tests {
             status: vec![],
             raw: String::new(),
             query_time_ms: 100.0,
+            partial: false,
+            timestamp: String::new(),
         };
         assert_eq!(result.nameserver_count(), 2);
     }
@@ -546,6 +821,8 @@ mod tests {
             status: vec![],
             raw: "No match".to_string(),
             query_time_ms: 200.0,
+            partial: false,
+            timestamp: String::new(),
         };
         assert!(!empty.is_parsed());
 
@@ -575,6 +852,8 @@ mod tests {
             status: vec![],
             raw: String::new(),
             query_time_ms: 150.0,
+            partial: false,
+            timestamp: String::new(),
         };
         let s = result.summary();
         assert!(s.contains("example.com"));
@@ -596,6 +875,8 @@ mod tests {
             status: vec![],
             raw: String::new(),
             query_time_ms: 50.0,
+            partial: false,
+            timestamp: String::new(),
         };
         let s = result.summary();
         assert!(s.contains("unknown registrar"));
@@ -616,6 +897,8 @@ mod tests {
             status: vec![],
             raw: String::new(),
             query_time_ms: 75.0,
+            partial: false,
+            timestamp: String::new(),
         };
         let s = result.summary();
         assert!(s.contains("1 nameserver"));
@@ -636,6 +919,8 @@ mod tests {
             status: vec!["clientTransferProhibited".to_string()],
             raw: String::new(),
             query_time_ms: 150.5,
+            partial: false,
+            timestamp: String::new(),
         };
         let display = format!("{result}");
         assert!(display.contains("WHOIS: example.com"));
@@ -663,6 +948,8 @@ mod tests {
             status: vec![],
             raw: String::new(),
             query_time_ms: 300.0,
+            partial: false,
+            timestamp: String::new(),
         };
         let display = format!("{result}");
         assert!(display.contains("WHOIS: unknown.xyz"));
@@ -0,0 +1,349 @@
+//! Minimal X.509 certificate decoding.
+//!
+//! Reads just the fields `cert` reports — serial, subject and issuer common
+//! names, validity, subject alternative names and the basic-constraints CA
+//! flag — from DER (RFC 5280).
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::SystemTime;
+
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+const INTEGER: u8 = 0x02;
+const BOOLEAN: u8 = 0x01;
+const OID: u8 = 0x06;
+const OCTET_STRING: u8 = 0x04;
+const UTC_TIME: u8 = 0x17;
+const GENERALIZED_TIME: u8 = 0x18;
+const BMP_STRING: u8 = 0x1E;
+/// `[0] EXPLICIT` version in the TBS certificate.
+const VERSION: u8 = 0xA0;
+/// `[3] EXPLICIT` extensions in the TBS certificate.
+const EXTENSIONS: u8 = 0xA3;
+/// `[2] IMPLICIT IA5String` dNSName in GeneralNames.
+const SAN_DNS: u8 = 0x82;
+/// `[7] IMPLICIT OCTET STRING` iPAddress in GeneralNames.
+const SAN_IP: u8 = 0x87;
+
+/// 2.5.4.3, commonName.
+const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+/// 2.5.29.19, basicConstraints.
+const OID_BASIC_CONSTRAINTS: &[u8] = &[0x55, 0x1D, 0x13];
+/// 2.5.29.17, subjectAltName.
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1D, 0x11];
+
+/// The fields of a certificate `cert` reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Certificate {
+    /// Serial number bytes, without the sign padding byte.
+    pub serial: Vec<u8>,
+    pub subject: String,
+    /// The subject's commonName, if it has one.
+    pub common_name: Option<String>,
+    pub issuer: String,
+    pub not_before: SystemTime,
+    pub not_after: SystemTime,
+    /// Basic constraints mark this as a CA certificate.
+    pub is_ca: bool,
+    /// DNS names and IP addresses from the subjectAltName extension.
+    pub subject_alt_names: Vec<String>,
+}
+
+/// A cursor over a run of DER elements.
+struct Der<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Der<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    /// Read the next element's tag and contents.
+    fn read(&mut self) -> Result<(u8, &'a [u8]), String> {
+        let (&tag, rest) = self.data.split_first().ok_or("Truncated DER element")?;
+        let (&first, rest) = rest.split_first().ok_or("Truncated DER length")?;
+        let (len, rest) = if first < 0x80 {
+            (usize::from(first), rest)
+        } else {
+            let n = usize::from(first & 0x7F);
+            if n == 0 || n > 4 || rest.len() < n {
+                return Err("Unsupported DER length".to_string());
+            }
+            let len = rest[..n]
+                .iter()
+                .fold(0usize, |acc, &b| acc << 8 | usize::from(b));
+            (len, &rest[n..])
+        };
+        if rest.len() < len {
+            return Err("Truncated DER contents".to_string());
+        }
+        self.data = &rest[len..];
+        Ok((tag, &rest[..len]))
+    }
+
+    /// Read the next element, which must have `tag`.
+    fn expect(&mut self, tag: u8) -> Result<&'a [u8], String> {
+        match self.read()? {
+            (t, contents) if t == tag => Ok(contents),
+            (t, _) => Err(format!("Expected DER tag {tag:#04x}, found {t:#04x}")),
+        }
+    }
+}
+
+/// Decode a DER-encoded X.509 certificate.
+pub fn parse(der: &[u8]) -> Result<Certificate, String> {
+    let cert = Der::new(der).expect(SEQUENCE)?;
+    let mut tbs = Der::new(Der::new(cert).expect(SEQUENCE)?);
+    if tbs.peek_tag() == Some(VERSION) {
+        tbs.read()?;
+    }
+    let serial = tbs.expect(INTEGER)?;
+    let serial = match serial {
+        [0, rest @ ..] if !rest.is_empty() => rest.to_vec(),
+        _ => serial.to_vec(),
+    };
+    tbs.expect(SEQUENCE)?; // signature algorithm
+    let (issuer, _) = name(tbs.expect(SEQUENCE)?)?;
+    let mut validity = Der::new(tbs.expect(SEQUENCE)?);
+    let not_before = time(&mut validity)?;
+    let not_after = time(&mut validity)?;
+    let (subject, common_name) = name(tbs.expect(SEQUENCE)?)?;
+    tbs.expect(SEQUENCE)?; // subject public key info
+
+    let mut is_ca = false;
+    let mut subject_alt_names = Vec::new();
+    while !tbs.is_empty() {
+        let (tag, contents) = tbs.read()?;
+        if tag != EXTENSIONS {
+            continue;
+        }
+        let mut extensions = Der::new(Der::new(contents).expect(SEQUENCE)?);
+        while !extensions.is_empty() {
+            let mut ext = Der::new(extensions.expect(SEQUENCE)?);
+            let oid = ext.expect(OID)?;
+            if ext.peek_tag() == Some(BOOLEAN) {
+                ext.read()?; // critical
+            }
+            let value = ext.expect(OCTET_STRING)?;
+            if oid == OID_BASIC_CONSTRAINTS {
+                is_ca = basic_constraints_ca(value)?;
+            } else if oid == OID_SUBJECT_ALT_NAME {
+                subject_alt_names = alt_names(value)?;
+            }
+        }
+    }
+
+    Ok(Certificate {
+        serial,
+        subject,
+        common_name,
+        issuer,
+        not_before,
+        not_after,
+        is_ca,
+        subject_alt_names,
+    })
+}
+
+/// The display name of a distinguished name — its common name, or its first
+/// attribute if it has none — and the common name itself.
+fn name(rdns: &[u8]) -> Result<(String, Option<String>), String> {
+    let mut rdns = Der::new(rdns);
+    let mut first = None;
+    while !rdns.is_empty() {
+        let mut set = Der::new(rdns.expect(SET)?);
+        while !set.is_empty() {
+            let mut attr = Der::new(set.expect(SEQUENCE)?);
+            let oid = attr.expect(OID)?;
+            let (tag, value) = attr.read()?;
+            let value = string(tag, value);
+            if oid == OID_COMMON_NAME {
+                return Ok((value.clone(), Some(value)));
+            }
+            first.get_or_insert(value);
+        }
+    }
+    Ok((first.unwrap_or_else(|| "unknown".to_string()), None))
+}
+
+/// Decode a directory string; BMPString is UTF-16, the rest are read as UTF-8.
+fn string(tag: u8, value: &[u8]) -> String {
+    if tag == BMP_STRING {
+        let units: Vec<u16> = value
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        return String::from_utf16_lossy(&units);
+    }
+    String::from_utf8_lossy(value).into_owned()
+}
+
+/// Read a UTCTime (`YYMMDDHHMMSSZ`) or GeneralizedTime (`YYYYMMDDHHMMSSZ`).
+fn time(der: &mut Der) -> Result<SystemTime, String> {
+    let (tag, value) = der.read()?;
+    let text = std::str::from_utf8(value).map_err(|_| "Invalid certificate time")?;
+    let full = match tag {
+        // RFC 5280 §4.1.2.5.1: two-digit years 50–99 are 19xx
+        UTC_TIME => {
+            let yy: u32 = text
+                .get(..2)
+                .and_then(|y| y.parse().ok())
+                .ok_or("Invalid certificate time")?;
+            format!("{}{text}", if yy >= 50 { "19" } else { "20" })
+        }
+        GENERALIZED_TIME => text.to_string(),
+        _ => return Err(format!("Expected a certificate time, found tag {tag:#04x}")),
+    };
+    let digits = full
+        .strip_suffix('Z')
+        .filter(|d| d.len() == 14 && d.bytes().all(|b| b.is_ascii_digit()))
+        .ok_or_else(|| format!("Unsupported certificate time: {text}"))?;
+    let rfc3339 = format!(
+        "{}-{}-{}T{}:{}:{}Z",
+        &digits[..4],
+        &digits[4..6],
+        &digits[6..8],
+        &digits[8..10],
+        &digits[10..12],
+        &digits[12..14]
+    );
+    crate::timestamp::parse(&rfc3339).ok_or_else(|| format!("Invalid certificate time: {text}"))
+}
+
+/// The `cA` flag from a basicConstraints extension value.
+fn basic_constraints_ca(value: &[u8]) -> Result<bool, String> {
+    let mut constraints = Der::new(Der::new(value).expect(SEQUENCE)?);
+    Ok(constraints.peek_tag() == Some(BOOLEAN)
+        && constraints
+            .expect(BOOLEAN)?
+            .first()
+            .is_some_and(|&b| b != 0))
+}
+
+/// The DNS names and IP addresses of a subjectAltName extension value; other
+/// kinds of name (email, URI, ...) are skipped.
+fn alt_names(value: &[u8]) -> Result<Vec<String>, String> {
+    let mut general_names = Der::new(Der::new(value).expect(SEQUENCE)?);
+    let mut names = Vec::new();
+    while !general_names.is_empty() {
+        match general_names.read()? {
+            (SAN_DNS, dns) => names.push(String::from_utf8_lossy(dns).into_owned()),
+            (SAN_IP, ip) => {
+                let ip = match ip.len() {
+                    4 => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(ip).unwrap())),
+                    16 => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(ip).unwrap())),
+                    _ => continue,
+                };
+                names.push(ip.to_string());
+            }
+            _ => {}
+        }
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::testdata;
+    use rustls::pki_types::pem::PemObject;
+    use rustls::pki_types::CertificateDer;
+
+    fn load(name: &str) -> Certificate {
+        let der = CertificateDer::from_pem_file(testdata(name)).unwrap();
+        parse(der.as_ref()).unwrap()
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02X}")).collect()
+    }
+
+    #[test]
+    fn test_parse_server_certificate() {
+        let cert = load("test-server.pem");
+        assert_eq!(
+            hex(&cert.serial),
+            "418E1B38495F39F945DFB3388619C25E8CA84966"
+        );
+        assert_eq!(cert.subject, "127.0.0.1");
+        assert_eq!(cert.common_name.as_deref(), Some("127.0.0.1"));
+        assert_eq!(cert.subject_alt_names, vec!["127.0.0.1"]);
+        assert_eq!(cert.issuer, "netscout test CA");
+        assert!(!cert.is_ca);
+        // notBefore is a UTCTime, notAfter (2126) a GeneralizedTime
+        assert_eq!(
+            crate::timestamp::format(cert.not_before),
+            "2026-10-16T01:40:42.000Z"
+        );
+        assert_eq!(
+            crate::timestamp::format(cert.not_after),
+            "2126-09-22T01:40:42.000Z"
+        );
+    }
+
+    #[test]
+    fn test_parse_ca_certificate() {
+        let cert = load("test-ca.pem");
+        assert_eq!(
+            hex(&cert.serial),
+            "0272D48206D2427C8264062009E9BB6786530D95"
+        );
+        assert_eq!(cert.subject, "netscout test CA");
+        assert_eq!(cert.subject, cert.issuer);
+        assert!(cert.is_ca);
+        assert!(cert.subject_alt_names.is_empty());
+    }
+
+    #[test]
+    fn test_utc_time_century() {
+        let utc = |s: &str| {
+            let mut der = vec![UTC_TIME, s.len() as u8];
+            der.extend_from_slice(s.as_bytes());
+            crate::timestamp::format(time(&mut Der::new(&der)).unwrap())
+        };
+        assert_eq!(utc("491231235959Z"), "2049-12-31T23:59:59.000Z");
+        assert_eq!(utc("991231235959Z"), "1999-12-31T23:59:59.000Z");
+    }
+
+    #[test]
+    fn test_malformed_time_is_an_error() {
+        let generalized = |s: &str| {
+            let mut der = vec![GENERALIZED_TIME, s.len() as u8];
+            der.extend_from_slice(s.as_bytes());
+            time(&mut Der::new(&der))
+        };
+        assert!(generalized("20491231235959Z").is_ok());
+        // 14 bytes before the Z, but "é" straddles a field boundary
+        assert!(generalized("204é123123595Z").is_err());
+        assert!(generalized("2049-231235959Z").is_err());
+    }
+
+    #[test]
+    fn test_alt_names_keeps_dns_and_ip() {
+        let mut names = vec![SAN_DNS, 9];
+        names.extend_from_slice(b"a.example");
+        names.extend_from_slice(&[0x81, 3]); // rfc822Name, skipped
+        names.extend_from_slice(b"x@y");
+        names.extend_from_slice(&[SAN_IP, 4, 192, 0, 2, 1]);
+        let mut value = vec![SEQUENCE, names.len() as u8];
+        value.extend_from_slice(&names);
+        assert_eq!(alt_names(&value).unwrap(), vec!["a.example", "192.0.2.1"]);
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&[0u8; 32]).is_err());
+        // Length runs past the end of the data
+        assert!(parse(&[SEQUENCE, 0x82, 0x10, 0x00, 0x30]).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 use netscout_core::dns::{DnsConfig, RecordType};
-use netscout_core::ping::PingConfig;
+use netscout_core::ping::{PingConfig, PingMode};
 use netscout_core::port::{parse_ports, top_ports};
 use netscout_core::scan::parse_subnet;
 use netscout_core::OutputFormat;
@@ -20,6 +20,8 @@ fn test_ping_config_construction() {
         interval: Duration::from_millis(500),
         timeout: Duration::from_secs(1),
         port: 443,
+        duration: None,
+        mode: PingMode::Tcp,
     };
     assert_eq!(config.count, 10);
     assert_eq!(config.port, 443);
@@ -64,3 +66,48 @@ fn test_subnet_parse_various() {
     // /31 should give 2 hosts
     assert_eq!(parse_subnet("10.0.0.0/31").unwrap().len(), 2);
 }
+
+#[test]
+fn test_http_options_reports_allow_and_cors() {
+    use netscout_core::http::{probe, HttpConfig};
+    use netscout_core::output::HumanReadable;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+        let (mut sock, _) = listener.accept().unwrap();
+        let mut buf = [0u8; 1024];
+        let n = sock.read(&mut buf).unwrap();
+        let req = String::from_utf8_lossy(&buf[..n]).to_string();
+        sock.write_all(
+            b"HTTP/1.1 200 OK\r\nAllow: GET,POST\r\n\
+              Access-Control-Allow-Origin: https://app.example\r\n\
+              Access-Control-Allow-Methods: GET, POST\r\n\r\n",
+        )
+        .unwrap();
+        // No body framing and no close: the client must not wait for more
+        std::thread::sleep(Duration::from_millis(1500));
+        req
+    });
+
+    let config = HttpConfig {
+        url: format!("http://127.0.0.1:{port}/api"),
+        method: "OPTIONS".to_string(),
+        timeout: Duration::from_secs(1),
+        ..Default::default()
+    };
+    let result = probe(&config).unwrap();
+    assert!(server.join().unwrap().starts_with("OPTIONS /api HTTP/1.1"));
+
+    let options = result.options.as_ref().unwrap();
+    assert_eq!(options.allow, vec!["GET", "POST"]);
+    assert_eq!(
+        options.cors["access-control-allow-origin"],
+        "https://app.example"
+    );
+    let human = result.to_human();
+    assert!(human.contains("Allow: GET, POST"));
+    assert!(human.contains("access-control-allow-methods: GET, POST"));
+}
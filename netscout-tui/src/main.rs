@@ -1,3 +1,6 @@
+mod results;
+mod tools;
+
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
@@ -5,66 +8,161 @@ use crossterm::{
 };
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Tabs},
 };
+use results::ResultsPane;
 use std::io::stdout;
 
+/// Dashboard state outside the results pane.
+#[derive(Default)]
+struct App {
+    results: ResultsPane,
+    target: String,
+    editing: bool,
+    status: String,
+    /// Rows of output visible in the results pane at the last draw.
+    viewport: usize,
+}
+
+fn draw(frame: &mut Frame, app: &mut App) {
+    let area = frame.area();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),
+        ])
+        .split(area);
+
+    // Title
+    let title = Paragraph::new("🔍 netscout — Network Diagnostic Dashboard")
+        .style(Style::default().fg(Color::Cyan).bold())
+        .block(Block::default().borders(Borders::ALL));
+    frame.render_widget(title, chunks[0]);
+
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(26), Constraint::Min(0)])
+        .split(chunks[1]);
+
+    // Tools
+    let items: Vec<ListItem> = tools::TOOLS
+        .iter()
+        .enumerate()
+        .map(|(i, name)| ListItem::new(format!("  [{}] {}", i + 1, name)))
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().title(" Tools ").borders(Borders::ALL))
+        .style(Style::default().fg(Color::White));
+    frame.render_widget(list, body[0]);
+
+    // Results: history tabs above the scrollable output
+    let pane = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(body[1]);
+    let labels: Vec<String> = app.results.history().iter().map(|r| r.label()).collect();
+    let tabs = Tabs::new(labels)
+        .select(app.results.selected())
+        .highlight_style(Style::default().fg(Color::Yellow).bold())
+        .block(Block::default().title(" History ").borders(Borders::ALL));
+    frame.render_widget(tabs, pane[0]);
+
+    app.viewport = pane[1].height.saturating_sub(2) as usize;
+    let heading = match app.results.current() {
+        Some(run) => format!(
+            " {} — {} [{}] ",
+            run.label(),
+            run.timestamp,
+            app.results.format
+        ),
+        None => " Results ".to_string(),
+    };
+    let output = Paragraph::new(app.results.text())
+        .scroll((app.results.scroll().min(u16::MAX as usize) as u16, 0))
+        .block(Block::default().title(heading).borders(Borders::ALL));
+    frame.render_widget(output, pane[1]);
+
+    // Status bar
+    let status = if app.editing {
+        format!("Target: {}▏ (Enter to confirm)", app.target)
+    } else if !app.status.is_empty() {
+        app.status.clone()
+    } else {
+        format!(
+            "Target: {} | 1-9 run | / target | Tab history | PgUp/PgDn scroll | f format | e export | q quit",
+            if app.target.is_empty() { "-" } else { &app.target }
+        )
+    };
+    let status = Paragraph::new(status)
+        .style(Style::default().fg(Color::DarkGray))
+        .block(Block::default().borders(Borders::ALL));
+    frame.render_widget(status, chunks[2]);
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    // Results are shown as plain text in the pane, so no ANSI escapes
+    colored::control::set_override(false);
+
     // Setup terminal
     enable_raw_mode()?;
     stdout().execute(EnterAlternateScreen)?;
     let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+    let mut app = App::default();
 
     // Main loop
     loop {
-        terminal.draw(|frame| {
-            let area = frame.area();
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Length(3),
-                    Constraint::Min(0),
-                    Constraint::Length(3),
-                ])
-                .split(area);
-
-            // Title
-            let title = Paragraph::new("🔍 netscout — Network Diagnostic Dashboard")
-                .style(Style::default().fg(Color::Cyan).bold())
-                .block(Block::default().borders(Borders::ALL));
-            frame.render_widget(title, chunks[0]);
-
-            // Main content
-            let items = vec![
-                ListItem::new("  [1] Ping"),
-                ListItem::new("  [2] DNS Lookup"),
-                ListItem::new("  [3] Port Scan"),
-                ListItem::new("  [4] Traceroute"),
-                ListItem::new("  [5] HTTP Probe"),
-                ListItem::new("  [6] TLS Certificate"),
-                ListItem::new("  [7] Speed Test"),
-                ListItem::new("  [8] WHOIS"),
-                ListItem::new("  [9] LAN Scan"),
-            ];
-            let list = List::new(items)
-                .block(Block::default().title(" Tools ").borders(Borders::ALL))
-                .style(Style::default().fg(Color::White));
-            frame.render_widget(list, chunks[1]);
-
-            // Status bar
-            let status = Paragraph::new("Press 'q' to quit | Select a tool by number")
-                .style(Style::default().fg(Color::DarkGray))
-                .block(Block::default().borders(Borders::ALL));
-            frame.render_widget(status, chunks[2]);
-        })?;
+        terminal.draw(|frame| draw(frame, &mut app))?;
 
         // Handle input
-        if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press && key.code == KeyCode::Char('q') {
-                    break;
+        if !event::poll(std::time::Duration::from_millis(100))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if app.editing {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => app.editing = false,
+                KeyCode::Backspace => {
+                    app.target.pop();
                 }
+                KeyCode::Char(c) => app.target.push(c),
+                _ => {}
+            }
+            continue;
+        }
+        app.status.clear();
+        match key.code {
+            KeyCode::Char('q') => break,
+            KeyCode::Char('/') => app.editing = true,
+            KeyCode::Char(c @ '1'..='9') => {
+                let index = c as usize - '1' as usize;
+                app.status = format!("Running {}…", tools::TOOLS[index]);
+                terminal.draw(|frame| draw(frame, &mut app))?;
+                let record = runtime.block_on(tools::run(index, &app.target));
+                app.results.push(record);
+                app.status.clear();
+            }
+            KeyCode::Tab => app.results.next_run(),
+            KeyCode::BackTab => app.results.prev_run(),
+            KeyCode::PageDown => app.results.page_down(app.viewport),
+            KeyCode::PageUp => app.results.page_up(app.viewport),
+            KeyCode::Down => app.results.scroll_by(1, app.viewport),
+            KeyCode::Up => app.results.scroll_by(-1, app.viewport),
+            KeyCode::Char('f') => app.results.next_format(),
+            KeyCode::Char('e') => {
+                app.status = match app.results.export(std::path::Path::new(".")) {
+                    Ok(path) => format!("Exported to {}", path.display()),
+                    Err(e) => e,
+                };
             }
+            _ => {}
         }
     }
 
@@ -0,0 +1,278 @@
+//! Results pane state: run history, scrollback and export.
+//!
+//! Kept free of terminal types so the scrolling and history logic can be
+//! unit tested without a backend.
+
+use netscout_core::OutputFormat;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+/// Number of recent runs kept in the history.
+pub const HISTORY_LEN: usize = 10;
+
+/// One completed tool run, pre-rendered in every output format.
+#[derive(Debug, Clone)]
+pub struct RunRecord {
+    pub tool: String,
+    pub target: String,
+    pub timestamp: String,
+    /// Rendered output per format, or the error message if the run failed.
+    pub outputs: Vec<(OutputFormat, String)>,
+}
+
+impl RunRecord {
+    /// Render a result in all output formats.
+    pub fn new(tool: &str, target: &str, render: impl Fn(OutputFormat) -> String) -> Self {
+        Self {
+            tool: tool.to_string(),
+            target: target.to_string(),
+            timestamp: netscout_core::timestamp::now(),
+            outputs: OutputFormat::all()
+                .into_iter()
+                .map(|f| (f, render(f)))
+                .collect(),
+        }
+    }
+
+    /// Record a failed run; the error is shown regardless of format.
+    pub fn failed(tool: &str, target: &str, error: &str) -> Self {
+        Self::new(tool, target, |_| format!("Error: {error}"))
+    }
+
+    /// The output rendered in `format`.
+    pub fn output(&self, format: OutputFormat) -> &str {
+        self.outputs
+            .iter()
+            .find(|(f, _)| *f == format)
+            .map(|(_, s)| s.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Short label for the history bar, e.g. `ping example.com`.
+    pub fn label(&self) -> String {
+        format!("{} {}", self.tool, self.target)
+    }
+}
+
+/// State behind the results pane.
+#[derive(Debug)]
+pub struct ResultsPane {
+    history: VecDeque<RunRecord>,
+    selected: usize,
+    scroll: usize,
+    pub format: OutputFormat,
+}
+
+impl Default for ResultsPane {
+    fn default() -> Self {
+        Self {
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            selected: 0,
+            scroll: 0,
+            format: OutputFormat::Human,
+        }
+    }
+}
+
+impl ResultsPane {
+    /// Add a run as the newest entry and show it, dropping the oldest past [`HISTORY_LEN`].
+    pub fn push(&mut self, record: RunRecord) {
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_back();
+        }
+        self.history.push_front(record);
+        self.selected = 0;
+        self.scroll = 0;
+    }
+
+    pub fn history(&self) -> &VecDeque<RunRecord> {
+        &self.history
+    }
+
+    /// Index into [`history`](Self::history) of the displayed run (0 = newest).
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn current(&self) -> Option<&RunRecord> {
+        self.history.get(self.selected)
+    }
+
+    /// The displayed run's output in the selected format.
+    pub fn text(&self) -> &str {
+        self.current()
+            .map(|r| r.output(self.format))
+            .unwrap_or_default()
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.text().lines().count()
+    }
+
+    pub fn scroll(&self) -> usize {
+        self.scroll
+    }
+
+    /// Move to the next older run, wrapping back to the newest.
+    pub fn next_run(&mut self) {
+        if !self.history.is_empty() {
+            self.selected = (self.selected + 1) % self.history.len();
+            self.scroll = 0;
+        }
+    }
+
+    /// Move to the next newer run, wrapping to the oldest.
+    pub fn prev_run(&mut self) {
+        if !self.history.is_empty() {
+            self.selected = (self.selected + self.history.len() - 1) % self.history.len();
+            self.scroll = 0;
+        }
+    }
+
+    /// Cycle through the output formats.
+    pub fn next_format(&mut self) {
+        let all = OutputFormat::all();
+        let idx = all.iter().position(|f| *f == self.format).unwrap_or(0);
+        self.format = all[(idx + 1) % all.len()];
+        self.scroll = 0;
+    }
+
+    /// Scroll by `delta` lines, never past the last page of a `height`-line viewport.
+    pub fn scroll_by(&mut self, delta: isize, height: usize) {
+        let max = self.line_count().saturating_sub(height);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(max);
+    }
+
+    pub fn page_down(&mut self, height: usize) {
+        self.scroll_by(height.max(1) as isize, height);
+    }
+
+    pub fn page_up(&mut self, height: usize) {
+        self.scroll_by(-(height.max(1) as isize), height);
+    }
+
+    /// Write the displayed output to `dir`, returning the file written.
+    pub fn export(&self, dir: &Path) -> Result<PathBuf, String> {
+        let record = self.current().ok_or("Nothing to export")?;
+        let path = dir.join(export_file_name(record, self.format));
+        std::fs::write(&path, record.output(self.format))
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// File name for an export, e.g. `netscout-ping-20260102T030405.json`.
+pub fn export_file_name(record: &RunRecord, format: OutputFormat) -> String {
+    let stamp: String = record
+        .timestamp
+        .chars()
+        .take(19)
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    let ext = match format {
+        OutputFormat::Human | OutputFormat::Table => "txt",
+        OutputFormat::Json => "json",
+        OutputFormat::Csv => "csv",
+        OutputFormat::Ndjson => "jsonl",
+        OutputFormat::Prometheus => "prom",
+    };
+    format!("netscout-{}-{stamp}.{ext}", record.tool.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(lines: usize) -> RunRecord {
+        RunRecord::new("port", "example.com", |f| {
+            (0..lines).map(|i| format!("{f} line {i}\n")).collect()
+        })
+    }
+
+    #[test]
+    fn test_scroll_clamps_to_content() {
+        let mut pane = ResultsPane::default();
+        pane.push(record(30));
+        pane.scroll_by(100, 10);
+        assert_eq!(pane.scroll(), 20);
+        pane.page_down(10);
+        assert_eq!(pane.scroll(), 20);
+        pane.page_up(10);
+        assert_eq!(pane.scroll(), 10);
+        pane.scroll_by(-100, 10);
+        assert_eq!(pane.scroll(), 0);
+    }
+
+    #[test]
+    fn test_scroll_short_output_stays_at_top() {
+        let mut pane = ResultsPane::default();
+        pane.push(record(5));
+        pane.page_down(10);
+        assert_eq!(pane.scroll(), 0);
+
+        let mut empty = ResultsPane::default();
+        empty.scroll_by(3, 10);
+        assert_eq!(empty.scroll(), 0);
+    }
+
+    #[test]
+    fn test_history_is_bounded_and_tab_wraps() {
+        let mut pane = ResultsPane::default();
+        for i in 0..HISTORY_LEN + 3 {
+            pane.push(RunRecord::new("ping", &format!("host{i}"), |_| {
+                String::new()
+            }));
+        }
+        assert_eq!(pane.history().len(), HISTORY_LEN);
+        assert_eq!(
+            pane.current().unwrap().target,
+            format!("host{}", HISTORY_LEN + 2)
+        );
+
+        for _ in 0..HISTORY_LEN {
+            pane.next_run();
+        }
+        assert_eq!(pane.selected(), 0);
+        pane.prev_run();
+        assert_eq!(pane.selected(), HISTORY_LEN - 1);
+    }
+
+    #[test]
+    fn test_switching_run_resets_scroll() {
+        let mut pane = ResultsPane::default();
+        pane.push(record(30));
+        pane.push(record(30));
+        pane.page_down(10);
+        pane.next_run();
+        assert_eq!(pane.scroll(), 0);
+    }
+
+    #[test]
+    fn test_export_writes_selected_format() {
+        let dir = std::env::temp_dir().join(format!("netscout-tui-export-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut pane = ResultsPane::default();
+        assert!(pane.export(&dir).is_err());
+
+        pane.push(record(2));
+        pane.next_format();
+        assert_eq!(pane.format, OutputFormat::Json);
+        let path = pane.export(&dir).unwrap();
+        assert!(path.extension().is_some_and(|e| e == "json"));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "json line 0\njson line 1\n"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_file_name() {
+        let mut rec = record(1);
+        rec.timestamp = "2026-01-02T03:04:05.678Z".to_string();
+        assert_eq!(
+            export_file_name(&rec, OutputFormat::Csv),
+            "netscout-port-20260102T030405.csv"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Running netscout tools from the dashboard.
+
+use crate::results::RunRecord;
+use netscout_core::output::{format_output, HumanReadable};
+use netscout_core::{cert, dns, http, ping, port, scan, speed, trace, whois};
+use serde::Serialize;
+
+/// The tools listed in the dashboard, in menu order.
+pub const TOOLS: [&str; 9] = [
+    "Ping",
+    "DNS Lookup",
+    "Port Scan",
+    "Traceroute",
+    "HTTP Probe",
+    "TLS Certificate",
+    "Speed Test",
+    "WHOIS",
+    "LAN Scan",
+];
+
+fn record<T: Serialize + HumanReadable>(
+    tool: &str,
+    target: &str,
+    result: Result<T, String>,
+) -> RunRecord {
+    match result {
+        Ok(value) => RunRecord::new(tool, target, |f| format_output(&value, f)),
+        Err(e) => RunRecord::failed(tool, target, &e),
+    }
+}
+
+/// Run tool number `index` (0-based, see [`TOOLS`]) against `target`.
+pub async fn run(index: usize, target: &str) -> RunRecord {
+    let tool = TOOLS.get(index).copied().unwrap_or("Unknown");
+    let target = target.trim();
+    if target.is_empty() && !matches!(index, 6 | 8) {
+        return RunRecord::failed(tool, target, "No target set (press '/' to enter one)");
+    }
+    match index {
+        0 => {
+            let config = ping::PingConfig {
+                target: target.to_string(),
+                ..Default::default()
+            };
+            record(tool, target, ping::ping(&config).await)
+        }
+        1 => {
+            let config = dns::DnsConfig {
+                domain: target.to_string(),
+                ..Default::default()
+            };
+            record(tool, target, dns::query_async(&config).await)
+        }
+        2 => {
+            let config = port::PortConfig {
+                target: target.to_string(),
+                ..Default::default()
+            };
+            record(tool, target, port::scan(&config).await)
+        }
+        3 => {
+            let config = trace::TraceConfig {
+                target: target.to_string(),
+                ..Default::default()
+            };
+            record(tool, target, trace::trace(&config).await)
+        }
+        4 => {
+            let url = if target.contains("://") {
+                target.to_string()
+            } else {
+                format!("http://{target}/")
+            };
+            let config = http::HttpConfig {
+                url,
+                ..Default::default()
+            };
+            record(tool, target, http::probe_async(&config).await)
+        }
+        5 => {
+            let config = cert::CertConfig {
+                host: target.to_string(),
+                ..Default::default()
+            };
+            record(tool, target, cert::inspect_async(&config).await)
+        }
+        6 => record(
+            tool,
+            target,
+            speed::test_speed_async(&Default::default()).await,
+        ),
+        7 => {
+            let config = whois::WhoisConfig {
+                target: target.to_string(),
+                ..Default::default()
+            };
+            record(tool, target, whois::query_async(&config).await)
+        }
+        8 => {
+            let mut config = scan::LanScanConfig::default();
+            if !target.is_empty() {
+                config.subnet = target.to_string();
+            }
+            let subnet = config.subnet.clone();
+            record(tool, &subnet, scan::scan(&config).await)
+        }
+        _ => RunRecord::failed(tool, target, "Unknown tool"),
+    }
+}